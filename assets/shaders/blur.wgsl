@@ -0,0 +1,38 @@
+// One pass of a separable gaussian blur; run it once horizontally and once vertically
+struct BlurParams {
+    direction: vec2<i32>,
+    radius: i32,
+};
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+@group(0) @binding(2)
+var<uniform> params: BlurParams;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dims = vec2<i32>(textureDimensions(output));
+    if (i32(global_id.x) >= dims.x || i32(global_id.y) >= dims.y) {
+        return;
+    }
+
+    let coord = vec2<i32>(global_id.xy);
+
+    // the radius covers about two standard deviations
+    let sigma = max(f32(params.radius) / 2.0, 0.5);
+
+    var sum = vec4<f32>(0.0);
+    var total_weight = 0.0;
+    for (var i = -params.radius; i <= params.radius; i++) {
+        let weight = exp(-f32(i * i) / (2.0 * sigma * sigma));
+        let sample_coord = clamp(coord + params.direction * i, vec2<i32>(0), dims - 1);
+        sum += weight * textureLoad(input, sample_coord, 0);
+        total_weight += weight;
+    }
+
+    textureStore(output, coord, sum / total_weight);
+}
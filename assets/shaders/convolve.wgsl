@@ -0,0 +1,59 @@
+struct ConvolveParams {
+    size: u32,
+    edge_mode: u32,
+};
+
+const EDGE_CLAMP: u32 = 0u;
+const EDGE_WRAP: u32 = 1u;
+const EDGE_TRANSPARENT: u32 = 2u;
+
+const MAX_KERNEL_SIZE: i32 = 5;
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+// row by row on a 5x5 grid; smaller kernels only use the top left corner
+@group(0) @binding(2)
+var<storage, read> weights: array<f32, 25>;
+
+@group(0) @binding(3)
+var<uniform> params: ConvolveParams;
+
+fn sample(coord: vec2<i32>, dims: vec2<i32>) -> vec4<f32> {
+    if (params.edge_mode == EDGE_WRAP) {
+        return textureLoad(input, ((coord % dims) + dims) % dims, 0);
+    }
+
+    if (params.edge_mode == EDGE_TRANSPARENT) {
+        if (any(coord < vec2<i32>(0)) || any(coord >= dims)) {
+            return vec4<f32>(0.0);
+        }
+    }
+
+    return textureLoad(input, clamp(coord, vec2<i32>(0), dims - 1), 0);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dims = vec2<i32>(textureDimensions(output));
+    if (i32(global_id.x) >= dims.x || i32(global_id.y) >= dims.y) {
+        return;
+    }
+
+    let coord = vec2<i32>(global_id.xy);
+    let size = i32(params.size);
+    let radius = size / 2;
+
+    var sum = vec4<f32>(0.0);
+    for (var y = 0; y < size; y++) {
+        for (var x = 0; x < size; x++) {
+            let weight = weights[y * MAX_KERNEL_SIZE + x];
+            sum += weight * sample(coord + vec2<i32>(x - radius, y - radius), dims);
+        }
+    }
+
+    textureStore(output, coord, clamp(sum, vec4<f32>(0.0), vec4<f32>(1.0)));
+}
@@ -0,0 +1,31 @@
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+// the tone curve sampled at 256 evenly spaced inputs
+@group(0) @binding(2)
+var<storage, read> lut: array<f32, 256>;
+
+fn apply_curve(value: f32) -> f32 {
+    let position = clamp(value, 0.0, 1.0) * 255.0;
+    let lower = u32(floor(position));
+    let upper = min(lower + 1u, 255u);
+    return mix(lut[lower], lut[upper], fract(position));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dims = textureDimensions(output);
+    if (global_id.x >= dims.x || global_id.y >= dims.y) {
+        return;
+    }
+
+    let coord = vec2<i32>(global_id.xy);
+    let color = textureLoad(input, coord, 0);
+
+    let curved = vec3<f32>(apply_curve(color.r), apply_curve(color.g), apply_curve(color.b));
+
+    textureStore(output, coord, vec4<f32>(curved, color.a));
+}
@@ -0,0 +1,31 @@
+// The layout every Custom Compute shader has to match.
+// img_b is img_a again when nothing is connected to the second input.
+@group(0) @binding(0)
+var img_a: texture_2d<f32>;
+
+@group(0) @binding(1)
+var img_b: texture_2d<f32>;
+
+@group(0) @binding(2)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+// param_0 through param_7, packed four to a vector. Param N is params[N / 4][N % 4].
+// A `// @param N Name` line names param N in the inspector and on the node's port.
+// @param 0 Mix
+@group(0) @binding(3)
+var<uniform> params: array<vec4<f32>, 2>;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dims = textureDimensions(output);
+    if (global_id.x >= dims.x || global_id.y >= dims.y) {
+        return;
+    }
+
+    let coord = vec2<i32>(global_id.xy);
+    let color_a = textureLoad(img_a, coord, 0);
+    let color_b = textureLoad(img_b, coord, 0);
+
+    // mixes the two inputs by param_0
+    textureStore(output, coord, mix(color_a, color_b, clamp(params[0].x, 0.0, 1.0)));
+}
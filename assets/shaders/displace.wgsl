@@ -0,0 +1,54 @@
+struct DisplaceParams {
+    strength: f32,
+    edge_mode: u32,
+};
+
+const EDGE_CLAMP: u32 = 0u;
+const EDGE_WRAP: u32 = 1u;
+const EDGE_TRANSPARENT: u32 = 2u;
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+// stretched over the input when the sizes differ
+@group(0) @binding(2)
+var displacement_map: texture_2d<f32>;
+
+@group(0) @binding(3)
+var<uniform> params: DisplaceParams;
+
+fn sample(coord: vec2<i32>, dims: vec2<i32>) -> vec4<f32> {
+    if (params.edge_mode == EDGE_WRAP) {
+        return textureLoad(input, ((coord % dims) + dims) % dims, 0);
+    }
+
+    if (params.edge_mode == EDGE_TRANSPARENT) {
+        if (any(coord < vec2<i32>(0)) || any(coord >= dims)) {
+            return vec4<f32>(0.0);
+        }
+    }
+
+    return textureLoad(input, clamp(coord, vec2<i32>(0), dims - 1), 0);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dims = vec2<i32>(textureDimensions(output));
+    if (i32(global_id.x) >= dims.x || i32(global_id.y) >= dims.y) {
+        return;
+    }
+
+    let coord = vec2<i32>(global_id.xy);
+
+    let map_dims = vec2<i32>(textureDimensions(displacement_map));
+    let map_coord = min(coord * map_dims / dims, map_dims - 1);
+    let displacement = textureLoad(displacement_map, map_coord, 0);
+
+    // red shifts horizontally and green vertically, so a grayscale map shifts diagonally
+    let offset = vec2<i32>(round(displacement.rg * params.strength));
+
+    textureStore(output, coord, sample(coord + offset, dims));
+}
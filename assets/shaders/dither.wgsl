@@ -0,0 +1,49 @@
+struct DitherParams {
+    levels: u32,
+    seed: u32,
+};
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+@group(0) @binding(2)
+var<uniform> params: DitherParams;
+
+// 4x4 Bayer matrix, in units of 1/16
+const BAYER: array<f32, 16> = array<f32, 16>(
+     0.0,  8.0,  2.0, 10.0,
+    12.0,  4.0, 14.0,  6.0,
+     3.0, 11.0,  1.0,  9.0,
+    15.0,  7.0, 13.0,  5.0,
+);
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dims = textureDimensions(output);
+    if (global_id.x >= dims.x || global_id.y >= dims.y) {
+        return;
+    }
+
+    let coord = vec2<i32>(global_id.xy);
+    let color = textureLoad(input, coord, 0);
+
+    // threshold in (-0.5, 0.5) so the pattern is centered around the original value
+    // the seed shifts the pattern origin and, past 16, transposes it so each seed tiles differently
+    var bayer = BAYER;
+    let shift = params.seed % 16u;
+    var cell = (global_id.xy + vec2<u32>(shift % 4u, shift / 4u)) % 4u;
+    if ((params.seed / 16u) % 2u == 1u) {
+        cell = cell.yx;
+    }
+    let index = cell.y * 4u + cell.x;
+    let threshold = (bayer[index] + 0.5) / 16.0 - 0.5;
+
+    // levels - 1 steps between black and white, offset by at most half a step
+    let steps = f32(max(params.levels, 2u) - 1u);
+    let quantized = clamp(floor(color.rgb * steps + threshold + 0.5) / steps, vec3<f32>(0.0), vec3<f32>(1.0));
+
+    textureStore(output, coord, vec4<f32>(quantized, color.a));
+}
@@ -0,0 +1,32 @@
+struct LevelsParams {
+    black_point: f32,
+    white_point: f32,
+    gamma: f32,
+};
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+@group(0) @binding(2)
+var<uniform> params: LevelsParams;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dims = textureDimensions(output);
+    if (global_id.x >= dims.x || global_id.y >= dims.y) {
+        return;
+    }
+
+    let coord = vec2<i32>(global_id.xy);
+    let color = textureLoad(input, coord, 0);
+
+    // the node keeps white_point above black_point, so this never divides by zero
+    let range = params.white_point - params.black_point;
+    let normalized = clamp((color.rgb - vec3<f32>(params.black_point)) / range, vec3<f32>(0.0), vec3<f32>(1.0));
+    let adjusted = pow(normalized, vec3<f32>(1.0 / params.gamma));
+
+    textureStore(output, coord, vec4<f32>(adjusted, color.a));
+}
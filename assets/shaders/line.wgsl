@@ -16,6 +16,8 @@ struct VertexOutput {
 
 @group(2) @binding(0)
 var<uniform> line_material: LineMaterial;
+@group(2) @binding(1)
+var<uniform> dim: f32;
 
 struct LineMaterial {
     thickness: f32,
@@ -39,5 +41,5 @@ fn vertex(vertex: LineVertex, @builtin(instance_index) instance_index: u32) -> V
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    return vec4<f32>(in.color.rgb * dim, in.color.a);
 }
\ No newline at end of file
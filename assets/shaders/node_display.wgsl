@@ -23,9 +23,42 @@ var<uniform> content_padding: f32;
 var<uniform> texture_dimensions: vec2<f32>;
 @group(2) @binding(10)
 var<uniform> texture_background_color: vec4<f32>;
+@group(2) @binding(11)
+var<uniform> dim: f32;
+@group(2) @binding(12)
+var<uniform> error: f32;
+
+const ERROR_COLOR: vec4<f32> = vec4<f32>(0.8, 0.1, 0.1, 1.0);
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = error_overlay(in, node_color(in));
+    return vec4<f32>(color.rgb * dim, color.a);
+}
+
+// Failed nodes get a red border inside their own, and their content is washed red
+fn error_overlay(in: VertexOutput, color: vec4<f32>) -> vec4<f32> {
+    if error <= 0.0 {
+        return color;
+    }
+
+    let uv = in.uv;
+    let title_bar_ratio = title_bar_height / node_dimensions.y;
+    let border_ratio = border_width / node_dimensions.y;
+
+    // the outer border keeps showing hover and selection
+    if uv.y < title_bar_ratio || uv.x < border_ratio || uv.x > 1.0 - border_ratio || uv.y > 1.0 - border_ratio {
+        return color;
+    }
+
+    let frame = vec2<f32>(border_ratio) + 2.0 / node_dimensions;
+    if uv.x < frame.x || uv.x > 1.0 - frame.x || uv.y > 1.0 - frame.y {
+        return mix(color, ERROR_COLOR, error);
+    }
+    return mix(color, ERROR_COLOR, 0.3 * error);
+}
+
+fn node_color(in: VertexOutput) -> vec4<f32> {
     let uv = in.uv;
     let title_bar_ratio = title_bar_height / node_dimensions.y;
     let border_ratio = border_width / node_dimensions.y;
@@ -50,20 +83,24 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
             (uv.y - title_bar_ratio) / (1.0 - title_bar_ratio - border_ratio)
         );
        
-        // Calculate the available space for the texture
-        let available_space = vec2<f32>(
-            1.0 - 2.0 * padding_ratio,
-            1.0 - 2.0 * padding_ratio
+        // Size of the content area in pixels, so the texture can be fit by its real aspect ratio
+        let content_size = vec2<f32>(
+            node_dimensions.x * (1.0 - 2.0 * border_ratio),
+            node_dimensions.y * (1.0 - title_bar_ratio - border_ratio)
         );
+
+        // Calculate the available space for the texture
+        let available_space = max(content_size - vec2<f32>(2.0 * content_padding), vec2<f32>(1.0));
        
         // Calculate the scale factor to fit the texture within the available space
+        let safe_texture_dimensions = max(texture_dimensions, vec2<f32>(1.0));
         let scale = min(
-            available_space.x / texture_dimensions.x,
-            available_space.y / texture_dimensions.y
+            available_space.x / safe_texture_dimensions.x,
+            available_space.y / safe_texture_dimensions.y
         );
        
-        // Calculate the size of the scaled texture
-        let scaled_texture_size = texture_dimensions * scale;
+        // Calculate the size of the scaled texture, relative to the content area
+        let scaled_texture_size = safe_texture_dimensions * scale / content_size;
        
         // Calculate the position of the scaled texture
         let texture_position = vec2<f32>(
@@ -1,4 +1,4 @@
-#import bevy_sprite::mesh2d_view_bindings
+#import bevy_sprite::mesh2d_view_bindings::globals
 #import bevy_sprite::mesh2d_vertex_output::VertexOutput
 
 @group(2) @binding(0)
@@ -9,6 +9,8 @@ var<uniform> outline_color: vec4<f32>;
 var<uniform> outline_thickness: f32;
 @group(2) @binding(3)
 var<uniform> is_hovered: f32;
+@group(2) @binding(4)
+var<uniform> snap_state: f32; // 0 not snapped, 1 compatible, 2 incompatible
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
@@ -20,7 +22,23 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     
     if distance_from_center > outer_radius {
         discard;
-    } else if distance_from_center > inner_radius {
+    }
+
+    // A pulsing ring while a dragged edge is snapped here, green if dropping would connect
+    if snap_state > 0.5 {
+        let compatible_color = vec4<f32>(0.25, 0.85, 0.35, 1.0);
+        let incompatible_color = vec4<f32>(0.9, 0.25, 0.2, 1.0);
+        let ring_color = select(incompatible_color, compatible_color, snap_state < 1.5);
+
+        let pulse = 0.5 + 0.5 * sin(globals.time * 8.0);
+        let ring_thickness = 0.12 + 0.1 * pulse;
+
+        if distance_from_center > outer_radius - ring_thickness {
+            return ring_color;
+        }
+    }
+
+    if distance_from_center > inner_radius {
         return outline_color;
     } else {
         // Lighten the color when hovered
@@ -0,0 +1,32 @@
+struct SharpenParams {
+    amount: f32,
+};
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var blurred: texture_2d<f32>;
+
+@group(0) @binding(2)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+@group(0) @binding(3)
+var<uniform> params: SharpenParams;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dims = textureDimensions(output);
+    if (global_id.x >= dims.x || global_id.y >= dims.y) {
+        return;
+    }
+
+    let coord = vec2<i32>(global_id.xy);
+    let color = textureLoad(input, coord, 0);
+    let blur = textureLoad(blurred, coord, 0);
+
+    // unsharp mask: push each pixel away from its blurred surroundings
+    let sharpened = color.rgb + params.amount * (color.rgb - blur.rgb);
+
+    textureStore(output, coord, vec4<f32>(clamp(sharpened, vec3<f32>(0.0), vec3<f32>(1.0)), color.a));
+}
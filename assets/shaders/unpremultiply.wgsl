@@ -0,0 +1,24 @@
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dims = textureDimensions(output);
+    if (global_id.x >= dims.x || global_id.y >= dims.y) {
+        return;
+    }
+
+    let coord = vec2<i32>(global_id.xy);
+    let color = textureLoad(input, coord, 0);
+
+    // fully transparent pixels carry no color to recover, so they stay black
+    var rgb = vec3<f32>(0.0);
+    if (color.a > 0.0) {
+        rgb = clamp(color.rgb / color.a, vec3<f32>(0.0), vec3<f32>(1.0));
+    }
+
+    textureStore(output, coord, vec4<f32>(rgb, color.a));
+}
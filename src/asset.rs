@@ -45,6 +45,8 @@ pub const NODE_WIDTH: f32 = NODE_TEXTURE_DISPLAY_DIMENSION + NODE_CONTENT_PADDIN
 
 pub const PORT_RADIUS: f32 = 10.;
 
+pub const NODE_SHADOW_OFFSET: Vec2 = Vec2::new(5., -5.);
+
 fn generate_meshes(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -62,10 +64,16 @@ fn generate_meshes(
         Vec2::splat(NODE_TEXTURE_DISPLAY_DIMENSION) + Vec2::Y * NODE_TITLE_BAR_SIZE,
     )));
 
+    let node_shadow_material = materials.add(ColorMaterial {
+        color: Color::BLACK.with_alpha(0.35),
+        ..default()
+    });
+
     commands.insert_resource(GeneratedMeshes {
         canvas_quad,
         canvas_quad_material,
         node_display_quad,
+        node_shadow_material,
         port_mesh
     });
 }
@@ -93,6 +101,26 @@ pub struct ShaderAssets {
     pub shape: Handle<Shader>,
     #[asset(path="shaders/blend.wgsl")]
     pub blend: Handle<Shader>,
+    #[asset(path="shaders/dither.wgsl")]
+    pub dither: Handle<Shader>,
+    #[asset(path="shaders/levels.wgsl")]
+    pub levels: Handle<Shader>,
+    #[asset(path="shaders/curves.wgsl")]
+    pub curves: Handle<Shader>,
+    #[asset(path="shaders/premultiply.wgsl")]
+    pub premultiply: Handle<Shader>,
+    #[asset(path="shaders/unpremultiply.wgsl")]
+    pub unpremultiply: Handle<Shader>,
+    #[asset(path="shaders/convolve.wgsl")]
+    pub convolve: Handle<Shader>,
+    #[asset(path="shaders/blur.wgsl")]
+    pub blur: Handle<Shader>,
+    #[asset(path="shaders/sharpen.wgsl")]
+    pub sharpen: Handle<Shader>,
+    #[asset(path="shaders/displace.wgsl")]
+    pub displace: Handle<Shader>,
+    #[asset(path="shaders/custom_compute.wgsl")]
+    pub custom_compute: Handle<Shader>,
 }
 
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
@@ -118,6 +146,12 @@ pub struct NodeDisplayMaterial {
     pub texture_dimensions: Vec2,
     #[uniform(10)]
     pub texture_background_color: LinearRgba,
+    // scales the node's brightness, 1 draws it as is
+    #[uniform(11)]
+    pub dim: f32,
+    // 1 marks the node's last process as failed, 0 draws it normally
+    #[uniform(12)]
+    pub error: f32,
 
     pub default_border_color: LinearRgba,
     pub hover_border_color: LinearRgba,
@@ -140,8 +174,15 @@ pub struct PortMaterial {
     pub outline_thickness: f32,
     #[uniform(3)]
     pub is_hovered: f32, // Using f32 as a boolean (0.0 or 1.0)
+    #[uniform(4)]
+    pub snap_state: f32, // one of the PORT_SNAP_* values
 }
 
+// What a dragged edge would do if dropped on this port, drawn as a pulsing ring
+pub const PORT_SNAP_NONE: f32 = 0.;
+pub const PORT_SNAP_COMPATIBLE: f32 = 1.;
+pub const PORT_SNAP_INCOMPATIBLE: f32 = 2.;
+
 impl PartialEq for PortMaterial {
     fn eq(&self, other: &Self) -> bool {
         let self_string = format!(
@@ -158,7 +199,9 @@ impl PartialEq for PortMaterial {
             other.outline_thickness
         );
 
-        self_string == other_string && self.is_hovered.to_bits() == other.is_hovered.to_bits()
+        self_string == other_string
+            && self.is_hovered.to_bits() == other.is_hovered.to_bits()
+            && self.snap_state.to_bits() == other.snap_state.to_bits()
     }
 }
 
@@ -175,6 +218,7 @@ impl std::hash::Hash for PortMaterial {
 
         hash_string.hash(state);
         self.is_hovered.to_bits().hash(state); // Can directly hash the bits of the float
+        self.snap_state.to_bits().hash(state);
     }
 }
 
@@ -195,5 +239,6 @@ pub struct GeneratedMeshes {
     pub canvas_quad: Mesh2dHandle,
     pub canvas_quad_material: Handle<ColorMaterial>,
     pub node_display_quad: Mesh2dHandle,
+    pub node_shadow_material: Handle<ColorMaterial>,
     pub port_mesh: Mesh2dHandle,
 }
@@ -1,8 +1,15 @@
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_cosmic_edit::FocusedWidget;
 use bevy_mod_picking::prelude::*;
 
+use crate::asset::{NODE_TEXTURE_DISPLAY_DIMENSION, NODE_TITLE_BAR_SIZE};
+use crate::nodes::drag_threshold::{apply_drag_threshold, ThresholdDrag};
+use crate::nodes::{NodeDisplay, Selected};
+use crate::preferences::UiPreferences;
 use crate::setup::ApplicationCanvas;
+use crate::ui::NodeEditArea;
 use crate::ApplicationState;
 
 pub struct CameraPlugin;
@@ -18,13 +25,26 @@ impl Plugin for CameraPlugin {
             Update,
             (
                 camera_zoom,
-                camera_pan,
+                camera_pan.after(apply_drag_threshold),
+                handle_frame_input,
             )
                 .run_if(in_state(ApplicationState::MainLoop))
         );
+
+        app.observe(frame_nodes);
     }
 }
 
+// screen space left around the framed nodes, in pixels
+const FRAME_MARGIN: f32 = 40.;
+
+#[derive(Event, Clone, Copy)]
+pub enum RequestFrameNodes {
+    All,
+    // falls back to All when nothing is selected
+    Selected,
+}
+
 #[derive(Component)]
 pub struct MainCamera {
     pub min_zoom: f32,
@@ -78,6 +98,8 @@ fn camera_pan(
     mut camera_query: Query<&mut Transform, With<MainCamera>>,
     canvas_query: Query<Entity, With<ApplicationCanvas>>,
     mut drag_events: EventReader<Pointer<Drag>>,
+    mut threshold_drag_events: EventReader<ThresholdDrag>,
+    ui_preferences: Res<UiPreferences>,
 ) {
     let mut camera_transform = camera_query.single_mut();
 
@@ -89,4 +111,86 @@ fn camera_pan(
             camera_transform.translation.y += delta.y;
         }
     }
-}
\ No newline at end of file
+
+    // right drags go through the threshold so a still right click can open the context menu instead
+    for event in threshold_drag_events.read() {
+        if ui_preferences.right_drag_pan
+            && event.button == PointerButton::Secondary
+            && canvas_query.contains(event.target)
+        {
+            let delta = event.delta;
+
+            camera_transform.translation.x -= delta.x;
+            camera_transform.translation.y += delta.y;
+        }
+    }
+}
+fn handle_frame_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    focused: Res<FocusedWidget>,
+) {
+    // these keys are just text while an input has focus
+    if focused.0.is_some() {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Home) {
+        commands.trigger(RequestFrameNodes::All);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Period) {
+        commands.trigger(RequestFrameNodes::Selected);
+    }
+}
+
+fn frame_nodes(
+    trigger: Trigger<RequestFrameNodes>,
+    mut q_camera: Query<(&mut Transform, &mut OrthographicProjection, &MainCamera)>,
+    q_nodes: Query<(&GlobalTransform, Has<Selected>), With<NodeDisplay>>,
+    q_node_edit_area: Query<(&Node, &GlobalTransform), With<NodeEditArea>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let any_selected = q_nodes.iter().any(|(_, is_selected)| is_selected);
+    let only_selected = matches!(trigger.event(), RequestFrameNodes::Selected) && any_selected;
+
+    let node_half_size = Vec2::new(
+        NODE_TEXTURE_DISPLAY_DIMENSION,
+        NODE_TEXTURE_DISPLAY_DIMENSION + NODE_TITLE_BAR_SIZE,
+    ) / 2.;
+
+    let bounds = q_nodes
+        .iter()
+        .filter(|(_, is_selected)| !only_selected || *is_selected)
+        .map(|(transform, _)| {
+            let center = transform.translation().truncate();
+            Rect::from_corners(center - node_half_size, center + node_half_size)
+        })
+        .reduce(|a, b| a.union(b));
+
+    let Some(bounds) = bounds else {
+        return;
+    };
+
+    let (Ok(window), Ok((node_edit_area, node_edit_area_transform))) =
+        (q_window.get_single(), q_node_edit_area.get_single())
+    else {
+        return;
+    };
+
+    // nodes are framed within the edit area, which is off-center in the window because of the panels around it
+    let viewport_size = (node_edit_area.size() - Vec2::splat(FRAME_MARGIN * 2.)).max(Vec2::ONE);
+    let viewport_offset = node_edit_area_transform.translation().truncate() - window.size() / 2.;
+
+    let (mut camera_transform, mut projection, main_camera) = q_camera.single_mut();
+
+    let scale = (bounds.size() / viewport_size)
+        .max_element()
+        .clamp(main_camera.min_zoom, main_camera.max_zoom);
+
+    let center = bounds.center() - Vec2::new(viewport_offset.x, -viewport_offset.y) * scale;
+
+    projection.scale = scale;
+    camera_transform.translation.x = center.x;
+    camera_transform.translation.y = center.y;
+}
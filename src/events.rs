@@ -1,13 +1,17 @@
-use crate::{nodes::{fields::FieldMeta, NodeDisplay}, ApplicationState};
+use crate::{nodes::{fields::FieldMeta, node_kind_name, NodeDisplay}, ApplicationState};
 use bevy::prelude::*;
+use comment_events::{SetCommentEvent, RemoveCommentEvent, UndoableAddCommentEvent, UndoableRemoveCommentEvent};
 use edge_events::{AddEdgeEvent, AddNodeEdge, RemoveEdgeEvent, UndoableAddEdgeEvent, UndoableRemoveEdgeEvent};
+use group_events::{SetGroupEvent, RemoveGroupEvent, UndoableAddGroupEvent, UndoableRemoveGroupEvent};
 use field_events::{
     SetInputFieldEvent, SetOutputFieldEvent, UndoableSetInputFieldEvent, UndoableSetInputFieldMetaEvent, UndoableSetOutputFieldEvent, UndoableSetOutputFieldMetaEvent
 };
 use node_events::{RemoveNodeEvent, UndoableAddNodeEvent, UndoableDragNodeEvent, UndoableRemoveNodeEvent};
 
+pub mod comment_events;
 pub mod edge_events;
 pub mod field_events;
+pub mod group_events;
 pub mod node_events;
 
 // Maybe call this "DataEventsPlugin"? CoreEvents? What's "EVENTS"?
@@ -17,7 +21,8 @@ impl Plugin for EventsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             PreUpdate,
-            (handle_undo_redo_input).run_if(in_state(ApplicationState::MainLoop)),
+            (handle_undo_redo_input, step_towards_history_target)
+                .run_if(in_state(ApplicationState::MainLoop)),
         );
 
         app.add_systems(Last, flush_undoable_events);
@@ -28,13 +33,16 @@ impl Plugin for EventsPlugin {
         });
 
         app.init_resource::<CurrentFrameUndoableEvents>();
+        app.init_resource::<HistoryJumpTarget>();
 
         app.observe(handle_undoable);
         app.observe(handle_undo);
         app.observe(handle_redo);
+        app.observe(handle_history_jump_request);
 
         app.observe(edge_events::add_edge);
         app.observe(edge_events::remove_edge);
+        app.observe(edge_events::splice_node_onto_edge);
 
         app.observe(field_events::handle_set_input_field);
         app.observe(field_events::handle_set_output_field);
@@ -48,6 +56,18 @@ impl Plugin for EventsPlugin {
         app.observe(node_events::add_node);
         app.observe(node_events::add_node_from_undo);
         app.observe(node_events::drag_node_from_undo);
+        app.observe(node_events::reseed_all);
+
+        app.observe(comment_events::add_comment);
+        app.observe(comment_events::add_comment_from_undo);
+        app.observe(comment_events::remove_comment);
+        app.observe(comment_events::remove_comment_from_undo);
+        app.observe(comment_events::set_comment);
+        app.observe(group_events::add_group);
+        app.observe(group_events::add_group_from_undo);
+        app.observe(group_events::remove_group);
+        app.observe(group_events::remove_group_from_undo);
+        app.observe(group_events::set_group);
     }
 }
 
@@ -63,6 +83,109 @@ pub enum UndoableEvent {
     SetInputField(UndoableSetInputFieldEvent),
     SetOutputField(UndoableSetOutputFieldEvent),
     DragNode(UndoableDragNodeEvent),
+    AddComment(UndoableAddCommentEvent),
+    RemoveComment(UndoableRemoveCommentEvent),
+    SetComment(SetCommentEvent),
+    AddGroup(UndoableAddGroupEvent),
+    RemoveGroup(UndoableRemoveGroupEvent),
+    SetGroup(SetGroupEvent),
+}
+
+impl UndoableEvent {
+    // A short label for this step, for the history panel and undo/redo tooltips
+    pub fn description(&self) -> String {
+        match self {
+            UndoableEvent::AddNode(e) => format!("Add {} node", node_kind_name(&e.node.kind)),
+            UndoableEvent::RemoveNode(e) => format!("Delete {} node", node_kind_name(&e.node.kind)),
+            UndoableEvent::AddEdge(_) => String::from("Connect edge"),
+            UndoableEvent::RemoveEdge(_) => String::from("Delete edge"),
+            UndoableEvent::SetInputMeta(e) => port_meta_description("input", &e.meta, &e.old_meta),
+            UndoableEvent::SetOutputMeta(e) => port_meta_description("output", &e.meta, &e.old_meta),
+            UndoableEvent::SetInputField(e) => format!("Set {}", e.input_id.1.replace('_', " ")),
+            UndoableEvent::SetOutputField(e) => format!("Set {}", e.output_id.1.replace('_', " ")),
+            UndoableEvent::DragNode(_) => String::from("Move node"),
+            UndoableEvent::AddComment(_) => String::from("Add comment"),
+            UndoableEvent::RemoveComment(_) => String::from("Delete comment"),
+            UndoableEvent::SetComment(_) => String::from("Edit comment"),
+            UndoableEvent::AddGroup(_) => String::from("Add group"),
+            UndoableEvent::RemoveGroup(_) => String::from("Delete group"),
+            UndoableEvent::SetGroup(_) => String::from("Edit group"),
+        }
+    }
+
+    // (verb, noun) used when several events are summarized together
+    fn summary_parts(&self) -> (&'static str, &'static str) {
+        match self {
+            UndoableEvent::AddNode(_) => ("Add", "node"),
+            UndoableEvent::RemoveNode(_) => ("Delete", "node"),
+            UndoableEvent::AddEdge(_) => ("Connect", "edge"),
+            UndoableEvent::RemoveEdge(_) => ("Delete", "edge"),
+            UndoableEvent::SetInputMeta(_) | UndoableEvent::SetOutputMeta(_) => ("Change", "port"),
+            UndoableEvent::SetInputField(_) | UndoableEvent::SetOutputField(_) => ("Set", "value"),
+            UndoableEvent::DragNode(_) => ("Move", "node"),
+            UndoableEvent::AddComment(_) => ("Add", "comment"),
+            UndoableEvent::RemoveComment(_) => ("Delete", "comment"),
+            UndoableEvent::SetComment(_) => ("Edit", "comment"),
+            UndoableEvent::AddGroup(_) => ("Add", "group"),
+            UndoableEvent::RemoveGroup(_) => ("Delete", "group"),
+            UndoableEvent::SetGroup(_) => ("Edit", "group"),
+        }
+    }
+}
+
+fn port_meta_description(direction: &str, meta: &FieldMeta, old_meta: &FieldMeta) -> String {
+    match (old_meta.visible, meta.visible) {
+        (false, true) => format!("Show {} port", direction),
+        (true, false) => format!("Hide {} port", direction),
+        _ => format!("Change {} port", direction),
+    }
+}
+
+// Summarizes one undo step, e.g. "Delete 2 nodes, 3 edges"
+pub fn describe_events(events: &[UndoableEvent]) -> String {
+    if let [event] = events {
+        return event.description();
+    }
+
+    // counts grouped by verb, then noun, in the order they first appear
+    let mut groups: Vec<(&'static str, Vec<(&'static str, usize)>)> = vec![];
+
+    for event in events {
+        let (verb, noun) = event.summary_parts();
+
+        let nouns = match groups.iter().position(|(group_verb, _)| *group_verb == verb) {
+            Some(index) => &mut groups[index].1,
+            None => {
+                groups.push((verb, vec![]));
+                &mut groups.last_mut().unwrap().1
+            }
+        };
+
+        match nouns.iter_mut().find(|(group_noun, _)| *group_noun == noun) {
+            Some((_, count)) => *count += 1,
+            None => nouns.push((noun, 1)),
+        }
+    }
+
+    if groups.is_empty() {
+        return String::from("Nothing");
+    }
+
+    groups
+        .iter()
+        .map(|(verb, nouns)| {
+            let counted: Vec<String> = nouns
+                .iter()
+                .map(|(noun, count)| {
+                    let plural = if *count == 1 { "" } else { "s" };
+                    format!("{} {}{}", count, noun, plural)
+                })
+                .collect();
+
+            format!("{} {}", verb, counted.join(", "))
+        })
+        .collect::<Vec<String>>()
+        .join("; ")
 }
 
 impl From<AddEdgeEvent> for UndoableEvent {
@@ -119,12 +242,59 @@ impl From<UndoableDragNodeEvent> for UndoableEvent {
     }
 }
 
+impl From<UndoableAddCommentEvent> for UndoableEvent {
+    fn from(event: UndoableAddCommentEvent) -> Self {
+        UndoableEvent::AddComment(event)
+    }
+}
+
+impl From<UndoableRemoveCommentEvent> for UndoableEvent {
+    fn from(event: UndoableRemoveCommentEvent) -> Self {
+        UndoableEvent::RemoveComment(event)
+    }
+}
+
+impl From<SetCommentEvent> for UndoableEvent {
+    fn from(event: SetCommentEvent) -> Self {
+        UndoableEvent::SetComment(event)
+    }
+}
+
+impl From<UndoableAddGroupEvent> for UndoableEvent {
+    fn from(event: UndoableAddGroupEvent) -> Self {
+        UndoableEvent::AddGroup(event)
+    }
+}
+
+impl From<UndoableRemoveGroupEvent> for UndoableEvent {
+    fn from(event: UndoableRemoveGroupEvent) -> Self {
+        UndoableEvent::RemoveGroup(event)
+    }
+}
+
+impl From<SetGroupEvent> for UndoableEvent {
+    fn from(event: SetGroupEvent) -> Self {
+        UndoableEvent::SetGroup(event)
+    }
+}
+
 #[derive(Resource)]
 pub struct HistoricalActions {
     actions: Vec<Vec<UndoableEvent>>,
     current_index: usize,
 }
 
+impl HistoricalActions {
+    pub fn actions(&self) -> &[Vec<UndoableEvent>] {
+        &self.actions
+    }
+
+    // the number of actions currently applied; actions at or past this index are redoable
+    pub fn current_index(&self) -> usize {
+        self.current_index
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct CurrentFrameUndoableEvents {
     events: Vec<UndoableEvent>,
@@ -141,6 +311,7 @@ fn handle_undoable(
 fn flush_undoable_events(
     mut current_frame_events: ResMut<CurrentFrameUndoableEvents>,
     mut history: ResMut<HistoricalActions>,
+    mut jump_target: ResMut<HistoryJumpTarget>,
 ) {
     if !current_frame_events.events.is_empty() && !current_frame_events.is_undo_or_redo {
         let events = std::mem::take(&mut current_frame_events.events);
@@ -150,6 +321,9 @@ fn flush_undoable_events(
 
         history.actions.push(events);
         history.current_index += 1;
+
+        // a fresh action abandons any jump still being walked
+        jump_target.0 = None;
     }
 
     current_frame_events.events.clear();
@@ -172,6 +346,42 @@ fn handle_undo_redo_input(
     }
 }
 
+// Jump to the point in history where `index` actions are applied
+#[derive(Event, Clone)]
+pub struct RequestHistoryJump {
+    pub index: usize,
+}
+
+// Only one undo or redo can happen per frame, so a jump is walked one step per frame
+#[derive(Resource, Default)]
+struct HistoryJumpTarget(Option<usize>);
+
+fn handle_history_jump_request(
+    trigger: Trigger<RequestHistoryJump>,
+    history: Res<HistoricalActions>,
+    mut jump_target: ResMut<HistoryJumpTarget>,
+) {
+    jump_target.0 = Some(trigger.event().index.min(history.actions.len()));
+}
+
+fn step_towards_history_target(
+    mut commands: Commands,
+    history: Res<HistoricalActions>,
+    mut jump_target: ResMut<HistoryJumpTarget>,
+) {
+    let Some(target) = jump_target.0 else {
+        return;
+    };
+
+    if target > history.actions.len() || target == history.current_index {
+        jump_target.0 = None;
+    } else if target < history.current_index {
+        commands.trigger(RequestUndo);
+    } else {
+        commands.trigger(RequestRedo);
+    }
+}
+
 #[derive(Event, Clone)]
 pub struct RequestUndo;
 
@@ -246,17 +456,43 @@ fn handle_undo(
                     UndoableEvent::AddNode(e) => {
                         commands.trigger(RemoveNodeEvent {
                             node_entity: e.node_entity,
+                            bridge: false,
                         });
                     }
                     UndoableEvent::RemoveNode(e) => commands.trigger(UndoableAddNodeEvent {
                         node: e.node.clone(),
                         node_entity: e.node_entity,
+                        node_id: e.node_id,
                     }),
                     UndoableEvent::DragNode(e) => commands.trigger(UndoableDragNodeEvent {
                         node_entity: e.node_entity,
                         old_position: e.new_position,
                         new_position: e.old_position,
                     }),
+                    UndoableEvent::AddComment(e) => commands.trigger(RemoveCommentEvent {
+                        comment_entity: e.comment_entity,
+                    }),
+                    UndoableEvent::RemoveComment(e) => commands.trigger(UndoableAddCommentEvent {
+                        comment_entity: e.comment_entity,
+                        data: e.data.clone(),
+                    }),
+                    UndoableEvent::SetComment(e) => commands.trigger(SetCommentEvent {
+                        comment_entity: e.comment_entity,
+                        old_data: e.new_data.clone(),
+                        new_data: e.old_data.clone(),
+                    }),
+                    UndoableEvent::AddGroup(e) => commands.trigger(RemoveGroupEvent {
+                        group_entity: e.group_entity,
+                    }),
+                    UndoableEvent::RemoveGroup(e) => commands.trigger(UndoableAddGroupEvent {
+                        group_entity: e.group_entity,
+                        data: e.data.clone(),
+                    }),
+                    UndoableEvent::SetGroup(e) => commands.trigger(SetGroupEvent {
+                        group_entity: e.group_entity,
+                        old_data: e.new_data.clone(),
+                        new_data: e.old_data.clone(),
+                    }),
                 }
             }
         }
@@ -309,6 +545,24 @@ fn handle_redo(
                     UndoableEvent::DragNode(e) => {
                         commands.trigger(e.clone());
                     }
+                    UndoableEvent::AddComment(e) => {
+                        commands.trigger(e.clone());
+                    }
+                    UndoableEvent::RemoveComment(e) => {
+                        commands.trigger(e.clone());
+                    }
+                    UndoableEvent::SetComment(e) => {
+                        commands.trigger(e.clone());
+                    }
+                    UndoableEvent::AddGroup(e) => {
+                        commands.trigger(e.clone());
+                    }
+                    UndoableEvent::RemoveGroup(e) => {
+                        commands.trigger(e.clone());
+                    }
+                    UndoableEvent::SetGroup(e) => {
+                        commands.trigger(e.clone());
+                    }
                 }
             }
         }
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use bevy_cosmic_edit::CosmicFontSystem;
+
+use crate::{
+    nodes::Selected,
+    ui::comments::{Comment, CommentData},
+};
+
+use super::UndoableEvent;
+
+#[derive(Event, Clone)]
+pub struct AddCommentEvent {
+    pub data: CommentData,
+}
+
+pub fn add_comment(
+    trigger: Trigger<AddCommentEvent>,
+    mut commands: Commands,
+    mut font_system: ResMut<CosmicFontSystem>,
+) {
+    let data = trigger.event().data.clone();
+    let comment_entity = Comment::spawn(&mut commands, &mut font_system, data.clone());
+
+    commands.trigger(UndoableEvent::from(UndoableAddCommentEvent {
+        comment_entity,
+        data,
+    }));
+}
+
+#[derive(Event, Clone)]
+pub struct UndoableAddCommentEvent {
+    pub comment_entity: Entity,
+    pub data: CommentData,
+}
+
+// Brings back a comment that was hidden by a removal
+pub fn add_comment_from_undo(trigger: Trigger<UndoableAddCommentEvent>, mut commands: Commands) {
+    commands
+        .entity(trigger.event().comment_entity)
+        .insert(Comment {
+            data: trigger.event().data.clone(),
+        })
+        .insert(Visibility::Inherited);
+}
+
+#[derive(Event, Clone)]
+pub struct RemoveCommentEvent {
+    pub comment_entity: Entity,
+}
+
+pub fn remove_comment(
+    trigger: Trigger<RemoveCommentEvent>,
+    mut commands: Commands,
+    q_comments: Query<&Comment>,
+) {
+    let comment_entity = trigger.event().comment_entity;
+
+    if let Ok(comment) = q_comments.get(comment_entity) {
+        // like nodes, the entity is kept around so undo can bring it back
+        commands
+            .entity(comment_entity)
+            .remove::<Comment>()
+            .remove::<Selected>()
+            .insert(Visibility::Hidden);
+
+        commands.trigger(UndoableEvent::from(UndoableRemoveCommentEvent {
+            comment_entity,
+            data: comment.data.clone(),
+        }));
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct UndoableRemoveCommentEvent {
+    pub comment_entity: Entity,
+    pub data: CommentData,
+}
+
+pub fn remove_comment_from_undo(trigger: Trigger<UndoableRemoveCommentEvent>, mut commands: Commands) {
+    commands
+        .entity(trigger.event().comment_entity)
+        .remove::<Comment>()
+        .remove::<Selected>()
+        .insert(Visibility::Hidden);
+}
+
+// Moving, resizing, recoloring and retyping a comment all go through here
+#[derive(Event, Clone)]
+pub struct SetCommentEvent {
+    pub comment_entity: Entity,
+    pub old_data: CommentData,
+    pub new_data: CommentData,
+}
+
+pub fn set_comment(trigger: Trigger<SetCommentEvent>, mut commands: Commands, mut q_comments: Query<&mut Comment>) {
+    if let Ok(mut comment) = q_comments.get_mut(trigger.event().comment_entity) {
+        comment.data = trigger.event().new_data.clone();
+
+        if trigger.event().old_data != trigger.event().new_data {
+            commands.trigger(UndoableEvent::SetComment(trigger.event().clone()));
+        }
+    }
+}
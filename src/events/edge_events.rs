@@ -2,13 +2,15 @@ use bevy::prelude::*;
 use bevy_mod_picking::prelude::Pickable;
 
 use crate::{
+    asset::NODE_TEXTURE_DISPLAY_DIMENSION,
     graph::{
         AddEdgeChecked, DisjointPipelineGraph, Edge, RequestProcessPipeline, SerializableEdge,
     },
-    line_renderer::{generate_color_gradient, generate_curved_line, Line},
+    line_renderer::{generate_color_gradient, generate_edge_line, Line, EDGE_LINE_SEGMENTS},
     nodes::{
-        fields::FieldMeta, ports::{port_color, InputPort, OutputPort}, EdgeLine, InputId, NodeDisplay, NodeIdMapping, NodeTrait, OutputId
+        fields::{can_convert_field, field_color, FieldMeta}, ports::{InputPort, OutputPort}, EdgeLine, InputId, EDGE_LINE_THICKNESS, NodeDisplay, NodeIdMapping, NodeTrait, OutputId
     },
+    preferences::UiPreferences,
 };
 
 use super::UndoableEvent;
@@ -43,6 +45,7 @@ pub fn add_edge(
     q_output_ports: Query<(Entity, &GlobalTransform, &OutputPort)>,
     mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
     node_id_map: Res<NodeIdMapping>,
+    ui_preferences: Res<UiPreferences>,
 ) {
     let mut pipeline = q_pipeline.single_mut();
 
@@ -69,7 +72,17 @@ pub fn add_edge(
                 .node_weight(to_node_display.index)
                 .expect("Forgot to add the serialized nodes to the graph?");
     
-            let edge = Edge::from_serializable(&ev.edge, &from_node.kind, &to_node.kind);
+            // the rest of the project still loads without the connection
+            let edge = match Edge::from_serializable(&ev.edge, &from_node.kind, &to_node.kind) {
+                Ok(edge) => edge,
+                Err(e) => {
+                    eprintln!(
+                        "Dropped the connection from {}.{} to {}.{}: {}",
+                        ev.edge.from_field.0, ev.edge.from_field.1, ev.edge.to_field.0, ev.edge.to_field.1, e
+                    );
+                    return;
+                }
+            };
             &AddNodeEdge {
                 start_node: edge.from_node,
                 start_id: edge.from_field,
@@ -126,7 +139,7 @@ pub fn add_edge(
         Ok(()) => {
             let start = start_port_transfom.translation().truncate();
             let end = end_port_transform.translation().truncate();
-            let curve_points = generate_curved_line(start, end, 50);
+            let curve_points = generate_edge_line(ui_preferences.edge_style, ui_preferences.orientation, start, end, EDGE_LINE_SEGMENTS);
 
             // cloning so we can borrow mutably from the graph....can that be improved?
             let start_node = pipeline
@@ -140,14 +153,14 @@ pub fn add_edge(
             end_node.kind.set_input_meta(
                 end_port.input_id,
                 FieldMeta {
-                    visible: old_input_field_meta.visible,
                     storage: end_node.kind.get_input(end_port.input_id).unwrap(),
+                    ..old_input_field_meta.clone()
                 },
             );
 
             let start_color =
-                port_color(&start_node.kind.get_output(start_port.output_id).unwrap());
-            let end_color = port_color(&end_node.kind.get_input(end_port.input_id).unwrap());
+                field_color(&start_node.kind.get_output(start_port.output_id).unwrap());
+            let end_color = field_color(&end_node.kind.get_input(end_port.input_id).unwrap());
 
             let curve_colors = generate_color_gradient(start_color, end_color, curve_points.len());
 
@@ -155,7 +168,8 @@ pub fn add_edge(
                 Line {
                     points: curve_points,
                     colors: curve_colors,
-                    thickness: 2.0,
+                    thickness: EDGE_LINE_THICKNESS,
+                    dim: 1.,
                 },
                 EdgeLine {
                     start_port: start_port_entity,
@@ -262,3 +276,128 @@ pub fn remove_edge(
         ev_process_pipeline.send(RequestProcessPipeline);
     }
 }
+
+// How close to a wire a dropped node's center has to land for it to splice in
+const SPLICE_DISTANCE: f32 = NODE_TEXTURE_DISPLAY_DIMENSION / 2.;
+
+// Dropping an unconnected node onto a wire routes that connection through it
+#[derive(Event, Clone, Debug)]
+pub struct SpliceNodeOntoEdgeEvent {
+    pub node_entity: Entity,
+}
+
+pub fn splice_node_onto_edge(
+    trigger: Trigger<SpliceNodeOntoEdgeEvent>,
+    mut commands: Commands,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_nodes: Query<(&NodeDisplay, &Transform)>,
+    q_edges: Query<(&Line, &EdgeLine)>,
+    q_input_ports: Query<&InputPort>,
+    q_output_ports: Query<&OutputPort>,
+) {
+    let pipeline = q_pipeline.single();
+    let node_entity = trigger.event().node_entity;
+
+    let Ok((node_display, node_transform)) = q_nodes.get(node_entity) else {
+        return;
+    };
+
+    // only a free-floating node splices, anything already wired up is just being moved around
+    if pipeline.graph.neighbors_undirected(node_display.index).next().is_some() {
+        return;
+    }
+
+    let Some(node) = pipeline.graph.node_weight(node_display.index) else {
+        return;
+    };
+
+    let center = node_transform.translation.truncate();
+    let nearest_edge = q_edges
+        .iter()
+        .map(|(line, edge_line)| (distance_to_polyline(center, &line.points), edge_line))
+        .filter(|(distance, _)| *distance <= SPLICE_DISTANCE)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    let Some((_, edge_line)) = nearest_edge else {
+        return;
+    };
+
+    let (Ok(start_port), Ok(end_port)) = (
+        q_output_ports.get(edge_line.start_port),
+        q_input_ports.get(edge_line.end_port),
+    ) else {
+        return;
+    };
+
+    let (Ok((start_display, _)), Ok((end_display, _))) =
+        (q_nodes.get(start_port.node_entity), q_nodes.get(end_port.node_entity))
+    else {
+        return;
+    };
+
+    let Some(from_value) = pipeline
+        .graph
+        .node_weight(start_display.index)
+        .and_then(|start_node| start_node.kind.get_output(start_port.output_id))
+    else {
+        return;
+    };
+
+    let Some(to_value) = pipeline
+        .graph
+        .node_weight(end_display.index)
+        .and_then(|end_node| end_node.kind.get_input(end_port.input_id))
+    else {
+        return;
+    };
+
+    let maybe_input_id = node.kind.input_fields().iter().find(|input_id| {
+        node.kind.get_input(**input_id).map_or(false, |input| can_convert_field(&from_value, &input))
+    });
+    let maybe_output_id = node.kind.output_fields().iter().find(|output_id| {
+        node.kind.get_output(**output_id).map_or(false, |output| can_convert_field(&output, &to_value))
+    });
+
+    let (Some(input_id), Some(output_id)) = (maybe_input_id, maybe_output_id) else {
+        return;
+    };
+
+    // all three land in the same frame as the drag, so undo takes the whole splice back in one step
+    commands.trigger(RemoveEdgeEvent {
+        start_node: start_port.node_entity,
+        start_id: start_port.output_id,
+        end_node: end_port.node_entity,
+        end_id: end_port.input_id,
+    });
+
+    commands.trigger(AddEdgeEvent::FromNodes(AddNodeEdge {
+        start_node: start_port.node_entity,
+        start_id: start_port.output_id,
+        end_node: node_entity,
+        end_id: *input_id,
+    }));
+
+    commands.trigger(AddEdgeEvent::FromNodes(AddNodeEdge {
+        start_node: node_entity,
+        start_id: *output_id,
+        end_node: end_port.node_entity,
+        end_id: end_port.input_id,
+    }));
+}
+
+fn distance_to_polyline(point: Vec2, points: &[Vec2]) -> f32 {
+    points
+        .windows(2)
+        .map(|segment| {
+            let (a, b) = (segment[0], segment[1]);
+            let ab = b - a;
+            let t = if ab.length_squared() > 0. {
+                ((point - a).dot(ab) / ab.length_squared()).clamp(0., 1.)
+            } else {
+                0.
+            };
+
+            point.distance(a + ab * t)
+        })
+        .fold(f32::INFINITY, f32::min)
+}
@@ -101,39 +101,21 @@ pub fn handle_set_input_field_meta(
     q_input_ports: Query<&InputPort>,
 ) {
     let mut pipeline = q_pipeline.single_mut();
-    if let Ok(input_port) = q_input_ports.get(trigger.event().input_port) {
-        let input_node_index = q_nodes.get(input_port.node_entity).unwrap().index;
-
-        if let Some(node) = pipeline.graph.node_weight_mut(input_node_index) {
-            if let Some(old_meta) = node.kind.get_input_meta(input_port.input_id) {
-                let old_meta_copy = old_meta.clone();
-                node.kind
-                    .set_input_meta(input_port.input_id, trigger.event().meta.clone());
-
-                // Find the correct switch entity and update it
-                for (mut switch, mut background_color) in q_switches.iter_mut() {
-                    if switch.input_port == trigger.event().input_port {
-                        switch.is_visible = trigger.event().meta.visible;
-                        *background_color = if trigger.event().meta.visible {
-                            GREEN.into()
-                        } else {
-                            RED.into()
-                        };
-                        break;
-                    }
-                }
-
-                commands.trigger(RequestInputPortRelayout {
-                    node_entity: input_port.node_entity,
-                });
-
-                commands.trigger(UndoableEvent::from(UndoableSetInputFieldMetaEvent {
-                    input_port: trigger.event().input_port,
-                    meta: trigger.event().meta.clone(),
-                    old_meta: old_meta_copy,
-                }));
-            }
-        }
+
+    if let Some((meta, old_meta)) = apply_input_field_meta(
+        &mut commands,
+        &mut pipeline,
+        &q_nodes,
+        &mut q_switches,
+        &q_input_ports,
+        trigger.event().input_port,
+        &trigger.event().meta,
+    ) {
+        commands.trigger(UndoableEvent::from(UndoableSetInputFieldMetaEvent {
+            input_port: trigger.event().input_port,
+            meta,
+            old_meta,
+        }));
     }
 }
 
@@ -153,32 +135,56 @@ pub fn handle_set_input_field_meta_from_undo(
     q_input_ports: Query<&InputPort>,
 ) {
     let mut pipeline = q_pipeline.single_mut();
-    if let Ok(input_port) = q_input_ports.get(trigger.event().input_port) {
-        let input_node_index = q_nodes.get(input_port.node_entity).unwrap().index;
-
-        if let Some(node) = pipeline.graph.node_weight_mut(input_node_index) {
-            if let Some(_) = node.kind.get_input_meta(input_port.input_id) {
-                node.kind
-                    .set_input_meta(input_port.input_id, trigger.event().meta.clone());
-
-                for (mut switch, mut background_color) in q_switches.iter_mut() {
-                    if switch.input_port == trigger.event().input_port {
-                        switch.is_visible = trigger.event().meta.visible;
-                        *background_color = if trigger.event().meta.visible {
-                            GREEN.into()
-                        } else {
-                            RED.into()
-                        };
-                        break;
-                    }
-                }
-
-                commands.trigger(RequestInputPortRelayout {
-                    node_entity: input_port.node_entity,
-                });
-            }
+
+    apply_input_field_meta(
+        &mut commands,
+        &mut pipeline,
+        &q_nodes,
+        &mut q_switches,
+        &q_input_ports,
+        trigger.event().input_port,
+        &trigger.event().meta,
+    );
+}
+
+// Shared by the original event and its undo/redo so both update the switch and relayout the same way.
+// The stored value belongs to the edge that may be attached, so it is never replaced by a meta change.
+// Returns the applied meta and the meta it replaced.
+fn apply_input_field_meta(
+    commands: &mut Commands,
+    pipeline: &mut DisjointPipelineGraph,
+    q_nodes: &Query<&NodeDisplay>,
+    q_switches: &mut Query<(&mut InputPortVisibilitySwitch, &mut BackgroundColor)>,
+    q_input_ports: &Query<&InputPort>,
+    input_port_entity: Entity,
+    meta: &FieldMeta,
+) -> Option<(FieldMeta, FieldMeta)> {
+    let input_port = q_input_ports.get(input_port_entity).ok()?;
+    let input_node_index = q_nodes.get(input_port.node_entity).ok()?.index;
+    let node = pipeline.graph.node_weight_mut(input_node_index)?;
+    let old_meta = node.kind.get_input_meta(input_port.input_id)?.clone();
+
+    let meta = FieldMeta {
+        storage: old_meta.storage.clone(),
+        ..meta.clone()
+    };
+
+    node.kind.set_input_meta(input_port.input_id, meta.clone());
+
+    // Find the correct switch entity and update it
+    for (mut switch, mut background_color) in q_switches.iter_mut() {
+        if switch.input_port == input_port_entity {
+            switch.is_visible = meta.visible;
+            *background_color = visibility_switch_color(meta.visible);
+            break;
         }
     }
+
+    commands.trigger(RequestInputPortRelayout {
+        node_entity: input_port.node_entity,
+    });
+
+    Some((meta, old_meta))
 }
 
 #[derive(Event, Clone, Debug)]
@@ -196,38 +202,21 @@ pub fn handle_set_output_field_meta(
     q_output_ports: Query<&OutputPort>,
 ) {
     let mut pipeline = q_pipeline.single_mut();
-    if let Ok(output_port) = q_output_ports.get(trigger.event().output_port) {
-        let output_node_index = q_nodes.get(output_port.node_entity).unwrap().index;
-        if let Some(node) = pipeline.graph.node_weight_mut(output_node_index) {
-            if let Some(old_meta) = node.kind.get_output_meta(output_port.output_id) {
-                let old_meta_copy = old_meta.clone();
-                node.kind
-                    .set_output_meta(output_port.output_id, trigger.event().meta.clone());
-
-                // Find the correct switch entity and update it
-                for (mut switch, mut background_color) in q_switches.iter_mut() {
-                    if switch.output_port == trigger.event().output_port {
-                        switch.is_visible = trigger.event().meta.visible;
-                        *background_color = if trigger.event().meta.visible {
-                            GREEN.into()
-                        } else {
-                            RED.into()
-                        };
-                        break;
-                    }
-                }
-
-                commands.trigger(RequestOutputPortRelayout {
-                    node_entity: output_port.node_entity,
-                });
-
-                commands.trigger(UndoableEvent::from(UndoableSetOutputFieldMetaEvent {
-                    output_port: trigger.event().output_port,
-                    meta: trigger.event().meta.clone(),
-                    old_meta: old_meta_copy,
-                }));
-            }
-        }
+
+    if let Some((meta, old_meta)) = apply_output_field_meta(
+        &mut commands,
+        &mut pipeline,
+        &q_nodes,
+        &mut q_switches,
+        &q_output_ports,
+        trigger.event().output_port,
+        &trigger.event().meta,
+    ) {
+        commands.trigger(UndoableEvent::from(UndoableSetOutputFieldMetaEvent {
+            output_port: trigger.event().output_port,
+            meta,
+            old_meta,
+        }));
     }
 }
 
@@ -247,29 +236,59 @@ pub fn handle_set_output_field_meta_from_undo(
     q_output_ports: Query<&OutputPort>,
 ) {
     let mut pipeline = q_pipeline.single_mut();
-    if let Ok(output_port) = q_output_ports.get(trigger.event().output_port) {
-        let output_node_index = q_nodes.get(output_port.node_entity).unwrap().index;
-        if let Some(node) = pipeline.graph.node_weight_mut(output_node_index) {
-            if let Some(_) = node.kind.get_output_meta(output_port.output_id) {
-                node.kind
-                    .set_output_meta(output_port.output_id, trigger.event().meta.clone());
-
-                for (mut switch, mut background_color) in q_switches.iter_mut() {
-                    if switch.output_port == trigger.event().output_port {
-                        switch.is_visible = trigger.event().meta.visible;
-                        *background_color = if trigger.event().meta.visible {
-                            GREEN.into()
-                        } else {
-                            RED.into()
-                        };
-                        break;
-                    }
-                }
-
-                commands.trigger(RequestOutputPortRelayout {
-                    node_entity: output_port.node_entity,
-                });
-            }
+
+    apply_output_field_meta(
+        &mut commands,
+        &mut pipeline,
+        &q_nodes,
+        &mut q_switches,
+        &q_output_ports,
+        trigger.event().output_port,
+        &trigger.event().meta,
+    );
+}
+
+fn apply_output_field_meta(
+    commands: &mut Commands,
+    pipeline: &mut DisjointPipelineGraph,
+    q_nodes: &Query<&NodeDisplay>,
+    q_switches: &mut Query<(&mut OutputPortVisibilitySwitch, &mut BackgroundColor)>,
+    q_output_ports: &Query<&OutputPort>,
+    output_port_entity: Entity,
+    meta: &FieldMeta,
+) -> Option<(FieldMeta, FieldMeta)> {
+    let output_port = q_output_ports.get(output_port_entity).ok()?;
+    let output_node_index = q_nodes.get(output_port.node_entity).ok()?.index;
+    let node = pipeline.graph.node_weight_mut(output_node_index)?;
+    let old_meta = node.kind.get_output_meta(output_port.output_id)?.clone();
+
+    let meta = FieldMeta {
+        storage: old_meta.storage.clone(),
+        ..meta.clone()
+    };
+
+    node.kind.set_output_meta(output_port.output_id, meta.clone());
+
+    // Find the correct switch entity and update it
+    for (mut switch, mut background_color) in q_switches.iter_mut() {
+        if switch.output_port == output_port_entity {
+            switch.is_visible = meta.visible;
+            *background_color = visibility_switch_color(meta.visible);
+            break;
         }
     }
+
+    commands.trigger(RequestOutputPortRelayout {
+        node_entity: output_port.node_entity,
+    });
+
+    Some((meta, old_meta))
+}
+
+fn visibility_switch_color(is_visible: bool) -> BackgroundColor {
+    if is_visible {
+        GREEN.into()
+    } else {
+        RED.into()
+    }
 }
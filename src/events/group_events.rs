@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use bevy_cosmic_edit::CosmicFontSystem;
+
+use crate::ui::groups::{Group, GroupData};
+
+use super::UndoableEvent;
+
+#[derive(Event, Clone)]
+pub struct AddGroupEvent {
+    pub data: GroupData,
+}
+
+pub fn add_group(
+    trigger: Trigger<AddGroupEvent>,
+    mut commands: Commands,
+    mut font_system: ResMut<CosmicFontSystem>,
+) {
+    let data = trigger.event().data.clone();
+    let group_entity = Group::spawn(&mut commands, &mut font_system, data.clone());
+
+    commands.trigger(UndoableEvent::from(UndoableAddGroupEvent {
+        group_entity,
+        data,
+    }));
+}
+
+#[derive(Event, Clone)]
+pub struct UndoableAddGroupEvent {
+    pub group_entity: Entity,
+    pub data: GroupData,
+}
+
+// Brings back a group that was hidden by a removal
+pub fn add_group_from_undo(trigger: Trigger<UndoableAddGroupEvent>, mut commands: Commands) {
+    commands
+        .entity(trigger.event().group_entity)
+        .insert(Group {
+            data: trigger.event().data.clone(),
+        })
+        .insert(Visibility::Inherited);
+}
+
+#[derive(Event, Clone)]
+pub struct RemoveGroupEvent {
+    pub group_entity: Entity,
+}
+
+pub fn remove_group(
+    trigger: Trigger<RemoveGroupEvent>,
+    mut commands: Commands,
+    q_groups: Query<&Group>,
+) {
+    let group_entity = trigger.event().group_entity;
+
+    if let Ok(group) = q_groups.get(group_entity) {
+        // like nodes, the entity is kept around so undo can bring it back
+        commands
+            .entity(group_entity)
+            .remove::<Group>()
+            .insert(Visibility::Hidden);
+
+        commands.trigger(UndoableEvent::from(UndoableRemoveGroupEvent {
+            group_entity,
+            data: group.data.clone(),
+        }));
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct UndoableRemoveGroupEvent {
+    pub group_entity: Entity,
+    pub data: GroupData,
+}
+
+pub fn remove_group_from_undo(trigger: Trigger<UndoableRemoveGroupEvent>, mut commands: Commands) {
+    commands
+        .entity(trigger.event().group_entity)
+        .remove::<Group>()
+        .insert(Visibility::Hidden);
+}
+
+// Moving, resizing, recoloring and renaming a group all go through here
+#[derive(Event, Clone)]
+pub struct SetGroupEvent {
+    pub group_entity: Entity,
+    pub old_data: GroupData,
+    pub new_data: GroupData,
+}
+
+pub fn set_group(trigger: Trigger<SetGroupEvent>, mut commands: Commands, mut q_groups: Query<&mut Group>) {
+    if let Ok(mut group) = q_groups.get_mut(trigger.event().group_entity) {
+        group.data = trigger.event().new_data.clone();
+
+        if trigger.event().old_data != trigger.event().new_data {
+            commands.trigger(UndoableEvent::SetGroup(trigger.event().clone()));
+        }
+    }
+}
@@ -2,43 +2,61 @@ use std::time::Duration;
 
 use crate::{
     asset::{
-        FontAssets, GeneratedMeshes, NodeDisplayMaterial, PortMaterial, ShaderAssets, NODE_CONTENT_PADDING, NODE_TEXTURE_DISPLAY_DIMENSION, NODE_TITLE_BAR_SIZE, NODE_WIDTH
+        FontAssets, GeneratedMeshes, NodeDisplayMaterial, PortMaterial, ShaderAssets, NODE_CONTENT_PADDING, NODE_SHADOW_OFFSET, NODE_TEXTURE_DISPLAY_DIMENSION, NODE_TITLE_BAR_SIZE, NODE_WIDTH
     },
     graph::{DisjointPipelineGraph, Edge, RequestProcessPipeline},
     nodes::{
-        kinds::{blend::BlendNode, color::ColorNode, example::ExampleNode, shape::{Shape, ShapeNode}}, node_kind_name, ports::{InputPort, OutputPort, PortMaterialIndex, RequestInputPortRelayout, RequestOutputPortRelayout}, shared::shader_source, EdgeLine, GraphNode, GraphNodeKind, NodeCount, NodeDisplay, NodeId, NodeIdMapping, NodeProcessText, NodeTrait, RequestSpawnNodeKind, Selected, SerializableGraphNode, SerializableGraphNodeKind
+        custom_shader::load_custom_shader, fields::{can_convert_field, Field}, image_decode::spawn_image_decode, kinds::{blend::BlendNode, color::ColorNode, dither::DitherNode, example::ExampleNode, image::ImageNode, shape::ShapeNode, levels::LevelsNode, curves::CurvesNode, number::NumberNode, math::{MathNode, MathOperation}, clear::ClearNode, premultiply::{PremultiplyNode, UnpremultiplyNode}, convolve::ConvolveNode, sharpen::SharpenNode, displace::DisplaceNode, probe::ProbeNode, custom_compute::CustomComputeNode}, node_kind_name, ports::{InputPort, OutputPort, PortMaterialIndex, RequestInputPortRelayout, RequestOutputPortRelayout}, shared::{format_process_time, shader_source, ShaderError}, EdgeLine, NodeShadow, GraphNode, GraphNodeKind, NodeCount, NodeDisplay, NodeId, NodeIdMapping, InputId, NodePlacement, NodeProcessText, NodeTrait, OutputId, RequestSpawnNodeKind, Selected, SerializableGraphNode, SerializableGraphNodeKind
     },
+    preferences::{NodeDefaults, Preferences, UiPreferences},
     setup::{CustomGpuDevice, CustomGpuQueue},
-    ui::context_menu::UIContext,
+    ui::{context_menu::UIContext, menu_bar::Project},
+    validation::{NODE_BACKGROUND_COLOR, NODE_TITLE_BAR_COLOR},
 };
 use bevy::{
     color::palettes::{
-        css::{MAGENTA, ORANGE, RED, WHITE},
-        tailwind::{BLUE_600, GRAY_200, GRAY_400, GRAY_600, GRAY_800, SLATE_700, SLATE_800, SLATE_900},
+        css::{ORANGE, RED, WHITE},
+        tailwind::{BLUE_600, GRAY_200, GRAY_400, GRAY_600, GRAY_800, SLATE_900},
     },
+    ecs::system::SystemParam,
     prelude::*,
+    render::render_resource::TextureFormat,
     sprite::{Anchor, MaterialMesh2dBundle},
 };
-use bevy_mod_picking::focus::PickingInteraction;
+use bevy_mod_picking::{focus::PickingInteraction, prelude::Pickable};
+use petgraph::prelude::StableDiGraph;
 use uuid::Uuid;
-use wgpu::TextureFormat;
 
-use super::{edge_events::RemoveEdgeEvent, UndoableEvent};
+use super::{edge_events::{AddEdgeEvent, AddNodeEdge, RemoveEdgeEvent}, field_events::SetInputFieldEvent, UndoableEvent};
+
+// What building a node's GPU side takes
+#[derive(SystemParam)]
+pub struct NodeResources<'w> {
+    pub render_device: Res<'w, CustomGpuDevice>,
+    pub render_queue: Res<'w, CustomGpuQueue>,
+    pub shaders: Res<'w, Assets<Shader>>,
+    pub shader_handles: Res<'w, ShaderAssets>,
+}
 
 #[derive(Event, Clone, Debug)]
 pub struct RemoveNodeEvent {
     pub node_entity: Entity,
+    // Reconnect the node's neighbors when the bridge_on_delete preference is on. Only user deletes do,
+    // so clearing a graph or undoing an add doesn't wire up nodes that are going away.
+    pub bridge: bool,
 }
 
 pub fn remove_node(
     trigger: Trigger<RemoveNodeEvent>,
     mut commands: Commands,
     mut q_pipeline: Query<&mut DisjointPipelineGraph>,
-    q_nodes: Query<(Entity, &NodeDisplay)>,
+    q_nodes: Query<(Entity, &NodeDisplay, &NodeId)>,
+    mut node_id_map: ResMut<NodeIdMapping>,
     mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+    ui_preferences: Res<UiPreferences>,
 ) {
     let mut pipeline = q_pipeline.single_mut();
-    let (node_entity, node_display) =
+    let (node_entity, node_display, node_id) =
         q_nodes.get(trigger.event().node_entity).unwrap();
 
     let removed_edges: Vec<Edge> = pipeline
@@ -54,6 +72,12 @@ pub fn remove_node(
         })
         .collect();
 
+    let bridge = if trigger.event().bridge && ui_preferences.bridge_on_delete {
+        find_bridge_edge(&pipeline.graph, &q_nodes, node_entity, &removed_edges)
+    } else {
+        None
+    };
+
     if let Some(removed_node) = pipeline.graph.remove_node(node_display.index) {
         for removed_edge in removed_edges.iter() {
             commands.trigger(RemoveEdgeEvent {
@@ -71,19 +95,57 @@ pub fn remove_node(
             .remove::<Selected>()
             .insert(Visibility::Hidden);
 
+        // the map only holds live nodes; undo puts the id back
+        node_id_map.0.remove(&node_id.0);
+
+        // triggered after the removals above, so it lands in the same undo step as the delete
+        if let Some(bridge) = bridge {
+            commands.trigger(AddEdgeEvent::FromNodes(bridge));
+        }
+
         commands.trigger(UndoableEvent::from(UndoableRemoveNodeEvent {
             node: removed_node,
             node_entity,
+            node_id: node_id.0,
         }));
 
         ev_process_pipeline.send(RequestProcessPipeline);
     }
 }
 
+// For a node in the middle of a chain, the edge that would join its single upstream node straight to its
+// single downstream node. None when it has more or fewer connections, or the two ends aren't compatible.
+fn find_bridge_edge(
+    graph: &StableDiGraph<GraphNode, Edge>,
+    q_nodes: &Query<(Entity, &NodeDisplay, &NodeId)>,
+    node_entity: Entity,
+    edges: &[Edge],
+) -> Option<AddNodeEdge> {
+    let (incoming, outgoing): (Vec<&Edge>, Vec<&Edge>) = edges.iter().partition(|edge| edge.to_node == node_entity);
+
+    let ([incoming], [outgoing]) = (incoming.as_slice(), outgoing.as_slice()) else {
+        return None;
+    };
+
+    let (_, from_display, _) = q_nodes.get(incoming.from_node).ok()?;
+    let (_, to_display, _) = q_nodes.get(outgoing.to_node).ok()?;
+
+    let from_value = graph.node_weight(from_display.index)?.kind.get_output(incoming.from_field)?;
+    let to_value = graph.node_weight(to_display.index)?.kind.get_input(outgoing.to_field)?;
+
+    can_convert_field(&from_value, &to_value).then_some(AddNodeEdge {
+        start_node: incoming.from_node,
+        start_id: incoming.from_field,
+        end_node: outgoing.to_node,
+        end_id: outgoing.to_field,
+    })
+}
+
 #[derive(Event, Clone)]
 pub struct UndoableRemoveNodeEvent {
     pub node: GraphNode,
     pub node_entity: Entity,
+    pub node_id: Uuid,
 }
 
 pub fn remove_node_from_undo(
@@ -94,10 +156,12 @@ pub fn remove_node_from_undo(
     q_edge_lines: Query<(Entity, &EdgeLine)>,
     q_input_ports: Query<(Entity, &InputPort)>,
     q_output_ports: Query<(Entity, &OutputPort)>,
+    mut node_id_map: ResMut<NodeIdMapping>,
     mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
 ) {
     let mut pipeline = q_pipeline.single_mut();
     let (node_entity, node_display) = q_nodes.get(trigger.event().node_entity).unwrap();
+    node_id_map.0.remove(&trigger.event().node_id);
 
     if let Some(_) = pipeline.graph.remove_node(node_display.index) {
         let node_ports: Vec<Entity> =
@@ -121,6 +185,7 @@ pub fn remove_node_from_undo(
         commands
             .entity(trigger.event().node_entity)
             .remove::<NodeDisplay>()
+            .remove::<Selected>()
             .insert(Visibility::Hidden);
 
         ev_process_pipeline.send(RequestProcessPipeline);
@@ -137,6 +202,22 @@ pub enum AddNodeEvent {
 pub struct AddNodeKind {
     pub position: Vec2,
     pub spawn_kind: RequestSpawnNodeKind,
+    pub connect: Option<ConnectOnSpawn>,
+}
+
+// An existing port to wire the new node to, through its first compatible field
+#[derive(Clone, Debug)]
+pub enum ConnectOnSpawn {
+    FromOutput {
+        node: Entity,
+        output_id: OutputId,
+        field: Field,
+    },
+    ToInput {
+        node: Entity,
+        input_id: InputId,
+        field: Field,
+    },
 }
 
 #[derive(Clone)]
@@ -149,10 +230,8 @@ pub fn add_node(
     trigger: Trigger<AddNodeEvent>,
     mut commands: Commands,
     mut q_pipeline: Query<&mut DisjointPipelineGraph>,
-    render_device: Res<CustomGpuDevice>,
-    render_queue: Res<CustomGpuQueue>,
-    shader_handles: Res<ShaderAssets>,
-    shaders: Res<Assets<Shader>>,
+    node_resources: NodeResources,
+    project: Res<Project>,
     mut images: ResMut<Assets<Image>>,
     mut node_display_materials: ResMut<Assets<NodeDisplayMaterial>>,
     mut port_materials: ResMut<Assets<PortMaterial>>,
@@ -161,12 +240,30 @@ pub fn add_node(
     mut node_count: ResMut<NodeCount>,
     fonts: Res<FontAssets>,
     mut node_id_map: ResMut<NodeIdMapping>,
+    preferences: Preferences,
+    placement: NodePlacement,
     mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
 ) {
     let mut pipeline = q_pipeline.single_mut();
+    let node_defaults = &preferences.node_defaults;
+    let ui_preferences = &preferences.ui;
+    let NodeResources { render_device, render_queue, shaders, shader_handles } = node_resources;
+    let texture_format = project.texture_format();
 
     let world_position = match trigger.event() {
-        AddNodeEvent::FromKind(ev) => ev.position,
+        // freshly stamped nodes are nudged off whatever they would cover; loads and undos go exactly where they were
+        AddNodeEvent::FromKind(ev) => {
+            // a node wired up as it spawns lines up with the node it's wired to, unless it's being placed precisely
+            let connected_position = match &ev.connect {
+                _ if placement.is_precise() => None,
+                Some(ConnectOnSpawn::FromOutput { node, .. }) => placement.connected_position(*node, true, ui_preferences.orientation),
+                Some(ConnectOnSpawn::ToInput { node, .. }) => placement.connected_position(*node, false, ui_preferences.orientation),
+                None => None,
+            };
+            let position = connected_position.unwrap_or(ev.position);
+
+            position + placement.free_offset(&[position])
+        }
         AddNodeEvent::FromSerialized(ev) => ev.node.position.truncate(),
     }.extend(node_count.0 as f32);
 
@@ -179,106 +276,65 @@ pub fn add_node(
 
     let node_entity = commands.spawn(placeholder_node_display).id();
     
-    let spawned_node_index = match trigger.event() {
+    let maybe_node = match trigger.event() {
         AddNodeEvent::FromKind(ev) => {
-            match ev.spawn_kind {
-                RequestSpawnNodeKind::Example => {
-                    let frag_shader = shader_source(&shaders, &shader_handles.default_frag);
-                    let vert_shader = shader_source(&shaders, &shader_handles.default_vert);
-                    let example_node = ExampleNode::new(
-                        node_entity,
-                        &render_device,
-                        &render_queue,
-                        &frag_shader,
-                        &vert_shader,
-                        512u32, // TODO: Is here where we want to choose and handle node defaults?
-                        TextureFormat::Rgba8Unorm,
-                    );
-        
-                    pipeline.graph.add_node(GraphNode {
-                        kind: GraphNodeKind::Example(example_node),
-                        last_process_time: Duration::ZERO,
-                    })
-                }
-                RequestSpawnNodeKind::Color => {
-                    let color_node = ColorNode::new(node_entity, MAGENTA.into(), MAGENTA.into());
-                    pipeline.graph.add_node(GraphNode {
-                        kind: GraphNodeKind::Color(color_node),
-                        last_process_time: Duration::ZERO,
-                    })
-                },
-                RequestSpawnNodeKind::Shape => {
-                    let shape_shader = shader_source(&shaders, &shader_handles.shape);
-                    let shape_node = match node_count.0 % 2 {
-                        0 => ShapeNode::new(node_entity, Shape::Triangle(200.0, 200.0), 512u32, &render_device, &render_queue, &shape_shader),
-                        1 => ShapeNode::new(node_entity, Shape::Circle(100.0), 512u32, &render_device, &render_queue, &shape_shader),
-                        _ => panic!("am i bad at math?")
-                    };
-
-                    pipeline.graph.add_node(GraphNode {
-                        kind: GraphNodeKind::Shape(shape_node),
-                        last_process_time: Duration::ZERO,
-                    })
-                },
-                RequestSpawnNodeKind::Blend => {
-                    let blend_shader = shader_source(&shaders, &shader_handles.blend);
-                    let blend_node = BlendNode::new(node_entity, &render_device, &render_queue, &blend_shader);
-
-                    pipeline.graph.add_node(GraphNode {
-                        kind: GraphNodeKind::Blend(blend_node),
-                        last_process_time: Duration::ZERO,
-                    })
-                }
-            }
+            kind_from_spawn_request(&ev.spawn_kind, node_entity, node_defaults, &render_device, &render_queue, &shaders, &shader_handles, texture_format)
+                .map(|kind| GraphNode {
+                    kind,
+                    last_process_time: Duration::ZERO,
+                    cacheable: true,
+                    processed_inputs: None,
+                    custom_shader: None,
+                    last_error: None,
+                })
         },
         AddNodeEvent::FromSerialized(ev) => {
-            let spawned_node_index = match &ev.node.kind {
-                SerializableGraphNodeKind::Example(sex) => {
-                    let frag_shader = shader_source(&shaders, &shader_handles.default_frag);
-                    let vert_shader = shader_source(&shaders, &shader_handles.default_vert);
-                    pipeline.graph.add_node(GraphNode {
-                        last_process_time: Duration::ZERO,
-                        kind: GraphNodeKind::Example(
-                            ExampleNode::from_serializable(sex, &render_device, &render_queue, &frag_shader, &vert_shader)
-                        )
-                    })
-                },
-                SerializableGraphNodeKind::Color(sc) => {
-                    pipeline.graph.add_node(GraphNode {
-                        last_process_time: Duration::ZERO,
-                        kind: GraphNodeKind::Color(
-                            ColorNode::from_serializable(sc)
-                        )
-                    })
-                },
-                SerializableGraphNodeKind::Shape(ss) => {
-                    let shape_shader = shader_source(&shaders, &shader_handles.shape);
-                    pipeline.graph.add_node(GraphNode {
-                        last_process_time: Duration::ZERO,
-                        kind: GraphNodeKind::Shape(
-                            ShapeNode::from_serializable(ss, &render_device, &&render_queue, &shape_shader)
-                        )
-                    })
+            // a shader file that went missing or stopped fitting the node shouldn't keep the graph from loading
+            let custom_shader = ev.node.custom_shader.as_ref().and_then(|path| {
+                match load_custom_shader(ev.node.kind_name(), path, &shaders, &shader_handles) {
+                    Ok(custom_shader) => Some(custom_shader),
+                    Err(e) => {
+                        eprintln!("Using the built-in shader instead: {}", e);
+                        None
+                    }
                 }
-                SerializableGraphNodeKind::Blend(bs) => {
-                    let blend_shader = shader_source(&shaders, &shader_handles.blend);
-                    pipeline.graph.add_node(GraphNode {
-                        last_process_time: Duration::ZERO,
-                        kind: GraphNodeKind::Blend(
-                            BlendNode::from_serializable(bs, &render_device, &&render_queue, &blend_shader)
-                        )
-                    })
-                },
-            };
-
-
-            let node = pipeline.graph.node_weight_mut(spawned_node_index).unwrap();
-            node.kind.set_entity(node_entity);
+            });
 
-            spawned_node_index
+            kind_from_serializable(
+                &ev.node.kind,
+                &render_device,
+                &render_queue,
+                &shaders,
+                &shader_handles,
+                texture_format,
+                custom_shader.as_ref().map(|custom_shader| custom_shader.source.as_str()),
+            )
+            .map(|mut kind| {
+                kind.set_entity(node_entity);
+                GraphNode {
+                    kind,
+                    last_process_time: Duration::ZERO,
+                    cacheable: ev.node.cacheable,
+                    processed_inputs: None,
+                    custom_shader,
+                    last_error: None,
+                }
+            })
         },
     };
 
+    // shaders finish loading before the main loop starts, so this only happens if one went missing since
+    let node = match maybe_node {
+        Ok(node) => node,
+        Err(e) => {
+            eprintln!("Couldn't add node: {}", e);
+            commands.entity(node_entity).despawn();
+            return;
+        }
+    };
+
+    let spawned_node_index = pipeline.graph.add_node(node);
+
     let node = pipeline.graph.node_weight_mut(spawned_node_index).unwrap();
     let node_id = match trigger.event() {
         AddNodeEvent::FromSerialized(ev) => {
@@ -290,11 +346,17 @@ pub fn add_node(
     node_id_map.0.insert(node_id, node_entity);
     node.kind.store_all();
 
+    if let GraphNodeKind::Image(image_node) = &node.kind {
+        if let Some(png) = image_node.pending_png() {
+            spawn_image_decode(&mut commands, node_entity, png.clone());
+        }
+    }
+
     let process_time_text_margin_top = 26.;
     let process_time_text = commands
         .spawn(Text2dBundle {
             text: Text::from_section(
-                format!("{:?}", node.last_process_time),
+                format_process_time(node.last_process_time),
                 TextStyle {
                     font: fonts.deja_vu_sans.clone(),
                     font_size: 18.,
@@ -307,6 +369,11 @@ pub fn add_node(
                 (-NODE_TEXTURE_DISPLAY_DIMENSION / 2.) - process_time_text_margin_top,
                 0.1,
             ),
+            visibility: if ui_preferences.show_process_time {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
             ..default()
         })
         .insert(NodeProcessText)
@@ -327,18 +394,17 @@ pub fn add_node(
             transform: Transform::from_translation(world_position),
             mesh: meshes.node_display_quad.clone(),
             material: node_display_materials.add(NodeDisplayMaterial {
-                title_bar_color: SLATE_800.into(),
+                title_bar_color: NODE_TITLE_BAR_COLOR.into(),
                 node_texture: images.add(Image::transparent()),
                 title_bar_height: NODE_TITLE_BAR_SIZE,
                 node_dimensions: Vec2::new(NODE_WIDTH, NODE_TITLE_BAR_SIZE + NODE_TEXTURE_DISPLAY_DIMENSION + NODE_CONTENT_PADDING),
-                background_color: SLATE_700.into(),
-                texture_background_color: match &node.kind {
-                    GraphNodeKind::Color(cn) => cn.out_color,
-                    _ => GRAY_600.into(),
-                },
+                background_color: NODE_BACKGROUND_COLOR.into(),
+                texture_background_color: node.kind.primary_display_color().unwrap_or(GRAY_600.into()),
                 border_width: 1.,
                 content_padding: 16.,
                 texture_dimensions: Vec2::splat(NODE_TEXTURE_DISPLAY_DIMENSION),
+                dim: 1.,
+                error: 0.,
                 border_color: LinearRgba {red: 0.0, green: 0.0, blue: 0.0, alpha: 1.0},
                 default_border_color: LinearRgba {red: 0.0, green: 0.0, blue: 0.0, alpha: 1.0},
                 hover_border_color: GRAY_200.into(),
@@ -373,6 +439,23 @@ pub fn add_node(
                 ..default()
             });
 
+            // drop shadow, between this node and anything stacked below it
+            child_builder.spawn((
+                MaterialMesh2dBundle {
+                    mesh: meshes.node_display_quad.clone(),
+                    material: meshes.node_shadow_material.clone(),
+                    transform: Transform::from_translation(NODE_SHADOW_OFFSET.extend(-0.5)),
+                    visibility: if ui_preferences.node_shadows {
+                        Visibility::Inherited
+                    } else {
+                        Visibility::Hidden
+                    },
+                    ..default()
+                },
+                NodeShadow,
+                Pickable::IGNORE,
+            ));
+
             // Spawn input ports
             for input_id in node.kind.input_fields() {
                 InputPort::spawn(
@@ -385,12 +468,13 @@ pub fn add_node(
                     &meshes,
                     fonts.deja_vu_sans_bold.clone(),
                 );
-
-                child_builder.add_command(move |world: &mut World| {
-                    world.trigger(RequestInputPortRelayout { node_entity });
-                });
             }
 
+            // one layout pass once every port exists, rather than one per port
+            child_builder.add_command(move |world: &mut World| {
+                world.trigger(RequestInputPortRelayout { node_entity });
+            });
+
             // Spawn output ports
             for output_id in node.kind.output_fields() {
                 OutputPort::spawn(
@@ -403,16 +487,50 @@ pub fn add_node(
                     &meshes,
                     fonts.deja_vu_sans_bold.clone(),
                 );
+            }
 
-                child_builder.add_command(move |world: &mut World| {
-                    world.trigger(RequestOutputPortRelayout { node_entity });
+            child_builder.add_command(move |world: &mut World| {
+                world.trigger(RequestOutputPortRelayout { node_entity });
+            });
+        });
+
+    if let AddNodeEvent::FromKind(AddNodeKind { connect: Some(connect), .. }) = trigger.event() {
+        match connect {
+            ConnectOnSpawn::FromOutput { node: start_node, output_id, field } => {
+                let maybe_input_id = node.kind.input_fields().iter().find(|input_id| {
+                    node.kind.get_input(**input_id).map_or(false, |input| can_convert_field(field, &input))
                 });
+
+                if let Some(input_id) = maybe_input_id {
+                    commands.trigger(AddEdgeEvent::FromNodes(AddNodeEdge {
+                        start_node: *start_node,
+                        start_id: *output_id,
+                        end_node: node_entity,
+                        end_id: *input_id,
+                    }));
+                }
             }
-        });
+            ConnectOnSpawn::ToInput { node: end_node, input_id, field } => {
+                let maybe_output_id = node.kind.output_fields().iter().find(|output_id| {
+                    node.kind.get_output(**output_id).map_or(false, |output| can_convert_field(&output, field))
+                });
+
+                if let Some(output_id) = maybe_output_id {
+                    commands.trigger(AddEdgeEvent::FromNodes(AddNodeEdge {
+                        start_node: node_entity,
+                        start_id: *output_id,
+                        end_node: *end_node,
+                        end_id: *input_id,
+                    }));
+                }
+            }
+        }
+    }
 
     commands.trigger(UndoableEvent::from(UndoableAddNodeEvent {
         node: node.clone(),
         node_entity,
+        node_id,
     }));
 
     // TODO - Does it make sense to process the whole graph here, long term?
@@ -420,10 +538,269 @@ pub fn add_node(
     ev_process_pipeline.send(RequestProcessPipeline);
 }
 
+// Rebuilds a live node from its serialized form, compiling its shaders from the currently loaded sources
+pub fn kind_from_serializable(
+    kind: &SerializableGraphNodeKind,
+    render_device: &CustomGpuDevice,
+    render_queue: &CustomGpuQueue,
+    shaders: &Res<Assets<Shader>>,
+    shader_handles: &ShaderAssets,
+    texture_format: TextureFormat,
+    custom_shader: Option<&str>,
+) -> Result<GraphNodeKind, ShaderError> {
+    let kind = match kind {
+        SerializableGraphNodeKind::Example(sex) => {
+            let frag_shader = shader_source(shaders, &shader_handles.default_frag)?;
+            let vert_shader = shader_source(shaders, &shader_handles.default_vert)?;
+            GraphNodeKind::Example(
+                ExampleNode::from_serializable(sex, render_device, render_queue, &frag_shader, &vert_shader)
+            )
+        },
+        SerializableGraphNodeKind::Color(sc) => {
+            GraphNodeKind::Color(ColorNode::from_serializable(sc))
+        },
+        SerializableGraphNodeKind::Shape(ss) => {
+            let shape_shader = primary_shader_source(custom_shader, shaders, &shader_handles.shape)?;
+            GraphNodeKind::Shape(
+                ShapeNode::from_serializable(ss, render_device, render_queue, &shape_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Blend(bs) => {
+            let blend_shader = primary_shader_source(custom_shader, shaders, &shader_handles.blend)?;
+            GraphNodeKind::Blend(
+                BlendNode::from_serializable(bs, render_device, render_queue, &blend_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Dither(ds) => {
+            let dither_shader = primary_shader_source(custom_shader, shaders, &shader_handles.dither)?;
+            GraphNodeKind::Dither(
+                DitherNode::from_serializable(ds, render_device, render_queue, &dither_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Image(is) => {
+            GraphNodeKind::Image(ImageNode::from_serializable(is))
+        },
+        SerializableGraphNodeKind::Levels(ls) => {
+            let levels_shader = primary_shader_source(custom_shader, shaders, &shader_handles.levels)?;
+            GraphNodeKind::Levels(
+                LevelsNode::from_serializable(ls, render_device, render_queue, &levels_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Curves(cs) => {
+            let curves_shader = primary_shader_source(custom_shader, shaders, &shader_handles.curves)?;
+            GraphNodeKind::Curves(
+                CurvesNode::from_serializable(cs, render_device, render_queue, &curves_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Number(ns) => {
+            GraphNodeKind::Number(NumberNode::from_serializable(ns))
+        },
+        SerializableGraphNodeKind::Math(ms) => {
+            GraphNodeKind::Math(MathNode::from_serializable(ms))
+        },
+        SerializableGraphNodeKind::Clear(cs) => {
+            GraphNodeKind::Clear(ClearNode::from_serializable(cs))
+        },
+        SerializableGraphNodeKind::Premultiply(ps) => {
+            let premultiply_shader = primary_shader_source(custom_shader, shaders, &shader_handles.premultiply)?;
+            GraphNodeKind::Premultiply(
+                PremultiplyNode::from_serializable(ps, render_device, render_queue, &premultiply_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Unpremultiply(us) => {
+            let unpremultiply_shader = primary_shader_source(custom_shader, shaders, &shader_handles.unpremultiply)?;
+            GraphNodeKind::Unpremultiply(
+                UnpremultiplyNode::from_serializable(us, render_device, render_queue, &unpremultiply_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Convolve(cs) => {
+            let convolve_shader = primary_shader_source(custom_shader, shaders, &shader_handles.convolve)?;
+            GraphNodeKind::Convolve(
+                ConvolveNode::from_serializable(cs, render_device, render_queue, &convolve_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Sharpen(ss) => {
+            let blur_shader = shader_source(shaders, &shader_handles.blur)?;
+            let sharpen_shader = primary_shader_source(custom_shader, shaders, &shader_handles.sharpen)?;
+            GraphNodeKind::Sharpen(
+                SharpenNode::from_serializable(ss, render_device, render_queue, &blur_shader, &sharpen_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Displace(ds) => {
+            let displace_shader = primary_shader_source(custom_shader, shaders, &shader_handles.displace)?;
+            GraphNodeKind::Displace(
+                DisplaceNode::from_serializable(ds, render_device, render_queue, &displace_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::CustomCompute(cs) => {
+            let template_shader = shader_source(shaders, &shader_handles.custom_compute)?;
+            GraphNodeKind::CustomCompute(
+                CustomComputeNode::from_serializable(cs, render_device, render_queue, &template_shader, texture_format)
+            )
+        },
+        SerializableGraphNodeKind::Probe(ps) => {
+            GraphNodeKind::Probe(ProbeNode::from_serializable(ps))
+        },
+    };
+
+    Ok(kind)
+}
+
+// A node's custom shader stands in for its main compute pass, e.g. the sharpen pass but not the blur feeding it
+fn primary_shader_source(
+    custom_shader: Option<&str>,
+    shaders: &Res<Assets<Shader>>,
+    shader: &Handle<Shader>,
+) -> Result<String, ShaderError> {
+    match custom_shader {
+        Some(source) => Ok(source.to_string()),
+        None => shader_source(shaders, shader),
+    }
+}
+
+// Builds a fresh node of the requested kind from the node defaults, compiling its shaders from the currently loaded sources
+pub fn kind_from_spawn_request(
+    spawn_kind: &RequestSpawnNodeKind,
+    node_entity: Entity,
+    node_defaults: &NodeDefaults,
+    render_device: &CustomGpuDevice,
+    render_queue: &CustomGpuQueue,
+    shaders: &Res<Assets<Shader>>,
+    shader_handles: &ShaderAssets,
+    texture_format: TextureFormat,
+) -> Result<GraphNodeKind, ShaderError> {
+    let kind = match spawn_kind {
+        RequestSpawnNodeKind::Example => {
+            let frag_shader = shader_source(shaders, &shader_handles.default_frag)?;
+            let vert_shader = shader_source(shaders, &shader_handles.default_vert)?;
+            let example_node = ExampleNode::new(
+                node_entity,
+                render_device,
+                render_queue,
+                &frag_shader,
+                &vert_shader,
+                node_defaults.texture_size,
+                node_defaults.texture_format,
+            );
+
+            GraphNodeKind::Example(example_node)
+        }
+        RequestSpawnNodeKind::Color => {
+            let color_node = ColorNode::new(node_entity, node_defaults.color, node_defaults.color);
+            GraphNodeKind::Color(color_node)
+        },
+        RequestSpawnNodeKind::Shape => {
+            let shape_shader = shader_source(shaders, &shader_handles.shape)?;
+            let shape_node = ShapeNode::new(
+                node_entity,
+                node_defaults.shape.clone(),
+                node_defaults.texture_size,
+                render_device,
+                render_queue,
+                &shape_shader,
+                texture_format,
+            );
+
+            GraphNodeKind::Shape(shape_node)
+        },
+        RequestSpawnNodeKind::Blend => {
+            let blend_shader = shader_source(shaders, &shader_handles.blend)?;
+            let blend_node = BlendNode::new(node_entity, render_device, render_queue, &blend_shader, texture_format);
+
+            GraphNodeKind::Blend(blend_node)
+        }
+        RequestSpawnNodeKind::Dither => {
+            let dither_shader = shader_source(shaders, &shader_handles.dither)?;
+            let dither_node = DitherNode::new(node_entity, render_device, render_queue, &dither_shader, texture_format);
+
+            GraphNodeKind::Dither(dither_node)
+        }
+        RequestSpawnNodeKind::Levels => {
+            let levels_shader = shader_source(shaders, &shader_handles.levels)?;
+            let levels_node = LevelsNode::new(node_entity, render_device, render_queue, &levels_shader, texture_format);
+
+            GraphNodeKind::Levels(levels_node)
+        }
+        RequestSpawnNodeKind::Curves => {
+            let curves_shader = shader_source(shaders, &shader_handles.curves)?;
+            let curves_node = CurvesNode::new(node_entity, render_device, render_queue, &curves_shader, texture_format);
+
+            GraphNodeKind::Curves(curves_node)
+        }
+        RequestSpawnNodeKind::Number => {
+            let number_node = NumberNode::new(node_entity, 1.);
+
+            GraphNodeKind::Number(number_node)
+        }
+        RequestSpawnNodeKind::Math => {
+            let math_node = MathNode::new(node_entity, MathOperation::Add);
+
+            GraphNodeKind::Math(math_node)
+        }
+        RequestSpawnNodeKind::Clear => {
+            let clear_node = ClearNode::new(node_entity, node_defaults.texture_size, LinearRgba::NONE);
+
+            GraphNodeKind::Clear(clear_node)
+        }
+        RequestSpawnNodeKind::Premultiply => {
+            let premultiply_shader = shader_source(shaders, &shader_handles.premultiply)?;
+            let premultiply_node = PremultiplyNode::new(node_entity, render_device, render_queue, &premultiply_shader, texture_format);
+
+            GraphNodeKind::Premultiply(premultiply_node)
+        }
+        RequestSpawnNodeKind::Unpremultiply => {
+            let unpremultiply_shader = shader_source(shaders, &shader_handles.unpremultiply)?;
+            let unpremultiply_node = UnpremultiplyNode::new(node_entity, render_device, render_queue, &unpremultiply_shader, texture_format);
+
+            GraphNodeKind::Unpremultiply(unpremultiply_node)
+        }
+        RequestSpawnNodeKind::Convolve => {
+            let convolve_shader = shader_source(shaders, &shader_handles.convolve)?;
+            let convolve_node = ConvolveNode::new(node_entity, render_device, render_queue, &convolve_shader, texture_format);
+
+            GraphNodeKind::Convolve(convolve_node)
+        }
+        RequestSpawnNodeKind::Sharpen => {
+            let blur_shader = shader_source(shaders, &shader_handles.blur)?;
+            let sharpen_shader = shader_source(shaders, &shader_handles.sharpen)?;
+            let sharpen_node = SharpenNode::new(node_entity, render_device, render_queue, &blur_shader, &sharpen_shader, texture_format);
+
+            GraphNodeKind::Sharpen(sharpen_node)
+        }
+        RequestSpawnNodeKind::Displace => {
+            let displace_shader = shader_source(shaders, &shader_handles.displace)?;
+            let displace_node = DisplaceNode::new(node_entity, render_device, render_queue, &displace_shader, texture_format);
+
+            GraphNodeKind::Displace(displace_node)
+        }
+        RequestSpawnNodeKind::CustomCompute => {
+            // new nodes start out running the template, ready to be edited
+            let template_shader = shader_source(shaders, &shader_handles.custom_compute)?;
+            let custom_compute_node = CustomComputeNode::new(
+                node_entity,
+                render_device,
+                render_queue,
+                &template_shader,
+                &template_shader,
+                texture_format,
+            );
+
+            GraphNodeKind::CustomCompute(custom_compute_node)
+        }
+        RequestSpawnNodeKind::Probe => {
+            GraphNodeKind::Probe(ProbeNode::new(node_entity))
+        }
+    };
+
+    Ok(kind)
+}
+
 #[derive(Event, Clone)]
 pub struct UndoableAddNodeEvent {
     pub node: GraphNode,
     pub node_entity: Entity,
+    // restored along with the node, so saves and pastes still see the same node after undo/redo
+    pub node_id: Uuid,
 }
 
 pub fn add_node_from_undo(
@@ -433,10 +810,14 @@ pub fn add_node_from_undo(
     mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
     q_children: Query<&Children>,
     q_process_time_text: Query<Entity, With<NodeProcessText>>,
+    mut node_id_map: ResMut<NodeIdMapping>,
 ) {
     let mut pipeline = q_pipeline.single_mut();
 
     let node_entity = trigger.event().node_entity;
+    let node_id = trigger.event().node_id;
+    node_id_map.0.insert(node_id, node_entity);
+
     let spawned_node_index = pipeline.graph.add_node(trigger.event().node.clone());
     let node = pipeline.graph.node_weight_mut(spawned_node_index).unwrap();
 
@@ -453,6 +834,7 @@ pub fn add_node_from_undo(
                 .find(|e| q_process_time_text.contains(**e))
                 .unwrap(),
         })
+        .insert(NodeId(node_id))
         .insert(UIContext::Node(node_entity))
         .insert(Visibility::Visible);
 
@@ -476,3 +858,33 @@ pub fn drag_node_from_undo(
         transform.translation = trigger.event().new_position;
     }
 }
+
+#[derive(Event, Clone)]
+pub struct RequestReseedAll;
+
+// every seed change is triggered in the same frame, so the whole reseed is one undo step
+pub fn reseed_all(
+    _trigger: Trigger<RequestReseedAll>,
+    mut commands: Commands,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+) {
+    let pipeline = q_pipeline.single();
+
+    for index in pipeline.graph.node_indices() {
+        let node = &pipeline.graph[index];
+
+        if let Some(input_id) = node.kind.seed_input() {
+            if let Some(old_value) = node.kind.get_input(input_id) {
+                commands.trigger(SetInputFieldEvent {
+                    node: index,
+                    input_id,
+                    old_value,
+                    new_value: Field::U32(Uuid::new_v4().as_u128() as u32),
+                });
+            }
+        }
+    }
+
+    ev_process_pipeline.send(RequestProcessPipeline);
+}
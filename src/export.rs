@@ -0,0 +1,437 @@
+use std::{collections::VecDeque, ffi::OsString, io::Cursor, path::PathBuf};
+
+use bevy::{
+    prelude::*,
+    render::render_resource::TextureFormat,
+    tasks::{block_on, poll_once, AsyncComputeTaskPool, Task},
+};
+use bevy_file_dialog::{DialogDirectoryPicked, DialogFileSaved, FileDialogExt};
+use petgraph::Direction;
+
+use crate::{
+    graph::{directed_reachable_nodes, process_graph, DisjointPipelineGraph, PipelineProcessTask, ProcessNode},
+    nodes::{kinds::image::decode_png, set_max_tile_size, set_render_scale, GraphNodeKind, NodeDisplay, NodeTrait},
+    preferences::UiPreferences,
+    ui::menu_bar::{folder_pngs, Project},
+    ApplicationState,
+};
+
+// Exporting reprocesses a copy of the graph at the project's render scale, so the editor
+// can keep previewing at low resolution while exports come out at full size.
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (start_pending_export, poll_export_task, export_save_complete)
+                .chain()
+                .run_if(in_state(ApplicationState::MainLoop)),
+        );
+        app.add_systems(
+            Update,
+            (batch_source_picked, batch_output_picked, start_batch_frame, poll_batch_frame)
+                .chain()
+                .run_if(in_state(ApplicationState::MainLoop)),
+        );
+
+        app.init_resource::<PendingExport>();
+        app.init_resource::<BatchExport>();
+
+        app.observe(request_export_image);
+        app.observe(request_batch_export);
+    }
+}
+
+// Marks PNG bytes handed to the save dialog
+pub struct ExportedImage;
+
+#[derive(Event, Clone)]
+pub struct RequestExportImage {
+    pub node_entity: Entity,
+}
+
+// The node waiting for an export pass, held until the editor's own processing finishes
+#[derive(Resource, Default)]
+struct PendingExport(Option<Entity>);
+
+#[derive(Component)]
+pub struct ExportProcessTask {
+    node_entity: Entity,
+    task: Task<Vec<ProcessNode>>,
+}
+
+fn request_export_image(trigger: Trigger<RequestExportImage>, mut pending_export: ResMut<PendingExport>) {
+    pending_export.0 = Some(trigger.event().node_entity);
+}
+
+// Node copies share their GPU buffers with the editor's graph, so only one pass of either kind runs at a time
+fn start_pending_export(
+    mut commands: Commands,
+    mut pending_export: ResMut<PendingExport>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_editor_task: Query<(), With<PipelineProcessTask>>,
+    q_export_task: Query<(), With<ExportProcessTask>>,
+    q_batch_task: Query<(), With<BatchFrameTask>>,
+    project: Res<Project>,
+    ui_preferences: Res<UiPreferences>,
+) {
+    if pending_export.0.is_none() || !q_editor_task.is_empty() || !q_export_task.is_empty() || !q_batch_task.is_empty() {
+        return;
+    }
+
+    let Some(node_entity) = pending_export.0.take() else {
+        return;
+    };
+
+    let mut graph_copy = q_pipeline.single().graph.clone();
+    for node in graph_copy.node_weights_mut() {
+        set_render_scale(&mut node.kind, project.render_scale());
+        set_max_tile_size(&mut node.kind, ui_preferences.max_tile_size);
+        // the render scale isn't an input, so outputs cached at the editor's scale can't be reused
+        node.processed_inputs = None;
+    }
+
+    let task = AsyncComputeTaskPool::get().spawn(process_graph(graph_copy, ui_preferences.max_concurrent_nodes));
+    commands.spawn(ExportProcessTask { node_entity, task });
+}
+
+fn poll_export_task(mut commands: Commands, mut q_export_task: Query<(Entity, &mut ExportProcessTask)>) {
+    for (task_entity, mut export_task) in q_export_task.iter_mut() {
+        let Some(processed_nodes) = block_on(poll_once(&mut export_task.task)) else {
+            continue;
+        };
+
+        commands.entity(task_entity).despawn();
+
+        let node_entity = export_task.node_entity;
+        let Some(image) = processed_nodes
+            .iter()
+            .find(|processed| processed.node.kind.entity() == node_entity)
+            .and_then(|processed| processed.node.kind.primary_output_image())
+        else {
+            eprintln!("Nothing to export, the node produced no image.");
+            continue;
+        };
+
+        match encode_png(image) {
+            Ok(png) => {
+                commands
+                    .dialog()
+                    .add_filter("PNG Image", &["png"])
+                    .set_file_name("export.png")
+                    .save_file::<ExportedImage>(png);
+            }
+            Err(e) => eprintln!("Failed to encode export: {}", e),
+        }
+    }
+}
+
+// Bevy can't convert float textures itself, so Rgba16Float is widened to f32 here and clamped when written out
+fn to_dynamic_image(image: &Image) -> Result<image::DynamicImage, String> {
+    if image.texture_descriptor.format != TextureFormat::Rgba16Float {
+        return image.clone().try_into_dynamic().map_err(|e| e.to_string());
+    }
+
+    let size = image.texture_descriptor.size;
+    let pixels: Vec<f32> = image
+        .data
+        .chunks_exact(2)
+        .map(|half| f16_to_f32(u16::from_le_bytes([half[0], half[1]])))
+        .collect();
+
+    image::Rgba32FImage::from_raw(size.width, size.height, pixels)
+        .map(image::DynamicImage::ImageRgba32F)
+        .ok_or_else(|| String::from("image data doesn't match its size"))
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1. } else { 1. };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0. => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1. + mantissa / 1024.) * 2f32.powi(exponent - 15),
+    }
+}
+
+fn encode_png(image: &Image) -> Result<Vec<u8>, String> {
+    let dynamic_image = to_dynamic_image(image)?;
+
+    let mut png = Cursor::new(Vec::new());
+    dynamic_image
+        .to_rgba8()
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+
+    Ok(png.into_inner())
+}
+
+// A PNG no larger than max_dimension on either side, for previews embedded in project files
+pub fn encode_thumbnail(image: &Image, max_dimension: u32) -> Result<Vec<u8>, String> {
+    let dynamic_image = to_dynamic_image(image)?;
+
+    let mut png = Cursor::new(Vec::new());
+    dynamic_image
+        .thumbnail(max_dimension, max_dimension)
+        .to_rgba8()
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+
+    Ok(png.into_inner())
+}
+
+fn export_save_complete(mut ev_saved: EventReader<DialogFileSaved<ExportedImage>>) {
+    for ev in ev_saved.read() {
+        match ev.result {
+            Ok(_) => eprintln!("Exported {}", ev.file_name),
+            Err(ref err) => eprintln!("Failed to export {}: {}", ev.file_name, err),
+        }
+    }
+}
+
+// Runs every PNG in a folder through the subgraph feeding one node, swapping each one in for the
+// subgraph's single Image node, and writes the results to another folder under the same names
+#[derive(Event, Clone)]
+pub struct RequestBatchExport {
+    pub node_entity: Entity,
+}
+
+// Mark the folder dialogs of a batch export
+pub struct BatchSourceFolder;
+pub struct BatchOutputFolder;
+
+// The batch export being set up or run, one at a time
+#[derive(Resource, Default)]
+pub struct BatchExport(Option<BatchExportJob>);
+
+impl BatchExport {
+    // (exported so far, total), once the batch is running
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        let job = self.0.as_ref()?;
+        job.output_folder.as_ref()?;
+        Some((job.finished, job.total))
+    }
+}
+
+pub struct BatchExportJob {
+    output_node: Entity,
+    source_node: Entity,
+    frames: VecDeque<PathBuf>,
+    output_folder: Option<PathBuf>,
+    finished: usize,
+    failed: usize,
+    total: usize,
+}
+
+#[derive(Component)]
+pub struct BatchFrameTask {
+    file_name: OsString,
+    task: Task<Result<Vec<u8>, String>>,
+}
+
+fn request_batch_export(
+    trigger: Trigger<RequestBatchExport>,
+    mut commands: Commands,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_nodes: Query<&NodeDisplay>,
+    mut batch_export: ResMut<BatchExport>,
+) {
+    // one still waiting on its folders was probably abandoned at a dialog, so it's replaced
+    if batch_export.0.as_ref().map_or(false, |job| job.output_folder.is_some()) {
+        eprintln!("A batch export is already running.");
+        return;
+    }
+
+    let output_node = trigger.event().node_entity;
+    let Ok(node_display) = q_nodes.get(output_node) else {
+        return;
+    };
+
+    let graph = &q_pipeline.single().graph;
+    let sources: Vec<Entity> = directed_reachable_nodes(graph, node_display.index, Direction::Incoming)
+        .into_iter()
+        .filter_map(|index| match &graph[index].kind {
+            GraphNodeKind::Image(image_node) => Some(image_node.entity()),
+            _ => None,
+        })
+        .collect();
+
+    let [source_node] = sources[..] else {
+        eprintln!(
+            "Batch export needs exactly one Image node feeding the exported node, found {}.",
+            sources.len()
+        );
+        return;
+    };
+
+    batch_export.0 = Some(BatchExportJob {
+        output_node,
+        source_node,
+        frames: VecDeque::new(),
+        output_folder: None,
+        finished: 0,
+        failed: 0,
+        total: 0,
+    });
+
+    commands.dialog().pick_directory_path::<BatchSourceFolder>();
+}
+
+fn batch_source_picked(
+    mut commands: Commands,
+    mut ev_picked: EventReader<DialogDirectoryPicked<BatchSourceFolder>>,
+    mut batch_export: ResMut<BatchExport>,
+) {
+    for ev in ev_picked.read() {
+        let Some(job) = batch_export.0.as_mut() else {
+            continue;
+        };
+
+        match folder_pngs(&ev.path) {
+            Ok(frames) if !frames.is_empty() => {
+                job.total = frames.len();
+                job.frames = frames.into();
+                commands.dialog().pick_directory_path::<BatchOutputFolder>();
+            }
+            Ok(_) => {
+                eprintln!("There are no PNG images in {}", ev.path.display());
+                batch_export.0 = None;
+            }
+            Err(e) => {
+                eprintln!("Couldn't read {}: {}", ev.path.display(), e);
+                batch_export.0 = None;
+            }
+        }
+    }
+}
+
+fn batch_output_picked(
+    mut ev_picked: EventReader<DialogDirectoryPicked<BatchOutputFolder>>,
+    mut batch_export: ResMut<BatchExport>,
+) {
+    for ev in ev_picked.read() {
+        if let Some(job) = batch_export.0.as_mut() {
+            println!("Batch exporting {} images to {}", job.total, ev.path.display());
+            job.output_folder = Some(ev.path.clone());
+        }
+    }
+}
+
+// One image at a time, and like a single export only while no other pass is using the nodes' GPU buffers
+fn start_batch_frame(
+    mut commands: Commands,
+    mut batch_export: ResMut<BatchExport>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_nodes: Query<&NodeDisplay>,
+    q_editor_task: Query<(), With<PipelineProcessTask>>,
+    q_export_task: Query<(), With<ExportProcessTask>>,
+    q_batch_task: Query<(), With<BatchFrameTask>>,
+    project: Res<Project>,
+    ui_preferences: Res<UiPreferences>,
+) {
+    let is_running = batch_export.0.as_ref().map_or(false, |job| job.output_folder.is_some());
+    if !is_running || !q_editor_task.is_empty() || !q_export_task.is_empty() || !q_batch_task.is_empty() {
+        return;
+    }
+
+    let Some(job) = batch_export.0.as_mut() else {
+        return;
+    };
+
+    let Some(frame_path) = job.frames.pop_front() else {
+        println!("Batch export finished, {} of {} images exported", job.finished - job.failed, job.total);
+        batch_export.0 = None;
+        return;
+    };
+
+    let Ok(output_display) = q_nodes.get(job.output_node) else {
+        eprintln!("The exported node was removed, stopping the batch export.");
+        batch_export.0 = None;
+        return;
+    };
+
+    let png = match std::fs::read(&frame_path) {
+        Ok(png) => png,
+        Err(e) => {
+            eprintln!("Couldn't read {}: {}", frame_path.display(), e);
+            job.finished += 1;
+            job.failed += 1;
+            return;
+        }
+    };
+
+    // only the template subgraph needs to run
+    let mut graph_copy = q_pipeline.single().graph.clone();
+    let template = directed_reachable_nodes(&graph_copy, output_display.index, Direction::Incoming);
+    graph_copy.retain_nodes(|_, index| template.contains(&index));
+
+    for node in graph_copy.node_weights_mut() {
+        set_render_scale(&mut node.kind, project.render_scale());
+        set_max_tile_size(&mut node.kind, ui_preferences.max_tile_size);
+        node.processed_inputs = None;
+    }
+
+    let source_node = job.source_node;
+    let output_node = job.output_node;
+    let max_concurrent_nodes = ui_preferences.max_concurrent_nodes;
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let image = decode_png(&png)?;
+
+        let source = graph_copy
+            .node_weights_mut()
+            .find_map(|node| match &mut node.kind {
+                GraphNodeKind::Image(image_node) if image_node.entity() == source_node => Some(image_node),
+                _ => None,
+            })
+            .ok_or_else(|| String::from("the source Image node was removed"))?;
+        source.finish_decode(image);
+
+        let processed_nodes = process_graph(graph_copy, max_concurrent_nodes).await;
+        let image = processed_nodes
+            .iter()
+            .find(|processed| processed.node.kind.entity() == output_node)
+            .and_then(|processed| processed.node.kind.primary_output_image())
+            .ok_or_else(|| String::from("the node produced no image"))?;
+
+        encode_png(image)
+    });
+
+    commands.spawn(BatchFrameTask {
+        file_name: frame_path.file_name().unwrap_or_default().to_os_string(),
+        task,
+    });
+}
+
+fn poll_batch_frame(
+    mut commands: Commands,
+    mut q_batch_task: Query<(Entity, &mut BatchFrameTask)>,
+    mut batch_export: ResMut<BatchExport>,
+) {
+    for (task_entity, mut frame) in q_batch_task.iter_mut() {
+        let Some(result) = block_on(poll_once(&mut frame.task)) else {
+            continue;
+        };
+        commands.entity(task_entity).despawn();
+
+        let Some(job) = batch_export.0.as_mut() else {
+            continue;
+        };
+        let Some(output_folder) = &job.output_folder else {
+            continue;
+        };
+
+        let output_path = output_folder.join(&frame.file_name);
+        let written = result.and_then(|png| std::fs::write(&output_path, png).map_err(|e| e.to_string()));
+
+        if let Err(e) = written {
+            eprintln!("Failed to export {}: {}", output_path.display(), e);
+            job.failed += 1;
+        }
+        job.finished += 1;
+    }
+}
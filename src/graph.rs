@@ -1,7 +1,12 @@
-use std::{borrow::Cow, time::Instant};
+use std::{borrow::Cow, collections::VecDeque, time::Instant};
 
 use crate::{
-    nodes::{fields::can_convert_field, GraphNode, InputId, NodeTrait, OutputId, SerializableInputId, SerializableOutputId},
+    export::{BatchFrameTask, ExportProcessTask},
+    nodes::{
+        fields::{can_convert_field, Field}, node_error, node_progress, set_max_tile_size, GraphNode, InputId, NodeDisplay, NodeTrait, OutputId, SerializableInputId,
+        SerializableOutputId,
+    },
+    preferences::UiPreferences,
     ApplicationState,
 };
 use bevy::{
@@ -30,6 +35,12 @@ impl Plugin for GraphPlugin {
 
         app.add_event::<RequestProcessPipeline>();
         app.init_resource::<PendingReprocess>();
+        app.init_resource::<AutoProcess>();
+        app.init_resource::<StaleWhilePaused>();
+        app.init_resource::<ProcessNowRequested>();
+        app.init_resource::<ProcessProgress>();
+
+        app.observe(toggle_auto_process).observe(process_now).observe(reprocess_from);
     }
 }
 
@@ -38,12 +49,64 @@ pub struct DisjointPipelineGraph {
     pub graph: StableDiGraph<GraphNode, Edge>,
 }
 
+impl DisjointPipelineGraph {
+    // The image a node last produced on the given output, if it has produced one
+    pub fn output_image(&self, index: NodeIndex, id: OutputId) -> Option<&Image> {
+        self.graph.node_weight(index)?.kind.get_output_image(id)
+    }
+}
+
 #[derive(Component, Deref)]
 pub struct PipelineProcessTask(Task<Vec<ProcessNode>>);
 
 #[derive(Resource, Default)]
 struct PendingReprocess(bool);
 
+// When false, edits no longer reprocess the graph until the user asks for it
+#[derive(Resource)]
+pub struct AutoProcess(pub bool);
+
+impl Default for AutoProcess {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+// Set when a reprocess was skipped because auto processing is paused
+#[derive(Resource, Default)]
+pub struct StaleWhilePaused(pub bool);
+
+#[derive(Resource, Default)]
+struct ProcessNowRequested(bool);
+
+// Progress of the nodes still working on the current pass, keyed by node entity.
+// Empty whenever nothing is processing or no running node reports progress.
+#[derive(Resource, Default)]
+pub struct ProcessProgress(pub HashMap<Entity, f32>);
+
+impl ProcessProgress {
+    // The running nodes' average, for a single overall readout
+    pub fn overall(&self) -> Option<f32> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        Some(self.0.values().sum::<f32>() / self.0.len() as f32)
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct ToggleAutoProcess;
+
+#[derive(Event, Clone)]
+pub struct ProcessNow;
+
+// Reprocesses a node and everything downstream of it, leaving upstream results cached
+#[derive(Event, Clone)]
+pub struct RequestReprocessFrom {
+    pub node_entity: Entity,
+}
+
 #[derive(Event)]
 pub struct GraphWasUpdated;
 
@@ -52,8 +115,8 @@ pub struct RequestProcessPipeline;
 
 #[derive(Clone)]
 pub struct ProcessNode {
-    index: NodeIndex,
-    node: GraphNode,
+    pub index: NodeIndex,
+    pub node: GraphNode,
 }
 
 #[derive(Clone, Debug)]
@@ -84,27 +147,28 @@ impl SerializableEdge {
 }
 
 impl Edge {
-    pub fn from_serializable(serialized: &SerializableEdge, from_node: &impl NodeTrait, to_node: &impl NodeTrait) -> Self {
+    // Fails when a saved field no longer exists on its node, e.g. a project saved before the node's fields changed
+    pub fn from_serializable(serialized: &SerializableEdge, from_node: &impl NodeTrait, to_node: &impl NodeTrait) -> Result<Self, String> {
         let from_field = from_node.output_fields()
             .iter()
             .find(|&&output_id| 
                 SerializableOutputId(output_id.0.to_string(), output_id.1.to_string()) == serialized.from_field
             )
-            .expect("Serialized from_field not found in from_node");
+            .ok_or_else(|| format!("{} has no output {}", serialized.from_field.0, serialized.from_field.1))?;
 
         let to_field = to_node.input_fields()
             .iter()
             .find(|&&input_id| 
                 SerializableInputId(input_id.0.to_string(), input_id.1.to_string()) == serialized.to_field
             )
-            .expect("Serialized to_field not found in to_node");
+            .ok_or_else(|| format!("{} has no input {}", serialized.to_field.0, serialized.to_field.1))?;
         
-        Edge {
+        Ok(Edge {
             from_node: from_node.entity(),
             from_field: *from_field,
             to_node: to_node.entity(),
             to_field: *to_field,
-        }
+        })
     }
 }
 
@@ -114,18 +178,38 @@ fn poll_processed_pipeline(
     mut commands: Commands,
     mut q_pipeline: Query<&mut DisjointPipelineGraph>,
     mut q_task: Query<(Entity, &mut PipelineProcessTask)>,
+    mut process_progress: ResMut<ProcessProgress>,
 ) {
+    // the graph's nodes share their progress counters with the copies being processed
+    if let Ok(pipeline) = q_pipeline.get_single() {
+        let running: HashMap<Entity, f32> = pipeline
+            .graph
+            .node_weights()
+            .filter_map(|node| {
+                node_progress(&node.kind)
+                    .filter(|progress| progress.is_running())
+                    .map(|progress| (node.kind.entity(), progress.fraction()))
+            })
+            .collect();
+
+        if running != process_progress.0 {
+            process_progress.0 = running;
+        }
+    }
+
     for (task_entity, mut task) in q_task.iter_mut() {
         if let Some(updated_node_data) = block_on(poll_once(&mut task.0)) {
             let mut pipeline = q_pipeline.single_mut();
 
             for processed_node in updated_node_data {
-                let node = pipeline
-                    .graph
-                    .node_weight_mut(processed_node.index)
-                    .unwrap();
-
-                *node = processed_node.node;
+                // the graph may have changed since the pass started. a removed node's index can be empty,
+                // or already reused by a node added (or restored by undo) after it, so match on the entity too
+                match pipeline.graph.node_weight_mut(processed_node.index) {
+                    Some(node) if node.kind.entity() == processed_node.node.kind.entity() => {
+                        *node = processed_node.node;
+                    }
+                    _ => {}
+                }
             }
 
             commands.entity(task_entity).despawn();
@@ -141,10 +225,29 @@ fn process_pipeline(
     mut commands: Commands,
     q_pipeline: Query<&DisjointPipelineGraph>,
     q_task: Query<Entity, With<PipelineProcessTask>>,
+    q_export_task: Query<(), Or<(With<ExportProcessTask>, With<BatchFrameTask>)>>,
     mut is_pending_reprocess: ResMut<PendingReprocess>,
+    auto_process: Res<AutoProcess>,
+    mut stale_while_paused: ResMut<StaleWhilePaused>,
+    mut process_now_requested: ResMut<ProcessNowRequested>,
+    ui_preferences: Res<UiPreferences>,
 ) {
     let is_new_request = event_reader.read().next().is_some();
-    let is_task_in_flight = !q_task.iter().count().is_zero();
+
+    if process_now_requested.0 {
+        process_now_requested.0 = false;
+        stale_while_paused.0 = false;
+        is_pending_reprocess.0 = true;
+    } else if !auto_process.0 && !is_pending_reprocess.0 {
+        // a pending reprocess was already let through, e.g. Process Now while a pass was in flight
+        if is_new_request {
+            stale_while_paused.0 = true;
+        }
+        return;
+    }
+
+    // export and batch export passes share GPU buffers with this graph, so they count as in flight too
+    let is_task_in_flight = !q_task.iter().count().is_zero() || !q_export_task.is_empty();
     let should_continue = is_new_request || is_pending_reprocess.0;
     let is_newly_pending = should_continue && is_task_in_flight && !is_pending_reprocess.0;
 
@@ -156,81 +259,12 @@ fn process_pipeline(
 
         let thread_pool = AsyncComputeTaskPool::get();
 
-        let graph_copy = pipeline.graph.clone();
-
-        let graph_processing_work = async move {
-            let mut unprocessed_nodes: HashSet<NodeIndex> = graph_copy.node_indices().collect();
-            let mut in_flight_nodes: HashSet<NodeIndex> = HashSet::new();
-            let nodes_to_process: Vec<ProcessNode> =
-                get_processible_nodes(&graph_copy, &unprocessed_nodes, &in_flight_nodes);
-            let mut results: HashMap<NodeIndex, ProcessNode> = HashMap::new();
-
-            let mut subtasks: Vec<BoxFuture<'static, ProcessNode>> = Vec::new();
-
-            for node in nodes_to_process.into_iter() {
-                in_flight_nodes.insert(node.index);
-                let subtask = process_node(node).boxed();
-                subtasks.push(subtask);
-            }
-
-            while !subtasks.is_empty() {
-                // Await the first subtask to complete
-                let result = if subtasks.len() == 1 {
-                    // Only one task left, no need to use select_all
-                    subtasks.pop().unwrap().await
-                } else {
-                    let (result, _index, remaining) = select_all(subtasks).await;
-                    subtasks = remaining;
-                    result
-                };
-
-                // TODO: Take the finished 'result' and send it back to main thread early
-                // rather than waiting for the entire graph to complete
-                // but don't bother until it's noticably annoying that you dont do this (i.e. until partial completion actually matters to the UX)
-
-                let result_idx = result.index.clone();
-                results.insert(result_idx, result);
-                in_flight_nodes.remove(&result_idx);
-                unprocessed_nodes.remove(&result_idx);
-
-                // Add any new node processing tasks for nodes that now have resolved dependencies
-                let new_nodes_to_process =
-                    get_processible_nodes(&graph_copy, &unprocessed_nodes, &in_flight_nodes);
-                for node in new_nodes_to_process.into_iter() {
-                    in_flight_nodes.insert(node.index);
-
-                    let node_dependencies =
-                        graph_copy.edges_directed(node.index, Direction::Incoming);
-
-                    let mut node_with_resolved_dependencies = node.clone();
-
-                    for edge in node_dependencies {
-                        // Use the post-process version of the dependency node, since the entry in graph itself isn't updated yet
-                        let from = results
-                            .get(&edge.source())
-                            .expect("Tried to depend on a node that hasn't been processed yet.");
-                        let edge_data = edge.weight();
-
-                        // Update the dependant node
-                        
-                        let _ = node_with_resolved_dependencies.node.kind.set_input(
-                            edge_data.to_field,
-                            from.node.kind.get_output(edge_data.from_field).unwrap(),
-                        );
-                    }
-
-                    let subtask = process_node(node_with_resolved_dependencies).boxed();
-
-                    subtasks.push(subtask);
-                }
-            }
+        let mut graph_copy = pipeline.graph.clone();
+        for node in graph_copy.node_weights_mut() {
+            set_max_tile_size(&mut node.kind, ui_preferences.max_tile_size);
+        }
 
-            let mut results_vec = Vec::with_capacity(results.len());
-            results
-                .into_iter()
-                .for_each(|(_index, process_node)| results_vec.push(process_node));
-            results_vec
-        };
+        let graph_processing_work = process_graph(graph_copy, ui_preferences.max_concurrent_nodes);
 
         let task = thread_pool.spawn(graph_processing_work);
         commands.spawn(PipelineProcessTask(task));
@@ -246,16 +280,186 @@ fn process_pipeline(
     }
 }
 
+fn toggle_auto_process(
+    _trigger: Trigger<ToggleAutoProcess>,
+    mut auto_process: ResMut<AutoProcess>,
+    mut stale_while_paused: ResMut<StaleWhilePaused>,
+    mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+) {
+    auto_process.0 = !auto_process.0;
+
+    // catch up on everything that was skipped while paused
+    if auto_process.0 && stale_while_paused.0 {
+        stale_while_paused.0 = false;
+        ev_process_pipeline.send(RequestProcessPipeline);
+    }
+}
+
+// Processes the graph once, even while auto processing is paused
+fn process_now(_trigger: Trigger<ProcessNow>, mut process_now_requested: ResMut<ProcessNowRequested>) {
+    process_now_requested.0 = true;
+}
+
+// Asked for explicitly, so it runs even while auto processing is paused
+fn reprocess_from(
+    trigger: Trigger<RequestReprocessFrom>,
+    mut q_pipeline: Query<&mut DisjointPipelineGraph>,
+    q_nodes: Query<&NodeDisplay>,
+    mut process_now_requested: ResMut<ProcessNowRequested>,
+    mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+) {
+    let Ok(node_display) = q_nodes.get(trigger.event().node_entity) else {
+        return;
+    };
+
+    let mut pipeline = q_pipeline.single_mut();
+    let downstream = directed_reachable_nodes(&pipeline.graph, node_display.index, Direction::Outgoing);
+
+    for index in downstream {
+        if let Some(node) = pipeline.graph.node_weight_mut(index) {
+            node.processed_inputs = None;
+        }
+    }
+
+    process_now_requested.0 = true;
+    ev_process_pipeline.send(RequestProcessPipeline);
+}
+
+// Processes every node of a copy of the graph in dependency order, running independent nodes concurrently.
+// The editor and export passes both go through here; neither touches the copy they were handed.
+// At most max_concurrent_nodes run at once, None leaves it to the task pool.
+pub async fn process_graph(
+    graph_copy: StableDiGraph<GraphNode, Edge>,
+    max_concurrent_nodes: Option<u32>,
+) -> Vec<ProcessNode> {
+    let mut unprocessed_nodes: HashSet<NodeIndex> = graph_copy.node_indices().collect();
+    let mut in_flight_nodes: HashSet<NodeIndex> = HashSet::new();
+    let mut results: HashMap<NodeIndex, ProcessNode> = HashMap::new();
+
+    // nodes whose inputs are resolved, waiting for a free slot. they count as in flight so they aren't queued twice
+    let mut ready_nodes: VecDeque<ProcessNode> =
+        get_processible_nodes(&graph_copy, &unprocessed_nodes, &in_flight_nodes).into();
+    for node in ready_nodes.iter() {
+        in_flight_nodes.insert(node.index);
+    }
+
+    let concurrency_cap = max_concurrent_nodes.map_or(usize::MAX, |cap| cap.max(1) as usize);
+    let mut subtasks: Vec<BoxFuture<'static, ProcessNode>> = Vec::new();
+
+    loop {
+        while subtasks.len() < concurrency_cap {
+            let Some(node) = ready_nodes.pop_front() else {
+                break;
+            };
+
+            subtasks.push(process_node(node).boxed());
+        }
+
+        if subtasks.is_empty() {
+            break;
+        }
+
+        // Await the first subtask to complete
+        let result = if subtasks.len() == 1 {
+            // Only one task left, no need to use select_all
+            subtasks.pop().unwrap().await
+        } else {
+            let (result, _index, remaining) = select_all(subtasks).await;
+            subtasks = remaining;
+            result
+        };
+
+        // TODO: Take the finished 'result' and send it back to main thread early
+        // rather than waiting for the entire graph to complete
+        // but don't bother until it's noticably annoying that you dont do this (i.e. until partial completion actually matters to the UX)
+
+        let result_idx = result.index.clone();
+        results.insert(result_idx, result);
+        in_flight_nodes.remove(&result_idx);
+        unprocessed_nodes.remove(&result_idx);
+
+        // Add any new node processing tasks for nodes that now have resolved dependencies
+        let new_nodes_to_process =
+            get_processible_nodes(&graph_copy, &unprocessed_nodes, &in_flight_nodes);
+        for node in new_nodes_to_process.into_iter() {
+            in_flight_nodes.insert(node.index);
+
+            let node_dependencies =
+                graph_copy.edges_directed(node.index, Direction::Incoming);
+
+            let mut node_with_resolved_dependencies = node.clone();
+
+            for edge in node_dependencies {
+                // Use the post-process version of the dependency node, since the entry in graph itself isn't updated yet
+                let from = results
+                    .get(&edge.source())
+                    .expect("Tried to depend on a node that hasn't been processed yet.");
+                let edge_data = edge.weight();
+
+                // Update the dependant node
+                
+                let _ = node_with_resolved_dependencies.node.kind.set_input(
+                    edge_data.to_field,
+                    from.node.kind.get_output(edge_data.from_field).unwrap(),
+                );
+            }
+
+            ready_nodes.push_back(node_with_resolved_dependencies);
+        }
+    }
+
+    let mut results_vec = Vec::with_capacity(results.len());
+    results
+        .into_iter()
+        .for_each(|(_index, process_node)| results_vec.push(process_node));
+    results_vec
+}
+
+// Skips the work when the node is cacheable and its inputs match the ones its outputs came from
 async fn process_node(mut p_node: ProcessNode) -> ProcessNode {
+    let inputs: Vec<Field> = p_node
+        .node
+        .kind
+        .input_fields()
+        .iter()
+        .filter_map(|&input_id| p_node.node.kind.get_input(input_id))
+        .collect();
+
+    let is_unchanged = p_node
+        .node
+        .processed_inputs
+        .as_ref()
+        .map_or(false, |processed_inputs| same_inputs(processed_inputs, &inputs));
+
+    if p_node.node.cacheable && is_unchanged {
+        return p_node;
+    }
+
     let start = Instant::now();
 
     p_node.node.kind.process().await;
 
     p_node.node.last_process_time = start.elapsed();
+    p_node.node.processed_inputs = Some(inputs);
+    p_node.node.last_error = node_error(&p_node.node.kind).map(str::to_string);
 
     p_node
 }
 
+// Field equality never matches images, so they're compared by their contents here
+fn same_inputs(a: &[Field], b: &[Field]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b.iter()).all(|pair| match pair {
+            (Field::Image(Some(a)), Field::Image(Some(b))) => {
+                a.texture_descriptor.size == b.texture_descriptor.size
+                    && a.texture_descriptor.format == b.texture_descriptor.format
+                    && a.data == b.data
+            }
+            (Field::Image(None), Field::Image(None)) => true,
+            (a, b) => a == b,
+        })
+}
+
 // Determines which nodes have resolved dependencies and are not currently being processed.
 fn get_processible_nodes(
     graph: &StableDiGraph<GraphNode, Edge>,
@@ -288,6 +492,43 @@ fn get_processible_nodes(
     processible_nodes
 }
 
+// Every node reachable from start by following edges in either direction, start included
+pub fn connected_nodes(graph: &StableDiGraph<GraphNode, Edge>, start: NodeIndex) -> HashSet<NodeIndex> {
+    let mut visited: HashSet<NodeIndex> = HashSet::new();
+    let mut to_visit = vec![start];
+
+    while let Some(node_idx) = to_visit.pop() {
+        if graph.node_weight(node_idx).is_none() || !visited.insert(node_idx) {
+            continue;
+        }
+
+        to_visit.extend(graph.neighbors_undirected(node_idx));
+    }
+
+    visited
+}
+
+// Every node reachable from start by following edges only in the given direction, start included.
+// Incoming finds everything start depends on, Outgoing everything that depends on start.
+pub fn directed_reachable_nodes(
+    graph: &StableDiGraph<GraphNode, Edge>,
+    start: NodeIndex,
+    direction: Direction,
+) -> HashSet<NodeIndex> {
+    let mut visited: HashSet<NodeIndex> = HashSet::new();
+    let mut to_visit = vec![start];
+
+    while let Some(node_idx) = to_visit.pop() {
+        if graph.node_weight(node_idx).is_none() || !visited.insert(node_idx) {
+            continue;
+        }
+
+        to_visit.extend(graph.neighbors_directed(node_idx, direction));
+    }
+
+    visited
+}
+
 pub trait AddEdgeChecked {
     fn add_edge_checked(
         &mut self,
@@ -9,6 +9,7 @@ use bevy::{
         Extract, RenderApp,
     }, sprite::{Material2d, Material2dKey, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle}, utils::HashMap
 };
+use serde::{Deserialize, Serialize};
 use wgpu::PrimitiveTopology;
 
 pub struct LineRenderingPlugin;
@@ -29,6 +30,8 @@ pub struct Line {
     pub points: Vec<Vec2>,
     pub colors: Vec<LinearRgba>,
     pub thickness: f32,
+    // scales the line's brightness, 1 draws it as is
+    pub dim: f32,
 }
 
 const ATTRIBUTE_POSITION: MeshVertexAttribute = MeshVertexAttribute::new("Vertex_Position", 0, VertexFormat::Float32x3);
@@ -40,6 +43,8 @@ const ATTRIBUTE_COLOR: MeshVertexAttribute = MeshVertexAttribute::new("Vertex_Co
 pub struct LineMaterial {
     #[uniform(0)]
     pub thickness: f32,
+    #[uniform(1)]
+    pub dim: f32,
 }
 
 impl Material2d for LineMaterial {
@@ -74,19 +79,32 @@ fn update_line_meshes(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<LineMaterial>>,
-    mut material_cache: Local<HashMap<u32, Handle<LineMaterial>>>,
-    query: Query<(Entity, &Line, Option<&Mesh2dHandle>), Changed<Line>>,
+    mut material_cache: Local<HashMap<(u32, u32), Handle<LineMaterial>>>,
+    query: Query<(Entity, &Line, Option<&Mesh2dHandle>, Option<&Handle<LineMaterial>>), Changed<Line>>,
 ) {
-    for (entity, line, maybe_mesh_handle) in query.iter() {
+    for (entity, line, maybe_mesh_handle, maybe_material_handle) in query.iter() {
         if line.points.len() < 2 || line.colors.len() != line.points.len() {
             continue; // Not enough points or mismatched colors
         }
 
         let attribute_size = line.points.len() * 2;
-        let rounded_key = (line.thickness * 1000.0).round() as u32; // thicknesses less than .00001 apart will use the same material
+        let rounded_key = (
+            (line.thickness * 1000.0).round() as u32, // thicknesses less than .00001 apart will use the same material
+            (line.dim * 1000.0).round() as u32,
+        );
+
+        let material_handle = material_cache
+            .entry(rounded_key)
+            .or_insert_with(|| materials.add(LineMaterial { thickness: line.thickness, dim: line.dim }))
+            .clone();
 
         let mesh = match maybe_mesh_handle {
             Some(mesh_handle) => {
+                // thickness and dim can change after spawning
+                if maybe_material_handle != Some(&material_handle) {
+                    commands.entity(entity).insert(material_handle);
+                }
+
                 meshes.get_mut(mesh_handle.id()).unwrap()
             },
             None => {
@@ -100,17 +118,9 @@ fn update_line_meshes(
                 let mesh_handle = Mesh2dHandle(meshes.add(mesh));
                 let id = mesh_handle.id();
 
-                let material_handle = if let Some(cached) = material_cache.get(&rounded_key) {
-                    cached
-                } else {
-                    let handle = materials.add(LineMaterial { thickness: line.thickness });
-                    material_cache.insert(rounded_key, handle.clone());
-                    &handle.clone()
-                };
-
                 commands.entity(entity).insert(MaterialMesh2dBundle {
                     mesh: mesh_handle,
-                    material: material_handle.clone(),
+                    material: material_handle,
                     ..default()
                 });
 
@@ -248,13 +258,129 @@ fn extract_lines(
 Below this line shouldn't get copy/pasted into crate, thank you future me.
 */
 
-pub fn generate_curved_line(start: Vec2, end: Vec2, segments: usize) -> Vec<Vec2> {
+pub const EDGE_LINE_SEGMENTS: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EdgeStyle {
+    Curved,
+    Straight,
+    Orthogonal,
+}
+
+impl EdgeStyle {
+    pub const ALL: [EdgeStyle; 3] = [EdgeStyle::Curved, EdgeStyle::Straight, EdgeStyle::Orthogonal];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EdgeStyle::Curved => "Curved",
+            EdgeStyle::Straight => "Straight",
+            EdgeStyle::Orthogonal => "Orthogonal",
+        }
+    }
+}
+
+// Which way data flows across the canvas: Horizontal puts inputs on the left of a node and outputs on the right,
+// Vertical puts inputs on top and outputs on the bottom
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GraphOrientation {
+    Horizontal,
+    Vertical,
+}
+
+impl GraphOrientation {
+    pub const ALL: [GraphOrientation; 2] = [GraphOrientation::Horizontal, GraphOrientation::Vertical];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GraphOrientation::Horizontal => "Horizontal",
+            GraphOrientation::Vertical => "Vertical",
+        }
+    }
+
+    // The direction an edge leaves an output port in
+    pub fn flow(&self) -> Vec2 {
+        match self {
+            GraphOrientation::Horizontal => Vec2::X,
+            GraphOrientation::Vertical => Vec2::NEG_Y,
+        }
+    }
+}
+
+// Every style yields the same number of points, so the color gradient along an edge never has to be rebuilt
+pub fn generate_edge_line(
+    style: EdgeStyle,
+    orientation: GraphOrientation,
+    start: Vec2,
+    end: Vec2,
+    segments: usize,
+) -> Vec<Vec2> {
+    match style {
+        EdgeStyle::Curved => generate_curved_line(start, end, orientation.flow(), segments),
+        EdgeStyle::Straight => generate_straight_line(start, end, segments),
+        EdgeStyle::Orthogonal => generate_orthogonal_line(start, end, orientation, segments),
+    }
+}
+
+pub fn generate_straight_line(start: Vec2, end: Vec2, segments: usize) -> Vec<Vec2> {
+    sample_polyline(&[start, end], segments)
+}
+
+// Along the flow out of the start, across it at the midpoint, then along the flow into the end
+pub fn generate_orthogonal_line(start: Vec2, end: Vec2, orientation: GraphOrientation, segments: usize) -> Vec<Vec2> {
+    let corners = match orientation {
+        GraphOrientation::Horizontal => {
+            let mid_x = (start.x + end.x) / 2.;
+            [start, Vec2::new(mid_x, start.y), Vec2::new(mid_x, end.y), end]
+        }
+        GraphOrientation::Vertical => {
+            let mid_y = (start.y + end.y) / 2.;
+            [start, Vec2::new(start.x, mid_y), Vec2::new(end.x, mid_y), end]
+        }
+    };
+
+    sample_polyline(&corners, segments)
+}
+
+// Evenly spaced points along a polyline, with each corner pinned to its nearest point so corners stay sharp
+fn sample_polyline(corners: &[Vec2], segments: usize) -> Vec<Vec2> {
+    let leg_lengths: Vec<f32> = corners.windows(2).map(|leg| leg[0].distance(leg[1])).collect();
+    let total_length: f32 = leg_lengths.iter().sum();
+
+    if total_length <= f32::EPSILON || segments < 2 {
+        return vec![corners[0]; segments];
+    }
+
+    let point_at = |mut distance: f32| -> Vec2 {
+        for (leg, length) in corners.windows(2).zip(&leg_lengths) {
+            if distance <= *length && *length > 0. {
+                return leg[0].lerp(leg[1], distance / length);
+            }
+            distance -= length;
+        }
+        *corners.last().unwrap()
+    };
+
+    let step = total_length / (segments - 1) as f32;
+    let mut points: Vec<Vec2> = (0..segments).map(|i| point_at(i as f32 * step)).collect();
+
+    let mut distance_to_corner = 0.;
+    for (corner, length) in corners[1..corners.len() - 1].iter().zip(&leg_lengths) {
+        distance_to_corner += length;
+        let nearest = ((distance_to_corner / step).round() as usize).clamp(1, segments - 2);
+        points[nearest] = *corner;
+    }
+
+    points
+}
+
+// flow is the direction the curve leaves the start and enters the end
+pub fn generate_curved_line(start: Vec2, end: Vec2, flow: Vec2, segments: usize) -> Vec<Vec2> {
     let diff = end - start;
     let dist = diff.length();
     
     // Calculate control points
-    let control1 = start + Vec2::new(dist * 0.25, 0.0);
-    let control2 = end - Vec2::new(dist * 0.25, 0.0);
+    let control1 = start + flow * dist * 0.25;
+    let control2 = end - flow * dist * 0.25;
 
     generate_cubic_bezier(start, control1, control2, end, segments)
 }
@@ -12,13 +12,19 @@ mod ui;
 mod camera;
 mod line_renderer;
 mod events;
+mod export;
+mod preferences;
+mod validation;
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
+        .add_plugins(preferences::PreferencesPlugin)
         .add_plugins(asset::AssetPlugin)
         .add_plugins(setup::SetupPlugin)
         .add_plugins(graph::GraphPlugin)
+        .add_plugins(export::ExportPlugin)
+        .add_plugins(validation::ValidationPlugin)
         .add_plugins(ui::UiPlugin)
         .add_plugins(nodes::NodePlugin)
         .add_plugins(camera::CameraPlugin)
@@ -40,4 +46,6 @@ enum ApplicationState {
     AssetProcessing,
     Setup,
     MainLoop,
+    // no GPU device could be created for node processing; only an error screen is shown
+    GpuUnavailable,
 }
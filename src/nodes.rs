@@ -1,63 +1,92 @@
 #![allow(non_upper_case_globals)]
 
+pub mod custom_shader;
+pub mod drag_threshold;
 pub mod fields;
+pub mod image_decode;
 pub mod kinds;
 pub mod macros;
 pub mod ports;
+pub mod progress;
+pub mod shader_reload;
 pub mod shared;
+pub mod tiling;
 
-use std::time::Duration;
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::PathBuf,
+    time::Duration,
+};
 
 use crate::{
-    asset::NodeDisplayMaterial,
+    asset::{GeneratedMeshes, NodeDisplayMaterial, NODE_CONTENT_PADDING, NODE_TEXTURE_DISPLAY_DIMENSION, NODE_TITLE_BAR_SIZE, NODE_WIDTH},
     camera::MainCamera,
-    events::{node_events::UndoableDragNodeEvent, UndoableEvent},
-    graph::{DisjointPipelineGraph, GraphWasUpdated},
-    line_renderer::{generate_curved_line, Line},
+    events::{edge_events::SpliceNodeOntoEdgeEvent, node_events::UndoableDragNodeEvent, UndoableEvent},
+    graph::{DisjointPipelineGraph, GraphWasUpdated, ProcessProgress},
+    line_renderer::{generate_edge_line, GraphOrientation, Line},
+    preferences::{NodeDefaults, UiPreferences},
     setup::ApplicationCanvas,
     ApplicationState,
 };
 use bevy::{
+    ecs::system::SystemParam,
     prelude::*,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
     utils::HashMap,
 };
 use bevy_mod_picking::{
-    events::{Down, Drag, DragEnd, DragStart, Pointer},
+    events::{Down, Pointer},
     focus::PickingInteraction,
     prelude::PointerButton,
 };
+use custom_shader::{CustomShader, CustomShaderPlugin};
+use drag_threshold::{apply_drag_threshold, DragThresholdPlugin, ThresholdDrag, ThresholdDragEnd, ThresholdDragStart};
 use fields::{Field, FieldMeta};
-use kinds::{blend::{BlendNode, SerializableBlendNode}, color::{ColorNode, SerializableColorNode}, example::SerializableExampleNode, shape::{SerializableShapeNode, ShapeNode}};
+use image_decode::ImageDecodePlugin;
+use kinds::{blend::{BlendNode, SerializableBlendNode}, color::{ColorNode, SerializableColorNode}, dither::{DitherNode, SerializableDitherNode}, example::SerializableExampleNode, image::{ImageNode, SerializableImageNode}, shape::{SerializableShapeNode, ShapeNode}, levels::{LevelsNode, SerializableLevelsNode}, curves::{CurvesNode, SerializableCurvesNode}, number::{NumberNode, SerializableNumberNode}, math::{MathNode, SerializableMathNode}, clear::{ClearNode, SerializableClearNode}, premultiply::{PremultiplyNode, SerializablePremultiplyNode, SerializableUnpremultiplyNode, UnpremultiplyNode}, convolve::{ConvolveNode, SerializableConvolveNode}, sharpen::{SerializableSharpenNode, SharpenNode}, displace::{DisplaceNode, SerializableDisplaceNode}, probe::{ProbeNode, SerializableProbeNode}, custom_compute::{CustomComputeNode, SerializableCustomComputeNode}};
 use kinds::example::ExampleNode;
 use macros::macros::declare_node_enum_and_impl_trait;
 use petgraph::{graph::NodeIndex, visit::IntoNodeReferences};
-use ports::{InputPort, OutputPort, PortPlugin};
+use ports::{format_label_text, InputPort, OutputPort, PortPlugin};
+use progress::NodeProgress;
+use shader_reload::ShaderReloadPlugin;
 use serde::{Deserialize, Serialize};
+use shared::{downsample_image, format_process_time};
 use uuid::Uuid;
 
 pub struct NodePlugin;
 
 impl Plugin for NodePlugin {
     fn build(&self, app: &mut App) {
+        app.add_plugins(DragThresholdPlugin);
         app.add_plugins(PortPlugin);
+        app.add_plugins(ShaderReloadPlugin);
+        app.add_plugins(CustomShaderPlugin);
+        app.add_plugins(ImageDecodePlugin);
         app.insert_resource(NodeCount(0u32));
 
         app.add_systems(
             Update,
             (
-                (handle_node_drag, update_edge_lines, handle_node_selection),
-                (update_node_border),
+                (handle_node_drag, update_edge_lines, handle_node_selection, update_edge_highlights),
+                (update_node_border, update_node_decoration_visibility, update_node_progress_text),
             )
                 .chain()
+                .after(apply_drag_threshold)
                 .run_if(in_state(ApplicationState::MainLoop)),
         );
 
         app.insert_resource(NodeIdMapping(HashMap::new()));
-        app.observe(update_nodes).observe(node_z_to_top);
+        app.init_resource::<PreviewImageCache>();
+        app.init_resource::<IsolateSelection>();
+        app.observe(update_nodes).observe(node_z_to_top).observe(toggle_isolate_selection);
     }
 }
 
+// Drop shadow quad behind each node
+#[derive(Component)]
+pub struct NodeShadow;
+
 #[derive(Component)]
 pub struct NodeDisplay {
     pub index: NodeIndex,
@@ -94,11 +123,16 @@ pub enum FieldId {
 pub trait NodeTrait {
     fn get_input(&self, id: InputId) -> Option<Field>;
     fn get_output(&self, id: OutputId) -> Option<Field>;
+    // Like get_output, but borrowed. None unless the output is an image that has been produced.
+    fn get_output_image(&self, id: OutputId) -> Option<&Image>;
+    fn get_output_color(&self, id: OutputId) -> Option<LinearRgba>;
     fn set_input(&mut self, id: InputId, value: Field) -> Result<(), String>;
     fn set_output(&mut self, id: OutputId, value: Field) -> Result<(), String>;
     fn input_fields(&self) -> &[InputId];
     fn output_fields(&self) -> &[OutputId];
 
+    // Runs on the async compute pool with no access to the render world. Nodes that need the GPU
+    //  hold their own device and queue from construction, so purely CPU nodes (Number, Math) need neither
     async fn process(&mut self);
     
     fn entity(&self) -> Entity;
@@ -112,14 +146,45 @@ pub trait NodeTrait {
 
     fn store_all(&mut self);
     fn load_all(&mut self);
+
+    // The first image output the node has produced, which is what its preview and exports show
+    fn primary_output_image(&self) -> Option<&Image> {
+        self.output_fields().iter().find_map(|&id| self.get_output_image(id))
+    }
+
+    // The first color output, shown behind the node's preview for nodes that make colors rather than images
+    fn primary_display_color(&self) -> Option<LinearRgba> {
+        self.output_fields().iter().find_map(|&id| self.get_output_color(id))
+    }
+
+    // Procedural nodes opt in to Reseed All by declaring `#[input] seed: u32`
+    fn seed_input(&self) -> Option<InputId> {
+        self.input_fields().iter().copied().find(|id| id.1 == SEED_FIELD)
+    }
 }
 
+pub const SEED_FIELD: &str = "seed";
+
 declare_node_enum_and_impl_trait! {
     pub enum GraphNodeKind {
         Example(ExampleNode),
         Color(ColorNode),
         Shape(ShapeNode),
         Blend(BlendNode),
+        Dither(DitherNode),
+        Image(ImageNode),
+        Levels(LevelsNode),
+        Curves(CurvesNode),
+        Number(NumberNode),
+        Math(MathNode),
+        Clear(ClearNode),
+        Premultiply(PremultiplyNode),
+        Unpremultiply(UnpremultiplyNode),
+        Convolve(ConvolveNode),
+        Sharpen(SharpenNode),
+        Displace(DisplaceNode),
+        CustomCompute(CustomComputeNode),
+        Probe(ProbeNode),
     }
 }
 
@@ -129,6 +194,120 @@ pub enum RequestSpawnNodeKind {
     Color,
     Shape,
     Blend,
+    Dither,
+    Levels,
+    Curves,
+    Number,
+    Math,
+    Clear,
+    Premultiply,
+    Unpremultiply,
+    Convolve,
+    Sharpen,
+    Displace,
+    CustomCompute,
+    Probe,
+}
+
+impl RequestSpawnNodeKind {
+    pub const ALL: [RequestSpawnNodeKind; 17] = [
+        RequestSpawnNodeKind::Example,
+        RequestSpawnNodeKind::Color,
+        RequestSpawnNodeKind::Shape,
+        RequestSpawnNodeKind::Blend,
+        RequestSpawnNodeKind::Dither,
+        RequestSpawnNodeKind::Levels,
+        RequestSpawnNodeKind::Curves,
+        RequestSpawnNodeKind::Number,
+        RequestSpawnNodeKind::Math,
+        RequestSpawnNodeKind::Clear,
+        RequestSpawnNodeKind::Premultiply,
+        RequestSpawnNodeKind::Unpremultiply,
+        RequestSpawnNodeKind::Convolve,
+        RequestSpawnNodeKind::Sharpen,
+        RequestSpawnNodeKind::Displace,
+        RequestSpawnNodeKind::CustomCompute,
+        RequestSpawnNodeKind::Probe,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RequestSpawnNodeKind::Example => "Example",
+            RequestSpawnNodeKind::Color => "Color",
+            RequestSpawnNodeKind::Shape => "Shape",
+            RequestSpawnNodeKind::Blend => "Blend",
+            RequestSpawnNodeKind::Dither => "Dither",
+            RequestSpawnNodeKind::Levels => "Levels",
+            RequestSpawnNodeKind::Curves => "Curves",
+            RequestSpawnNodeKind::Number => "Number",
+            RequestSpawnNodeKind::Math => "Math",
+            RequestSpawnNodeKind::Clear => "Clear",
+            RequestSpawnNodeKind::Premultiply => "Premultiply",
+            RequestSpawnNodeKind::Unpremultiply => "Unpremultiply",
+            RequestSpawnNodeKind::Convolve => "Convolve",
+            RequestSpawnNodeKind::Sharpen => "Sharpen",
+            RequestSpawnNodeKind::Displace => "Displace",
+            RequestSpawnNodeKind::CustomCompute => "Custom Compute",
+            RequestSpawnNodeKind::Probe => "Probe",
+        }
+    }
+
+    // Instrumentation for working on the editor itself, hidden from menus unless the preference is on
+    pub fn is_developer_only(&self) -> bool {
+        matches!(self, RequestSpawnNodeKind::Probe)
+    }
+
+    pub fn input_prototypes(&self) -> Vec<(InputId, Field)> {
+        match self {
+            RequestSpawnNodeKind::Example => ExampleNode::input_prototypes(),
+            RequestSpawnNodeKind::Color => ColorNode::input_prototypes(),
+            RequestSpawnNodeKind::Shape => ShapeNode::input_prototypes(),
+            RequestSpawnNodeKind::Blend => BlendNode::input_prototypes(),
+            RequestSpawnNodeKind::Dither => DitherNode::input_prototypes(),
+            RequestSpawnNodeKind::Levels => LevelsNode::input_prototypes(),
+            RequestSpawnNodeKind::Curves => CurvesNode::input_prototypes(),
+            RequestSpawnNodeKind::Number => NumberNode::input_prototypes(),
+            RequestSpawnNodeKind::Math => MathNode::input_prototypes(),
+            RequestSpawnNodeKind::Clear => ClearNode::input_prototypes(),
+            RequestSpawnNodeKind::Premultiply => PremultiplyNode::input_prototypes(),
+            RequestSpawnNodeKind::Unpremultiply => UnpremultiplyNode::input_prototypes(),
+            RequestSpawnNodeKind::Convolve => ConvolveNode::input_prototypes(),
+            RequestSpawnNodeKind::Sharpen => SharpenNode::input_prototypes(),
+            RequestSpawnNodeKind::Displace => DisplaceNode::input_prototypes(),
+            RequestSpawnNodeKind::CustomCompute => CustomComputeNode::input_prototypes(),
+            RequestSpawnNodeKind::Probe => ProbeNode::input_prototypes(),
+        }
+    }
+
+    // Takes an image in and gives an image back, so it can be chained after any image source
+    pub fn is_image_effect(&self) -> bool {
+        let is_image = |field: &Field| matches!(field, Field::Image(_));
+
+        self.input_prototypes().iter().any(|(_, field)| is_image(field))
+            && self.output_prototypes().iter().any(|(_, field)| is_image(field))
+    }
+
+    pub fn output_prototypes(&self) -> Vec<(OutputId, Field)> {
+        match self {
+            RequestSpawnNodeKind::Example => ExampleNode::output_prototypes(),
+            RequestSpawnNodeKind::Color => ColorNode::output_prototypes(),
+            RequestSpawnNodeKind::Shape => ShapeNode::output_prototypes(),
+            RequestSpawnNodeKind::Blend => BlendNode::output_prototypes(),
+            RequestSpawnNodeKind::Dither => DitherNode::output_prototypes(),
+            RequestSpawnNodeKind::Levels => LevelsNode::output_prototypes(),
+            RequestSpawnNodeKind::Curves => CurvesNode::output_prototypes(),
+            RequestSpawnNodeKind::Number => NumberNode::output_prototypes(),
+            RequestSpawnNodeKind::Math => MathNode::output_prototypes(),
+            RequestSpawnNodeKind::Clear => ClearNode::output_prototypes(),
+            RequestSpawnNodeKind::Premultiply => PremultiplyNode::output_prototypes(),
+            RequestSpawnNodeKind::Unpremultiply => UnpremultiplyNode::output_prototypes(),
+            RequestSpawnNodeKind::Convolve => ConvolveNode::output_prototypes(),
+            RequestSpawnNodeKind::Sharpen => SharpenNode::output_prototypes(),
+            RequestSpawnNodeKind::Displace => DisplaceNode::output_prototypes(),
+            RequestSpawnNodeKind::CustomCompute => CustomComputeNode::output_prototypes(),
+            RequestSpawnNodeKind::Probe => ProbeNode::output_prototypes(),
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -136,7 +315,46 @@ pub enum SerializableGraphNodeKind {
     Example(SerializableExampleNode),
     Color(SerializableColorNode),
     Shape(SerializableShapeNode),
-    Blend(SerializableBlendNode)
+    Blend(SerializableBlendNode),
+    Dither(SerializableDitherNode),
+    Image(SerializableImageNode),
+    Levels(SerializableLevelsNode),
+    Curves(SerializableCurvesNode),
+    Number(SerializableNumberNode),
+    Math(SerializableMathNode),
+    Clear(SerializableClearNode),
+    Premultiply(SerializablePremultiplyNode),
+    Unpremultiply(SerializableUnpremultiplyNode),
+    Convolve(SerializableConvolveNode),
+    Sharpen(SerializableSharpenNode),
+    Displace(SerializableDisplaceNode),
+    CustomCompute(SerializableCustomComputeNode),
+    Probe(SerializableProbeNode),
+}
+
+impl From<&GraphNodeKind> for SerializableGraphNodeKind {
+    fn from(kind: &GraphNodeKind) -> Self {
+        match kind {
+            GraphNodeKind::Example(example_node) => SerializableGraphNodeKind::from(example_node),
+            GraphNodeKind::Color(color_node) => SerializableGraphNodeKind::from(color_node),
+            GraphNodeKind::Shape(shape_node) => SerializableGraphNodeKind::from(shape_node),
+            GraphNodeKind::Blend(blend_node) => SerializableGraphNodeKind::from(blend_node),
+            GraphNodeKind::Dither(dither_node) => SerializableGraphNodeKind::from(dither_node),
+            GraphNodeKind::Image(image_node) => SerializableGraphNodeKind::from(image_node),
+            GraphNodeKind::Levels(levels_node) => SerializableGraphNodeKind::from(levels_node),
+            GraphNodeKind::Curves(curves_node) => SerializableGraphNodeKind::from(curves_node),
+            GraphNodeKind::Number(number_node) => SerializableGraphNodeKind::from(number_node),
+            GraphNodeKind::Math(math_node) => SerializableGraphNodeKind::from(math_node),
+            GraphNodeKind::Clear(clear_node) => SerializableGraphNodeKind::from(clear_node),
+            GraphNodeKind::Premultiply(premultiply_node) => SerializableGraphNodeKind::from(premultiply_node),
+            GraphNodeKind::Unpremultiply(unpremultiply_node) => SerializableGraphNodeKind::from(unpremultiply_node),
+            GraphNodeKind::Convolve(convolve_node) => SerializableGraphNodeKind::from(convolve_node),
+            GraphNodeKind::Sharpen(sharpen_node) => SerializableGraphNodeKind::from(sharpen_node),
+            GraphNodeKind::Displace(displace_node) => SerializableGraphNodeKind::from(displace_node),
+            GraphNodeKind::CustomCompute(custom_compute_node) => SerializableGraphNodeKind::from(custom_compute_node),
+            GraphNodeKind::Probe(probe_node) => SerializableGraphNodeKind::from(probe_node),
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -144,6 +362,15 @@ pub struct SerializableGraphNode {
     pub id: Uuid,
     pub position: Vec3,
     pub kind: SerializableGraphNodeKind,
+    #[serde(default = "default_cacheable")]
+    pub cacheable: bool,
+    // a WGSL file the node runs in place of its built-in shader
+    #[serde(default)]
+    pub custom_shader: Option<PathBuf>,
+}
+
+fn default_cacheable() -> bool {
+    true
 }
 
 impl SerializableGraphNode {
@@ -153,6 +380,65 @@ impl SerializableGraphNode {
             SerializableGraphNodeKind::Color(n) => n.entity,
             SerializableGraphNodeKind::Shape(n) => n.entity,
             SerializableGraphNodeKind::Blend(n) => n.entity,
+            SerializableGraphNodeKind::Dither(n) => n.entity,
+            SerializableGraphNodeKind::Image(n) => n.entity,
+            SerializableGraphNodeKind::Levels(n) => n.entity,
+            SerializableGraphNodeKind::Curves(n) => n.entity,
+            SerializableGraphNodeKind::Number(n) => n.entity,
+            SerializableGraphNodeKind::Math(n) => n.entity,
+            SerializableGraphNodeKind::Clear(n) => n.entity,
+            SerializableGraphNodeKind::Premultiply(n) => n.entity,
+            SerializableGraphNodeKind::Unpremultiply(n) => n.entity,
+            SerializableGraphNodeKind::Convolve(n) => n.entity,
+            SerializableGraphNodeKind::Sharpen(n) => n.entity,
+            SerializableGraphNodeKind::Displace(n) => n.entity,
+            SerializableGraphNodeKind::CustomCompute(n) => n.entity,
+            SerializableGraphNodeKind::Probe(n) => n.entity,
+        }
+    }
+    pub fn set_entity(&mut self, entity: Entity) {
+        match &mut self.kind {
+            SerializableGraphNodeKind::Example(n) => n.entity = entity,
+            SerializableGraphNodeKind::Color(n) => n.entity = entity,
+            SerializableGraphNodeKind::Shape(n) => n.entity = entity,
+            SerializableGraphNodeKind::Blend(n) => n.entity = entity,
+            SerializableGraphNodeKind::Dither(n) => n.entity = entity,
+            SerializableGraphNodeKind::Image(n) => n.entity = entity,
+            SerializableGraphNodeKind::Levels(n) => n.entity = entity,
+            SerializableGraphNodeKind::Curves(n) => n.entity = entity,
+            SerializableGraphNodeKind::Number(n) => n.entity = entity,
+            SerializableGraphNodeKind::Math(n) => n.entity = entity,
+            SerializableGraphNodeKind::Clear(n) => n.entity = entity,
+            SerializableGraphNodeKind::Premultiply(n) => n.entity = entity,
+            SerializableGraphNodeKind::Unpremultiply(n) => n.entity = entity,
+            SerializableGraphNodeKind::Convolve(n) => n.entity = entity,
+            SerializableGraphNodeKind::Sharpen(n) => n.entity = entity,
+            SerializableGraphNodeKind::Displace(n) => n.entity = entity,
+            SerializableGraphNodeKind::CustomCompute(n) => n.entity = entity,
+            SerializableGraphNodeKind::Probe(n) => n.entity = entity,
+        }
+    }
+
+    pub fn kind_name(&self) -> &'static str {
+        match &self.kind {
+            SerializableGraphNodeKind::Example(_) => "Example",
+            SerializableGraphNodeKind::Color(_) => "Color",
+            SerializableGraphNodeKind::Shape(_) => "Shape",
+            SerializableGraphNodeKind::Blend(_) => "Blend",
+            SerializableGraphNodeKind::Dither(_) => "Dither",
+            SerializableGraphNodeKind::Image(_) => "Image",
+            SerializableGraphNodeKind::Levels(_) => "Levels",
+            SerializableGraphNodeKind::Curves(_) => "Curves",
+            SerializableGraphNodeKind::Number(_) => "Number",
+            SerializableGraphNodeKind::Math(_) => "Math",
+            SerializableGraphNodeKind::Clear(_) => "Clear",
+            SerializableGraphNodeKind::Premultiply(_) => "Premultiply",
+            SerializableGraphNodeKind::Unpremultiply(_) => "Unpremultiply",
+            SerializableGraphNodeKind::Convolve(_) => "Convolve",
+            SerializableGraphNodeKind::Sharpen(_) => "Sharpen",
+            SerializableGraphNodeKind::Displace(_) => "Displace",
+            SerializableGraphNodeKind::CustomCompute(_) => "Custom Compute",
+            SerializableGraphNodeKind::Probe(_) => "Probe",
         }
     }
 }
@@ -161,11 +447,64 @@ impl SerializableGraphNode {
 pub struct GraphNode {
     pub last_process_time: Duration,
     pub kind: GraphNodeKind,
+    // When false the node reprocesses on every pass, for nodes whose output depends on more than their inputs
+    pub cacheable: bool,
+    // The inputs the current outputs were produced from. None until the node has processed once.
+    pub processed_inputs: Option<Vec<Field>>,
+    pub custom_shader: Option<CustomShader>,
+    // Why the node's last process couldn't produce its output, if it couldn't
+    pub last_error: Option<String>,
 }
 
 #[derive(Component)]
 pub struct NodeProcessText;
 
+// Node preview textures keyed by a hash of their contents, so nodes showing identical images share one texture.
+// Only the display assets are shared; each node's full resolution output stays its own Image in the graph.
+// Handles are weak, so an entry disappears once no node displays it anymore.
+#[derive(Resource, Default)]
+pub struct PreviewImageCache(HashMap<u64, Vec<Handle<Image>>>);
+
+impl PreviewImageCache {
+    pub fn get_or_insert(&mut self, image: Image, images: &mut Assets<Image>) -> Handle<Image> {
+        let hash = hash_image(&image);
+        let entries = self.0.entry(hash).or_default();
+
+        // a matching hash could still be a collision, so compare the bytes before sharing
+        let existing = entries.iter().find(|handle| {
+            images.get(handle.id()).map_or(false, |cached| {
+                cached.texture_descriptor.size == image.texture_descriptor.size
+                    && cached.texture_descriptor.format == image.texture_descriptor.format
+                    && cached.data == image.data
+            })
+        });
+
+        if let Some(handle) = existing.and_then(|handle| images.get_strong_handle(handle.id())) {
+            return handle;
+        }
+
+        let handle = images.add(image);
+        entries.push(handle.clone_weak());
+        handle
+    }
+
+    pub fn prune(&mut self, images: &Assets<Image>) {
+        self.0.retain(|_, entries| {
+            entries.retain(|handle| images.contains(handle.id()));
+            !entries.is_empty()
+        });
+    }
+}
+
+fn hash_image(image: &Image) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.texture_descriptor.size.width.hash(&mut hasher);
+    image.texture_descriptor.size.height.hash(&mut hasher);
+    image.texture_descriptor.format.hash(&mut hasher);
+    image.data.hash(&mut hasher);
+    hasher.finish()
+}
+
 // Extract data from updated graph to the properties of the display entities
 fn update_nodes(
     _trigger: Trigger<GraphWasUpdated>,
@@ -174,9 +513,13 @@ fn update_nodes(
     mut q_process_time_text: Query<&mut Text, With<NodeProcessText>>,
     mut images: ResMut<Assets<Image>>,
     mut materials: ResMut<Assets<NodeDisplayMaterial>>,
+    ui_preferences: Res<UiPreferences>,
+    mut preview_cache: ResMut<PreviewImageCache>,
 ) {
     let graph = &q_pipeline.single().graph;
 
+    preview_cache.prune(&images);
+
     for (idx, node) in graph.node_references() {
         let probably_node = q_initialized_nodes.get_mut(node.kind.entity());
 
@@ -185,36 +528,38 @@ fn update_nodes(
                 node_display.index = idx; // The NodeIndex could've changed if the graph was modified...is that still true with the stable graph? i think no UNLESS we start preserving index across undo/redo
 
                 if let Ok(mut text) = q_process_time_text.get_mut(node_display.process_time_text) {
-                    text.sections[0].value = format!("{:?}", node.last_process_time);
+                    text.sections[0].value = format_process_time(node.last_process_time);
                 };
 
                 let material = materials.get_mut(material_handle.id()).unwrap();
-                let old_image = images.get_mut(material.node_texture.id()).expect(
-                    "Found an image handle on a node sprite that does not reference a known image.",
-                );
-                match &node.kind {
-                    GraphNodeKind::Example(ex) => {
-                        if let Some(image) = &ex.output_image {
-                            *old_image = image.clone();
-                        }
-                    }
-                    GraphNodeKind::Color(color_node) => {
-                        material.texture_background_color = color_node.out_color;
-                    },
-                    GraphNodeKind::Shape(shape_node) => {
-                        if let Some(image) = &shape_node.output_image {
-                            *old_image = image.clone();
-                        }
+                let error = if node.last_error.is_some() { 1. } else { 0. };
+                if material.error != error {
+                    material.error = error;
+                }
+
+                if let Some(color) = node.kind.primary_display_color() {
+                    material.texture_background_color = color;
+                }
+
+                if let Some(image) = node.kind.primary_output_image() {
+                    // the shader fits the preview to the real aspect ratio of the output
+                    material.texture_dimensions = image.size_f32();
+
+                    // the full resolution image stays in the graph, only the preview is downsampled
+                    let preview = ui_preferences
+                        .preview_max_dimension
+                        .and_then(|max_dimension| downsample_image(image, max_dimension))
+                        .unwrap_or_else(|| image.clone());
+
+                    let preview_handle = preview_cache.get_or_insert(preview, &mut images);
+                    if material.node_texture != preview_handle {
+                        material.node_texture = preview_handle;
                     }
-                    GraphNodeKind::Blend(blend_node) =>  {
-                        if let Some(image) = &blend_node.output_image {
-                            *old_image = image.clone();
-                        }
-                    },
                 }
             }
             Err(_) => {
-                panic!("A node in the graph did not have a matching display entity.");
+                // the display can be briefly missing, e.g. a pass finishing right after the node was spawned
+                // or removed, since those go through commands. Its next update will catch it up.
             }
         }
     }
@@ -224,9 +569,9 @@ fn handle_node_drag(
     mut commands: Commands,
     mut node_query: Query<(Entity, &mut Transform, Option<&Selected>), With<NodeDisplay>>,
     camera_query: Query<&OrthographicProjection>,
-    mut drag_start_events: EventReader<Pointer<DragStart>>,
-    mut drag_events: EventReader<Pointer<Drag>>,
-    mut drag_end_events: EventReader<Pointer<DragEnd>>,
+    mut drag_start_events: EventReader<ThresholdDragStart>,
+    mut drag_events: EventReader<ThresholdDrag>,
+    mut drag_end_events: EventReader<ThresholdDragEnd>,
     mut drag_info: Local<Option<HashMap<Entity, UndoableDragNodeEvent>>>,
 ) {
     let projection = camera_query.single();
@@ -297,9 +642,17 @@ fn handle_node_drag(
     // On drag end, empty the map and fire the event wrapped in an UndoableEvent
     for _ in drag_end_events.read() {
         if let Some(info) = drag_info.take() {
+            // a lone node dropped onto a wire gets spliced into it
+            let is_single_node = info.len() == 1;
+
             for drag_event in info.into_values() {
                 if drag_event.old_position != drag_event.new_position {
+                    let node_entity = drag_event.node_entity;
                     commands.trigger(UndoableEvent::DragNode(drag_event));
+
+                    if is_single_node {
+                        commands.trigger(SpliceNodeOntoEdgeEvent { node_entity });
+                    }
                 }
             }
         }
@@ -317,9 +670,9 @@ pub struct Selected;
 
 fn handle_node_selection(
     mut commands: Commands,
-    mut drag_start_events: EventReader<Pointer<DragStart>>,
-    mut drag_events: EventReader<Pointer<Drag>>,
-    mut drag_end_events: EventReader<Pointer<DragEnd>>,
+    mut drag_start_events: EventReader<ThresholdDragStart>,
+    mut drag_events: EventReader<ThresholdDrag>,
+    mut drag_end_events: EventReader<ThresholdDragEnd>,
     mut down_events: EventReader<Pointer<Down>>,
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     node_query: Query<
@@ -441,14 +794,12 @@ fn handle_node_selection(
                 }
 
                 for (entity, transform, mesh_handle, is_selected) in node_query.iter() {
-                    if let Some(mesh) = meshes.get(mesh_handle.0.id()) {
-                        let node_aabb = mesh.compute_aabb().unwrap();
-                        let node_min = transform
-                            .transform_point(node_aabb.min().truncate().extend(0.0))
-                            .truncate();
-                        let node_max = transform
-                            .transform_point(node_aabb.max().truncate().extend(0.0))
-                            .truncate();
+                    if let Some(node_rect) = meshes
+                        .get(mesh_handle.0.id())
+                        .and_then(|mesh| node_bounds(mesh, transform))
+                    {
+                        let node_min = node_rect.min;
+                        let node_max = node_rect.max;
 
                         if node_min.x <= max_x
                             && node_max.x >= min_x
@@ -482,6 +833,110 @@ fn handle_node_selection(
     }
 }
 
+// The world space rectangle a node's quad covers
+pub fn node_bounds(mesh: &Mesh, transform: &GlobalTransform) -> Option<Rect> {
+    let aabb = mesh.compute_aabb()?;
+    let min = transform.transform_point(aabb.min().truncate().extend(0.0)).truncate();
+    let max = transform.transform_point(aabb.max().truncate().extend(0.0)).truncate();
+
+    Some(Rect::from_corners(min, max))
+}
+
+const PLACEMENT_SEARCH_STEP: f32 = 32.;
+const PLACEMENT_SEARCH_RINGS: i32 = 24;
+
+// The smallest offset that keeps every placed rect clear of the occupied ones, found by
+// searching square rings of candidate offsets outward from where they were asked to go
+pub fn find_free_placement_offset(placed: &[Rect], occupied: &[Rect]) -> Vec2 {
+    let is_free = |offset: Vec2| {
+        placed.iter().all(|rect| {
+            let moved = Rect::from_corners(rect.min + offset, rect.max + offset);
+            occupied.iter().all(|other| moved.intersect(*other).is_empty())
+        })
+    };
+
+    for ring in 0..=PLACEMENT_SEARCH_RINGS {
+        let mut candidates: Vec<Vec2> = (-ring..=ring)
+            .flat_map(|x| (-ring..=ring).map(move |y| IVec2::new(x, y)))
+            .filter(|cell| cell.x.abs() == ring || cell.y.abs() == ring)
+            .map(|cell| cell.as_vec2() * PLACEMENT_SEARCH_STEP)
+            .collect();
+
+        candidates.sort_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+
+        if let Some(offset) = candidates.into_iter().find(|offset| is_free(*offset)) {
+            return offset;
+        }
+    }
+
+    // the neighborhood is packed; stacking is better than flinging the node far away
+    Vec2::ZERO
+}
+
+// Everything needed to keep new nodes from landing on top of existing ones
+#[derive(SystemParam)]
+pub struct NodePlacement<'w, 's> {
+    q_nodes: Query<'w, 's, (&'static Transform, &'static Mesh2dHandle), With<NodeDisplay>>,
+    meshes: Res<'w, Assets<Mesh>>,
+    generated_meshes: Res<'w, GeneratedMeshes>,
+    keyboard_input: Res<'w, ButtonInput<KeyCode>>,
+}
+
+impl NodePlacement<'_, '_> {
+    // Holding Alt places nodes exactly where they were asked to go, overlapping or not
+    pub fn is_precise(&self) -> bool {
+        self.keyboard_input.pressed(KeyCode::AltLeft) || self.keyboard_input.pressed(KeyCode::AltRight)
+    }
+
+    // The offset that moves nodes centered at the given positions off every existing node, keeping their layout
+    pub fn free_offset(&self, positions: &[Vec2]) -> Vec2 {
+        if self.is_precise() {
+            return Vec2::ZERO;
+        }
+
+        let Some(node_mesh) = self.meshes.get(self.generated_meshes.node_display_quad.0.id()) else {
+            return Vec2::ZERO;
+        };
+
+        let placed: Vec<Rect> = positions
+            .iter()
+            .filter_map(|position| node_bounds(node_mesh, &GlobalTransform::from_translation(position.extend(0.))))
+            .collect();
+
+        let occupied: Vec<Rect> = self
+            .q_nodes
+            .iter()
+            .filter_map(|(transform, mesh_handle)| {
+                self.meshes
+                    .get(mesh_handle.0.id())
+                    .and_then(|mesh| node_bounds(mesh, &GlobalTransform::from(*transform)))
+            })
+            .collect();
+
+        find_free_placement_offset(&placed, &occupied)
+    }
+
+    // Where a node spawned wired to neighbor belongs: one node along the direction edges flow, level with it.
+    // Downstream when the new node takes the neighbor's output, upstream when it feeds one of its inputs.
+    pub fn connected_position(&self, neighbor: Entity, downstream: bool, orientation: GraphOrientation) -> Option<Vec2> {
+        let (transform, _) = self.q_nodes.get(neighbor).ok()?;
+
+        let step = match orientation {
+            GraphOrientation::Horizontal => Vec2::new(NODE_WIDTH + CONNECTED_NODE_GAP, 0.),
+            GraphOrientation::Vertical => Vec2::new(
+                0.,
+                -(NODE_TITLE_BAR_SIZE + NODE_TEXTURE_DISPLAY_DIMENSION + NODE_CONTENT_PADDING + CONNECTED_NODE_GAP),
+            ),
+        };
+
+        let direction = if downstream { 1. } else { -1. };
+        Some(transform.translation.truncate() + step * direction)
+    }
+}
+
+// The space left between a node spawned wired to another and that node
+const CONNECTED_NODE_GAP: f32 = 80.;
+
 #[derive(Event)]
 struct NodeZIndexToTop {
     node: Entity,
@@ -516,6 +971,90 @@ fn node_z_to_top(
     }
 }
 
+// Shows or hides the optional parts of every node when their preferences change
+fn update_node_decoration_visibility(
+    ui_preferences: Res<UiPreferences>,
+    mut q_shadows: Query<&mut Visibility, (With<NodeShadow>, Without<NodeProcessText>)>,
+    mut q_process_time_text: Query<&mut Visibility, (With<NodeProcessText>, Without<NodeShadow>)>,
+) {
+    if !ui_preferences.is_changed() {
+        return;
+    }
+
+    let visibility_for = |shown: bool| {
+        if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    };
+
+    for mut visibility in q_shadows.iter_mut() {
+        *visibility = visibility_for(ui_preferences.node_shadows);
+    }
+
+    for mut visibility in q_process_time_text.iter_mut() {
+        *visibility = visibility_for(ui_preferences.show_process_time);
+    }
+}
+
+// While a node reports progress its readout shows the percentage instead of the last process time,
+// even if process times are hidden. update_nodes puts the new time back once the pass finishes.
+fn update_node_progress_text(
+    process_progress: Res<ProcessProgress>,
+    ui_preferences: Res<UiPreferences>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_nodes: Query<&NodeDisplay>,
+    mut q_process_time_text: Query<(&mut Text, &mut Visibility), With<NodeProcessText>>,
+) {
+    if !process_progress.is_changed() {
+        return;
+    }
+
+    let Ok(pipeline) = q_pipeline.get_single() else {
+        return;
+    };
+
+    for node in pipeline.graph.node_weights() {
+        let Ok(node_display) = q_nodes.get(node.kind.entity()) else {
+            continue;
+        };
+
+        let Ok((mut text, mut visibility)) = q_process_time_text.get_mut(node_display.process_time_text) else {
+            continue;
+        };
+
+        match process_progress.0.get(&node.kind.entity()) {
+            Some(fraction) => {
+                text.sections[0].value = format!("{:.0}%", fraction * 100.);
+                *visibility = Visibility::Inherited;
+            }
+            None => {
+                text.sections[0].value = format_process_time(node.last_process_time);
+                *visibility = if ui_preferences.show_process_time {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+    }
+}
+
+// While on, nodes outside the selection and edges touching none of it are drawn darker.
+// Purely visual; nothing is reprocessed.
+#[derive(Resource, Default)]
+pub struct IsolateSelection(pub bool);
+
+#[derive(Event, Clone)]
+pub struct ToggleIsolateSelection;
+
+fn toggle_isolate_selection(_trigger: Trigger<ToggleIsolateSelection>, mut isolate: ResMut<IsolateSelection>) {
+    isolate.0 = !isolate.0;
+}
+
+pub const ISOLATE_DIM: f32 = 0.3;
+
 fn update_node_border(
     mut materials: ResMut<Assets<NodeDisplayMaterial>>,
     query: Query<(
@@ -523,9 +1062,15 @@ fn update_node_border(
         &PickingInteraction,
         Option<&Selected>,
     )>,
+    isolate: Res<IsolateSelection>,
 ) {
+    // with nothing selected there's nothing to isolate, so everything stays lit
+    let isolating = isolate.0 && query.iter().any(|(_, _, selected)| selected.is_some());
+
     for (material_handle, interaction, selected) in query.iter() {
         if let Some(material) = materials.get_mut(material_handle) {
+            material.dim = if isolating && selected.is_none() { ISOLATE_DIM } else { 1. };
+
             if selected.is_some() {
                 material.border_color = material.selected_border_color;
             } else {
@@ -548,10 +1093,63 @@ pub struct EdgeLine {
     pub end_port: Entity,
 }
 
+pub const EDGE_LINE_THICKNESS: f32 = 2.0;
+pub const HIGHLIGHTED_EDGE_LINE_THICKNESS: f32 = 4.0;
+
+// An edge drawn thicker to show it was followed by a selection traversal.
+// Cleared as soon as either end is deselected.
+#[derive(Component)]
+pub struct HighlightedEdge;
+
+fn update_edge_highlights(
+    mut commands: Commands,
+    mut q_lines: Query<(Entity, &EdgeLine, &mut Line, Has<HighlightedEdge>)>,
+    q_output_ports: Query<&OutputPort>,
+    q_input_ports: Query<&InputPort>,
+    q_selected: Query<(), With<Selected>>,
+    isolate: Res<IsolateSelection>,
+) {
+    let isolating = isolate.0 && !q_selected.is_empty();
+
+    for (entity, edge_line, mut line, is_highlighted) in q_lines.iter_mut() {
+        let (both_ends_selected, either_end_selected) = match (
+            q_output_ports.get(edge_line.start_port),
+            q_input_ports.get(edge_line.end_port),
+        ) {
+            (Ok(output), Ok(input)) => {
+                let output_selected = q_selected.contains(output.node_entity);
+                let input_selected = q_selected.contains(input.node_entity);
+                (output_selected && input_selected, output_selected || input_selected)
+            }
+            _ => (false, false),
+        };
+
+        if is_highlighted && !both_ends_selected {
+            commands.entity(entity).remove::<HighlightedEdge>();
+        }
+
+        let thickness = if is_highlighted && both_ends_selected {
+            HIGHLIGHTED_EDGE_LINE_THICKNESS
+        } else {
+            EDGE_LINE_THICKNESS
+        };
+
+        if line.thickness != thickness {
+            line.thickness = thickness;
+        }
+
+        let dim = if isolating && !either_end_selected { ISOLATE_DIM } else { 1. };
+        if line.dim != dim {
+            line.dim = dim;
+        }
+    }
+}
+
 fn update_edge_lines(
     mut q_lines: Query<(&mut Line, &EdgeLine)>,
     q_output_ports: Query<&GlobalTransform, With<OutputPort>>,
     q_input_ports: Query<&GlobalTransform, With<InputPort>>,
+    ui_preferences: Res<UiPreferences>,
 ) {
     for (mut line, edge_line) in q_lines.iter_mut() {
         if let (Ok(start_transform), Ok(end_transform)) = (
@@ -560,18 +1158,110 @@ fn update_edge_lines(
         ) {
             let start = start_transform.translation().truncate();
             let end = end_transform.translation().truncate();
-            let new_points = generate_curved_line(start, end, line.points.len());
+            let new_points = generate_edge_line(ui_preferences.edge_style, ui_preferences.orientation, start, end, line.points.len());
             line.points = new_points;
         }
     }
 }
 
+// Source nodes render at their own size times this. Everything downstream follows the size of its inputs.
+pub fn set_render_scale(kind: &mut GraphNodeKind, render_scale: f32) {
+    match kind {
+        GraphNodeKind::Example(ex) => ex.render_scale = render_scale,
+        GraphNodeKind::Shape(shape_node) => shape_node.render_scale = render_scale,
+        GraphNodeKind::Clear(clear_node) => clear_node.render_scale = render_scale,
+        _ => {}
+    }
+}
+
+// Nodes that report how far along they are while processing
+pub fn node_progress(kind: &GraphNodeKind) -> Option<&NodeProgress> {
+    match kind {
+        GraphNodeKind::Shape(shape_node) => Some(&shape_node.progress),
+        GraphNodeKind::Dither(dither) => Some(&dither.progress),
+        GraphNodeKind::Blend(blend) => Some(&blend.progress),
+        GraphNodeKind::Levels(levels) => Some(&levels.progress),
+        GraphNodeKind::Curves(curves) => Some(&curves.progress),
+        GraphNodeKind::Premultiply(premultiply) => Some(&premultiply.progress),
+        GraphNodeKind::Unpremultiply(unpremultiply) => Some(&unpremultiply.progress),
+        GraphNodeKind::Convolve(convolve) => Some(&convolve.progress),
+        GraphNodeKind::Sharpen(sharpen) => Some(&sharpen.progress),
+        GraphNodeKind::Displace(displace) => Some(&displace.progress),
+        GraphNodeKind::CustomCompute(custom_compute) => Some(&custom_compute.progress),
+        _ => None,
+    }
+}
+
+// Why a node can't produce its output right now, for the nodes that can fail on their own
+pub fn node_error(kind: &GraphNodeKind) -> Option<&str> {
+    match kind {
+        GraphNodeKind::CustomCompute(custom_compute) => custom_compute.shader_error.as_deref(),
+        GraphNodeKind::Image(image_node) => image_node.decode_error.as_deref(),
+        _ => None,
+    }
+}
+
+// Only the per-pixel nodes can be tiled; everything else ignores the setting
+pub fn set_max_tile_size(kind: &mut GraphNodeKind, max_tile_size: Option<u32>) {
+    match kind {
+        GraphNodeKind::Levels(levels) => levels.max_tile_size = max_tile_size,
+        GraphNodeKind::Curves(curves) => curves.max_tile_size = max_tile_size,
+        GraphNodeKind::Premultiply(premultiply) => premultiply.max_tile_size = max_tile_size,
+        GraphNodeKind::Unpremultiply(unpremultiply) => unpremultiply.max_tile_size = max_tile_size,
+        _ => {}
+    }
+}
+
+// How an input is labeled on its port and in the inspector. Custom Compute params take their names from the shader.
+pub fn input_label(kind: &GraphNodeKind, input_id: InputId) -> String {
+    match kind {
+        GraphNodeKind::CustomCompute(custom_compute) => custom_compute
+            .param_label(input_id)
+            .map(str::to_string)
+            .unwrap_or_else(|| format_label_text(input_id.1)),
+        _ => format_label_text(input_id.1),
+    }
+}
+
 pub fn node_kind_name(kind: &GraphNodeKind) -> &'static str {
     match kind {
         GraphNodeKind::Example(_) => "Example",
         GraphNodeKind::Color(_) => "Color",
         GraphNodeKind::Shape(_) => "Shape",
         GraphNodeKind::Blend(_) => "Blend",
-        
+        GraphNodeKind::Dither(_) => "Dither",
+        GraphNodeKind::Image(_) => "Image",
+        GraphNodeKind::Levels(_) => "Levels",
+        GraphNodeKind::Curves(_) => "Curves",
+        GraphNodeKind::Number(_) => "Number",
+        GraphNodeKind::Math(_) => "Math",
+        GraphNodeKind::Clear(_) => "Clear",
+        GraphNodeKind::Premultiply(_) => "Premultiply",
+        GraphNodeKind::Unpremultiply(_) => "Unpremultiply",
+        GraphNodeKind::Convolve(_) => "Convolve",
+        GraphNodeKind::Sharpen(_) => "Sharpen",
+        GraphNodeKind::Displace(_) => "Displace",
+        GraphNodeKind::CustomCompute(_) => "Custom Compute",
+        GraphNodeKind::Probe(_) => "Probe",
     }
 }
+
+// The value an input starts at on a freshly spawned node: the node defaults where they apply, otherwise what the node declares
+pub fn input_default(kind: &GraphNodeKind, input_id: InputId, node_defaults: &NodeDefaults) -> Option<Field> {
+    let from_node_defaults = match (kind, input_id) {
+        (GraphNodeKind::Color(_), ColorNode::in_color) => Some(Field::LinearRgba(node_defaults.color)),
+        (GraphNodeKind::Shape(_), ShapeNode::shape) => Some(Field::Shape(node_defaults.shape.clone())),
+        (GraphNodeKind::Shape(_), ShapeNode::texture_size) => Some(Field::U32(node_defaults.texture_size)),
+        (GraphNodeKind::Example(_), ExampleNode::texture_extents) => {
+            Some(Field::Extent3d(bevy::render::render_resource::Extent3d {
+                width: node_defaults.texture_size,
+                height: node_defaults.texture_size,
+                depth_or_array_layers: 1,
+            }))
+        }
+        (GraphNodeKind::Example(_), ExampleNode::texture_format) => Some(Field::TextureFormat(node_defaults.texture_format)),
+        _ => None,
+    };
+
+    from_node_defaults.or_else(|| kind.get_input_meta(input_id).map(|meta| meta.storage.clone()))
+}
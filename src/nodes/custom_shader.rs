@@ -0,0 +1,332 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use bevy_file_dialog::{DialogFileLoaded, FileDialogExt};
+use wgpu::naga::{self, AddressSpace, ImageClass, ShaderStage, StorageAccess, TypeInner};
+
+use crate::{
+    asset::ShaderAssets,
+    events::node_events::NodeResources,
+    graph::{DisjointPipelineGraph, RequestProcessPipeline},
+    ui::{inspector::shader_source::RequestCompileShaderSource, menu_bar::Project},
+    ApplicationState,
+};
+
+use super::{
+    node_kind_name,
+    shader_reload::rebuild_node_kind,
+    shared::{shader_source, validate_wgsl},
+    GraphNodeKind, NodeDisplay,
+};
+
+// Lets a compute node run a WGSL file from disk in place of its built-in shader
+pub struct CustomShaderPlugin;
+
+impl Plugin for CustomShaderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingCustomShader>();
+        app.add_systems(
+            Update,
+            custom_shader_file_picked.run_if(in_state(ApplicationState::MainLoop)),
+        );
+
+        app.observe(pick_custom_shader);
+        app.observe(set_custom_shader);
+    }
+}
+
+// Marks WGSL picked through the load dialog
+pub struct CustomShaderFile;
+
+#[derive(Clone, Debug)]
+pub struct CustomShader {
+    pub path: PathBuf,
+    pub source: String,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestPickCustomShader {
+    pub node: Entity,
+}
+
+#[derive(Clone, Debug)]
+pub enum CustomShaderChange {
+    Load(PathBuf),
+    // reads the node's current file again, after editing it elsewhere
+    Reload,
+    BuiltIn,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestSetCustomShader {
+    pub node: Entity,
+    pub change: CustomShaderChange,
+}
+
+// Shows which shader a node runs, next to the inspector's shader buttons
+#[derive(Component)]
+pub struct CustomShaderLabel {
+    pub node: Entity,
+}
+
+// The node waiting on the open dialog
+#[derive(Resource, Default)]
+struct PendingCustomShader(Option<Entity>);
+
+// The built-in shader a custom one replaces, for the node kinds that have a single main compute pass
+pub fn custom_shader_handle<'a>(kind_name: &str, shader_handles: &'a ShaderAssets) -> Option<&'a Handle<Shader>> {
+    match kind_name {
+        "Shape" => Some(&shader_handles.shape),
+        "Blend" => Some(&shader_handles.blend),
+        "Dither" => Some(&shader_handles.dither),
+        "Levels" => Some(&shader_handles.levels),
+        "Curves" => Some(&shader_handles.curves),
+        "Premultiply" => Some(&shader_handles.premultiply),
+        "Unpremultiply" => Some(&shader_handles.unpremultiply),
+        "Convolve" => Some(&shader_handles.convolve),
+        "Sharpen" => Some(&shader_handles.sharpen),
+        "Displace" => Some(&shader_handles.displace),
+        _ => None,
+    }
+}
+
+pub fn supports_custom_shader(kind_name: &str) -> bool {
+    matches!(
+        kind_name,
+        "Shape" | "Blend" | "Dither" | "Levels" | "Curves" | "Premultiply" | "Unpremultiply" | "Convolve" | "Sharpen" | "Displace"
+    )
+}
+
+pub fn custom_shader_label(custom_shader: Option<&CustomShader>) -> String {
+    match custom_shader {
+        Some(custom_shader) => format!(
+            "Shader: {}",
+            custom_shader
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| custom_shader.path.display().to_string())
+        ),
+        None => "Shader: Built-in".to_string(),
+    }
+}
+
+// Reads a custom shader for a node of the given kind, rejecting it unless it could stand in for the built-in one
+pub fn load_custom_shader(
+    kind_name: &str,
+    path: &Path,
+    shaders: &Res<Assets<Shader>>,
+    shader_handles: &ShaderAssets,
+) -> Result<CustomShader, String> {
+    let Some(handle) = custom_shader_handle(kind_name, shader_handles) else {
+        return Err(format!("{} nodes don't run a compute shader that can be replaced", kind_name));
+    };
+    let builtin_source = shader_source(shaders, handle).map_err(|e| e.to_string())?;
+
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+
+    check_custom_shader(&source, &builtin_source)
+        .map_err(|e| format!("{} can't replace the {} shader:\n{}", path.display(), kind_name, e))?;
+
+    Ok(CustomShader {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+// The node binds its buffers and textures for the built-in shader, so the custom one has to declare the same slots
+pub fn check_custom_shader(source: &str, builtin_source: &str) -> Result<(), String> {
+    validate_wgsl(source)?;
+
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| e.emit_to_string(source))?;
+    let builtin = naga::front::wgsl::parse_str(builtin_source).map_err(|e| e.emit_to_string(builtin_source))?;
+
+    if !module
+        .entry_points
+        .iter()
+        .any(|entry_point| entry_point.name == "main" && entry_point.stage == ShaderStage::Compute)
+    {
+        return Err("there's no @compute entry point named `main`".to_string());
+    }
+
+    let expected = resource_bindings(&builtin);
+    let found = resource_bindings(&module);
+    let mut problems = Vec::new();
+
+    for (&(group, binding), &expected_kind) in &expected {
+        match found.get(&(group, binding)) {
+            None => problems.push(format!(
+                "@group({}) @binding({}) is missing, the node binds a {} there",
+                group, binding, expected_kind
+            )),
+            Some(&found_kind) if found_kind != expected_kind => problems.push(format!(
+                "@group({}) @binding({}) is a {}, but the node binds a {} there",
+                group, binding, found_kind, expected_kind
+            )),
+            _ => {}
+        }
+    }
+
+    for (&(group, binding), &found_kind) in &found {
+        if !expected.contains_key(&(group, binding)) {
+            problems.push(format!(
+                "@group({}) @binding({}) is a {}, but the node binds nothing there",
+                group, binding, found_kind
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("\n"))
+    }
+}
+
+// What each bound resource is, ignoring storage texture formats since nodes swap those for the project's format
+fn resource_bindings(module: &naga::Module) -> BTreeMap<(u32, u32), &'static str> {
+    module
+        .global_variables
+        .iter()
+        .filter_map(|(_, global)| {
+            let binding = global.binding.as_ref()?;
+            let kind = match global.space {
+                AddressSpace::Uniform => "uniform buffer",
+                AddressSpace::Storage { access } if access.contains(StorageAccess::STORE) => "read-write storage buffer",
+                AddressSpace::Storage { .. } => "read-only storage buffer",
+                AddressSpace::Handle => match module.types[global.ty].inner {
+                    TypeInner::Image { class: ImageClass::Storage { access, .. }, .. } => {
+                        if access.contains(StorageAccess::LOAD | StorageAccess::STORE) {
+                            "read-write storage texture"
+                        } else if access.contains(StorageAccess::STORE) {
+                            "write-only storage texture"
+                        } else {
+                            "read-only storage texture"
+                        }
+                    }
+                    TypeInner::Image { .. } => "sampled texture",
+                    TypeInner::Sampler { .. } => "sampler",
+                    _ => "resource",
+                },
+                _ => "resource",
+            };
+
+            Some(((binding.group, binding.binding), kind))
+        })
+        .collect()
+}
+
+fn pick_custom_shader(
+    trigger: Trigger<RequestPickCustomShader>,
+    mut commands: Commands,
+    mut pending: ResMut<PendingCustomShader>,
+) {
+    pending.0 = Some(trigger.event().node);
+
+    commands
+        .dialog()
+        .add_filter("WGSL Shader", &["wgsl"])
+        .load_file::<CustomShaderFile>();
+}
+
+fn custom_shader_file_picked(
+    mut commands: Commands,
+    mut ev_loaded: EventReader<DialogFileLoaded<CustomShaderFile>>,
+    mut pending: ResMut<PendingCustomShader>,
+) {
+    for ev in ev_loaded.read() {
+        if let Some(node) = pending.0.take() {
+            commands.trigger(RequestSetCustomShader {
+                node,
+                change: CustomShaderChange::Load(ev.path.clone()),
+            });
+        }
+    }
+}
+
+fn set_custom_shader(
+    trigger: Trigger<RequestSetCustomShader>,
+    mut commands: Commands,
+    mut q_pipeline: Query<&mut DisjointPipelineGraph>,
+    q_nodes: Query<&NodeDisplay>,
+    mut q_labels: Query<(&CustomShaderLabel, &mut Text)>,
+    node_resources: NodeResources,
+    project: Res<Project>,
+    mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+) {
+    let node_entity = trigger.event().node;
+    let Ok(node_display) = q_nodes.get(node_entity) else {
+        return;
+    };
+
+    let mut pipeline = q_pipeline.single_mut();
+    let Some(node) = pipeline.graph.node_weight_mut(node_display.index) else {
+        return;
+    };
+
+    // Custom Compute nodes keep their source in the project, so a file just fills their editor
+    if let (GraphNodeKind::CustomCompute(_), CustomShaderChange::Load(path)) = (&node.kind, &trigger.event().change) {
+        match std::fs::read_to_string(path) {
+            Ok(source) => commands.trigger(RequestCompileShaderSource {
+                node: node_entity,
+                source: Some(source),
+            }),
+            Err(e) => eprintln!("Couldn't read {}: {}", path.display(), e),
+        }
+        return;
+    }
+
+    let kind_name = node_kind_name(&node.kind);
+    let path = match &trigger.event().change {
+        CustomShaderChange::Load(path) => Some(path.clone()),
+        CustomShaderChange::Reload => match &node.custom_shader {
+            Some(custom_shader) => Some(custom_shader.path.clone()),
+            None => {
+                eprintln!("{} is using its built-in shader, there's no file to reload", kind_name);
+                return;
+            }
+        },
+        CustomShaderChange::BuiltIn => None,
+    };
+
+    let custom_shader = match path {
+        Some(path) => match load_custom_shader(kind_name, &path, &node_resources.shaders, &node_resources.shader_handles) {
+            Ok(custom_shader) => Some(custom_shader),
+            Err(e) => {
+                // the node keeps whatever shader it was running
+                eprintln!("{}", e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let previous = std::mem::replace(&mut node.custom_shader, custom_shader);
+    match rebuild_node_kind(node, &node_resources, project.texture_format()) {
+        Ok(kind) => node.kind = kind,
+        Err(e) => {
+            eprintln!("Couldn't rebuild {}: {}", kind_name, e);
+            node.custom_shader = previous;
+            return;
+        }
+    }
+    node.processed_inputs = None;
+
+    match &node.custom_shader {
+        Some(custom_shader) => println!("{} now runs {}", kind_name, custom_shader.path.display()),
+        None => println!("{} now runs its built-in shader", kind_name),
+    }
+
+    let label = custom_shader_label(node.custom_shader.as_ref());
+    for (shader_label, mut text) in q_labels.iter_mut() {
+        if shader_label.node == node_entity {
+            text.sections[0].value = label.clone();
+        }
+    }
+
+    ev_process_pipeline.send(RequestProcessPipeline);
+}
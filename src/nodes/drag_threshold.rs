@@ -0,0 +1,93 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_mod_picking::{
+    events::{Drag, DragEnd, DragStart, Pointer},
+    pointer::PointerId,
+    prelude::PointerButton,
+};
+
+use crate::{preferences::UiPreferences, ApplicationState};
+
+// Picking starts a drag on the first pixel of movement, so a slightly shaky click becomes a drag.
+// These events are the picking drag events held back until the pointer has moved past the
+// drag threshold preference. Drags that never get that far are dropped and stay plain clicks.
+pub struct DragThresholdPlugin;
+
+impl Plugin for DragThresholdPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ThresholdDragStart>()
+            .add_event::<ThresholdDrag>()
+            .add_event::<ThresholdDragEnd>();
+
+        app.add_systems(
+            Update,
+            apply_drag_threshold.run_if(in_state(ApplicationState::MainLoop)),
+        );
+    }
+}
+
+#[derive(Event, Deref, Clone)]
+pub struct ThresholdDragStart(pub Pointer<DragStart>);
+
+#[derive(Event, Deref, Clone)]
+pub struct ThresholdDrag(pub Pointer<Drag>);
+
+#[derive(Event, Deref, Clone)]
+pub struct ThresholdDragEnd(pub Pointer<DragEnd>);
+
+struct PendingDrag {
+    start: Pointer<DragStart>,
+    is_past_threshold: bool,
+}
+
+pub fn apply_drag_threshold(
+    mut drag_start_events: EventReader<Pointer<DragStart>>,
+    mut drag_events: EventReader<Pointer<Drag>>,
+    mut drag_end_events: EventReader<Pointer<DragEnd>>,
+    mut threshold_drag_starts: EventWriter<ThresholdDragStart>,
+    mut threshold_drags: EventWriter<ThresholdDrag>,
+    mut threshold_drag_ends: EventWriter<ThresholdDragEnd>,
+    ui_preferences: Res<UiPreferences>,
+    mut pending_drags: Local<HashMap<(PointerId, PointerButton, Entity), PendingDrag>>,
+) {
+    let threshold = ui_preferences.drag_threshold;
+
+    for event in drag_start_events.read() {
+        let is_past_threshold = threshold <= 0.;
+        if is_past_threshold {
+            threshold_drag_starts.send(ThresholdDragStart(event.clone()));
+        }
+
+        pending_drags.insert(
+            (event.pointer_id, event.button, event.target),
+            PendingDrag {
+                start: event.clone(),
+                is_past_threshold,
+            },
+        );
+    }
+
+    for event in drag_events.read() {
+        let Some(pending) = pending_drags.get_mut(&(event.pointer_id, event.button, event.target)) else {
+            continue;
+        };
+
+        if pending.is_past_threshold {
+            threshold_drags.send(ThresholdDrag(event.clone()));
+        } else if event.distance.length() >= threshold {
+            pending.is_past_threshold = true;
+            threshold_drag_starts.send(ThresholdDragStart(pending.start.clone()));
+
+            // the movement held back below the threshold is applied all at once
+            let mut caught_up = event.clone();
+            caught_up.event.delta = event.distance;
+            threshold_drags.send(ThresholdDrag(caught_up));
+        }
+    }
+
+    for event in drag_end_events.read() {
+        let pending = pending_drags.remove(&(event.pointer_id, event.button, event.target));
+        if pending.map_or(false, |pending| pending.is_past_threshold) {
+            threshold_drag_ends.send(ThresholdDragEnd(event.clone()));
+        }
+    }
+}
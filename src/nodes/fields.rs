@@ -1,10 +1,14 @@
 use bevy::{
+    color::palettes::{
+        css::{ORANGE, PINK, TEAL, VIOLET, WHEAT, YELLOW},
+        tailwind::{AMBER_600, GRAY_400, GREEN_400, INDIGO_400, RED_700, SKY_400},
+    },
     prelude::*,
     render::render_resource::{Extent3d, TextureFormat},
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use super::kinds::shape::Shape;
+use super::kinds::{convolve::{EdgeMode, Kernel}, curves::Curve, math::MathOperation, shape::Shape};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Field {
@@ -15,6 +19,10 @@ pub enum Field {
     Extent3d(Extent3d),
     TextureFormat(TextureFormat),
     Shape(Shape),
+    Curve(Curve),
+    MathOperation(MathOperation),
+    Kernel(Kernel),
+    EdgeMode(EdgeMode),
 
      // we never serialize images since they can't be manually input, always from an edge
     Image(#[serde(serialize_with = "serialize_none_image", deserialize_with = "deserialize_none_image")]Option<Image>),
@@ -39,6 +47,96 @@ where
 pub struct FieldMeta {
     pub visible: bool,
     pub storage: Field,
+    // limits for numeric fields, applied per-component for colors and vectors
+    #[serde(default)]
+    pub min: Option<f32>,
+    #[serde(default)]
+    pub max: Option<f32>,
+    #[serde(default)]
+    pub step: Option<f32>,
+    // the node can't produce a meaningful output unless this input has an incoming edge
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl FieldMeta {
+    pub fn clamp(&self, value: Field) -> Field {
+        if self.min.is_none() && self.max.is_none() {
+            return value;
+        }
+
+        let min = self.min.unwrap_or(f32::MIN);
+        let max = self.max.unwrap_or(f32::MAX);
+        let clamp = |v: f32| v.clamp(min, max);
+
+        match value {
+            Field::U32(v) => Field::U32(clamp(v as f32).max(0.) as u32),
+            Field::F32(v) => Field::F32(clamp(v)),
+            Field::Vec4(v) => Field::Vec4(v.clamp(Vec4::splat(min), Vec4::splat(max))),
+            Field::LinearRgba(v) => Field::LinearRgba(LinearRgba {
+                red: clamp(v.red),
+                green: clamp(v.green),
+                blue: clamp(v.blue),
+                alpha: clamp(v.alpha),
+            }),
+            other => other,
+        }
+    }
+}
+
+impl Field {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Field::U32(_) => "U32",
+            Field::F32(_) => "F32",
+            Field::Vec4(_) => "Vec4",
+            Field::LinearRgba(_) => "Color",
+            Field::Extent3d(_) => "Extent",
+            Field::TextureFormat(_) => "Format",
+            Field::Shape(_) => "Shape",
+            Field::Curve(_) => "Curve",
+            Field::MathOperation(_) => "Operation",
+            Field::Kernel(_) => "Kernel",
+            Field::EdgeMode(_) => "Edge Mode",
+            Field::Image(_) => "Image",
+        }
+    }
+
+    // One placeholder value of every field type, for anything that lists the types themselves
+    pub fn all_types() -> Vec<Field> {
+        vec![
+            Field::U32(0),
+            Field::F32(0.),
+            Field::Vec4(Vec4::ZERO),
+            Field::LinearRgba(LinearRgba::NONE),
+            Field::Extent3d(Extent3d::default()),
+            Field::TextureFormat(TextureFormat::Rgba8Unorm),
+            Field::Shape(Shape::default()),
+            Field::Curve(Curve::default()),
+            Field::MathOperation(MathOperation::default()),
+            Field::Kernel(Kernel::default()),
+            Field::EdgeMode(EdgeMode::default()),
+            Field::Image(None),
+        ]
+    }
+}
+
+// The color of a field's ports and of the edges carrying it
+pub fn field_color(field: &Field) -> LinearRgba {
+    match field {
+        Field::U32(_) => PINK.into(),
+        Field::F32(_) => YELLOW.into(),
+        Field::Vec4(_) => WHEAT.into(),
+        Field::LinearRgba(_) => ORANGE.into(),
+        Field::Extent3d(_) => TEAL.into(),
+        Field::TextureFormat(_) => RED_700.into(),
+        Field::Image(_) => GRAY_400.into(),
+        Field::Shape(_) => GREEN_400.into(),
+        Field::Curve(_) => VIOLET.into(),
+        Field::MathOperation(_) => SKY_400.into(),
+        Field::Kernel(_) => INDIGO_400.into(),
+        Field::EdgeMode(_) => AMBER_600.into(),
+    }
 }
 
 impl From<u32> for Field {
@@ -81,6 +179,61 @@ impl From<Shape> for Field {
         Field::Shape(value)
     }
 }
+impl From<Curve> for Field {
+    fn from(value: Curve) -> Self {
+        Field::Curve(value)
+    }
+}
+impl From<MathOperation> for Field {
+    fn from(value: MathOperation) -> Self {
+        Field::MathOperation(value)
+    }
+}
+impl From<Kernel> for Field {
+    fn from(value: Kernel) -> Self {
+        Field::Kernel(value)
+    }
+}
+impl From<EdgeMode> for Field {
+    fn from(value: EdgeMode) -> Self {
+        Field::EdgeMode(value)
+    }
+}
+
+// What a node field looks like on the node itself, for the output accessors declare_node generates
+pub trait DisplayField {
+    // borrowed, so previews don't clone every image
+    fn as_image(&self) -> Option<&Image> {
+        None
+    }
+
+    fn as_color(&self) -> Option<LinearRgba> {
+        None
+    }
+}
+
+impl DisplayField for Option<Image> {
+    fn as_image(&self) -> Option<&Image> {
+        self.as_ref()
+    }
+}
+
+impl DisplayField for LinearRgba {
+    fn as_color(&self) -> Option<LinearRgba> {
+        Some(*self)
+    }
+}
+
+impl DisplayField for u32 {}
+impl DisplayField for f32 {}
+impl DisplayField for Vec4 {}
+impl DisplayField for Extent3d {}
+impl DisplayField for TextureFormat {}
+impl DisplayField for Shape {}
+impl DisplayField for Curve {}
+impl DisplayField for MathOperation {}
+impl DisplayField for Kernel {}
+impl DisplayField for EdgeMode {}
 
 impl PartialEq for Field {
     fn eq(&self, other: &Self) -> bool {
@@ -91,6 +244,11 @@ impl PartialEq for Field {
             (Field::LinearRgba(a), Field::LinearRgba(b)) => a == b,
             (Field::Extent3d(a), Field::Extent3d(b)) => a == b,
             (Field::TextureFormat(a), Field::TextureFormat(b)) => a == b,
+            (Field::Shape(a), Field::Shape(b)) => a == b,
+            (Field::Curve(a), Field::Curve(b)) => a == b,
+            (Field::MathOperation(a), Field::MathOperation(b)) => a == b,
+            (Field::Kernel(a), Field::Kernel(b)) => a == b,
+            (Field::EdgeMode(a), Field::EdgeMode(b)) => a == b,
             (Field::Image(_), Field::Image(_)) => false, // Always return false for Image
             _ => false, // Different variants are never equal
         }
@@ -205,6 +363,54 @@ impl TryFrom<Field> for Shape {
     }
 }
 
+impl TryFrom<Field> for Curve {
+    type Error = String;
+
+    fn try_from(value: Field) -> Result<Self, Self::Error> {
+        if let Field::Curve(v) = value {
+            Ok(v)
+        } else {
+            Err(format!("Cannot convert {:?} to Curve", value))
+        }
+    }
+}
+
+impl TryFrom<Field> for MathOperation {
+    type Error = String;
+
+    fn try_from(value: Field) -> Result<Self, Self::Error> {
+        if let Field::MathOperation(v) = value {
+            Ok(v)
+        } else {
+            Err(format!("Cannot convert {:?} to MathOperation", value))
+        }
+    }
+}
+
+impl TryFrom<Field> for Kernel {
+    type Error = String;
+
+    fn try_from(value: Field) -> Result<Self, Self::Error> {
+        if let Field::Kernel(v) = value {
+            Ok(v)
+        } else {
+            Err(format!("Cannot convert {:?} to Kernel", value))
+        }
+    }
+}
+
+impl TryFrom<Field> for EdgeMode {
+    type Error = String;
+
+    fn try_from(value: Field) -> Result<Self, Self::Error> {
+        if let Field::EdgeMode(v) = value {
+            Ok(v)
+        } else {
+            Err(format!("Cannot convert {:?} to EdgeMode", value))
+        }
+    }
+}
+
 
 pub fn can_convert_field(from: &Field, to: &Field) -> bool {
     match to {
@@ -216,6 +422,10 @@ pub fn can_convert_field(from: &Field, to: &Field) -> bool {
         Field::TextureFormat(_) => TextureFormat::try_from(from.clone()).is_ok(),
         Field::Image(_) => Option::<Image>::try_from(from.clone()).is_ok(),
         Field::Shape(_) => Shape::try_from(from.clone()).is_ok(),
+        Field::Curve(_) => Curve::try_from(from.clone()).is_ok(),
+        Field::MathOperation(_) => MathOperation::try_from(from.clone()).is_ok(),
+        Field::Kernel(_) => Kernel::try_from(from.clone()).is_ok(),
+        Field::EdgeMode(_) => EdgeMode::try_from(from.clone()).is_ok(),
         
     }
 }
\ No newline at end of file
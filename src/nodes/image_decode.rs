@@ -0,0 +1,83 @@
+use bevy::{
+    prelude::*,
+    tasks::{block_on, poll_once, AsyncComputeTaskPool, Task},
+};
+
+use crate::{
+    graph::{DisjointPipelineGraph, PipelineProcessTask, RequestProcessPipeline},
+    ApplicationState,
+};
+
+use super::{
+    kinds::image::decode_png,
+    GraphNodeKind, NodeDisplay,
+};
+
+// Decodes the images saved in a project off the main thread, so opening a project with large images doesn't stall the editor
+pub struct ImageDecodePlugin;
+
+impl Plugin for ImageDecodePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            poll_image_decodes.run_if(in_state(ApplicationState::MainLoop)),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct ImageDecodeTask {
+    node_entity: Entity,
+    task: Task<Result<Image, String>>,
+}
+
+pub fn spawn_image_decode(commands: &mut Commands, node_entity: Entity, png: Vec<u8>) {
+    let task = AsyncComputeTaskPool::get().spawn(async move { decode_png(&png) });
+
+    commands.spawn(ImageDecodeTask { node_entity, task });
+}
+
+fn poll_image_decodes(
+    mut commands: Commands,
+    mut q_pipeline: Query<&mut DisjointPipelineGraph>,
+    mut q_tasks: Query<(Entity, &mut ImageDecodeTask)>,
+    q_nodes: Query<&NodeDisplay>,
+    q_process_task: Query<(), With<PipelineProcessTask>>,
+    mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+) {
+    // a pass in flight holds a copy of the still-decoding node and would overwrite the image when it lands
+    if !q_process_task.is_empty() {
+        return;
+    }
+
+    for (task_entity, mut decode) in q_tasks.iter_mut() {
+        let Some(result) = block_on(poll_once(&mut decode.task)) else {
+            continue;
+        };
+        commands.entity(task_entity).despawn();
+
+        // the node may have been deleted while its image was decoding
+        let Ok(node_display) = q_nodes.get(decode.node_entity) else {
+            continue;
+        };
+
+        let mut pipeline = q_pipeline.single_mut();
+        let Some(node) = pipeline.graph.node_weight_mut(node_display.index) else {
+            continue;
+        };
+
+        let GraphNodeKind::Image(image_node) = &mut node.kind else {
+            continue;
+        };
+
+        match result {
+            Ok(image) => image_node.finish_decode(image),
+            Err(e) => {
+                eprintln!("Couldn't decode image: {}", e);
+                image_node.decode_error = Some(e);
+            }
+        }
+        node.processed_inputs = None;
+        ev_process_pipeline.send(RequestProcessPipeline);
+    }
+}
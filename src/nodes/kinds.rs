@@ -1,4 +1,17 @@
 pub mod color;
 pub mod example;
 pub mod shape;
-pub mod blend;
\ No newline at end of file
+pub mod blend;
+pub mod dither;
+pub mod image;
+pub mod levels;
+pub mod curves;
+pub mod number;
+pub mod math;
+pub mod clear;
+pub mod premultiply;
+pub mod convolve;
+pub mod sharpen;
+pub mod displace;
+pub mod probe;
+pub mod custom_compute;
\ No newline at end of file
@@ -7,7 +7,9 @@ use bevy::prelude::*;
 use bevy::render::render_resource::*;
 use crate::nodes::macros::macros::declare_node;
 use crate::nodes::fields::{Field, FieldMeta};
-use crate::nodes::{InputId, NodeTrait, OutputId, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::{bytes_per_pixel, shader_for_format};
+use crate::nodes::{SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
 use crate::setup::{CustomGpuDevice, CustomGpuQueue};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -21,8 +23,8 @@ impl From<&BlendNode> for SerializableGraphNodeKind {
     fn from(node: &BlendNode) -> Self {
         SerializableGraphNodeKind::Blend(SerializableBlendNode {
             entity: node.entity,
-            input_meta: node.input_meta.iter().map(|(k, v)| (SerializableInputId(k.0.to_string(), k.1.to_string()), v.clone())).collect(),
-            output_meta: node.output_meta.iter().map(|(k, v)| (SerializableOutputId(k.0.to_string(), k.1.to_string()), v.clone())).collect(),
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
         })
     }
 }
@@ -33,27 +35,17 @@ impl BlendNode {
         render_device: &CustomGpuDevice,
         render_queue: &CustomGpuQueue,
         shader_source: &String,
+        texture_format: TextureFormat,
     ) -> Self {
         let mut node = Self::new(
             serialized.entity,
             render_device,
             render_queue,
             shader_source,
+            texture_format,
         );
 
-        let input_fields: Vec<InputId> = node.input_fields().to_vec();
-        for &input_id in &input_fields {
-            if let Some(meta) = serialized.input_meta.get(&SerializableInputId(input_id.0.to_string(), input_id.1.to_string())) {
-                node.set_input_meta(input_id, meta.clone());
-            }
-        }
-
-        let output_fields: Vec<OutputId> = node.output_fields().to_vec();
-        for &output_id in &output_fields {
-            if let Some(meta) = serialized.output_meta.get(&SerializableOutputId(output_id.0.to_string(), output_id.1.to_string())) {
-                node.set_output_meta(output_id, meta.clone());
-            }
-        }
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
 
         node
     }
@@ -66,14 +58,26 @@ declare_node!(
         #[input] input_image_a: Option<Image> { meta: FieldMeta {
             visible: true,
             storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
         }},
         #[input] input_image_b: Option<Image> { meta: FieldMeta {
             visible: true,
             storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
         }},
         #[output] output_image: Option<Image> { meta: FieldMeta {
             visible: true,
             storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
         }},
         render_device: CustomGpuDevice,
         render_queue: CustomGpuQueue,
@@ -89,6 +93,7 @@ declare_node!(
         input_texture_a_view: Option<TextureView>,
         input_texture_b_view: Option<TextureView>,
         output_texture_view: Option<TextureView>,
+        progress: NodeProgress,
     },
 
     methods: {
@@ -97,12 +102,11 @@ declare_node!(
             render_device: &CustomGpuDevice,
             render_queue: &CustomGpuQueue,
             shader_source: &String,
+            texture_format: TextureFormat,
         ) -> Self {
-            let texture_format = TextureFormat::Rgba8Unorm;
-
             let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
                 label: Some("Blend Shader"),
-                source: ShaderSource::Wgsl(Cow::Borrowed(shader_source)),
+                source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
             });
 
             let bind_group_layout = render_device.create_bind_group_layout(
@@ -177,6 +181,7 @@ declare_node!(
                 input_texture_a_view: None,
                 input_texture_b_view: None,
                 output_texture_view: None,
+                progress: NodeProgress::default(),
                 input_meta: Default::default(),
                 output_meta: Default::default(),
             }
@@ -185,6 +190,8 @@ declare_node!(
         process(&mut self) {
             // Ensure both input images are available
             if let (Some(ref image_a), Some(ref image_b)) = (self.input_image_a.as_ref(), self.input_image_b.as_ref()) {
+                self.progress.start();
+
                 // Check if we need to update resources (e.g., if image size changed)
                 let size = image_a.texture_descriptor.size;
                 if self.texture_size != size {
@@ -205,7 +212,7 @@ declare_node!(
 
                     self.output_texture_view = Some(self.output_texture.as_ref().unwrap().create_view(&Default::default()));
 
-                    let output_buffer_size = (4 * self.texture_size.width * self.texture_size.height) as BufferAddress;
+                    let output_buffer_size = (bytes_per_pixel(self.texture_format) * self.texture_size.width * self.texture_size.height) as BufferAddress;
                     self.output_buffer = Some(self.render_device.create_buffer(&BufferDescriptor {
                         label: Some("Blend Output Buffer"),
                         size: output_buffer_size,
@@ -217,15 +224,25 @@ declare_node!(
                     self.bind_group = None;
                 }
 
-                // Create input textures and views if they don't exist
-                if self.input_texture_a.is_none() {
+                // Create input textures and views if they don't exist, or if an input's format changed
+                let is_input_a_format_changed = self
+                    .input_texture_a
+                    .as_ref()
+                    .map_or(false, |texture| texture.format() != image_a.texture_descriptor.format);
+
+                if self.input_texture_a.is_none() || is_input_a_format_changed {
                     self.input_texture_a = Some(self.render_device.create_texture(&image_a.texture_descriptor));
                     self.input_texture_a_view = Some(self.input_texture_a.as_ref().unwrap().create_view(&Default::default()));
                     // Invalidate bind group
                     self.bind_group = None;
                 }
 
-                if self.input_texture_b.is_none() {
+                let is_input_b_format_changed = self
+                    .input_texture_b
+                    .as_ref()
+                    .map_or(false, |texture| texture.format() != image_b.texture_descriptor.format);
+
+                if self.input_texture_b.is_none() || is_input_b_format_changed {
                     self.input_texture_b = Some(self.render_device.create_texture(&image_b.texture_descriptor));
                     self.input_texture_b_view = Some(self.input_texture_b.as_ref().unwrap().create_view(&Default::default()));
                     // Invalidate bind group
@@ -243,7 +260,7 @@ declare_node!(
                     &image_a.data,
                     ImageDataLayout {
                         offset: 0,
-                        bytes_per_row: Some(4 * self.texture_size.width),
+                        bytes_per_row: Some(bytes_per_pixel(image_a.texture_descriptor.format) * self.texture_size.width),
                         rows_per_image: Some(self.texture_size.height),
                     },
                     self.texture_size,
@@ -259,7 +276,7 @@ declare_node!(
                     &image_b.data,
                     ImageDataLayout {
                         offset: 0,
-                        bytes_per_row: Some(4 * self.texture_size.width),
+                        bytes_per_row: Some(bytes_per_pixel(image_b.texture_descriptor.format) * self.texture_size.width),
                         rows_per_image: Some(self.texture_size.height),
                     },
                     self.texture_size,
@@ -329,7 +346,7 @@ declare_node!(
                         buffer: self.output_buffer.as_ref().unwrap(),
                         layout: ImageDataLayout {
                             offset: 0,
-                            bytes_per_row: Some(4 * self.texture_size.width),
+                            bytes_per_row: Some(bytes_per_pixel(self.texture_format) * self.texture_size.width),
                             rows_per_image: Some(self.texture_size.height),
                         },
                     },
@@ -375,6 +392,7 @@ declare_node!(
                 };
 
                 self.output_image = Some(image);
+                self.progress.finish();
             } else {
                 self.output_image = None;
             }
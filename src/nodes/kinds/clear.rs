@@ -0,0 +1,102 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::nodes::{
+    fields::{Field, FieldMeta}, kinds::image::rgba_image, macros::macros::declare_node, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId
+};
+
+// A blank canvas of a given size, transparent by default, to composite other images onto
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableClearNode {
+    pub entity: Entity,
+    pub texture_size: u32,
+    pub clear_color: LinearRgba,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&ClearNode> for SerializableGraphNodeKind {
+    fn from(node: &ClearNode) -> Self {
+        SerializableGraphNodeKind::Clear(SerializableClearNode {
+            entity: node.entity,
+            texture_size: node.texture_size,
+            clear_color: node.clear_color,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl ClearNode {
+    pub fn from_serializable(serialized: &SerializableClearNode) -> Self {
+        let mut node = Self::new(serialized.entity, serialized.texture_size, serialized.clear_color);
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node
+    }
+}
+
+// Rgba8Unorm holds the color's linear values directly, like every other node's output
+fn clear_color_bytes(color: LinearRgba) -> [u8; 4] {
+    color.to_f32_array().map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8)
+}
+
+declare_node!(
+    name: ClearNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] texture_size: u32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::U32(512),
+            min: Some(1.),
+            max: Some(4096.),
+            step: Some(1.),
+            required: false,
+        }},
+        #[input] clear_color: LinearRgba { meta: FieldMeta {
+            visible: false,
+            storage: Field::LinearRgba(LinearRgba::NONE),
+            min: Some(0.),
+            max: None,
+            step: Some(0.01),
+            required: false,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        // multiplied into texture_size; export passes raise it to render larger
+        render_scale: f32,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            texture_size: u32,
+            clear_color: LinearRgba,
+        ) -> Self {
+            Self {
+                entity,
+                texture_size,
+                clear_color,
+                output_image: None,
+                render_scale: 1.,
+                input_meta: HashMap::new(),
+                output_meta: HashMap::new(),
+            }
+        }
+
+        process(&mut self) {
+            let size = ((self.texture_size as f32 * self.render_scale).round() as u32).max(1);
+            let pixel = clear_color_bytes(self.clear_color);
+            let pixels = pixel.repeat((size * size) as usize);
+
+            self.output_image = Some(rgba_image(size, size, pixels));
+        }
+    }
+);
@@ -2,7 +2,7 @@ use bevy::{color::palettes::css::MAGENTA, prelude::*, utils::HashMap};
 use serde::{Deserialize, Serialize};
 
 use crate::nodes::{
-    fields::{Field, FieldMeta}, macros::macros::declare_node, InputId, NodeTrait, OutputId, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId
+    fields::{Field, FieldMeta}, macros::macros::declare_node, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId
 };
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -20,8 +20,8 @@ impl From<&ColorNode> for SerializableGraphNodeKind {
             entity: node.entity,
             in_color: node.in_color,
             out_color: node.out_color,
-            input_meta: node.input_meta.iter().map(|(k, v)| (SerializableInputId(k.0.to_string(), k.1.to_string()), v.clone())).collect(),
-            output_meta: node.output_meta.iter().map(|(k, v)| (SerializableOutputId(k.0.to_string(), k.1.to_string()), v.clone())).collect(),
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
         });
 
         it
@@ -37,19 +37,7 @@ impl ColorNode {
             serialized.out_color,
         );
 
-        let input_fields: Vec<InputId> = node.input_fields().to_vec();
-        for &input_id in &input_fields {
-            if let Some(meta) = serialized.input_meta.get(&SerializableInputId(input_id.0.to_string(), input_id.1.to_string())) {
-                node.set_input_meta(input_id, meta.clone());
-            }
-        }
-
-        let output_fields: Vec<OutputId> = node.output_fields().to_vec();
-        for &output_id in &output_fields {
-            if let Some(meta) = serialized.output_meta.get(&SerializableOutputId(output_id.0.to_string(), output_id.1.to_string())) {
-                node.set_output_meta(output_id, meta.clone());
-            }
-        }
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
 
         node
     }
@@ -61,11 +49,19 @@ declare_node!(
         #[entity] entity: Entity,
         #[input]  in_color: LinearRgba  { meta: FieldMeta {
             visible: false,
-            storage: LinearRgba::default().into()
+            storage: LinearRgba::default().into(),
+            min: Some(0.),
+            max: None,
+            step: Some(0.01),
+            required: false,
         }},
         #[output] out_color: LinearRgba { meta: FieldMeta {
             visible: true,
-            storage: LinearRgba::default().into()
+            storage: LinearRgba::default().into(),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
         }},
     },
 
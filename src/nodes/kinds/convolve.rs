@@ -0,0 +1,415 @@
+use std::borrow::Cow;
+
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+use bevy::render::render_resource::*;
+use crate::nodes::macros::macros::declare_node;
+use crate::nodes::fields::{Field, FieldMeta};
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::shader_for_format;
+use crate::nodes::tiling::process_whole_image;
+use crate::nodes::{SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::setup::{CustomGpuDevice, CustomGpuQueue};
+use bytemuck::{Pod, Zeroable};
+
+// What a sample that falls outside the image reads
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EdgeMode {
+    // the nearest pixel on the edge
+    #[default]
+    Clamp,
+    // the pixel from the opposite side, for tiling textures
+    Wrap,
+    // nothing, so edges fade out
+    Transparent,
+}
+
+impl EdgeMode {
+    pub const ALL: [EdgeMode; 3] = [EdgeMode::Clamp, EdgeMode::Wrap, EdgeMode::Transparent];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EdgeMode::Clamp => "Clamp",
+            EdgeMode::Wrap => "Wrap",
+            EdgeMode::Transparent => "Transparent",
+        }
+    }
+
+    // matches the EDGE_ constants in the shaders
+    pub fn shader_index(&self) -> u32 {
+        match self {
+            EdgeMode::Clamp => 0,
+            EdgeMode::Wrap => 1,
+            EdgeMode::Transparent => 2,
+        }
+    }
+}
+
+pub const KERNEL_SIZES: [u32; 2] = [3, 5];
+pub const KERNEL_MAX_SIZE: u32 = 5;
+const KERNEL_MAX_WEIGHTS: usize = (KERNEL_MAX_SIZE * KERNEL_MAX_SIZE) as usize;
+
+// A square grid of weights, row by row, centered on the pixel being computed
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Kernel {
+    pub size: u32,
+    pub weights: Vec<f32>,
+}
+
+impl Default for Kernel {
+    fn default() -> Self {
+        Kernel::identity(3)
+    }
+}
+
+impl Kernel {
+    pub fn identity(size: u32) -> Self {
+        let mut weights = vec![0.; (size * size) as usize];
+        weights[(size * size / 2) as usize] = 1.;
+        Self { size, weights }
+    }
+
+    // A size the shader supports, with exactly one weight per cell
+    pub fn normalized(&self) -> Kernel {
+        let size = if KERNEL_SIZES.contains(&self.size) { self.size } else { KERNEL_SIZES[0] };
+
+        let mut weights = self.weights.clone();
+        weights.resize((size * size) as usize, 0.);
+
+        Kernel { size, weights }
+    }
+
+    // Grows or shrinks the grid around its center, so the weights keep their offsets from it
+    pub fn resized(&self, size: u32) -> Kernel {
+        let kernel = self.normalized();
+        let mut resized = Kernel {
+            size,
+            weights: vec![0.; (size * size) as usize],
+        };
+
+        let old_radius = (kernel.size / 2) as i32;
+        let new_radius = (size / 2) as i32;
+
+        for y in -new_radius..=new_radius {
+            for x in -new_radius..=new_radius {
+                if x.abs() > old_radius || y.abs() > old_radius {
+                    continue;
+                }
+
+                let old_index = ((y + old_radius) * kernel.size as i32 + x + old_radius) as usize;
+                let new_index = ((y + new_radius) * size as i32 + x + new_radius) as usize;
+                resized.weights[new_index] = kernel.weights[old_index];
+            }
+        }
+
+        resized
+    }
+
+    // Laid out on a 5x5 grid for the shader, however big the kernel is
+    fn padded_weights(&self) -> [f32; KERNEL_MAX_WEIGHTS] {
+        let kernel = self.normalized();
+        let mut padded = [0.; KERNEL_MAX_WEIGHTS];
+
+        for (index, weight) in kernel.weights.iter().enumerate() {
+            let x = index as u32 % kernel.size;
+            let y = index as u32 / kernel.size;
+            padded[(y * KERNEL_MAX_SIZE + x) as usize] = *weight;
+        }
+
+        padded
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KernelPreset {
+    Sharpen,
+    BoxBlur,
+    Emboss,
+}
+
+impl KernelPreset {
+    pub const ALL: [KernelPreset; 3] = [KernelPreset::Sharpen, KernelPreset::BoxBlur, KernelPreset::Emboss];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KernelPreset::Sharpen => "Sharpen",
+            KernelPreset::BoxBlur => "Box Blur",
+            KernelPreset::Emboss => "Emboss",
+        }
+    }
+
+    // Presets are 3x3; a 5x5 kernel gets the same weights padded out with zeroes
+    pub fn kernel(&self, size: u32) -> Kernel {
+        let weights = match self {
+            KernelPreset::Sharpen => vec![0., -1., 0., -1., 5., -1., 0., -1., 0.],
+            KernelPreset::BoxBlur => vec![1. / 9.; 9],
+            KernelPreset::Emboss => vec![-2., -1., 0., -1., 1., 1., 0., 1., 2.],
+        };
+
+        Kernel { size: 3, weights }.resized(size)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, Copy, Pod, Zeroable)]
+pub struct ConvolveParams {
+    size: u32,
+    edge_mode: u32,
+    _padding: [u32; 2],
+}
+
+impl ConvolveParams {
+    fn new(kernel: &Kernel, edge_mode: EdgeMode) -> Self {
+        Self {
+            size: kernel.normalized().size,
+            edge_mode: edge_mode.shader_index(),
+            _padding: [0; 2],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableConvolveNode {
+    pub entity: Entity,
+    pub kernel: Kernel,
+    pub edge_mode: EdgeMode,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&ConvolveNode> for SerializableGraphNodeKind {
+    fn from(node: &ConvolveNode) -> Self {
+        SerializableGraphNodeKind::Convolve(SerializableConvolveNode {
+            entity: node.entity,
+            kernel: node.kernel.clone(),
+            edge_mode: node.edge_mode,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl ConvolveNode {
+    pub fn from_serializable(
+        serialized: &SerializableConvolveNode,
+        render_device: &CustomGpuDevice,
+        render_queue: &CustomGpuQueue,
+        shader_source: &String,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let mut node = Self::new(
+            serialized.entity,
+            render_device,
+            render_queue,
+            shader_source,
+            texture_format,
+        );
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node.kernel = serialized.kernel.normalized();
+        node.edge_mode = serialized.edge_mode;
+
+        node
+    }
+}
+
+declare_node!(
+    name: ConvolveNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[input] kernel: Kernel { meta: FieldMeta {
+            visible: false,
+            storage: Field::Kernel(Kernel::default()),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        #[input] edge_mode: EdgeMode { meta: FieldMeta {
+            visible: false,
+            storage: Field::EdgeMode(EdgeMode::default()),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        render_device: CustomGpuDevice,
+        render_queue: CustomGpuQueue,
+        compute_pipeline: ComputePipeline,
+        bind_group_layout: BindGroupLayout,
+        weights_buffer: Buffer,
+        params_buffer: Buffer,
+        texture_format: TextureFormat,
+        progress: NodeProgress,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            render_device: &CustomGpuDevice,
+            render_queue: &CustomGpuQueue,
+            shader_source: &String,
+            texture_format: TextureFormat,
+        ) -> Self {
+            let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
+                label: Some("Convolve Shader"),
+                source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
+            });
+
+            let weights_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("Convolve Weights Buffer"),
+                contents: bytemuck::cast_slice(&Kernel::default().padded_weights()),
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            });
+
+            let params_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("Convolve Params Buffer"),
+                contents: bytemuck::cast_slice(&[ConvolveParams::new(&Kernel::default(), EdgeMode::default())]),
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            });
+
+            let bind_group_layout = render_device.create_bind_group_layout(
+                "Convolve Compute Bind Group Layout",
+                &[
+                    // Input texture
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Texture {
+                            multisampled: false,
+                            sample_type: TextureSampleType::Float { filterable: false },
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Output texture
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: texture_format,
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Kernel weights
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // Kernel size and edge mode
+                    BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            );
+
+            let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("Convolve Compute Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+            let compute_pipeline = render_device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Convolve Compute Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &shader_module,
+                entry_point: "main",
+                compilation_options: default(),
+            });
+
+            Self {
+                entity,
+                input_image: None,
+                kernel: Kernel::default(),
+                edge_mode: EdgeMode::default(),
+                output_image: None,
+                render_device: render_device.clone(),
+                render_queue: render_queue.clone(),
+                compute_pipeline,
+                bind_group_layout,
+                weights_buffer,
+                params_buffer,
+                texture_format,
+                progress: NodeProgress::default(),
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            }
+        }
+
+        process(&mut self) {
+            if let Some(ref image) = self.input_image.as_ref() {
+                self.progress.start();
+
+                self.render_queue.write_buffer(
+                    &self.weights_buffer,
+                    0,
+                    bytemuck::cast_slice(&self.kernel.padded_weights()),
+                );
+
+                self.render_queue.write_buffer(
+                    &self.params_buffer,
+                    0,
+                    bytemuck::cast_slice(&[ConvolveParams::new(&self.kernel, self.edge_mode)]),
+                );
+
+                // every pixel reads its neighbors, so the image can't be split into tiles; it goes through as one
+                self.output_image = Some(process_whole_image(
+                    &self.render_device,
+                    &self.render_queue,
+                    &self.compute_pipeline,
+                    &self.bind_group_layout,
+                    &[
+                        BindGroupEntry {
+                            binding: 2,
+                            resource: self.weights_buffer.as_entire_binding(),
+                        },
+                        BindGroupEntry {
+                            binding: 3,
+                            resource: self.params_buffer.as_entire_binding(),
+                        },
+                    ],
+                    image,
+                    self.texture_format,
+                    8,
+                    &self.progress,
+                ));
+                self.progress.finish();
+            } else {
+                self.output_image = None;
+            }
+        }
+    }
+);
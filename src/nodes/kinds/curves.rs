@@ -0,0 +1,301 @@
+use std::borrow::Cow;
+
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+use bevy::render::render_resource::*;
+use crate::nodes::macros::macros::declare_node;
+use crate::nodes::fields::{Field, FieldMeta};
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::shader_for_format;
+use crate::nodes::tiling::{needs_tiling, process_per_pixel_tiled, process_whole_image};
+use crate::nodes::{SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::setup::{CustomGpuDevice, CustomGpuQueue};
+
+// A tone curve through control points in the unit square, mapping input brightness (x) to output (y)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Curve {
+    pub points: Vec<Vec2>,
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        Self {
+            points: vec![Vec2::ZERO, Vec2::ONE],
+        }
+    }
+}
+
+pub const CURVE_LUT_SIZE: usize = 256;
+
+impl Curve {
+    // Points sorted by x and clamped to the unit square; a curve needs at least its two ends
+    pub fn normalized(&self) -> Curve {
+        let mut points: Vec<Vec2> = self
+            .points
+            .iter()
+            .map(|point| point.clamp(Vec2::ZERO, Vec2::ONE))
+            .collect();
+
+        points.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+        if points.len() < 2 {
+            return Curve::default();
+        }
+
+        Curve { points }
+    }
+
+    // Linear interpolation between control points, flat beyond the first and last
+    pub fn sample(&self, x: f32) -> f32 {
+        let curve = self.normalized();
+        let points = &curve.points;
+
+        if x <= points[0].x {
+            return points[0].y;
+        }
+
+        for pair in points.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            if x <= end.x {
+                let span = end.x - start.x;
+                if span <= f32::EPSILON {
+                    return end.y;
+                }
+                let t = (x - start.x) / span;
+                return start.y + (end.y - start.y) * t;
+            }
+        }
+
+        points[points.len() - 1].y
+    }
+
+    pub fn lut(&self) -> [f32; CURVE_LUT_SIZE] {
+        let mut lut = [0.; CURVE_LUT_SIZE];
+        for (i, entry) in lut.iter_mut().enumerate() {
+            *entry = self.sample(i as f32 / (CURVE_LUT_SIZE - 1) as f32);
+        }
+        lut
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableCurvesNode {
+    pub entity: Entity,
+    pub curve: Curve,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&CurvesNode> for SerializableGraphNodeKind {
+    fn from(node: &CurvesNode) -> Self {
+        SerializableGraphNodeKind::Curves(SerializableCurvesNode {
+            entity: node.entity,
+            curve: node.curve.clone(),
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl CurvesNode {
+    pub fn from_serializable(
+        serialized: &SerializableCurvesNode,
+        render_device: &CustomGpuDevice,
+        render_queue: &CustomGpuQueue,
+        shader_source: &String,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let mut node = Self::new(
+            serialized.entity,
+            render_device,
+            render_queue,
+            shader_source,
+            texture_format,
+        );
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node.curve = serialized.curve.clone();
+
+        node
+    }
+}
+
+declare_node!(
+    name: CurvesNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[input] curve: Curve { meta: FieldMeta {
+            visible: false,
+            storage: Field::Curve(Curve::default()),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        render_device: CustomGpuDevice,
+        render_queue: CustomGpuQueue,
+        compute_pipeline: ComputePipeline,
+        bind_group_layout: BindGroupLayout,
+        lut_buffer: Buffer,
+        texture_format: TextureFormat,
+        // images larger than this on either side are processed in tiles; None processes them whole
+        max_tile_size: Option<u32>,
+        progress: NodeProgress,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            render_device: &CustomGpuDevice,
+            render_queue: &CustomGpuQueue,
+            shader_source: &String,
+            texture_format: TextureFormat,
+        ) -> Self {
+            let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
+                label: Some("Curves Shader"),
+                source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
+            });
+
+            let lut_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("Curves LUT Buffer"),
+                contents: bytemuck::cast_slice(&Curve::default().lut()),
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            });
+
+            let bind_group_layout = render_device.create_bind_group_layout(
+                "Curves Compute Bind Group Layout",
+                &[
+                    // Input texture
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Texture {
+                            multisampled: false,
+                            sample_type: TextureSampleType::Float { filterable: false },
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Output texture
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: texture_format,
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Curve LUT
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            );
+
+            let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("Curves Compute Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+            let compute_pipeline = render_device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Curves Compute Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &shader_module,
+                entry_point: "main",
+                compilation_options: default(),
+            });
+
+            Self {
+                entity,
+                input_image: None,
+                curve: Curve::default(),
+                output_image: None,
+                render_device: render_device.clone(),
+                render_queue: render_queue.clone(),
+                compute_pipeline,
+                bind_group_layout,
+                lut_buffer,
+                texture_format,
+                max_tile_size: None,
+                progress: NodeProgress::default(),
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            }
+        }
+
+        process(&mut self) {
+            if let Some(ref image) = self.input_image.as_ref() {
+                self.progress.start();
+                let size = image.texture_descriptor.size;
+
+                self.render_queue.write_buffer(
+                    &self.lut_buffer,
+                    0,
+                    bytemuck::cast_slice(&self.curve.lut()),
+                );
+
+                let extra_entries = [BindGroupEntry {
+                    binding: 2,
+                    resource: self.lut_buffer.as_entire_binding(),
+                }];
+
+                self.output_image = Some(match needs_tiling(size, self.max_tile_size) {
+                    Some(max_tile_size) => process_per_pixel_tiled(
+                        &self.render_device,
+                        &self.render_queue,
+                        &self.compute_pipeline,
+                        &self.bind_group_layout,
+                        &extra_entries,
+                        image,
+                        self.texture_format,
+                        max_tile_size,
+                        8,
+                        &self.progress,
+                    ),
+                    None => process_whole_image(
+                        &self.render_device,
+                        &self.render_queue,
+                        &self.compute_pipeline,
+                        &self.bind_group_layout,
+                        &extra_entries,
+                        image,
+                        self.texture_format,
+                        8,
+                        &self.progress,
+                    ),
+                });
+                self.progress.finish();
+            } else {
+                self.output_image = None;
+            }
+        }
+    }
+);
@@ -0,0 +1,509 @@
+use std::borrow::Cow;
+
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+use bevy::render::render_resource::*;
+use crate::nodes::custom_shader::check_custom_shader;
+use crate::nodes::macros::macros::declare_node;
+use crate::nodes::fields::{Field, FieldMeta};
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::{bytes_per_pixel, shader_for_format, shader_param_labels, SHADER_PARAM_COUNT};
+use crate::nodes::{InputId, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::setup::{CustomGpuDevice, CustomGpuQueue};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableCustomComputeNode {
+    pub entity: Entity,
+    pub shader_source: String,
+    pub params: [f32; SHADER_PARAM_COUNT],
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&CustomComputeNode> for SerializableGraphNodeKind {
+    fn from(node: &CustomComputeNode) -> Self {
+        SerializableGraphNodeKind::CustomCompute(SerializableCustomComputeNode {
+            entity: node.entity,
+            shader_source: node.shader_source.clone(),
+            params: node.params(),
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl CustomComputeNode {
+    pub fn from_serializable(
+        serialized: &SerializableCustomComputeNode,
+        render_device: &CustomGpuDevice,
+        render_queue: &CustomGpuQueue,
+        template_source: &String,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let mut node = Self::new(
+            serialized.entity,
+            render_device,
+            render_queue,
+            &serialized.shader_source,
+            template_source,
+            texture_format,
+        );
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node.set_params(serialized.params);
+
+        node
+    }
+
+    pub fn params(&self) -> [f32; SHADER_PARAM_COUNT] {
+        [
+            self.param_0, self.param_1, self.param_2, self.param_3,
+            self.param_4, self.param_5, self.param_6, self.param_7,
+        ]
+    }
+
+    fn set_params(&mut self, params: [f32; SHADER_PARAM_COUNT]) {
+        [
+            self.param_0, self.param_1, self.param_2, self.param_3,
+            self.param_4, self.param_5, self.param_6, self.param_7,
+        ] = params;
+    }
+
+    const PARAM_INPUTS: [InputId; SHADER_PARAM_COUNT] = [
+        Self::param_0, Self::param_1, Self::param_2, Self::param_3,
+        Self::param_4, Self::param_5, Self::param_6, Self::param_7,
+    ];
+
+    // The name the shader gives a param input, if it names it
+    pub fn param_label(&self, input_id: InputId) -> Option<&str> {
+        let index = Self::PARAM_INPUTS.iter().position(|&param| param == input_id)?;
+        self.param_labels[index].as_deref()
+    }
+
+    // Swaps in new WGSL, keeping the node's previous pipeline out of use if it doesn't compile.
+    // The source is kept either way so it can be fixed in the inspector.
+    pub fn set_shader_source(&mut self, shader_source: String, template_source: &str) -> Result<(), String> {
+        self.param_labels = shader_param_labels(&shader_source);
+        self.shader_source = shader_source;
+
+        let result = compile_custom_compute(
+            &self.render_device,
+            &self.bind_group_layout,
+            &self.shader_source,
+            template_source,
+            self.texture_format,
+        );
+
+        match result {
+            Ok(compute_pipeline) => {
+                self.compute_pipeline = Some(compute_pipeline);
+                self.shader_error = None;
+                Ok(())
+            }
+            Err(e) => {
+                self.compute_pipeline = None;
+                self.shader_error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+}
+
+// The user's shader only reaches the device once it parses and matches the template's bindings,
+// since the device treats a broken shader as fatal
+fn compile_custom_compute(
+    render_device: &CustomGpuDevice,
+    bind_group_layout: &BindGroupLayout,
+    shader_source: &str,
+    template_source: &str,
+    texture_format: TextureFormat,
+) -> Result<ComputePipeline, String> {
+    check_custom_shader(shader_source, template_source)?;
+
+    let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
+        label: Some("Custom Compute Shader"),
+        source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
+    });
+
+    let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some("Custom Compute Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    Ok(render_device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Custom Compute Pipeline"),
+        layout: Some(&pipeline_layout),
+        module: &shader_module,
+        entry_point: "main",
+        compilation_options: default(),
+    }))
+}
+
+const PARAM_META: FieldMeta = FieldMeta {
+    visible: false,
+    storage: Field::F32(0.),
+    min: None,
+    max: None,
+    step: Some(0.01),
+    required: false,
+};
+
+// Runs WGSL written in the inspector against a fixed layout: two input textures, one output and eight float params.
+// The template shader documents the layout and is what a new node starts with.
+declare_node!(
+    name: CustomComputeNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image_a: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[input] input_image_b: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        #[input] param_0: f32 { meta: PARAM_META },
+        #[input] param_1: f32 { meta: PARAM_META },
+        #[input] param_2: f32 { meta: PARAM_META },
+        #[input] param_3: f32 { meta: PARAM_META },
+        #[input] param_4: f32 { meta: PARAM_META },
+        #[input] param_5: f32 { meta: PARAM_META },
+        #[input] param_6: f32 { meta: PARAM_META },
+        #[input] param_7: f32 { meta: PARAM_META },
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        shader_source: String,
+        // why the current source didn't compile, shown in the inspector
+        shader_error: Option<String>,
+        param_labels: [Option<String>; SHADER_PARAM_COUNT],
+        render_device: CustomGpuDevice,
+        render_queue: CustomGpuQueue,
+        compute_pipeline: Option<ComputePipeline>,
+        bind_group_layout: BindGroupLayout,
+        bind_group: Option<BindGroup>,
+        params_buffer: Buffer,
+        texture_size: Extent3d,
+        texture_format: TextureFormat,
+        output_texture: Option<Texture>,
+        output_buffer: Option<Buffer>,
+        input_texture_a: Option<Texture>,
+        input_texture_b: Option<Texture>,
+        input_texture_a_view: Option<TextureView>,
+        input_texture_b_view: Option<TextureView>,
+        output_texture_view: Option<TextureView>,
+        progress: NodeProgress,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            render_device: &CustomGpuDevice,
+            render_queue: &CustomGpuQueue,
+            shader_source: &String,
+            template_source: &String,
+            texture_format: TextureFormat,
+        ) -> Self {
+            let params_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("Custom Compute Params Buffer"),
+                contents: bytemuck::cast_slice(&[0f32; SHADER_PARAM_COUNT]),
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            });
+
+            let bind_group_layout = render_device.create_bind_group_layout(
+                "Custom Compute Bind Group Layout",
+                &[
+                    // Input texture A
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Texture {
+                            multisampled: false,
+                            sample_type: TextureSampleType::Float { filterable: false },
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Input texture B
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Texture {
+                            multisampled: false,
+                            sample_type: TextureSampleType::Float { filterable: false },
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Output texture
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: texture_format,
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Params
+                    BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            );
+
+            let mut node = Self {
+                entity,
+                input_image_a: None,
+                input_image_b: None,
+                param_0: 0.,
+                param_1: 0.,
+                param_2: 0.,
+                param_3: 0.,
+                param_4: 0.,
+                param_5: 0.,
+                param_6: 0.,
+                param_7: 0.,
+                output_image: None,
+                shader_source: String::new(),
+                shader_error: None,
+                param_labels: Default::default(),
+                render_device: render_device.clone(),
+                render_queue: render_queue.clone(),
+                compute_pipeline: None,
+                bind_group_layout,
+                bind_group: None,
+                params_buffer,
+                texture_size: Extent3d::default(),
+                texture_format,
+                output_texture: None,
+                output_buffer: None,
+                input_texture_a: None,
+                input_texture_b: None,
+                input_texture_a_view: None,
+                input_texture_b_view: None,
+                output_texture_view: None,
+                progress: NodeProgress::default(),
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            };
+
+            if let Err(e) = node.set_shader_source(shader_source.clone(), template_source) {
+                eprintln!("Custom Compute shader didn't compile:\n{}", e);
+            }
+
+            node
+        }
+
+        process(&mut self) {
+            let (Some(compute_pipeline), Some(image_a)) = (self.compute_pipeline.as_ref(), self.input_image_a.as_ref()) else {
+                self.output_image = None;
+                return;
+            };
+            // the second input is optional, a shader that ignores it shouldn't need something connected
+            let image_b = self.input_image_b.as_ref().unwrap_or(image_a);
+
+            self.progress.start();
+
+            let size = image_a.texture_descriptor.size;
+            // buffer copies need rows aligned to 256 bytes
+            let output_pixel_bytes = bytes_per_pixel(self.texture_format);
+            let padded_bytes_per_row = (output_pixel_bytes * size.width).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+            if self.texture_size != size {
+                self.texture_size = size;
+
+                self.output_texture = Some(self.render_device.create_texture(&TextureDescriptor {
+                    label: Some("Custom Compute Output Texture"),
+                    size: self.texture_size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: self.texture_format,
+                    usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST,
+                    view_formats: &[],
+                }));
+
+                self.output_texture_view = Some(self.output_texture.as_ref().unwrap().create_view(&Default::default()));
+
+                self.output_buffer = Some(self.render_device.create_buffer(&BufferDescriptor {
+                    label: Some("Custom Compute Output Buffer"),
+                    size: (padded_bytes_per_row * self.texture_size.height) as BufferAddress,
+                    usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }));
+
+                self.bind_group = None;
+            }
+
+            // Recreate an input texture when the image it holds changed shape or format
+            let is_stale = |texture: &Option<Texture>, image: &Image| {
+                texture.as_ref().map_or(true, |texture| {
+                    texture.format() != image.texture_descriptor.format || texture.size() != image.texture_descriptor.size
+                })
+            };
+
+            if is_stale(&self.input_texture_a, image_a) {
+                self.input_texture_a = Some(self.render_device.create_texture(&image_a.texture_descriptor));
+                self.input_texture_a_view = Some(self.input_texture_a.as_ref().unwrap().create_view(&Default::default()));
+                self.bind_group = None;
+            }
+
+            if is_stale(&self.input_texture_b, image_b) {
+                self.input_texture_b = Some(self.render_device.create_texture(&image_b.texture_descriptor));
+                self.input_texture_b_view = Some(self.input_texture_b.as_ref().unwrap().create_view(&Default::default()));
+                self.bind_group = None;
+            }
+
+            for (texture, image) in [(&self.input_texture_a, image_a), (&self.input_texture_b, image_b)] {
+                let image_size = image.texture_descriptor.size;
+                self.render_queue.write_texture(
+                    ImageCopyTexture {
+                        texture: texture.as_ref().unwrap(),
+                        mip_level: 0,
+                        origin: Origin3d::ZERO,
+                        aspect: TextureAspect::All,
+                    },
+                    &image.data,
+                    ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(bytes_per_pixel(image.texture_descriptor.format) * image_size.width),
+                        rows_per_image: Some(image_size.height),
+                    },
+                    image_size,
+                );
+            }
+
+            self.render_queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&self.params()));
+
+            if self.bind_group.is_none() {
+                self.bind_group = Some(self.render_device.create_bind_group(
+                    "Custom Compute Bind Group",
+                    &self.bind_group_layout,
+                    &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::TextureView(self.input_texture_a_view.as_ref().unwrap()),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::TextureView(self.input_texture_b_view.as_ref().unwrap()),
+                        },
+                        BindGroupEntry {
+                            binding: 2,
+                            resource: BindingResource::TextureView(self.output_texture_view.as_ref().unwrap()),
+                        },
+                        BindGroupEntry {
+                            binding: 3,
+                            resource: self.params_buffer.as_entire_binding(),
+                        },
+                    ],
+                ));
+            }
+
+            let mut encoder = self.render_device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Custom Compute Encoder"),
+            });
+
+            {
+                let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Custom Compute Pass"),
+                    timestamp_writes: None,
+                });
+                compute_pass.set_pipeline(compute_pipeline);
+                compute_pass.set_bind_group(0, self.bind_group.as_ref().unwrap(), &[]);
+                // the template's workgroup size; shaders declaring a bigger one just do redundant work at the edges
+                let workgroup_size = 8;
+                compute_pass.dispatch_workgroups(
+                    (self.texture_size.width + workgroup_size - 1) / workgroup_size,
+                    (self.texture_size.height + workgroup_size - 1) / workgroup_size,
+                    1,
+                );
+            }
+
+            encoder.copy_texture_to_buffer(
+                ImageCopyTexture {
+                    texture: self.output_texture.as_ref().unwrap(),
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                ImageCopyBuffer {
+                    buffer: self.output_buffer.as_ref().unwrap(),
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_bytes_per_row),
+                        rows_per_image: Some(self.texture_size.height),
+                    },
+                },
+                self.texture_size,
+            );
+
+            self.render_queue.submit(Some(encoder.finish()));
+
+            let buffer_slice = self.output_buffer.as_ref().unwrap().slice(..);
+            let (tx, rx) = crossbeam_channel::unbounded();
+
+            buffer_slice.map_async(MapMode::Read, move |result| {
+                tx.send(result).expect("Failed to send map_async result");
+            });
+
+            self.render_device.poll(Maintain::Wait);
+
+            match rx.recv().expect("Failed to receive map_async result") {
+                Ok(_) => {
+                    let data = {
+                        let mapped = buffer_slice.get_mapped_range();
+                        let row_bytes = (output_pixel_bytes * size.width) as usize;
+
+                        let mut data = Vec::with_capacity(row_bytes * size.height as usize);
+                        for row in 0..size.height {
+                            let start = (row * padded_bytes_per_row) as usize;
+                            data.extend_from_slice(&mapped[start..start + row_bytes]);
+                        }
+                        data
+                    };
+
+                    self.output_image = Some(Image::new(
+                        self.texture_size,
+                        TextureDimension::D2,
+                        data,
+                        self.texture_format,
+                        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+                    ));
+
+                    self.output_buffer.as_ref().unwrap().unmap();
+                }
+                Err(e) => {
+                    panic!("Failed to map output buffer: {:?}", e);
+                }
+            }
+
+            self.progress.finish();
+        }
+    }
+);
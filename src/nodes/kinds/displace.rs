@@ -0,0 +1,305 @@
+use std::borrow::Cow;
+
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+use bevy::render::render_resource::*;
+use crate::nodes::macros::macros::declare_node;
+use crate::nodes::fields::{Field, FieldMeta};
+use crate::nodes::kinds::convolve::EdgeMode;
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::{bytes_per_pixel, shader_for_format};
+use crate::nodes::tiling::process_whole_image;
+use crate::nodes::{SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::setup::{CustomGpuDevice, CustomGpuQueue};
+use bytemuck::{Pod, Zeroable};
+
+#[repr(C)]
+#[derive(Clone, Debug, Copy, Pod, Zeroable)]
+pub struct DisplaceParams {
+    strength: f32,
+    edge_mode: u32,
+    _padding: [u32; 2],
+}
+
+impl DisplaceParams {
+    fn new(strength: f32, edge_mode: EdgeMode) -> Self {
+        Self {
+            strength,
+            edge_mode: edge_mode.shader_index(),
+            _padding: [0; 2],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableDisplaceNode {
+    pub entity: Entity,
+    pub strength: f32,
+    pub edge_mode: EdgeMode,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&DisplaceNode> for SerializableGraphNodeKind {
+    fn from(node: &DisplaceNode) -> Self {
+        SerializableGraphNodeKind::Displace(SerializableDisplaceNode {
+            entity: node.entity,
+            strength: node.strength,
+            edge_mode: node.edge_mode,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl DisplaceNode {
+    pub fn from_serializable(
+        serialized: &SerializableDisplaceNode,
+        render_device: &CustomGpuDevice,
+        render_queue: &CustomGpuQueue,
+        shader_source: &String,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let mut node = Self::new(
+            serialized.entity,
+            render_device,
+            render_queue,
+            shader_source,
+            texture_format,
+        );
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node.strength = serialized.strength;
+        node.edge_mode = serialized.edge_mode;
+
+        node
+    }
+}
+
+// Moves each pixel's sample position by the displacement map, red along x and green along y,
+// each scaled by strength in pixels. A black map leaves the input as it is.
+declare_node!(
+    name: DisplaceNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[input] displacement_map: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[input] strength: f32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::F32(16.),
+            min: Some(-512.),
+            max: Some(512.),
+            step: Some(1.),
+            required: false,
+        }},
+        #[input] edge_mode: EdgeMode { meta: FieldMeta {
+            visible: false,
+            storage: Field::EdgeMode(EdgeMode::default()),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        render_device: CustomGpuDevice,
+        render_queue: CustomGpuQueue,
+        compute_pipeline: ComputePipeline,
+        bind_group_layout: BindGroupLayout,
+        params_buffer: Buffer,
+        texture_format: TextureFormat,
+        progress: NodeProgress,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            render_device: &CustomGpuDevice,
+            render_queue: &CustomGpuQueue,
+            shader_source: &String,
+            texture_format: TextureFormat,
+        ) -> Self {
+            let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
+                label: Some("Displace Shader"),
+                source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
+            });
+
+            let params_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("Displace Params Buffer"),
+                contents: bytemuck::cast_slice(&[DisplaceParams::new(16., EdgeMode::default())]),
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            });
+
+            let bind_group_layout = render_device.create_bind_group_layout(
+                "Displace Compute Bind Group Layout",
+                &[
+                    // Input texture
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Texture {
+                            multisampled: false,
+                            sample_type: TextureSampleType::Float { filterable: false },
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Output texture
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: texture_format,
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Displacement map
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Texture {
+                            multisampled: false,
+                            sample_type: TextureSampleType::Float { filterable: false },
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Strength and edge mode
+                    BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            );
+
+            let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("Displace Compute Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+            let compute_pipeline = render_device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Displace Compute Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &shader_module,
+                entry_point: "main",
+                compilation_options: default(),
+            });
+
+            Self {
+                entity,
+                input_image: None,
+                displacement_map: None,
+                strength: 16.,
+                edge_mode: EdgeMode::default(),
+                output_image: None,
+                render_device: render_device.clone(),
+                render_queue: render_queue.clone(),
+                compute_pipeline,
+                bind_group_layout,
+                params_buffer,
+                texture_format,
+                progress: NodeProgress::default(),
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            }
+        }
+
+        process(&mut self) {
+            if let (Some(ref image), Some(ref map)) = (self.input_image.as_ref(), self.displacement_map.as_ref()) {
+                self.progress.start();
+                let map_size = map.texture_descriptor.size;
+
+                self.render_queue.write_buffer(
+                    &self.params_buffer,
+                    0,
+                    bytemuck::cast_slice(&[DisplaceParams::new(self.strength, self.edge_mode)]),
+                );
+
+                let map_texture = self.render_device.create_texture(&TextureDescriptor {
+                    label: Some("Displacement Map Texture"),
+                    size: map_size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: map.texture_descriptor.format,
+                    usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                    view_formats: &[],
+                });
+
+                self.render_queue.write_texture(
+                    ImageCopyTexture {
+                        texture: &map_texture,
+                        mip_level: 0,
+                        origin: Origin3d::ZERO,
+                        aspect: TextureAspect::All,
+                    },
+                    &map.data,
+                    ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(bytes_per_pixel(map.texture_descriptor.format) * map_size.width),
+                        rows_per_image: Some(map_size.height),
+                    },
+                    map_size,
+                );
+
+                let map_view = map_texture.create_view(&Default::default());
+
+                // samples land anywhere in the input, so the image can't be split into tiles; it goes through as one
+                self.output_image = Some(process_whole_image(
+                    &self.render_device,
+                    &self.render_queue,
+                    &self.compute_pipeline,
+                    &self.bind_group_layout,
+                    &[
+                        BindGroupEntry {
+                            binding: 2,
+                            resource: BindingResource::TextureView(&map_view),
+                        },
+                        BindGroupEntry {
+                            binding: 3,
+                            resource: self.params_buffer.as_entire_binding(),
+                        },
+                    ],
+                    image,
+                    self.texture_format,
+                    8,
+                    &self.progress,
+                ));
+                self.progress.finish();
+            } else {
+                self.output_image = None;
+            }
+        }
+    }
+);
@@ -0,0 +1,243 @@
+use std::borrow::Cow;
+
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+use bevy::render::render_resource::*;
+use crate::nodes::macros::macros::declare_node;
+use crate::nodes::fields::{Field, FieldMeta};
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::shader_for_format;
+use crate::nodes::tiling::process_whole_image;
+use crate::nodes::{SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::setup::{CustomGpuDevice, CustomGpuQueue};
+use bytemuck::{Pod, Zeroable};
+
+#[repr(C)]
+#[derive(Clone, Debug, Copy, Pod, Zeroable)]
+pub struct DitherParams {
+    levels: u32,
+    seed: u32,
+    _padding: [u32; 2],
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableDitherNode {
+    pub entity: Entity,
+    pub levels: u32,
+    #[serde(default)]
+    pub seed: u32,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&DitherNode> for SerializableGraphNodeKind {
+    fn from(node: &DitherNode) -> Self {
+        SerializableGraphNodeKind::Dither(SerializableDitherNode {
+            entity: node.entity,
+            levels: node.levels,
+            seed: node.seed,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl DitherNode {
+    pub fn from_serializable(
+        serialized: &SerializableDitherNode,
+        render_device: &CustomGpuDevice,
+        render_queue: &CustomGpuQueue,
+        shader_source: &String,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let mut node = Self::new(
+            serialized.entity,
+            render_device,
+            render_queue,
+            shader_source,
+            texture_format,
+        );
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node.levels = serialized.levels;
+        node.seed = serialized.seed;
+
+        node
+    }
+}
+
+declare_node!(
+    name: DitherNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[input] levels: u32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::U32(4),
+            min: Some(2.),
+            max: Some(256.),
+            step: Some(1.),
+            required: false,
+        }},
+        #[input] seed: u32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::U32(0),
+            min: Some(0.),
+            max: None,
+            step: Some(1.),
+            required: false,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        render_device: CustomGpuDevice,
+        render_queue: CustomGpuQueue,
+        compute_pipeline: ComputePipeline,
+        bind_group_layout: BindGroupLayout,
+        params_buffer: Buffer,
+        texture_format: TextureFormat,
+        progress: NodeProgress,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            render_device: &CustomGpuDevice,
+            render_queue: &CustomGpuQueue,
+            shader_source: &String,
+            texture_format: TextureFormat,
+        ) -> Self {
+            let levels = 4;
+
+            let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
+                label: Some("Dither Shader"),
+                source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
+            });
+
+            let params_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("Dither Params Buffer"),
+                contents: bytemuck::cast_slice(&[DitherParams { levels, seed: 0, _padding: [0; 2] }]),
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            });
+
+            let bind_group_layout = render_device.create_bind_group_layout(
+                "Dither Compute Bind Group Layout",
+                &[
+                    // Input texture
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Texture {
+                            multisampled: false,
+                            sample_type: TextureSampleType::Float { filterable: false },
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Output texture
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: texture_format,
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Params
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            );
+
+            let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("Dither Compute Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+            let compute_pipeline = render_device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Dither Compute Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &shader_module,
+                entry_point: "main",
+                compilation_options: default(),
+            });
+
+            Self {
+                entity,
+                input_image: None,
+                levels,
+                seed: 0,
+                output_image: None,
+                render_device: render_device.clone(),
+                render_queue: render_queue.clone(),
+                compute_pipeline,
+                bind_group_layout,
+                params_buffer,
+                texture_format,
+                progress: NodeProgress::default(),
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            }
+        }
+
+        process(&mut self) {
+            if let Some(ref image) = self.input_image.as_ref() {
+                self.progress.start();
+
+                self.render_queue.write_buffer(
+                    &self.params_buffer,
+                    0,
+                    bytemuck::cast_slice(&[DitherParams {
+                        levels: self.levels.max(2),
+                        seed: self.seed,
+                        _padding: [0; 2],
+                    }]),
+                );
+
+                // the Bayer pattern is keyed to absolute pixel coordinates, so the image goes through as one
+                self.output_image = Some(process_whole_image(
+                    &self.render_device,
+                    &self.render_queue,
+                    &self.compute_pipeline,
+                    &self.bind_group_layout,
+                    &[BindGroupEntry {
+                        binding: 2,
+                        resource: self.params_buffer.as_entire_binding(),
+                    }],
+                    image,
+                    self.texture_format,
+                    8,
+                    &self.progress,
+                ));
+                self.progress.finish();
+            } else {
+                self.output_image = None;
+            }
+        }
+    }
+);
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc};
 
 use bevy::{
     color::palettes::css::WHITE,
@@ -12,10 +12,10 @@ use bevy::{
             CommandEncoderDescriptor, Extent3d, Face, FrontFace, ImageCopyBuffer,
             ImageCopyTextureBase, ImageDataLayout, IndexFormat, LoadOp, Maintain, MapMode,
             MultisampleState, Operations, Origin3d, PipelineCompilationOptions,
-            PipelineLayoutDescriptor, PrimitiveState, RawFragmentState,
+            PipelineLayout, PipelineLayoutDescriptor, PrimitiveState, RawFragmentState,
             RawRenderPipelineDescriptor, RawVertexBufferLayout, RawVertexState,
             RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline,
-            ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp, Texture, TextureAspect,
+            ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp, Texture, TextureAspect,
             TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
             VertexAttribute, VertexFormat, VertexStepMode,
         },
@@ -26,7 +26,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     nodes::{
-        fields::{Field, FieldMeta}, macros::macros::declare_node, shared::{Vertex, U32_SIZE}, InputId, NodeTrait, OutputId, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId
+        fields::{Field, FieldMeta}, macros::macros::declare_node, shared::{Vertex, U32_SIZE}, InputId, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId
     },
     setup::{CustomGpuDevice, CustomGpuQueue},
 };
@@ -48,8 +48,8 @@ impl From<&ExampleNode> for SerializableGraphNodeKind {
             texture_extents: node.texture_extents,
             texture_format: node.texture_format,
             triangle_color: node.triangle_color,
-            input_meta: node.input_meta.iter().map(|(k, v)| (SerializableInputId(k.0.to_string(), k.1.to_string()), v.clone())).collect(),
-            output_meta: node.output_meta.iter().map(|(k, v)| (SerializableOutputId(k.0.to_string(), k.1.to_string()), v.clone())).collect(),
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
         })
     }
 }
@@ -76,19 +76,7 @@ impl ExampleNode {
         node.texture_format = serialized.texture_format;
         node.triangle_color = serialized.triangle_color;
 
-        let input_fields: Vec<InputId> = node.input_fields().to_vec();
-        for &input_id in &input_fields {
-            if let Some(meta) = serialized.input_meta.get(&SerializableInputId(input_id.0.to_string(), input_id.1.to_string())) {
-                node.set_input_meta(input_id, meta.clone());
-            }
-        }
-
-        let output_fields: Vec<OutputId> = node.output_fields().to_vec();
-        for &output_id in &output_fields {
-            if let Some(meta) = serialized.output_meta.get(&SerializableOutputId(output_id.0.to_string(), output_id.1.to_string())) {
-                node.set_output_meta(output_id, meta.clone());
-            }
-        }
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
 
         node
     }
@@ -100,23 +88,43 @@ declare_node!(
         #[entity] entity: Entity,
         #[input] texture_extents: Extent3d   { meta: FieldMeta {
             visible: false,
-            storage: Field::Extent3d(Extent3d { ..default() })
+            storage: Field::Extent3d(Extent3d { ..default() }),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
         }},
         #[input] texture_format: TextureFormat  { meta: FieldMeta {
             visible: false,
-            storage: Field::TextureFormat(TextureFormat::Rgba8Unorm)
+            storage: Field::TextureFormat(TextureFormat::Rgba8Unorm),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
         }},
         #[input] triangle_color: LinearRgba   { meta: FieldMeta {
             visible: true,
-            storage: Field::LinearRgba(Color::WHITE.into())
+            storage: Field::LinearRgba(Color::WHITE.into()),
+            min: Some(0.),
+            max: None,
+            step: Some(0.01),
+            required: false,
         }},
         #[output] output_image: Option<Image>  { meta: FieldMeta {
             visible: true ,
             storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
         }},
         render_device: CustomGpuDevice,
         render_queue: CustomGpuQueue,
         render_pipeline: Box<RenderPipeline>,
+        // kept so the pipeline can be rebuilt when the texture format input changes
+        pipeline_layout: Arc<PipelineLayout>,
+        vert_shader_module: Arc<ShaderModule>,
+        frag_shader_module: Arc<ShaderModule>,
         texture_view: Box<TextureView>,
         bind_group: BindGroup, // todo: just one?
         texture: Texture,
@@ -125,6 +133,8 @@ declare_node!(
         index_buffer: Buffer,
         color_buffer: Buffer,
         num_vertices: u32,
+        // multiplied into texture_extents; export passes raise it to render larger
+        render_scale: f32,
     },
     methods: {
         new(
@@ -175,23 +185,6 @@ declare_node!(
                 usage: BufferUsages::INDEX,
             });
 
-            let vertex_buffer_layout = RawVertexBufferLayout {
-                array_stride: std::mem::size_of::<Vertex>() as BufferAddress,
-                step_mode: VertexStepMode::Vertex,
-                attributes: &[
-                    VertexAttribute {
-                        offset: 0,
-                        shader_location: 0,
-                        format: VertexFormat::Float32x3,
-                    },
-                    VertexAttribute {
-                        offset: std::mem::size_of::<[f32; 3]>() as BufferAddress,
-                        shader_location: 1,
-                        format: VertexFormat::Float32x3,
-                    },
-                ],
-            };
-
             let color_bind_group_layout = render_device.create_bind_group_layout(
                 "color bind group layout",
                 &[BindGroupLayoutEntry {
@@ -238,70 +231,28 @@ declare_node!(
                 depth_or_array_layers: 1,
             };
 
-            let texture = render_device.create_texture(&TextureDescriptor {
-                label: Some("Texture Name Or Something?"),
-                size: texture_extents,
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: TextureDimension::D2,
-                format: texture_format,
-                usage: TextureUsages::STORAGE_BINDING
-                    | TextureUsages::COPY_SRC
-                    | TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[],
-            });
+            let (texture, texture_view, output_buffer) =
+                create_example_target(render_device, texture_extents, texture_format);
 
-            let texture_view = texture.create_view(&Default::default());
+            let vert_shader_module = Arc::new(vert_shader_module);
+            let frag_shader_module = Arc::new(frag_shader_module);
+            let pipeline_layout = Arc::new(pipeline_layout);
 
-            let output_buffer_size = (U32_SIZE * texture_size * texture_size) as BufferAddress;
-            let output_buffer = render_device.create_buffer(&BufferDescriptor {
-                size: output_buffer_size,
-                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
-                label: None,
-                mapped_at_creation: false,
-            });
-
-            let render_pipeline = render_device.create_render_pipeline(&RawRenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&pipeline_layout),
-                vertex: RawVertexState {
-                    module: &vert_shader_module,
-                    entry_point: "vertex",
-                    buffers: &[vertex_buffer_layout],
-                    compilation_options: PipelineCompilationOptions::default(),
-                },
-                fragment: Some(RawFragmentState {
-                    module: &frag_shader_module,
-                    entry_point: "fragment",
-                    targets: &[Some(ColorTargetState {
-                        format: texture_format,
-                        blend: Some(BlendState::REPLACE),
-                        write_mask: ColorWrites::ALL,
-                    })],
-                    compilation_options: PipelineCompilationOptions::default(),
-                }),
-                primitive: PrimitiveState {
-                    topology: bevy::render::mesh::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: FrontFace::Ccw,
-                    cull_mode: Some(Face::Back),
-                    polygon_mode: bevy::render::render_resource::PolygonMode::Fill,
-                    unclipped_depth: false, // ????
-                    conservative: false,     // maybe? only on vulkan
-                },
-                depth_stencil: None,
-                multisample: MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-            });
+            let render_pipeline = create_example_pipeline(
+                render_device,
+                &pipeline_layout,
+                &vert_shader_module,
+                &frag_shader_module,
+                texture_format,
+            );
 
             Self {
                 render_device: render_device.clone(),
                 render_queue: render_queue.clone(),
                 render_pipeline: Box::new(render_pipeline),
+                pipeline_layout,
+                vert_shader_module,
+                frag_shader_module,
                 texture_view: Box::new(texture_view),
                 texture,
                 vertex_buffer,
@@ -312,6 +263,7 @@ declare_node!(
                 bind_group: color_bind_group,
                 texture_extents,
                 texture_format,
+                render_scale: 1.,
                 output_image: None,
                 triangle_color: WHITE.into(),
                 entity,
@@ -320,6 +272,16 @@ declare_node!(
             }
         }
         process(&mut self) {
+            let scaled_extents = scale_extents(self.texture_extents, self.render_scale);
+
+            if self.texture.size() != scaled_extents {
+                let (texture, texture_view, output_buffer) =
+                    create_example_target(&self.render_device, scaled_extents, self.texture_format);
+                self.texture = texture;
+                self.texture_view = Box::new(texture_view);
+                self.output_buffer = output_buffer;
+            }
+
             let mut encoder = self.render_device.create_command_encoder(&CommandEncoderDescriptor {
                 label: Some("Command Encoder Descriptor"),
             });
@@ -360,11 +322,11 @@ declare_node!(
                     buffer: &self.output_buffer,
                     layout: ImageDataLayout {
                         offset: 0,
-                        bytes_per_row: Some(U32_SIZE * self.texture_extents.width), // todo: width prob wrong here - what happens if aspect ratio != 1? or does aspect ratio HAVE to be padded to 1?
-                        rows_per_image: Some(self.texture_extents.width), // todo: width prob wrong here too
+                        bytes_per_row: Some(U32_SIZE * scaled_extents.width), // todo: width prob wrong here - what happens if aspect ratio != 1? or does aspect ratio HAVE to be padded to 1?
+                        rows_per_image: Some(scaled_extents.width), // todo: width prob wrong here too
                     },
                 },
-                self.texture_extents.clone(),
+                scaled_extents,
             );
 
             self.render_queue.submit(Some(encoder.finish()));
@@ -388,7 +350,7 @@ declare_node!(
                 let buffer: &[u8] = &buffer_slice.get_mapped_range();
 
                 Image::new_fill(
-                    self.texture_extents.clone(),
+                    scaled_extents,
                     TextureDimension::D2,
                     buffer,
                     self.texture_format.clone(),
@@ -402,11 +364,152 @@ declare_node!(
 
 
         set_input(&mut self, id: InputId, value: &Field) -> Result<(), String> {
-            // TODO: Update any internal state that might require an update due to an input change.
-            // Texture extents, texture format...
             // Field is guaranteed by the macro to be an appropriate type for the input id
-            println!("Custom set_input called with value: {:?}", value);
+            match (id, value) {
+                (Self::texture_extents, Field::Extent3d(extents)) => {
+                    if extents.width == 0 || extents.height == 0 {
+                        return Err(String::from("Texture extents must be at least 1x1"));
+                    }
+
+                    let (texture, texture_view, output_buffer) = create_example_target(
+                        &self.render_device,
+                        scale_extents(*extents, self.render_scale),
+                        self.texture_format,
+                    );
+                    self.texture = texture;
+                    self.texture_view = Box::new(texture_view);
+                    self.output_buffer = output_buffer;
+                }
+                (Self::texture_format, Field::TextureFormat(format)) => {
+                    // the readback assumes four bytes a pixel
+                    if format.block_copy_size(None) != Some(U32_SIZE) {
+                        return Err(format!("{:?} is not a 4 byte per pixel format", format));
+                    }
+
+                    self.render_pipeline = Box::new(create_example_pipeline(
+                        &self.render_device,
+                        &self.pipeline_layout,
+                        &self.vert_shader_module,
+                        &self.frag_shader_module,
+                        *format,
+                    ));
+
+                    let (texture, texture_view, output_buffer) = create_example_target(
+                        &self.render_device,
+                        scale_extents(self.texture_extents, self.render_scale),
+                        *format,
+                    );
+                    self.texture = texture;
+                    self.texture_view = Box::new(texture_view);
+                    self.output_buffer = output_buffer;
+                }
+                _ => {}
+            }
+
             Ok(())
         }
     }
 );
+
+// The render target, its view and the readback buffer, all sized to the given extents
+fn create_example_target(
+    render_device: &CustomGpuDevice,
+    texture_extents: Extent3d,
+    texture_format: TextureFormat,
+) -> (Texture, TextureView, Buffer) {
+    let texture = render_device.create_texture(&TextureDescriptor {
+        label: Some("Texture Name Or Something?"),
+        size: texture_extents,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: texture_format,
+        usage: TextureUsages::STORAGE_BINDING
+            | TextureUsages::COPY_SRC
+            | TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    let texture_view = texture.create_view(&Default::default());
+
+    let output_buffer_size = (U32_SIZE * texture_extents.width * texture_extents.height) as BufferAddress;
+    let output_buffer = render_device.create_buffer(&BufferDescriptor {
+        size: output_buffer_size,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        label: None,
+        mapped_at_creation: false,
+    });
+
+    (texture, texture_view, output_buffer)
+}
+
+fn scale_extents(extents: Extent3d, render_scale: f32) -> Extent3d {
+    Extent3d {
+        width: ((extents.width as f32 * render_scale).round() as u32).max(1),
+        height: ((extents.height as f32 * render_scale).round() as u32).max(1),
+        depth_or_array_layers: 1,
+    }
+}
+
+// The triangle pipeline, which has to match the format of the texture it renders into
+fn create_example_pipeline(
+    render_device: &CustomGpuDevice,
+    pipeline_layout: &PipelineLayout,
+    vert_shader_module: &ShaderModule,
+    frag_shader_module: &ShaderModule,
+    texture_format: TextureFormat,
+) -> RenderPipeline {
+    let vertex_buffer_layout = RawVertexBufferLayout {
+        array_stride: std::mem::size_of::<Vertex>() as BufferAddress,
+        step_mode: VertexStepMode::Vertex,
+        attributes: &[
+            VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: VertexFormat::Float32x3,
+            },
+            VertexAttribute {
+                offset: std::mem::size_of::<[f32; 3]>() as BufferAddress,
+                shader_location: 1,
+                format: VertexFormat::Float32x3,
+            },
+        ],
+    };
+
+    render_device.create_render_pipeline(&RawRenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(pipeline_layout),
+        vertex: RawVertexState {
+            module: vert_shader_module,
+            entry_point: "vertex",
+            buffers: &[vertex_buffer_layout],
+            compilation_options: PipelineCompilationOptions::default(),
+        },
+        fragment: Some(RawFragmentState {
+            module: frag_shader_module,
+            entry_point: "fragment",
+            targets: &[Some(ColorTargetState {
+                format: texture_format,
+                blend: Some(BlendState::REPLACE),
+                write_mask: ColorWrites::ALL,
+            })],
+            compilation_options: PipelineCompilationOptions::default(),
+        }),
+        primitive: PrimitiveState {
+            topology: bevy::render::mesh::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: Some(Face::Back),
+            polygon_mode: bevy::render::render_resource::PolygonMode::Fill,
+            unclipped_depth: false, // ????
+            conservative: false,     // maybe? only on vulkan
+        },
+        depth_stencil: None,
+        multisample: MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
@@ -0,0 +1,217 @@
+use std::{fmt, io::Cursor};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    utils::HashMap,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::nodes::{
+    fields::{Field, FieldMeta}, macros::macros::declare_node, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId
+};
+
+// An image with no file behind it (e.g. pasted from the clipboard), so its pixels are saved with the project
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableImageNode {
+    pub entity: Entity,
+    pub pixels: EmbeddedPixels,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&ImageNode> for SerializableGraphNodeKind {
+    fn from(node: &ImageNode) -> Self {
+        let size = node.image.texture_descriptor.size;
+
+        // an image still decoding saves the bytes it came from
+        let pixels = match &node.pending_png {
+            Some(png) => EmbeddedPixels::Png(png.clone()),
+            None => EmbeddedPixels::Rgba {
+                width: size.width,
+                height: size.height,
+                rgba: node.image.data.clone(),
+            },
+        };
+
+        SerializableGraphNodeKind::Image(SerializableImageNode {
+            entity: node.entity,
+            pixels,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl ImageNode {
+    pub fn from_serializable(serialized: &SerializableImageNode) -> Self {
+        let mut node = match &serialized.pixels {
+            EmbeddedPixels::Rgba { width, height, rgba } => {
+                Self::new(serialized.entity, rgba_image(*width, *height, rgba.clone()))
+            }
+            EmbeddedPixels::Png(png) => {
+                let mut node = Self::new(serialized.entity, Image::transparent());
+                node.pending_png = Some(png.clone());
+                node
+            }
+        };
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node
+    }
+
+    // Bytes waiting on a background decode, see image_decode
+    pub fn pending_png(&self) -> Option<&Vec<u8>> {
+        self.pending_png.as_ref()
+    }
+
+    pub fn finish_decode(&mut self, image: Image) {
+        self.image = image;
+        self.pending_png = None;
+    }
+}
+
+// The pixels of an image saved with the project, stored PNG-compressed when serialized
+#[derive(Clone, Debug)]
+pub enum EmbeddedPixels {
+    // Tightly packed 8-bit RGBA
+    Rgba {
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+    },
+    // Read from a project and not decoded yet. Decoding a large image takes long enough
+    // to hitch a frame, so it happens on a background task once the node exists.
+    Png(Vec<u8>),
+}
+
+impl Serialize for EmbeddedPixels {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (width, height, rgba) = match self {
+            EmbeddedPixels::Rgba { width, height, rgba } => (*width, *height, rgba),
+            EmbeddedPixels::Png(png) => return serializer.serialize_bytes(png),
+        };
+
+        let buffer = image::RgbaImage::from_raw(width, height, rgba.clone())
+            .ok_or_else(|| serde::ser::Error::custom("pixel data does not match image dimensions"))?;
+
+        let mut png = Cursor::new(Vec::new());
+        buffer
+            .write_to(&mut png, image::ImageFormat::Png)
+            .map_err(serde::ser::Error::custom)?;
+
+        serializer.serialize_bytes(png.get_ref())
+    }
+}
+
+impl<'de> Deserialize<'de> for EmbeddedPixels {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let png = deserializer.deserialize_byte_buf(PngBytesVisitor)?;
+
+        Ok(EmbeddedPixels::Png(png))
+    }
+}
+
+pub fn decode_png(png: &[u8]) -> Result<Image, String> {
+    let decoded = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?
+        .to_rgba8();
+
+    Ok(rgba_image(decoded.width(), decoded.height(), decoded.into_raw()))
+}
+
+// Formats differ in whether they hand bytes back as a blob or as a sequence
+struct PngBytesVisitor;
+
+impl<'de> de::Visitor<'de> for PngBytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("PNG encoded bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+// Wraps tightly packed 8-bit RGBA pixels in an Image
+pub fn rgba_image(width: u32, height: u32, pixels: Vec<u8>) -> Image {
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+    )
+}
+
+declare_node!(
+    name: ImageNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        image: Image,
+        pending_png: Option<Vec<u8>>,
+        // the pending bytes stay put when decoding fails, so saving the project doesn't lose them
+        decode_error: Option<String>,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            image: Image,
+        ) -> Self {
+            Self {
+                entity,
+                output_image: None,
+                image,
+                pending_png: None,
+                decode_error: None,
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            }
+        }
+
+        process(&mut self) {
+            // nothing to show until the background decode lands
+            self.output_image = match self.pending_png {
+                Some(_) => None,
+                None => Some(self.image.clone()),
+            };
+        }
+    }
+);
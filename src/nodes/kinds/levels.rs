@@ -0,0 +1,289 @@
+use std::borrow::Cow;
+
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+use bevy::render::render_resource::*;
+use crate::nodes::macros::macros::declare_node;
+use crate::nodes::fields::{Field, FieldMeta};
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::shader_for_format;
+use crate::nodes::tiling::{needs_tiling, process_per_pixel_tiled, process_whole_image};
+use crate::nodes::{SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::setup::{CustomGpuDevice, CustomGpuQueue};
+use bytemuck::{Pod, Zeroable};
+
+#[repr(C)]
+#[derive(Clone, Debug, Copy, Pod, Zeroable)]
+pub struct LevelsParams {
+    black_point: f32,
+    white_point: f32,
+    gamma: f32,
+    _padding: f32,
+}
+
+impl LevelsParams {
+    // A collapsed or inverted range would divide by zero, so the points are ordered and kept apart
+    fn new(black_point: f32, white_point: f32, gamma: f32) -> Self {
+        let (black_point, white_point) = if black_point <= white_point {
+            (black_point, white_point)
+        } else {
+            (white_point, black_point)
+        };
+
+        Self {
+            black_point,
+            white_point: white_point.max(black_point + MIN_LEVELS_RANGE),
+            gamma: gamma.max(MIN_GAMMA),
+            _padding: 0.,
+        }
+    }
+}
+
+const MIN_LEVELS_RANGE: f32 = 1. / 255.;
+const MIN_GAMMA: f32 = 0.01;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableLevelsNode {
+    pub entity: Entity,
+    pub black_point: f32,
+    pub white_point: f32,
+    pub gamma: f32,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&LevelsNode> for SerializableGraphNodeKind {
+    fn from(node: &LevelsNode) -> Self {
+        SerializableGraphNodeKind::Levels(SerializableLevelsNode {
+            entity: node.entity,
+            black_point: node.black_point,
+            white_point: node.white_point,
+            gamma: node.gamma,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl LevelsNode {
+    pub fn from_serializable(
+        serialized: &SerializableLevelsNode,
+        render_device: &CustomGpuDevice,
+        render_queue: &CustomGpuQueue,
+        shader_source: &String,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let mut node = Self::new(
+            serialized.entity,
+            render_device,
+            render_queue,
+            shader_source,
+            texture_format,
+        );
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node.black_point = serialized.black_point;
+        node.white_point = serialized.white_point;
+        node.gamma = serialized.gamma;
+
+        node
+    }
+}
+
+declare_node!(
+    name: LevelsNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[input] black_point: f32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::F32(0.),
+            min: Some(0.),
+            max: Some(1.),
+            step: Some(0.01),
+            required: false,
+        }},
+        #[input] white_point: f32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::F32(1.),
+            min: Some(0.),
+            max: Some(1.),
+            step: Some(0.01),
+            required: false,
+        }},
+        #[input] gamma: f32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::F32(1.),
+            min: Some(MIN_GAMMA),
+            max: Some(10.),
+            step: Some(0.01),
+            required: false,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        render_device: CustomGpuDevice,
+        render_queue: CustomGpuQueue,
+        compute_pipeline: ComputePipeline,
+        bind_group_layout: BindGroupLayout,
+        params_buffer: Buffer,
+        texture_format: TextureFormat,
+        // images larger than this on either side are processed in tiles; None processes them whole
+        max_tile_size: Option<u32>,
+        progress: NodeProgress,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            render_device: &CustomGpuDevice,
+            render_queue: &CustomGpuQueue,
+            shader_source: &String,
+            texture_format: TextureFormat,
+        ) -> Self {
+            let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
+                label: Some("Levels Shader"),
+                source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
+            });
+
+            let params_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("Levels Params Buffer"),
+                contents: bytemuck::cast_slice(&[LevelsParams::new(0., 1., 1.)]),
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            });
+
+            let bind_group_layout = render_device.create_bind_group_layout(
+                "Levels Compute Bind Group Layout",
+                &[
+                    // Input texture
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Texture {
+                            multisampled: false,
+                            sample_type: TextureSampleType::Float { filterable: false },
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Output texture
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: texture_format,
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    // Params
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            );
+
+            let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("Levels Compute Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+            let compute_pipeline = render_device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Levels Compute Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &shader_module,
+                entry_point: "main",
+                compilation_options: default(),
+            });
+
+            Self {
+                entity,
+                input_image: None,
+                black_point: 0.,
+                white_point: 1.,
+                gamma: 1.,
+                output_image: None,
+                render_device: render_device.clone(),
+                render_queue: render_queue.clone(),
+                compute_pipeline,
+                bind_group_layout,
+                params_buffer,
+                texture_format,
+                max_tile_size: None,
+                progress: NodeProgress::default(),
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            }
+        }
+
+        process(&mut self) {
+            if let Some(ref image) = self.input_image.as_ref() {
+                self.progress.start();
+                let size = image.texture_descriptor.size;
+
+                self.render_queue.write_buffer(
+                    &self.params_buffer,
+                    0,
+                    bytemuck::cast_slice(&[LevelsParams::new(self.black_point, self.white_point, self.gamma)]),
+                );
+
+                let extra_entries = [BindGroupEntry {
+                    binding: 2,
+                    resource: self.params_buffer.as_entire_binding(),
+                }];
+
+                self.output_image = Some(match needs_tiling(size, self.max_tile_size) {
+                    Some(max_tile_size) => process_per_pixel_tiled(
+                        &self.render_device,
+                        &self.render_queue,
+                        &self.compute_pipeline,
+                        &self.bind_group_layout,
+                        &extra_entries,
+                        image,
+                        self.texture_format,
+                        max_tile_size,
+                        8,
+                        &self.progress,
+                    ),
+                    None => process_whole_image(
+                        &self.render_device,
+                        &self.render_queue,
+                        &self.compute_pipeline,
+                        &self.bind_group_layout,
+                        &extra_entries,
+                        image,
+                        self.texture_format,
+                        8,
+                        &self.progress,
+                    ),
+                });
+                self.progress.finish();
+            } else {
+                self.output_image = None;
+            }
+        }
+    }
+);
@@ -0,0 +1,164 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::nodes::{
+    fields::{Field, FieldMeta}, macros::macros::declare_node, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MathOperation {
+    #[default]
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Min,
+    Max,
+    Lerp,
+}
+
+impl MathOperation {
+    pub const ALL: [MathOperation; 7] = [
+        MathOperation::Add,
+        MathOperation::Subtract,
+        MathOperation::Multiply,
+        MathOperation::Divide,
+        MathOperation::Min,
+        MathOperation::Max,
+        MathOperation::Lerp,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MathOperation::Add => "Add",
+            MathOperation::Subtract => "Subtract",
+            MathOperation::Multiply => "Multiply",
+            MathOperation::Divide => "Divide",
+            MathOperation::Min => "Min",
+            MathOperation::Max => "Max",
+            MathOperation::Lerp => "Lerp",
+        }
+    }
+
+    // t is only read by Lerp
+    pub fn apply(&self, a: f32, b: f32, t: f32) -> f32 {
+        match self {
+            MathOperation::Add => a + b,
+            MathOperation::Subtract => a - b,
+            MathOperation::Multiply => a * b,
+            // a NaN or infinity would poison every node downstream, so dividing by zero gives zero
+            MathOperation::Divide => if b == 0. { 0. } else { a / b },
+            MathOperation::Min => a.min(b),
+            MathOperation::Max => a.max(b),
+            MathOperation::Lerp => a + (b - a) * t,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableMathNode {
+    pub entity: Entity,
+    pub a: f32,
+    pub b: f32,
+    pub t: f32,
+    pub operation: MathOperation,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&MathNode> for SerializableGraphNodeKind {
+    fn from(node: &MathNode) -> Self {
+        SerializableGraphNodeKind::Math(SerializableMathNode {
+            entity: node.entity,
+            a: node.a,
+            b: node.b,
+            t: node.t,
+            operation: node.operation,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl MathNode {
+    pub fn from_serializable(serialized: &SerializableMathNode) -> Self {
+        let mut node = Self::new(serialized.entity, serialized.operation);
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node.a = serialized.a;
+        node.b = serialized.b;
+        node.t = serialized.t;
+
+        node
+    }
+}
+
+declare_node!(
+    name: MathNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] a: f32 { meta: FieldMeta {
+            visible: true,
+            storage: Field::F32(0.),
+            min: None,
+            max: None,
+            step: Some(0.01),
+            required: false,
+        }},
+        #[input] b: f32 { meta: FieldMeta {
+            visible: true,
+            storage: Field::F32(0.),
+            min: None,
+            max: None,
+            step: Some(0.01),
+            required: false,
+        }},
+        #[input] t: f32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::F32(0.5),
+            min: None,
+            max: None,
+            step: Some(0.01),
+            required: false,
+        }},
+        #[input] operation: MathOperation { meta: FieldMeta {
+            visible: false,
+            storage: Field::MathOperation(MathOperation::default()),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        #[output] result: f32 { meta: FieldMeta {
+            visible: true,
+            storage: Field::F32(0.),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            operation: MathOperation,
+        ) -> Self {
+            Self {
+                entity,
+                a: 0.,
+                b: 0.,
+                t: 0.5,
+                operation,
+                result: 0.,
+                input_meta: HashMap::new(),
+                output_meta: HashMap::new(),
+            }
+        }
+
+        process(&mut self) {
+            self.result = self.operation.apply(self.a, self.b, self.t);
+        }
+    }
+);
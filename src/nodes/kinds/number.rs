@@ -0,0 +1,78 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::nodes::{
+    fields::{Field, FieldMeta}, macros::macros::declare_node, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId
+};
+
+// A single scalar, so one value can drive parameters on several nodes through edges
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableNumberNode {
+    pub entity: Entity,
+    pub in_value: f32,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&NumberNode> for SerializableGraphNodeKind {
+    fn from(node: &NumberNode) -> Self {
+        SerializableGraphNodeKind::Number(SerializableNumberNode {
+            entity: node.entity,
+            in_value: node.in_value,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl NumberNode {
+    pub fn from_serializable(serialized: &SerializableNumberNode) -> Self {
+        let mut node = Self::new(serialized.entity, serialized.in_value);
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node
+    }
+}
+
+declare_node!(
+    name: NumberNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] in_value: f32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::F32(0.),
+            min: None,
+            max: None,
+            step: Some(0.01),
+            required: false,
+        }},
+        #[output] out_value: f32 { meta: FieldMeta {
+            visible: true,
+            storage: Field::F32(0.),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            in_value: f32,
+        ) -> Self {
+            Self {
+                entity,
+                in_value,
+                out_value: in_value,
+                input_meta: HashMap::new(),
+                output_meta: HashMap::new(),
+            }
+        }
+
+        process(&mut self) {
+            self.out_value = self.in_value;
+        }
+    }
+);
@@ -0,0 +1,323 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+use bevy::render::render_resource::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::nodes::fields::{Field, FieldMeta};
+use crate::nodes::macros::macros::declare_node;
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::shader_for_format;
+use crate::nodes::tiling::{needs_tiling, process_per_pixel_tiled};
+use crate::nodes::{SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::setup::{CustomGpuDevice, CustomGpuQueue};
+
+// Blend and the other compositing nodes work on straight alpha. Premultiply before an operation that
+// should treat transparent pixels as contributing nothing (e.g. blurring or scaling a cutout, so
+// its edges don't pick up the hidden color), then Unpremultiply to get straight alpha back.
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializablePremultiplyNode {
+    pub entity: Entity,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&PremultiplyNode> for SerializableGraphNodeKind {
+    fn from(node: &PremultiplyNode) -> Self {
+        SerializableGraphNodeKind::Premultiply(SerializablePremultiplyNode {
+            entity: node.entity,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl PremultiplyNode {
+    pub fn from_serializable(
+        serialized: &SerializablePremultiplyNode,
+        render_device: &CustomGpuDevice,
+        render_queue: &CustomGpuQueue,
+        shader_source: &String,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let mut node = Self::new(serialized.entity, render_device, render_queue, shader_source, texture_format);
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableUnpremultiplyNode {
+    pub entity: Entity,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&UnpremultiplyNode> for SerializableGraphNodeKind {
+    fn from(node: &UnpremultiplyNode) -> Self {
+        SerializableGraphNodeKind::Unpremultiply(SerializableUnpremultiplyNode {
+            entity: node.entity,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl UnpremultiplyNode {
+    pub fn from_serializable(
+        serialized: &SerializableUnpremultiplyNode,
+        render_device: &CustomGpuDevice,
+        render_queue: &CustomGpuQueue,
+        shader_source: &String,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let mut node = Self::new(serialized.entity, render_device, render_queue, shader_source, texture_format);
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node
+    }
+}
+
+// Both nodes run a shader with just an input texture and an output storage texture
+fn create_alpha_pipeline(
+    render_device: &CustomGpuDevice,
+    shader_source: &String,
+    texture_format: TextureFormat,
+    label: &str,
+) -> (ComputePipeline, BindGroupLayout) {
+    let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
+        label: Some(&format!("{} Shader", label)),
+        source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
+    });
+
+    let bind_group_layout = render_device.create_bind_group_layout(
+        format!("{} Compute Bind Group Layout", label).as_str(),
+        &[
+            // Input texture
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Texture {
+                    multisampled: false,
+                    sample_type: TextureSampleType::Float { filterable: false },
+                    view_dimension: TextureViewDimension::D2,
+                },
+                count: None,
+            },
+            // Output texture
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::StorageTexture {
+                    access: StorageTextureAccess::WriteOnly,
+                    format: texture_format,
+                    view_dimension: TextureViewDimension::D2,
+                },
+                count: None,
+            },
+        ],
+    );
+
+    let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some(&format!("{} Compute Pipeline Layout", label)),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let compute_pipeline = render_device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some(&format!("{} Compute Pipeline", label)),
+        layout: Some(&pipeline_layout),
+        module: &shader_module,
+        entry_point: "main",
+        compilation_options: default(),
+    });
+
+    (compute_pipeline, bind_group_layout)
+}
+
+// Without tiling enabled the whole image goes through as a single tile
+fn process_alpha(
+    render_device: &CustomGpuDevice,
+    render_queue: &CustomGpuQueue,
+    compute_pipeline: &ComputePipeline,
+    bind_group_layout: &BindGroupLayout,
+    input: &Image,
+    texture_format: TextureFormat,
+    max_tile_size: Option<u32>,
+    progress: &NodeProgress,
+) -> Image {
+    let size = input.texture_descriptor.size;
+    let tile_size = needs_tiling(size, max_tile_size).unwrap_or(size.width.max(size.height));
+
+    process_per_pixel_tiled(
+        render_device,
+        render_queue,
+        compute_pipeline,
+        bind_group_layout,
+        &[],
+        input,
+        texture_format,
+        tile_size,
+        8,
+        progress,
+    )
+}
+
+declare_node!(
+    name: PremultiplyNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        render_device: CustomGpuDevice,
+        render_queue: CustomGpuQueue,
+        compute_pipeline: ComputePipeline,
+        bind_group_layout: BindGroupLayout,
+        texture_format: TextureFormat,
+        max_tile_size: Option<u32>,
+        progress: NodeProgress,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            render_device: &CustomGpuDevice,
+            render_queue: &CustomGpuQueue,
+            shader_source: &String,
+            texture_format: TextureFormat,
+        ) -> Self {
+            let (compute_pipeline, bind_group_layout) =
+                create_alpha_pipeline(render_device, shader_source, texture_format, "Premultiply");
+
+            Self {
+                entity,
+                input_image: None,
+                output_image: None,
+                render_device: render_device.clone(),
+                render_queue: render_queue.clone(),
+                compute_pipeline,
+                bind_group_layout,
+                texture_format,
+                max_tile_size: None,
+                progress: NodeProgress::default(),
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            }
+        }
+
+        process(&mut self) {
+            self.progress.start();
+
+            self.output_image = self.input_image.as_ref().map(|image| {
+                process_alpha(
+                    &self.render_device,
+                    &self.render_queue,
+                    &self.compute_pipeline,
+                    &self.bind_group_layout,
+                    image,
+                    self.texture_format,
+                    self.max_tile_size,
+                    &self.progress,
+                )
+            });
+
+            self.progress.finish();
+        }
+    }
+);
+
+declare_node!(
+    name: UnpremultiplyNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        render_device: CustomGpuDevice,
+        render_queue: CustomGpuQueue,
+        compute_pipeline: ComputePipeline,
+        bind_group_layout: BindGroupLayout,
+        texture_format: TextureFormat,
+        max_tile_size: Option<u32>,
+        progress: NodeProgress,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            render_device: &CustomGpuDevice,
+            render_queue: &CustomGpuQueue,
+            shader_source: &String,
+            texture_format: TextureFormat,
+        ) -> Self {
+            let (compute_pipeline, bind_group_layout) =
+                create_alpha_pipeline(render_device, shader_source, texture_format, "Unpremultiply");
+
+            Self {
+                entity,
+                input_image: None,
+                output_image: None,
+                render_device: render_device.clone(),
+                render_queue: render_queue.clone(),
+                compute_pipeline,
+                bind_group_layout,
+                texture_format,
+                max_tile_size: None,
+                progress: NodeProgress::default(),
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            }
+        }
+
+        process(&mut self) {
+            self.progress.start();
+
+            self.output_image = self.input_image.as_ref().map(|image| {
+                process_alpha(
+                    &self.render_device,
+                    &self.render_queue,
+                    &self.compute_pipeline,
+                    &self.bind_group_layout,
+                    image,
+                    self.texture_format,
+                    self.max_tile_size,
+                    &self.progress,
+                )
+            });
+
+            self.progress.finish();
+        }
+    }
+);
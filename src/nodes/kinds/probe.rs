@@ -0,0 +1,124 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::nodes::{
+    fields::{Field, FieldMeta}, macros::macros::declare_node, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableProbeNode {
+    pub entity: Entity,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&ProbeNode> for SerializableGraphNodeKind {
+    fn from(node: &ProbeNode) -> Self {
+        SerializableGraphNodeKind::Probe(SerializableProbeNode {
+            entity: node.entity,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl ProbeNode {
+    pub fn from_serializable(serialized: &SerializableProbeNode) -> Self {
+        let mut node = Self::new(serialized.entity);
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node
+    }
+}
+
+fn hash_image(image: &Image) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.texture_descriptor.size.width.hash(&mut hasher);
+    image.texture_descriptor.size.height.hash(&mut hasher);
+    image.data.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Passes its image through untouched while counting how often it runs and whether the image it got was new,
+// which makes the processing cache observable. A developer node, only offered when the preference is on.
+declare_node!(
+    name: ProbeNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        #[output] process_count: u32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::U32(0),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        // how many of those runs saw a different image than the run before
+        #[output] change_count: u32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::U32(0),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        last_input_hash: Option<u64>,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+        ) -> Self {
+            Self {
+                entity,
+                input_image: None,
+                output_image: None,
+                process_count: 0,
+                change_count: 0,
+                last_input_hash: None,
+                input_meta: HashMap::new(),
+                output_meta: HashMap::new(),
+            }
+        }
+
+        process(&mut self) {
+            let input_hash = self.input_image.as_ref().map(hash_image);
+            let changed = input_hash != self.last_input_hash;
+
+            self.process_count += 1;
+            if changed {
+                self.change_count += 1;
+            }
+
+            println!(
+                "Probe {:?}: run {}, input {} (hash {:?})",
+                self.entity,
+                self.process_count,
+                if changed { "changed" } else { "unchanged" },
+                input_hash,
+            );
+
+            self.last_input_hash = input_hash;
+            self.output_image = self.input_image.clone();
+        }
+    }
+);
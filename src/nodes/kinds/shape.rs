@@ -8,14 +8,15 @@ use bevy::prelude::*;
 use bevy::render::render_resource::*;
 use crate::nodes::macros::macros::declare_node;
 use crate::nodes::fields::{Field, FieldMeta};
-use crate::nodes::shared::U32_SIZE;
-use crate::nodes::{InputId, NodeTrait, OutputId, SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::{bytes_per_pixel, shader_for_format};
+use crate::nodes::{SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
 use crate::setup::{CustomGpuDevice, CustomGpuQueue};
 use bytemuck::{Pod, Zeroable};
 
 
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Shape {
     Circle(f32), // radius
     Rectangle(f32, f32), // width, height
@@ -60,6 +61,68 @@ impl From<&Shape> for ShapeData {
     }
 }
 
+impl Shape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Circle(_) => "Circle",
+            Shape::Rectangle(_, _) => "Rectangle",
+            Shape::Triangle(_, _) => "Triangle",
+        }
+    }
+
+    // The largest dimension of the shape, so switching kinds keeps it roughly the same size.
+    pub fn extent(&self) -> f32 {
+        match self {
+            Shape::Circle(radius) => radius * 2.,
+            Shape::Rectangle(width, height) => width.max(*height),
+            Shape::Triangle(height, base) => height.max(*base),
+        }
+    }
+
+    pub fn param_names(&self) -> (&'static str, Option<&'static str>) {
+        match self {
+            Shape::Circle(_) => ("Radius", None),
+            Shape::Rectangle(_, _) => ("Width", Some("Height")),
+            Shape::Triangle(_, _) => ("Height", Some("Base")),
+        }
+    }
+
+    pub fn params(&self) -> (f32, f32) {
+        match self {
+            Shape::Circle(radius) => (*radius, 0.),
+            Shape::Rectangle(width, height) => (*width, *height),
+            Shape::Triangle(height, base) => (*height, *base),
+        }
+    }
+
+    pub fn with_param(&self, param: usize, value: f32) -> Shape {
+        match (self, param) {
+            (Shape::Circle(_), 0) => Shape::Circle(value),
+            (Shape::Rectangle(_, height), 0) => Shape::Rectangle(value, *height),
+            (Shape::Rectangle(width, _), 1) => Shape::Rectangle(*width, value),
+            (Shape::Triangle(_, base), 0) => Shape::Triangle(value, *base),
+            (Shape::Triangle(height, _), 1) => Shape::Triangle(*height, value),
+            (shape, _) => shape.clone(),
+        }
+    }
+
+    pub fn is_same_kind(&self, other: &Shape) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn circle_with_extent(extent: f32) -> Shape {
+        Shape::Circle(extent / 2.)
+    }
+
+    pub fn rectangle_with_extent(extent: f32) -> Shape {
+        Shape::Rectangle(extent, extent)
+    }
+
+    pub fn triangle_with_extent(extent: f32) -> Shape {
+        Shape::Triangle(extent, extent)
+    }
+}
+
 impl Default for Shape {
     fn default() -> Self {
         Shape::Circle(0.4)
@@ -82,8 +145,8 @@ impl From<&ShapeNode> for SerializableGraphNodeKind {
             entity: node.entity,
             shape: node.shape.clone(),
             texture_size: node.texture_size,
-            input_meta: node.input_meta.iter().map(|(k, v)| (SerializableInputId(k.0.to_string(), k.1.to_string()), v.clone())).collect(),
-            output_meta: node.output_meta.iter().map(|(k, v)| (SerializableOutputId(k.0.to_string(), k.1.to_string()), v.clone())).collect(),
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
         })
     }
 }
@@ -94,6 +157,7 @@ impl ShapeNode {
         render_device: &CustomGpuDevice,
         render_queue: &CustomGpuQueue,
         shader_source: &String,
+        texture_format: TextureFormat,
     ) -> Self {
         let mut node = Self::new(
             serialized.entity,
@@ -102,21 +166,10 @@ impl ShapeNode {
             render_device,
             render_queue,
             shader_source,
+            texture_format,
         );
 
-        let input_fields: Vec<InputId> = node.input_fields().to_vec();
-        for &input_id in &input_fields {
-            if let Some(meta) = serialized.input_meta.get(&SerializableInputId(input_id.0.to_string(), input_id.1.to_string())) {
-                node.set_input_meta(input_id, meta.clone());
-            }
-        }
-
-        let output_fields: Vec<OutputId> = node.output_fields().to_vec();
-        for &output_id in &output_fields {
-            if let Some(meta) = serialized.output_meta.get(&SerializableOutputId(output_id.0.to_string(), output_id.1.to_string())) {
-                node.set_output_meta(output_id, meta.clone());
-            }
-        }
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
 
         node
     }
@@ -128,29 +181,49 @@ declare_node!(
         #[entity] entity: Entity,
         #[input] shape: Shape { meta: FieldMeta {
             visible: false,
-            storage: Field::Shape(Shape::default())
+            storage: Field::Shape(Shape::default()),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
         }},
         #[input] texture_size: u32 { meta: FieldMeta {
             visible: false,
-            storage: Field::U32(512)
+            storage: Field::U32(512),
+            min: Some(1.),
+            max: Some(4096.),
+            step: Some(1.),
+            required: false,
         }},
         #[input] color: LinearRgba { meta: FieldMeta {
             visible: false,
-            storage: Field::LinearRgba(LinearRgba::default())
+            storage: Field::LinearRgba(LinearRgba::default()),
+            min: Some(0.),
+            max: None,
+            step: Some(0.01),
+            required: false,
         }},
         #[output] output_image: Option<Image> { meta: FieldMeta {
             visible: true,
             storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
         }},
         render_device: CustomGpuDevice,
         render_queue: CustomGpuQueue,
         compute_pipeline: ComputePipeline,
+        bind_group_layout: BindGroupLayout,
         bind_group: BindGroup,
         output_texture: Texture,
         output_buffer: Buffer,
         shape_buffer: Buffer,
         texture_format: TextureFormat,
         texture_extents: Extent3d,
+        // multiplied into texture_size and the shape's dimensions; export passes raise it to render larger
+        render_scale: f32,
+        progress: NodeProgress,
     },
 
     methods: {
@@ -161,8 +234,8 @@ declare_node!(
             render_device: &CustomGpuDevice,
             render_queue: &CustomGpuQueue,
             shader_source: &String,
+            texture_format: TextureFormat,
         ) -> Self {
-            let texture_format = TextureFormat::Rgba8Unorm;
             let texture_extents = Extent3d {
                 width: texture_size,
                 height: texture_size,
@@ -171,26 +244,7 @@ declare_node!(
 
             let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
                 label: Some("Shape Rasterizer Shader"),
-                source: ShaderSource::Wgsl(Cow::Borrowed(shader_source)),
-            });
-
-            let output_texture = render_device.create_texture(&TextureDescriptor {
-                label: Some("Shape Output Texture"),
-                size: texture_extents,
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: TextureDimension::D2,
-                format: texture_format,
-                usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
-                view_formats: &[],
-            });
-
-            let output_buffer_size = (U32_SIZE * texture_size * texture_size) as BufferAddress;
-            let output_buffer = render_device.create_buffer(&BufferDescriptor {
-                label: Some("Shape Output Buffer"),
-                size: output_buffer_size,
-                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
-                mapped_at_creation: false,
+                source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
             });
 
 
@@ -265,21 +319,12 @@ declare_node!(
                 compilation_options: default(),
             });
 
-            let bind_group = render_device.create_bind_group(
-                "Shape Compute Bind Group",
+            let (output_texture, output_buffer, bind_group) = create_shape_target(
+                render_device,
                 &bind_group_layout,
-                &[
-                    BindGroupEntry {
-                        binding: 0,
-                        resource: BindingResource::TextureView(
-                            &output_texture.create_view(&Default::default())
-                        ),
-                    },
-                    BindGroupEntry {
-                        binding: 1,
-                        resource: shape_buffer.as_entire_binding(),
-                    },
-                ],
+                &shape_buffer,
+                texture_format,
+                texture_extents,
             );
 
             Self {
@@ -290,12 +335,15 @@ declare_node!(
                 render_device: render_device.clone(),
                 render_queue: render_queue.clone(),
                 compute_pipeline,
+                bind_group_layout,
                 bind_group,
                 output_texture,
                 output_buffer,
                 shape_buffer,
                 texture_format,
                 texture_extents,
+                render_scale: 1.,
+                progress: NodeProgress::default(),
                 color: WHITE.into(),
                 input_meta: Default::default(),
                 output_meta: Default::default(),
@@ -303,29 +351,50 @@ declare_node!(
         }
 
         process(&mut self) {
+            self.progress.start();
+
+            let size = ((self.texture_size as f32 * self.render_scale).round() as u32).max(1);
+            if self.texture_extents.width != size || self.texture_extents.height != size {
+                self.texture_extents = Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                };
+
+                (self.output_texture, self.output_buffer, self.bind_group) = create_shape_target(
+                    &self.render_device,
+                    &self.bind_group_layout,
+                    &self.shape_buffer,
+                    self.texture_format,
+                    self.texture_extents,
+                );
+            }
+
             let mut encoder = self.render_device.create_command_encoder(&CommandEncoderDescriptor {
                 label: Some("Shape Compute Encoder"),
             });
 
+            // shape dimensions are in pixels, so they grow with the texture
+            let scale = self.render_scale;
             let shape_data: ShapeData = match self.shape {
                 Shape::Circle(radius) => ShapeData {
                     shape_type: 0,
                     _paddinga: [0.0, 0.0, 0.0],
-                    params: [radius, 0.0, 0.0],
+                    params: [radius * scale, 0.0, 0.0],
                     _paddingb: 0.0,
                     color: self.color.to_f32_array(), 
                 },
                 Shape::Rectangle(width, height) => ShapeData {
                     shape_type: 1,
                     _paddinga: [0.0, 0.0, 0.0],
-                    params: [width, height, 0.0],
+                    params: [width * scale, height * scale, 0.0],
                     _paddingb: 0.0,
                     color: self.color.to_f32_array(),
                 },
                 Shape::Triangle(height, base) => ShapeData {
                     shape_type: 2,
                     _paddinga: [0.0, 0.0, 0.0],
-                    params: [height, base, 0.0],
+                    params: [height * scale, base * scale, 0.0],
                     _paddingb: 0.0,
                     color: self.color.to_f32_array(),
                 },
@@ -342,8 +411,8 @@ declare_node!(
                 compute_pass.set_bind_group(0, &self.bind_group, &[]);
                 let workgroup_size = 32;
                 let workgroup_count = (
-                    (self.texture_size + workgroup_size - 1) / workgroup_size,
-                    (self.texture_size + workgroup_size - 1) / workgroup_size,
+                    (size + workgroup_size - 1) / workgroup_size,
+                    (size + workgroup_size - 1) / workgroup_size,
                     1
                 );
                 compute_pass.dispatch_workgroups(workgroup_count.0, workgroup_count.1, workgroup_count.2);
@@ -360,8 +429,8 @@ declare_node!(
                     buffer: &self.output_buffer,
                     layout: ImageDataLayout {
                         offset: 0,
-                        bytes_per_row: Some(4 * self.texture_size),
-                        rows_per_image: Some(self.texture_size),
+                        bytes_per_row: Some(bytes_per_pixel(self.texture_format) * size),
+                        rows_per_image: Some(size),
                     },
                 },
                 self.texture_extents,
@@ -397,6 +466,52 @@ declare_node!(
 
             self.output_buffer.unmap();
             self.output_image = Some(image);
+            self.progress.finish();
         }
     }
-);
\ No newline at end of file
+);
+
+// The output texture, its readback buffer and the bind group that writes into it, all sized to the given extents
+fn create_shape_target(
+    render_device: &CustomGpuDevice,
+    bind_group_layout: &BindGroupLayout,
+    shape_buffer: &Buffer,
+    texture_format: TextureFormat,
+    texture_extents: Extent3d,
+) -> (Texture, Buffer, BindGroup) {
+    let output_texture = render_device.create_texture(&TextureDescriptor {
+        label: Some("Shape Output Texture"),
+        size: texture_extents,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: texture_format,
+        usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    let output_buffer_size = (bytes_per_pixel(texture_format) * texture_extents.width * texture_extents.height) as BufferAddress;
+    let output_buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("Shape Output Buffer"),
+        size: output_buffer_size,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let bind_group = render_device.create_bind_group(
+        "Shape Compute Bind Group",
+        bind_group_layout,
+        &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&output_texture.create_view(&Default::default())),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: shape_buffer.as_entire_binding(),
+            },
+        ],
+    );
+
+    (output_texture, output_buffer, bind_group)
+}
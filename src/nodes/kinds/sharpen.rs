@@ -0,0 +1,484 @@
+use std::borrow::Cow;
+
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+use bevy::render::render_resource::*;
+use crate::nodes::macros::macros::declare_node;
+use crate::nodes::fields::{Field, FieldMeta};
+use crate::nodes::progress::NodeProgress;
+use crate::nodes::shared::{bytes_per_pixel, shader_for_format};
+use crate::nodes::{SerializableGraphNodeKind, SerializableInputId, SerializableOutputId};
+use crate::setup::{CustomGpuDevice, CustomGpuQueue};
+use bytemuck::{Pod, Zeroable};
+
+#[repr(C)]
+#[derive(Clone, Debug, Copy, Pod, Zeroable)]
+pub struct BlurParams {
+    direction: [i32; 2],
+    radius: i32,
+    _padding: i32,
+}
+
+impl BlurParams {
+    fn new(direction: IVec2, radius: u32) -> Self {
+        Self {
+            direction: direction.to_array(),
+            radius: radius as i32,
+            _padding: 0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, Copy, Pod, Zeroable)]
+pub struct SharpenParams {
+    amount: f32,
+    _padding: [f32; 3],
+}
+
+impl SharpenParams {
+    fn new(amount: f32) -> Self {
+        Self {
+            amount,
+            _padding: [0.; 3],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableSharpenNode {
+    pub entity: Entity,
+    pub amount: f32,
+    pub radius: u32,
+    pub input_meta: HashMap<SerializableInputId, FieldMeta>,
+    pub output_meta: HashMap<SerializableOutputId, FieldMeta>,
+}
+
+impl From<&SharpenNode> for SerializableGraphNodeKind {
+    fn from(node: &SharpenNode) -> Self {
+        SerializableGraphNodeKind::Sharpen(SerializableSharpenNode {
+            entity: node.entity,
+            amount: node.amount,
+            radius: node.radius,
+            input_meta: node.serializable_input_meta(),
+            output_meta: node.serializable_output_meta(),
+        })
+    }
+}
+
+impl SharpenNode {
+    pub fn from_serializable(
+        serialized: &SerializableSharpenNode,
+        render_device: &CustomGpuDevice,
+        render_queue: &CustomGpuQueue,
+        blur_shader_source: &String,
+        sharpen_shader_source: &String,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let mut node = Self::new(
+            serialized.entity,
+            render_device,
+            render_queue,
+            blur_shader_source,
+            sharpen_shader_source,
+            texture_format,
+        );
+
+        node.load_serializable_meta(&serialized.input_meta, &serialized.output_meta);
+
+        node.amount = serialized.amount;
+        node.radius = serialized.radius;
+
+        node
+    }
+}
+
+fn texture_layout_entry(binding: u32) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Texture {
+            multisampled: false,
+            sample_type: TextureSampleType::Float { filterable: false },
+            view_dimension: TextureViewDimension::D2,
+        },
+        count: None,
+    }
+}
+
+fn storage_texture_layout_entry(binding: u32, format: TextureFormat) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::StorageTexture {
+            access: StorageTextureAccess::WriteOnly,
+            format,
+            view_dimension: TextureViewDimension::D2,
+        },
+        count: None,
+    }
+}
+
+fn uniform_layout_entry(binding: u32) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+fn create_compute_pipeline(
+    render_device: &CustomGpuDevice,
+    shader_source: &String,
+    texture_format: TextureFormat,
+    bind_group_layout: &BindGroupLayout,
+    label: &str,
+) -> ComputePipeline {
+    let shader_module = render_device.create_shader_module(ShaderModuleDescriptor {
+        label: Some(&format!("{} Shader", label)),
+        source: ShaderSource::Wgsl(Cow::Owned(shader_for_format(shader_source, texture_format))),
+    });
+
+    let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some(&format!("{} Compute Pipeline Layout", label)),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    render_device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some(&format!("{} Compute Pipeline", label)),
+        layout: Some(&pipeline_layout),
+        module: &shader_module,
+        entry_point: "main",
+        compilation_options: default(),
+    })
+}
+
+// Unsharp masking: the input is blurred in two separable passes, then pushed away from the blur.
+// All three passes share one encoder, and only the final texture is read back.
+declare_node!(
+    name: SharpenNode,
+    fields: {
+        #[entity] entity: Entity,
+        #[input] input_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: true,
+        }},
+        #[input] amount: f32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::F32(1.),
+            min: Some(0.),
+            max: Some(10.),
+            step: Some(0.05),
+            required: false,
+        }},
+        #[input] radius: u32 { meta: FieldMeta {
+            visible: false,
+            storage: Field::U32(2),
+            min: Some(0.),
+            max: Some(32.),
+            step: Some(1.),
+            required: false,
+        }},
+        #[output] output_image: Option<Image> { meta: FieldMeta {
+            visible: true,
+            storage: Field::Image(None),
+            min: None,
+            max: None,
+            step: None,
+            required: false,
+        }},
+        render_device: CustomGpuDevice,
+        render_queue: CustomGpuQueue,
+        blur_pipeline: ComputePipeline,
+        blur_bind_group_layout: BindGroupLayout,
+        sharpen_pipeline: ComputePipeline,
+        sharpen_bind_group_layout: BindGroupLayout,
+        horizontal_params_buffer: Buffer,
+        vertical_params_buffer: Buffer,
+        sharpen_params_buffer: Buffer,
+        texture_format: TextureFormat,
+        progress: NodeProgress,
+    },
+
+    methods: {
+        new(
+            entity: Entity,
+            render_device: &CustomGpuDevice,
+            render_queue: &CustomGpuQueue,
+            blur_shader_source: &String,
+            sharpen_shader_source: &String,
+            texture_format: TextureFormat,
+        ) -> Self {
+            let blur_bind_group_layout = render_device.create_bind_group_layout(
+                "Sharpen Blur Bind Group Layout",
+                &[
+                    texture_layout_entry(0),
+                    storage_texture_layout_entry(1, texture_format),
+                    uniform_layout_entry(2),
+                ],
+            );
+
+            let sharpen_bind_group_layout = render_device.create_bind_group_layout(
+                "Sharpen Bind Group Layout",
+                &[
+                    // the original image, then its blur
+                    texture_layout_entry(0),
+                    texture_layout_entry(1),
+                    storage_texture_layout_entry(2, texture_format),
+                    uniform_layout_entry(3),
+                ],
+            );
+
+            let blur_pipeline = create_compute_pipeline(render_device, blur_shader_source, texture_format, &blur_bind_group_layout, "Sharpen Blur");
+            let sharpen_pipeline = create_compute_pipeline(render_device, sharpen_shader_source, texture_format, &sharpen_bind_group_layout, "Sharpen");
+
+            let create_params_buffer = |label: &str, contents: &[u8]| {
+                render_device.create_buffer_with_data(&BufferInitDescriptor {
+                    label: Some(label),
+                    contents,
+                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                })
+            };
+
+            let horizontal_params_buffer = create_params_buffer(
+                "Sharpen Horizontal Blur Params Buffer",
+                bytemuck::cast_slice(&[BlurParams::new(IVec2::X, 2)]),
+            );
+            let vertical_params_buffer = create_params_buffer(
+                "Sharpen Vertical Blur Params Buffer",
+                bytemuck::cast_slice(&[BlurParams::new(IVec2::Y, 2)]),
+            );
+            let sharpen_params_buffer = create_params_buffer(
+                "Sharpen Params Buffer",
+                bytemuck::cast_slice(&[SharpenParams::new(1.)]),
+            );
+
+            Self {
+                entity,
+                input_image: None,
+                amount: 1.,
+                radius: 2,
+                output_image: None,
+                render_device: render_device.clone(),
+                render_queue: render_queue.clone(),
+                blur_pipeline,
+                blur_bind_group_layout,
+                sharpen_pipeline,
+                sharpen_bind_group_layout,
+                horizontal_params_buffer,
+                vertical_params_buffer,
+                sharpen_params_buffer,
+                texture_format,
+                progress: NodeProgress::default(),
+                input_meta: Default::default(),
+                output_meta: Default::default(),
+            }
+        }
+
+        process(&mut self) {
+            if let Some(ref image) = self.input_image.as_ref() {
+                self.progress.start();
+                let size = image.texture_descriptor.size;
+
+                self.render_queue.write_buffer(
+                    &self.horizontal_params_buffer,
+                    0,
+                    bytemuck::cast_slice(&[BlurParams::new(IVec2::X, self.radius)]),
+                );
+                self.render_queue.write_buffer(
+                    &self.vertical_params_buffer,
+                    0,
+                    bytemuck::cast_slice(&[BlurParams::new(IVec2::Y, self.radius)]),
+                );
+                self.render_queue.write_buffer(
+                    &self.sharpen_params_buffer,
+                    0,
+                    bytemuck::cast_slice(&[SharpenParams::new(self.amount)]),
+                );
+
+                let create_texture = |label: &str, format: TextureFormat, usage: TextureUsages| {
+                    self.render_device.create_texture(&TextureDescriptor {
+                        label: Some(label),
+                        size,
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: TextureDimension::D2,
+                        format,
+                        usage,
+                        view_formats: &[],
+                    })
+                };
+
+                // the intermediate textures are written by one pass and read by the next
+                let intermediate_usage = TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING;
+                let input_texture = create_texture(
+                    "Sharpen Input Texture",
+                    image.texture_descriptor.format,
+                    TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                );
+                let horizontal_texture = create_texture("Sharpen Horizontal Blur Texture", self.texture_format, intermediate_usage);
+                let blurred_texture = create_texture("Sharpen Blurred Texture", self.texture_format, intermediate_usage);
+                let output_texture = create_texture(
+                    "Sharpen Output Texture",
+                    self.texture_format,
+                    TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+                );
+
+                let input_view = input_texture.create_view(&Default::default());
+                let horizontal_view = horizontal_texture.create_view(&Default::default());
+                let blurred_view = blurred_texture.create_view(&Default::default());
+                let output_view = output_texture.create_view(&Default::default());
+
+                self.render_queue.write_texture(
+                    ImageCopyTexture {
+                        texture: &input_texture,
+                        mip_level: 0,
+                        origin: Origin3d::ZERO,
+                        aspect: TextureAspect::All,
+                    },
+                    &image.data,
+                    ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(bytes_per_pixel(image.texture_descriptor.format) * size.width),
+                        rows_per_image: Some(size.height),
+                    },
+                    size,
+                );
+
+                let horizontal_bind_group = self.render_device.create_bind_group(
+                    "Sharpen Horizontal Blur Bind Group",
+                    &self.blur_bind_group_layout,
+                    &[
+                        BindGroupEntry { binding: 0, resource: BindingResource::TextureView(&input_view) },
+                        BindGroupEntry { binding: 1, resource: BindingResource::TextureView(&horizontal_view) },
+                        BindGroupEntry { binding: 2, resource: self.horizontal_params_buffer.as_entire_binding() },
+                    ],
+                );
+
+                let vertical_bind_group = self.render_device.create_bind_group(
+                    "Sharpen Vertical Blur Bind Group",
+                    &self.blur_bind_group_layout,
+                    &[
+                        BindGroupEntry { binding: 0, resource: BindingResource::TextureView(&horizontal_view) },
+                        BindGroupEntry { binding: 1, resource: BindingResource::TextureView(&blurred_view) },
+                        BindGroupEntry { binding: 2, resource: self.vertical_params_buffer.as_entire_binding() },
+                    ],
+                );
+
+                let sharpen_bind_group = self.render_device.create_bind_group(
+                    "Sharpen Bind Group",
+                    &self.sharpen_bind_group_layout,
+                    &[
+                        BindGroupEntry { binding: 0, resource: BindingResource::TextureView(&input_view) },
+                        BindGroupEntry { binding: 1, resource: BindingResource::TextureView(&blurred_view) },
+                        BindGroupEntry { binding: 2, resource: BindingResource::TextureView(&output_view) },
+                        BindGroupEntry { binding: 3, resource: self.sharpen_params_buffer.as_entire_binding() },
+                    ],
+                );
+
+                // buffer copies need rows aligned to 256 bytes
+                let output_pixel_bytes = bytes_per_pixel(self.texture_format);
+                let padded_bytes_per_row = (output_pixel_bytes * size.width).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+                let output_buffer = self.render_device.create_buffer(&BufferDescriptor {
+                    label: Some("Sharpen Output Buffer"),
+                    size: (padded_bytes_per_row * size.height) as BufferAddress,
+                    usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                });
+
+                let mut encoder = self.render_device.create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("Sharpen Compute Encoder"),
+                });
+
+                let workgroup_size = 8;
+                let workgroup_count = (
+                    (size.width + workgroup_size - 1) / workgroup_size,
+                    (size.height + workgroup_size - 1) / workgroup_size,
+                );
+
+                for (pipeline, bind_group) in [
+                    (&self.blur_pipeline, &horizontal_bind_group),
+                    (&self.blur_pipeline, &vertical_bind_group),
+                    (&self.sharpen_pipeline, &sharpen_bind_group),
+                ] {
+                    let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                        label: Some("Sharpen Compute Pass"),
+                        timestamp_writes: None,
+                    });
+                    compute_pass.set_pipeline(pipeline);
+                    compute_pass.set_bind_group(0, bind_group, &[]);
+                    compute_pass.dispatch_workgroups(workgroup_count.0, workgroup_count.1, 1);
+                }
+
+                encoder.copy_texture_to_buffer(
+                    ImageCopyTexture {
+                        texture: &output_texture,
+                        mip_level: 0,
+                        origin: Origin3d::ZERO,
+                        aspect: TextureAspect::All,
+                    },
+                    ImageCopyBuffer {
+                        buffer: &output_buffer,
+                        layout: ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: Some(padded_bytes_per_row),
+                            rows_per_image: Some(size.height),
+                        },
+                    },
+                    size,
+                );
+
+                self.render_queue.submit(Some(encoder.finish()));
+
+                let buffer_slice = output_buffer.slice(..);
+                let (tx, rx) = crossbeam_channel::unbounded();
+
+                buffer_slice.map_async(MapMode::Read, move |result| {
+                    tx.send(result).expect("Failed to send map_async result");
+                });
+
+                self.render_device.poll(Maintain::Wait);
+
+                let data = match rx.recv().expect("Failed to receive map_async result") {
+                    Ok(_) => {
+                        let mapped = buffer_slice.get_mapped_range();
+                        let row_bytes = (output_pixel_bytes * size.width) as usize;
+
+                        let mut data = Vec::with_capacity(row_bytes * size.height as usize);
+                        for row in 0..size.height {
+                            let start = (row * padded_bytes_per_row) as usize;
+                            data.extend_from_slice(&mapped[start..start + row_bytes]);
+                        }
+                        data
+                    }
+                    Err(e) => panic!("Failed to map output buffer: {:?}", e),
+                };
+
+                output_buffer.unmap();
+
+                self.output_image = Some(Image::new(
+                    size,
+                    TextureDimension::D2,
+                    data,
+                    self.texture_format,
+                    RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+                ));
+                self.progress.finish();
+            } else {
+                self.output_image = None;
+            }
+        }
+    }
+);
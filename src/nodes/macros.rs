@@ -55,6 +55,50 @@ pub mod macros {
                     )*
                 }
     
+                // The field metadata keyed the way it's saved, for the node's serializable form
+                pub fn serializable_input_meta(&self) -> bevy::utils::HashMap<$crate::nodes::SerializableInputId, $crate::nodes::FieldMeta> {
+                    self.input_meta
+                        .iter()
+                        .map(|(id, meta)| ($crate::nodes::SerializableInputId(id.0.to_string(), id.1.to_string()), meta.clone()))
+                        .collect()
+                }
+
+                pub fn serializable_output_meta(&self) -> bevy::utils::HashMap<$crate::nodes::SerializableOutputId, $crate::nodes::FieldMeta> {
+                    self.output_meta
+                        .iter()
+                        .map(|(id, meta)| ($crate::nodes::SerializableOutputId(id.0.to_string(), id.1.to_string()), meta.clone()))
+                        .collect()
+                }
+
+                // Restores metadata saved by the above, ignoring anything saved for fields the node no longer declares
+                pub fn load_serializable_meta(
+                    &mut self,
+                    input_meta: &bevy::utils::HashMap<$crate::nodes::SerializableInputId, $crate::nodes::FieldMeta>,
+                    output_meta: &bevy::utils::HashMap<$crate::nodes::SerializableOutputId, $crate::nodes::FieldMeta>,
+                ) {
+                    $(
+                        let id = Self::$input_field;
+                        if let Some(meta) = input_meta.get(&$crate::nodes::SerializableInputId(id.0.to_string(), id.1.to_string())) {
+                            self.input_meta.insert(id, meta.clone());
+                        }
+                    )*
+                    $(
+                        let id = Self::$output_field;
+                        if let Some(meta) = output_meta.get(&$crate::nodes::SerializableOutputId(id.0.to_string(), id.1.to_string())) {
+                            self.output_meta.insert(id, meta.clone());
+                        }
+                    )*
+                }
+
+                // The declared fields with their default values, available without constructing a node
+                pub fn input_prototypes() -> Vec<($crate::nodes::InputId, $crate::nodes::Field)> {
+                    vec![$((Self::$input_field, $input_meta.storage),)*]
+                }
+
+                pub fn output_prototypes() -> Vec<($crate::nodes::OutputId, $crate::nodes::Field)> {
+                    vec![$((Self::$output_field, $output_meta.storage),)*]
+                }
+    
                 $(pub const $input_field: $crate::nodes::InputId = $crate::nodes::InputId(stringify!($node_name), stringify!($input_field));)*
                 $(pub const $output_field: $crate::nodes::OutputId = $crate::nodes::OutputId(stringify!($node_name), stringify!($output_field));)*
     
@@ -83,8 +127,26 @@ pub mod macros {
                     }
                 }
     
+                fn get_output_image(&self, id: $crate::nodes::OutputId) -> Option<&bevy::prelude::Image> {
+                    match id {
+                        $(Self::$output_field => $crate::nodes::fields::DisplayField::as_image(&self.$output_field),)*
+                        _ => None,
+                    }
+                }
+
+                fn get_output_color(&self, id: $crate::nodes::OutputId) -> Option<bevy::prelude::LinearRgba> {
+                    match id {
+                        $(Self::$output_field => $crate::nodes::fields::DisplayField::as_color(&self.$output_field),)*
+                        _ => None,
+                    }
+                }
+
                 fn set_input(&mut self, id: $crate::nodes::InputId, value: $crate::nodes::Field) -> Result<(), String> {
                     let converted_value = self.convert_input(id, value)?;
+                    let converted_value = match self.input_meta.get(&id) {
+                        Some(meta) => meta.clamp(converted_value),
+                        None => converted_value,
+                    };
                     declare_node!(@optional_set_input, self, id, &converted_value, $($($set_input_args)*)?);
                     match id {
                         $(Self::$input_field => {
@@ -216,6 +278,18 @@ pub mod macros {
                     }
                 }
 
+                fn get_output_image(&self, id: OutputId) -> Option<&Image> {
+                    match self {
+                        $($enum_name::$variant(n) => n.get_output_image(id),)*
+                    }
+                }
+
+                fn get_output_color(&self, id: OutputId) -> Option<LinearRgba> {
+                    match self {
+                        $($enum_name::$variant(n) => n.get_output_color(id),)*
+                    }
+                }
+
                 fn set_input(&mut self, id: InputId, value: Field) -> Result<(), String> {
                     match self {
                         $($enum_name::$variant(n) => n.set_input(id, value),)*
@@ -1,33 +1,41 @@
+use std::f32::consts::FRAC_PI_2;
+
 use crate::{
-    asset::{GeneratedMeshes, PortMaterial, NODE_TEXTURE_DISPLAY_DIMENSION, PORT_RADIUS},
+    asset::{
+        FontAssets, GeneratedMeshes, PortMaterial, NODE_TEXTURE_DISPLAY_DIMENSION, NODE_TITLE_BAR_SIZE, PORT_RADIUS, PORT_SNAP_COMPATIBLE,
+        PORT_SNAP_INCOMPATIBLE, PORT_SNAP_NONE,
+    },
     camera::MainCamera,
-    events::edge_events::{AddEdgeEvent, AddNodeEdge},
-    graph::DisjointPipelineGraph,
-    line_renderer::Line,
+    events::{
+        edge_events::{AddEdgeEvent, AddNodeEdge},
+        node_events::ConnectOnSpawn,
+    },
+    graph::{DisjointPipelineGraph, Edge},
+    line_renderer::{generate_edge_line, GraphOrientation, Line, EDGE_LINE_SEGMENTS},
+    preferences::UiPreferences,
     ui::{
-        context_menu::{InputPortContext, OutputPortContext, UIContext},
+        context_menu::{InputPortContext, OutputPortContext, RequestOpenPortDropMenu, UIContext},
         Spawner,
     },
     ApplicationState,
 };
 
 use super::{
-    fields::{Field},
-    GraphNode, InputId, NodeDisplay, NodeTrait, OutputId, Selected,
+    drag_threshold::{apply_drag_threshold, ThresholdDragEnd, ThresholdDragStart},
+    fields::{can_convert_field, field_color, Field},
+    input_label, GraphNode, InputId, NodeDisplay, NodeTrait, OutputId, Selected,
 };
 use bevy::{
     color::palettes::{
-        css::{GREEN, ORANGE, PINK, TEAL, YELLOW},
-        tailwind::{GRAY_400, GREEN_400, RED_700},
+        css::{GREEN, RED},
     }, prelude::*, scene::ron::de, sprite::{Anchor, MaterialMesh2dBundle}, ui::Direction as UIDirection, utils::{HashMap, HashSet}, window::PrimaryWindow
 };
 use bevy_mod_picking::{
-    events::{DragEnd, DragStart, Pointer},
     focus::PickingInteraction,
     prelude::{Pickable, PointerButton},
     PickableBundle,
 };
-use petgraph::Direction;
+use petgraph::{stable_graph::StableDiGraph, visit::EdgeRef, Direction};
 
 pub struct PortPlugin;
 impl Plugin for PortPlugin {
@@ -37,8 +45,11 @@ impl Plugin for PortPlugin {
             (
                 handle_port_hover,
                 handle_port_selection,
+                connect_dropped_node_to_nearby_ports,
                 update_port_label_visibility,
+                relayout_ports,
             )
+                .after(apply_drag_threshold)
                 .run_if(in_state(ApplicationState::MainLoop)),
         );
 
@@ -47,6 +58,9 @@ impl Plugin for PortPlugin {
             position: Vec2::ZERO,
             line: Entity::PLACEHOLDER,
             direction: Direction::Incoming,
+            field: Field::Image(None),
+            snapped_port: None,
+            mismatch_label: Entity::PLACEHOLDER,
         });
 
         app.insert_resource(PortMaterialIndex(HashMap::new()));
@@ -99,10 +113,11 @@ impl InputPort {
         let meta = node.kind.get_input_meta(input_id).unwrap();
 
         let desired_material = PortMaterial {
-            port_color: port_color(&field),
+            port_color: field_color(&field),
             outline_color: Color::WHITE.into(),
             outline_thickness: 0.05,
             is_hovered: 0.,
+            snap_state: PORT_SNAP_NONE,
         };
 
         let port_material = if port_material_index.contains_key(&desired_material) {
@@ -113,7 +128,7 @@ impl InputPort {
             handle
         };
 
-        let label_text = format_label_text(input_id.1);
+        let label_text = input_label(&node.kind, input_id);
 
         let port_entity = spawner
             .spawn_bundle((
@@ -180,10 +195,11 @@ impl OutputPort {
 
 
         let desired_material = PortMaterial {
-            port_color: port_color(&field),
+            port_color: field_color(&field),
             outline_color: Color::WHITE.into(),
             outline_thickness: 0.05,
             is_hovered: 0.,
+            snap_state: PORT_SNAP_NONE,
         };
 
         let port_material = if port_material_index.contains_key(&desired_material) {
@@ -277,16 +293,164 @@ fn update_port_label_visibility(
     }
 }
 
-#[derive(Resource, Clone, Copy)]
+#[derive(Resource, Clone)]
 pub struct SelectingPort {
     pub port: Entity,
     pub position: Vec2,
     pub line: Entity,
     pub direction: Direction,
+    // the value at the port the drag started from, to check what it could connect to
+    pub field: Field,
+    // the one port the drag is snapped to, which is where it connects on release
+    pub snapped_port: Option<Entity>,
+    // text by the cursor explaining why the snapped port won't accept the connection
+    pub mismatch_label: Entity,
 }
 
+const MISMATCH_LABEL_OFFSET: Vec2 = Vec2::new(12., 12.);
+
 #[derive(Component)]
-pub struct SnappedPort;
+pub struct SnappedPort {
+    pub compatible: bool,
+}
+
+const PORT_SNAP_THRESHOLD: f32 = 25.0;
+
+// Holding Alt while dropping a node wires its free ports to dangling ports on nearby nodes
+fn connect_dropped_node_to_nearby_ports(
+    mut commands: Commands,
+    mut drag_end_events: EventReader<ThresholdDragEnd>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    q_nodes: Query<&NodeDisplay>,
+    q_input_port: Query<(&GlobalTransform, &InputPort, &InheritedVisibility)>,
+    q_output_port: Query<(&GlobalTransform, &OutputPort, &InheritedVisibility)>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+) {
+    let alt_pressed =
+        keyboard_input.pressed(KeyCode::AltLeft) || keyboard_input.pressed(KeyCode::AltRight);
+
+    let graph = &q_pipeline.single().graph;
+
+    for event in drag_end_events.read() {
+        if event.button != PointerButton::Primary || !alt_pressed {
+            continue;
+        }
+
+        let dropped_node = event.target;
+        if q_nodes.get(dropped_node).is_err() {
+            continue;
+        }
+
+        let input_field = |input_port: &InputPort| {
+            let node = graph.node_weight(q_nodes.get(input_port.node_entity).ok()?.index)?;
+            node.kind.get_input(input_port.input_id)
+        };
+
+        let output_field = |output_port: &OutputPort| {
+            let node = graph.node_weight(q_nodes.get(output_port.node_entity).ok()?.index)?;
+            node.kind.get_output(output_port.output_id)
+        };
+
+        let is_input_connected = |input_port: &InputPort| {
+            graph.edge_weights().any(|edge| {
+                edge.to_node == input_port.node_entity && edge.to_field == input_port.input_id
+            })
+        };
+
+        let is_output_dangling = |output_port: &OutputPort| {
+            !graph.edge_weights().any(|edge| {
+                edge.from_node == output_port.node_entity && edge.from_field == output_port.output_id
+            })
+        };
+
+        let can_connect = |output_port: &OutputPort, input_port: &InputPort| {
+            match (output_field(output_port), input_field(input_port)) {
+                (Some(from), Some(to)) => can_convert_field(&from, &to),
+                _ => false,
+            }
+        };
+
+        let mut connections: Vec<AddNodeEdge> = vec![];
+
+        // free inputs on the dropped node take the nearest dangling output
+        for (input_transform, input_port, input_visibility) in q_input_port.iter() {
+            if input_port.node_entity != dropped_node
+                || !input_visibility.get()
+                || is_input_connected(input_port)
+            {
+                continue;
+            }
+
+            let input_position = input_transform.translation().truncate();
+
+            let nearest_output = q_output_port
+                .iter()
+                .filter(|(_, output_port, output_visibility)| {
+                    output_port.node_entity != dropped_node
+                        && output_visibility.get()
+                        && is_output_dangling(output_port)
+                        && can_connect(output_port, input_port)
+                })
+                .map(|(output_transform, output_port, _)| {
+                    (output_transform.translation().truncate().distance(input_position), output_port)
+                })
+                .filter(|(distance, _)| *distance < PORT_SNAP_THRESHOLD)
+                .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+            if let Some((_, output_port)) = nearest_output {
+                connections.push(AddNodeEdge {
+                    start_node: output_port.node_entity,
+                    start_id: output_port.output_id,
+                    end_node: input_port.node_entity,
+                    end_id: input_port.input_id,
+                });
+            }
+        }
+
+        // dangling outputs on the dropped node feed the nearest free input
+        for (output_transform, output_port, output_visibility) in q_output_port.iter() {
+            if output_port.node_entity != dropped_node
+                || !output_visibility.get()
+                || !is_output_dangling(output_port)
+            {
+                continue;
+            }
+
+            let output_position = output_transform.translation().truncate();
+
+            let nearest_input = q_input_port
+                .iter()
+                .filter(|(_, input_port, input_visibility)| {
+                    input_port.node_entity != dropped_node
+                        && input_visibility.get()
+                        && !is_input_connected(input_port)
+                        && can_connect(output_port, input_port)
+                        // an input only takes one edge, so one claimed earlier in this drop is off limits
+                        && !connections.iter().any(|connection| {
+                            connection.end_node == input_port.node_entity && connection.end_id == input_port.input_id
+                        })
+                })
+                .map(|(input_transform, input_port, _)| {
+                    (input_transform.translation().truncate().distance(output_position), input_port)
+                })
+                .filter(|(distance, _)| *distance < PORT_SNAP_THRESHOLD)
+                .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+            if let Some((_, input_port)) = nearest_input {
+                connections.push(AddNodeEdge {
+                    start_node: output_port.node_entity,
+                    start_id: output_port.output_id,
+                    end_node: input_port.node_entity,
+                    end_id: input_port.input_id,
+                });
+            }
+        }
+
+        for connection in connections {
+            commands.trigger(AddEdgeEvent::FromNodes(connection));
+        }
+    }
+}
 
 pub fn handle_port_selection(
     mut commands: Commands,
@@ -297,10 +461,13 @@ pub fn handle_port_selection(
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     mut selecting_port: ResMut<SelectingPort>,
     window: Query<&Window, With<PrimaryWindow>>,
-    mut drag_start_events: EventReader<Pointer<DragStart>>,
-    mut drag_end_events: EventReader<Pointer<DragEnd>>,
+    mut drag_start_events: EventReader<ThresholdDragStart>,
+    mut drag_end_events: EventReader<ThresholdDragEnd>,
     q_pipeline: Query<&DisjointPipelineGraph>,
     q_snapped_ports: Query<Entity, With<SnappedPort>>,
+    mut q_mismatch_label: Query<(&mut Text, &mut Transform, &mut Visibility)>,
+    ui_preferences: Res<UiPreferences>,
+    fonts: Res<FontAssets>,
 ) {
     let (camera, camera_transform) = camera_query.single();
     let window = window.single();
@@ -345,30 +512,47 @@ pub fn handle_port_selection(
             .spawn((
                 Line {
                     points: vec![port_position, port_position],
-                    colors: vec![port_color(&field), port_color(&field)],
+                    colors: vec![field_color(&field), field_color(&field)],
                     thickness: 2.0,
+                    dim: 1.,
                 },
                 Transform::from_xyz(0., 0., -999.),
                 Pickable::IGNORE,
             ))
             .id();
 
+        let mismatch_label = commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: fonts.deja_vu_sans.clone(),
+                        font_size: 16.,
+                        color: RED.into(),
+                    },
+                ),
+                text_anchor: Anchor::BottomLeft,
+                visibility: Visibility::Hidden,
+                ..default()
+            })
+            .insert(Pickable::IGNORE)
+            .id();
+
         *selecting_port = SelectingPort {
             port: port_entity,
             position: port_position,
             line: line_entity,
             direction,
+            field,
+            snapped_port: None,
+            mismatch_label,
         };
     }
 
     // Update line position during drag
     if selecting_port.port != Entity::PLACEHOLDER {
-        let SelectingPort {
-            position: start_position,
-            line,
-            port,
-            ..
-        } = *selecting_port;
+        let start_position = selecting_port.position;
+        let line = selecting_port.line;
 
         if let Some(cursor_position) = window.cursor_position() {
             if let Some(cursor_world_position) =
@@ -376,16 +560,19 @@ pub fn handle_port_selection(
             {
                 let cursor_world_position = cursor_world_position.origin.truncate();
                 if let Ok((_, mut line)) = line_query.get_mut(line) {
-                    let snap_threshold = 25.0;
                     let mut closest_distance = f32::MAX;
                     let mut closest_entity = Entity::PLACEHOLDER;
                     let mut closest_position = cursor_world_position;
 
                     // Check for snapping to input ports
                     for (port_entity, transform, _, _) in q_input_port.iter() {
+                        if port_entity == selecting_port.port {
+                            continue;
+                        }
+
                         let port_position = transform.translation().truncate();
                         let distance = port_position.distance(cursor_world_position);
-                        if distance < snap_threshold && distance < closest_distance {
+                        if distance < PORT_SNAP_THRESHOLD && distance < closest_distance {
                             closest_distance = distance;
                             closest_entity = port_entity;
                             closest_position = port_position;
@@ -394,25 +581,68 @@ pub fn handle_port_selection(
 
                     // Check for snapping to output ports
                     for (port_entity, transform, _, _) in q_output_port.iter() {
+                        if port_entity == selecting_port.port {
+                            continue;
+                        }
+
                         let port_position = transform.translation().truncate();
                         let distance = port_position.distance(cursor_world_position);
-                        if distance < snap_threshold && distance < closest_distance {
+                        if distance < PORT_SNAP_THRESHOLD && distance < closest_distance {
                             closest_distance = distance;
                             closest_entity = port_entity;
                             closest_position = port_position;
                         }
                     }
 
-                    line.points = vec![start_position, closest_position];
+                    // edges always run from output to input, even when dragged from the input end
+                    let (from, to) = match selecting_port.direction {
+                        Direction::Incoming => (start_position, closest_position),
+                        Direction::Outgoing => (closest_position, start_position),
+                    };
+                    line.points = generate_edge_line(ui_preferences.edge_style, ui_preferences.orientation, from, to, EDGE_LINE_SEGMENTS);
+                    line.colors = vec![line.colors[0]; line.points.len()];
+
+                    let closest_port = (closest_entity != Entity::PLACEHOLDER).then_some(closest_entity);
+
+                    if selecting_port.snapped_port != closest_port {
+                        selecting_port.snapped_port = closest_port;
+
+                        // Remove SnappedPort component from all previously snapped ports
+                        q_snapped_ports.iter().for_each(|snapped_port_entity| {
+                            commands.entity(snapped_port_entity).remove::<SnappedPort>();
+                        });
+
+                        let mut mismatch = None;
+
+                        // Add SnappedPort component to the closest entity if one was found
+                        if closest_entity != Entity::PLACEHOLDER {
+                            mismatch = connection_mismatch(
+                                graph,
+                                &q_nodes,
+                                &q_input_port,
+                                &q_output_port,
+                                selecting_port.direction,
+                                &selecting_port.field,
+                                closest_entity,
+                            );
+
+                            commands.entity(closest_entity).insert(SnappedPort {
+                                compatible: mismatch.is_none(),
+                            });
+                        }
 
-                    // Remove SnappedPort component from all previously snapped ports
-                    q_snapped_ports.iter().for_each(|snapped_port_entity| {
-                        commands.entity(snapped_port_entity).remove::<SnappedPort>();
-                    });
+                        if let Ok((mut text, _, mut visibility)) = q_mismatch_label.get_mut(selecting_port.mismatch_label) {
+                            *visibility = if mismatch.is_some() {
+                                Visibility::Visible
+                            } else {
+                                Visibility::Hidden
+                            };
+                            text.sections[0].value = mismatch.unwrap_or_default();
+                        }
+                    }
 
-                    // Add SnappedPort component to the closest entity if one was found
-                    if closest_entity != Entity::PLACEHOLDER {
-                        commands.entity(closest_entity).insert(SnappedPort);
+                    if let Ok((_, mut transform, _)) = q_mismatch_label.get_mut(selecting_port.mismatch_label) {
+                        transform.translation = (cursor_world_position + MISMATCH_LABEL_OFFSET).extend(900.);
                     }
                 }
             }
@@ -431,21 +661,52 @@ pub fn handle_port_selection(
         }
 
         if selecting_port.port != Entity::PLACEHOLDER {
-            let SelectingPort {
-                port: start_port,
-                line,
-                direction,
-                ..
-            } = *selecting_port;
+            let start_port = selecting_port.port;
+            let start_position = selecting_port.position;
+            let line = selecting_port.line;
+            let direction = selecting_port.direction;
 
             q_snapped_ports.iter().for_each(|snapped_port_entity| {
                 commands.entity(snapped_port_entity).remove::<SnappedPort>();
             });
 
             commands.entity(line).despawn_recursive();
+            commands.entity(selecting_port.mismatch_label).despawn_recursive();
             selecting_port.port = Entity::PLACEHOLDER;
 
-            let maybe_snapped_port = q_snapped_ports.iter().last();
+            let maybe_snapped_port = selecting_port.snapped_port.take();
+
+            if maybe_snapped_port.is_none() {
+                let dropped_away_from_port = window
+                    .cursor_position()
+                    .and_then(|cursor_position| camera.viewport_to_world(camera_transform, cursor_position))
+                    .map_or(false, |ray| ray.origin.truncate().distance(start_position) > PORT_SNAP_THRESHOLD);
+
+                if dropped_away_from_port {
+                    let maybe_connect = match direction {
+                        Direction::Incoming => q_output_port.get(start_port).ok().and_then(|(_, _, output_port, _)| {
+                            let node = graph.node_weight(q_nodes.get(output_port.node_entity).ok()?.index)?;
+                            Some(ConnectOnSpawn::FromOutput {
+                                node: output_port.node_entity,
+                                output_id: output_port.output_id,
+                                field: node.kind.get_output(output_port.output_id)?,
+                            })
+                        }),
+                        Direction::Outgoing => q_input_port.get(start_port).ok().and_then(|(_, _, input_port, _)| {
+                            let node = graph.node_weight(q_nodes.get(input_port.node_entity).ok()?.index)?;
+                            Some(ConnectOnSpawn::ToInput {
+                                node: input_port.node_entity,
+                                input_id: input_port.input_id,
+                                field: node.kind.get_input(input_port.input_id)?,
+                            })
+                        }),
+                    };
+
+                    if let Some(connect) = maybe_connect {
+                        commands.trigger(RequestOpenPortDropMenu { connect });
+                    }
+                }
+            }
 
             match direction {
                 Direction::Incoming => {
@@ -481,15 +742,52 @@ pub fn handle_port_selection(
     }
 }
 
+// Why an edge dragged from a port holding dragged_field can't connect to target_port, or None if it can
+fn connection_mismatch(
+    graph: &StableDiGraph<GraphNode, Edge>,
+    q_nodes: &Query<&NodeDisplay>,
+    q_input_port: &Query<(Entity, &GlobalTransform, &InputPort, &PickingInteraction)>,
+    q_output_port: &Query<(Entity, &GlobalTransform, &OutputPort, &PickingInteraction)>,
+    direction: Direction,
+    dragged_field: &Field,
+    target_port: Entity,
+) -> Option<String> {
+    let target_field = match direction {
+        Direction::Incoming => q_input_port.get(target_port).ok().and_then(|(_, _, input, _)| {
+            let node = graph.node_weight(q_nodes.get(input.node_entity).ok()?.index)?;
+            node.kind.get_input(input.input_id)
+        }),
+        Direction::Outgoing => q_output_port.get(target_port).ok().and_then(|(_, _, output, _)| {
+            let node = graph.node_weight(q_nodes.get(output.node_entity).ok()?.index)?;
+            node.kind.get_output(output.output_id)
+        }),
+    };
+
+    let Some(target_field) = target_field else {
+        return Some(String::from(match direction {
+            Direction::Incoming => "Outputs connect to inputs",
+            Direction::Outgoing => "Inputs connect to outputs",
+        }));
+    };
+
+    // edges always run from output to input, even when dragged from the input end
+    let (from, to) = match direction {
+        Direction::Incoming => (dragged_field, &target_field),
+        Direction::Outgoing => (&target_field, dragged_field),
+    };
+
+    (!can_convert_field(from, to)).then(|| format!("{} → {} not allowed", from.type_name(), to.type_name()))
+}
+
 fn handle_port_hover(
     mut port_materials: ResMut<Assets<PortMaterial>>,
-    mut interaction_query: Query<(Entity, &PickingInteraction, &mut Handle<PortMaterial>)>,
+    mut interaction_query: Query<(Entity, &PickingInteraction, &mut Handle<PortMaterial>, Option<&SnappedPort>)>,
     mut port_material_index: ResMut<PortMaterialIndex>,
-    q_snapped_ports: Query<Entity, With<SnappedPort>>,
+    selecting_port: Res<SelectingPort>,
 ) {
-    let maybe_snapped_port = q_snapped_ports.iter().last();
+    let maybe_snapped_port = selecting_port.snapped_port;
 
-    for (port_entity, interaction, mut material_handle) in interaction_query.iter_mut() {
+    for (port_entity, interaction, mut material_handle, snapped) in interaction_query.iter_mut() {
         if let Some(material) = port_materials.get_mut(material_handle.id()) {
             let desired_hover = match maybe_snapped_port {
                 Some(snapped_port) => {
@@ -512,8 +810,15 @@ fn handle_port_hover(
                 },
             };
 
+            let snap_state = match snapped {
+                Some(SnappedPort { compatible: true }) => PORT_SNAP_COMPATIBLE,
+                Some(SnappedPort { compatible: false }) => PORT_SNAP_INCOMPATIBLE,
+                None => PORT_SNAP_NONE,
+            };
+
             let desired_material = PortMaterial {
                 is_hovered: desired_hover,
+                snap_state,
                 ..material.clone()
             };
 
@@ -530,27 +835,123 @@ fn handle_port_hover(
     }
 }
 
+// Orders ports along their side of the node by where the nodes they connect to sit, so edges leave in the same
+// order their ends are stacked and don't cross. Unconnected ports keep their declared order after the connected ones.
+fn order_ports_by_connection<T: Copy>(ports: &mut [T], connected_position: impl Fn(T) -> Option<f32>) {
+    ports.sort_by(|&a, &b| match (connected_position(a), connected_position(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+// How far along the port side a connected node sits, for the average over a port's edges.
+// Ports run top to bottom on horizontal graphs and left to right on vertical ones.
+fn average_position(orientation: GraphOrientation, positions: impl Iterator<Item = Vec3>) -> Option<f32> {
+    let (sum, count) = positions
+        .map(|position| match orientation {
+            GraphOrientation::Horizontal => -position.y,
+            GraphOrientation::Vertical => position.x,
+        })
+        .fold((0., 0), |(sum, count), position| (sum + position, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+// Space between the top of a horizontal node and its first port, which keeps ports clear of the title bar
+const PORT_GROUP_MARGIN: f32 = 36.;
+
+// Where the index-th visible port sits relative to its node, on the input or output side
+fn port_translation(orientation: GraphOrientation, direction: Direction, index: usize) -> Vec3 {
+    let side = match direction {
+        Direction::Incoming => -1.,
+        Direction::Outgoing => 1.,
+    };
+    let along = index as f32 * PORT_RADIUS * 3.;
+
+    match orientation {
+        GraphOrientation::Horizontal => Vec3::new(
+            side * NODE_TEXTURE_DISPLAY_DIMENSION / 2.,
+            (NODE_TEXTURE_DISPLAY_DIMENSION / 2.) - PORT_GROUP_MARGIN - along,
+            0.5,
+        ),
+        GraphOrientation::Vertical => Vec3::new(
+            -(NODE_TEXTURE_DISPLAY_DIMENSION / 2.) + PORT_RADIUS * 2. + along,
+            -side * (NODE_TEXTURE_DISPLAY_DIMENSION + NODE_TITLE_BAR_SIZE) / 2.,
+            0.5,
+        ),
+    }
+}
+
+// Labels sit outside the node, turned to run along the edges on vertical graphs so neighbors don't overlap
+fn port_label_placement(orientation: GraphOrientation, direction: Direction) -> (Transform, Anchor) {
+    let (offset, anchor, rotation) = match (orientation, direction) {
+        (GraphOrientation::Horizontal, Direction::Incoming) => (Vec2::new(-PORT_RADIUS * 1.5, 0.), Anchor::CenterRight, 0.),
+        (GraphOrientation::Horizontal, Direction::Outgoing) => (Vec2::new(PORT_RADIUS * 1.5, 0.), Anchor::CenterLeft, 0.),
+        (GraphOrientation::Vertical, Direction::Incoming) => (Vec2::new(0., PORT_RADIUS * 1.5), Anchor::CenterLeft, FRAC_PI_2),
+        (GraphOrientation::Vertical, Direction::Outgoing) => (Vec2::new(0., -PORT_RADIUS * 1.5), Anchor::CenterRight, FRAC_PI_2),
+    };
+
+    (
+        Transform::from_translation(offset.extend(0.5)).with_rotation(Quat::from_rotation_z(rotation)),
+        anchor,
+    )
+}
+
+fn place_port_labels(
+    children: Option<&Children>,
+    q_port_labels: &mut Query<(&mut Transform, &mut Anchor), (With<PortLabel>, Without<InputPort>, Without<OutputPort>)>,
+    orientation: GraphOrientation,
+    direction: Direction,
+) {
+    let (label_transform, label_anchor) = port_label_placement(orientation, direction);
+
+    for &child in children.into_iter().flatten() {
+        if let Ok((mut transform, mut anchor)) = q_port_labels.get_mut(child) {
+            *transform = label_transform;
+            *anchor = label_anchor;
+        }
+    }
+}
+
 pub fn reposition_input_ports(
     trigger: Trigger<RequestInputPortRelayout>,
     q_nodes: Query<&NodeDisplay>,
-    mut q_input_port: Query<(&mut Transform, &mut Visibility, &InputPort)>,
+    q_node_transforms: Query<&Transform, (With<NodeDisplay>, Without<InputPort>, Without<PortLabel>)>,
+    mut q_input_port: Query<(&mut Transform, &mut Visibility, &InputPort, Option<&Children>)>,
+    mut q_port_labels: Query<(&mut Transform, &mut Anchor), (With<PortLabel>, Without<InputPort>, Without<OutputPort>)>,
     pipeline_query: Query<&DisjointPipelineGraph>,
+    ui_preferences: Res<UiPreferences>,
 ) {
     let pipeline = pipeline_query.single();
     let input_node_index = q_nodes.get(trigger.event().node_entity).unwrap().index;
+    let orientation = ui_preferences.orientation;
 
     if let Some(node) = pipeline.graph.node_weight(input_node_index) {
-        let port_group_vertical_margin = 36.;
-        let visible_inputs: Vec<_> = node
+        let mut visible_inputs: Vec<_> = node
             .kind
             .input_fields()
             .iter()
             .filter(|&&id| node.kind.get_input_meta(id).unwrap().visible)
             .collect();
 
-        for (mut transform, mut visibility, port) in q_input_port
+        if ui_preferences.arrange_ports_by_connection {
+            order_ports_by_connection(&mut visible_inputs, |&input_id| {
+                average_position(
+                    orientation,
+                    pipeline
+                        .graph
+                        .edges_directed(input_node_index, Direction::Incoming)
+                        .filter(|edge| edge.weight().to_field == input_id)
+                        .filter_map(|edge| q_node_transforms.get(edge.weight().from_node).ok())
+                        .map(|transform| transform.translation),
+                )
+            });
+        }
+
+        for (mut transform, mut visibility, port, children) in q_input_port
             .iter_mut()
-            .filter(|(_, _, p)| p.node_entity == trigger.event().node_entity)
+            .filter(|(_, _, p, _)| p.node_entity == trigger.event().node_entity)
         {
             let meta = node.kind.get_input_meta(port.input_id).unwrap();
             if meta.visible {
@@ -558,13 +959,8 @@ pub fn reposition_input_ports(
                     .iter()
                     .position(|&&id| id == port.input_id)
                     .unwrap_or(0);
-                transform.translation = Vec3::new(
-                    -NODE_TEXTURE_DISPLAY_DIMENSION / 2.,
-                    (NODE_TEXTURE_DISPLAY_DIMENSION / 2.)
-                        - port_group_vertical_margin
-                        - (index as f32 * PORT_RADIUS * 3.),
-                    0.5,
-                );
+                transform.translation = port_translation(orientation, Direction::Incoming, index);
+                place_port_labels(children, &mut q_port_labels, orientation, Direction::Incoming);
                 *visibility = Visibility::Inherited;
             } else {
                 *visibility = Visibility::Hidden;
@@ -576,24 +972,41 @@ pub fn reposition_input_ports(
 pub fn reposition_output_ports(
     trigger: Trigger<RequestOutputPortRelayout>,
     q_nodes: Query<&NodeDisplay>,
-    mut q_output_port_mut: Query<(&mut Transform, &mut Visibility, &OutputPort)>,
+    q_node_transforms: Query<&Transform, (With<NodeDisplay>, Without<OutputPort>, Without<PortLabel>)>,
+    mut q_output_port_mut: Query<(&mut Transform, &mut Visibility, &OutputPort, Option<&Children>)>,
+    mut q_port_labels: Query<(&mut Transform, &mut Anchor), (With<PortLabel>, Without<InputPort>, Without<OutputPort>)>,
     pipeline_query: Query<&DisjointPipelineGraph>,
+    ui_preferences: Res<UiPreferences>,
 ) {
     let pipeline = pipeline_query.single();
     let output_node_index = q_nodes.get(trigger.event().node_entity).unwrap().index;
+    let orientation = ui_preferences.orientation;
 
     if let Some(node) = pipeline.graph.node_weight(output_node_index) {
-        let port_group_vertical_margin = 36.;
-        let visible_outputs: Vec<_> = node
+        let mut visible_outputs: Vec<_> = node
             .kind
             .output_fields()
             .iter()
             .filter(|&&id| node.kind.get_output_meta(id).unwrap().visible)
             .collect();
 
-        for (mut transform, mut visibility, port) in q_output_port_mut
+        if ui_preferences.arrange_ports_by_connection {
+            order_ports_by_connection(&mut visible_outputs, |&output_id| {
+                average_position(
+                    orientation,
+                    pipeline
+                        .graph
+                        .edges_directed(output_node_index, Direction::Outgoing)
+                        .filter(|edge| edge.weight().from_field == output_id)
+                        .filter_map(|edge| q_node_transforms.get(edge.weight().to_node).ok())
+                        .map(|transform| transform.translation),
+                )
+            });
+        }
+
+        for (mut transform, mut visibility, port, children) in q_output_port_mut
             .iter_mut()
-            .filter(|(_, _, p)| p.node_entity == trigger.event().node_entity)
+            .filter(|(_, _, p, _)| p.node_entity == trigger.event().node_entity)
         {
             let meta = node.kind.get_output_meta(port.output_id).unwrap();
             if meta.visible {
@@ -601,13 +1014,8 @@ pub fn reposition_output_ports(
                     .iter()
                     .position(|&&id| id == port.output_id)
                     .unwrap_or(0);
-                transform.translation = Vec3::new(
-                    NODE_TEXTURE_DISPLAY_DIMENSION / 2.,
-                    (NODE_TEXTURE_DISPLAY_DIMENSION / 2.)
-                        - port_group_vertical_margin
-                        - (index as f32 * PORT_RADIUS * 3.),
-                    0.5,
-                );
+                transform.translation = port_translation(orientation, Direction::Outgoing, index);
+                place_port_labels(children, &mut q_port_labels, orientation, Direction::Outgoing);
                 *visibility = Visibility::Inherited;
             } else {
                 *visibility = Visibility::Hidden;
@@ -616,16 +1024,38 @@ pub fn reposition_output_ports(
     }
 }
 
-pub fn port_color(field: &Field) -> LinearRgba {
-    match field {
-        Field::U32(_) => PINK.into(),
-        Field::F32(_) => YELLOW.into(),
-        Field::Vec4(_) => ORANGE.into(),
-        Field::LinearRgba(_) => ORANGE.into(),
-        Field::Extent3d(_) => TEAL.into(),
-        Field::TextureFormat(_) => RED_700.into(),
-        Field::Image(_) => GRAY_400.into(),
-        Field::Shape(_) => GREEN_400.into(),
+// With ports arranged by connection, moving a node can change the best order for it and its neighbors.
+// Any preference change, like the orientation or the port order itself, lays every node out again.
+fn relayout_ports(
+    mut commands: Commands,
+    q_moved_nodes: Query<&NodeDisplay, Changed<Transform>>,
+    q_all_nodes: Query<Entity, With<NodeDisplay>>,
+    pipeline_query: Query<&DisjointPipelineGraph>,
+    ui_preferences: Res<UiPreferences>,
+) {
+    let mut to_relayout: HashSet<Entity> = HashSet::new();
+
+    if ui_preferences.is_changed() {
+        to_relayout.extend(q_all_nodes.iter());
+    } else if ui_preferences.arrange_ports_by_connection {
+        let Ok(pipeline) = pipeline_query.get_single() else {
+            return;
+        };
+
+        for node_display in q_moved_nodes.iter() {
+            for index in std::iter::once(node_display.index)
+                .chain(pipeline.graph.neighbors_undirected(node_display.index))
+            {
+                if let Some(node) = pipeline.graph.node_weight(index) {
+                    to_relayout.insert(node.kind.entity());
+                }
+            }
+        }
+    }
+
+    for node_entity in to_relayout {
+        commands.trigger(RequestInputPortRelayout { node_entity });
+        commands.trigger(RequestOutputPortRelayout { node_entity });
     }
 }
 
@@ -0,0 +1,39 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+// How far along a node's current process() is, from 0 to 1. Nodes run on the async compute pool,
+// so the fraction lives behind an atomic the main thread can read while the pass is still going.
+// Clones share the same counter, which is how the editor's graph sees progress made on its copy.
+#[derive(Clone, Debug)]
+pub struct NodeProgress(Arc<AtomicU32>);
+
+impl Default for NodeProgress {
+    // a node that isn't processing reads as done
+    fn default() -> Self {
+        Self(Arc::new(AtomicU32::new(1f32.to_bits())))
+    }
+}
+
+impl NodeProgress {
+    pub fn start(&self) {
+        self.set(0.);
+    }
+
+    pub fn set(&self, fraction: f32) {
+        self.0.store(fraction.clamp(0., 1.).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn finish(&self) {
+        self.set(1.);
+    }
+
+    pub fn fraction(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.fraction() < 1.
+    }
+}
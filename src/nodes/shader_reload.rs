@@ -0,0 +1,172 @@
+use bevy::{prelude::*, render::render_resource::TextureFormat};
+
+use crate::{
+    asset::ShaderAssets,
+    events::node_events::{kind_from_serializable, NodeResources},
+    graph::{DisjointPipelineGraph, RequestProcessPipeline},
+    ui::menu_bar::Project,
+    ApplicationState,
+};
+
+use super::{
+    node_kind_name,
+    shared::{shader_source, validate_wgsl, ShaderError},
+    GraphNode, GraphNodeKind, NodeTrait, SerializableGraphNodeKind,
+};
+
+// Rebuilds the pipelines of nodes whose WGSL changed on disk.
+// Only does anything when asset watching is on, e.g. with the `dev` feature.
+pub struct ShaderReloadPlugin;
+
+impl Plugin for ShaderReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            reload_modified_shaders.run_if(in_state(ApplicationState::MainLoop)),
+        );
+    }
+}
+
+fn node_shaders<'a>(kind: &GraphNodeKind, shader_handles: &'a ShaderAssets) -> Vec<&'a Handle<Shader>> {
+    match kind {
+        GraphNodeKind::Example(_) => vec![&shader_handles.default_frag, &shader_handles.default_vert],
+        GraphNodeKind::Color(_) => vec![],
+        GraphNodeKind::Shape(_) => vec![&shader_handles.shape],
+        GraphNodeKind::Blend(_) => vec![&shader_handles.blend],
+        GraphNodeKind::Dither(_) => vec![&shader_handles.dither],
+        GraphNodeKind::Image(_) => vec![],
+        GraphNodeKind::Levels(_) => vec![&shader_handles.levels],
+        GraphNodeKind::Curves(_) => vec![&shader_handles.curves],
+        GraphNodeKind::Number(_) => vec![],
+        GraphNodeKind::Math(_) => vec![],
+        GraphNodeKind::Clear(_) => vec![],
+        GraphNodeKind::Premultiply(_) => vec![&shader_handles.premultiply],
+        GraphNodeKind::Unpremultiply(_) => vec![&shader_handles.unpremultiply],
+        GraphNodeKind::Convolve(_) => vec![&shader_handles.convolve],
+        GraphNodeKind::Sharpen(_) => vec![&shader_handles.blur, &shader_handles.sharpen],
+        GraphNodeKind::Displace(_) => vec![&shader_handles.displace],
+        // runs its own source; the template only shapes new nodes and checks edits
+        GraphNodeKind::CustomCompute(_) => vec![],
+        GraphNodeKind::Probe(_) => vec![],
+    }
+}
+
+// Builds a fresh copy of a node's kind, recreating its pipelines from the current shaders in the given format.
+// A custom shader on the node takes the place of its built-in one.
+pub fn rebuild_node_kind(
+    node: &GraphNode,
+    node_resources: &NodeResources,
+    texture_format: TextureFormat,
+) -> Result<GraphNodeKind, ShaderError> {
+    let mut old_kind = node.kind.clone();
+    old_kind.store_all();
+
+    let mut new_kind = kind_from_serializable(
+        &SerializableGraphNodeKind::from(&old_kind),
+        &node_resources.render_device,
+        &node_resources.render_queue,
+        &node_resources.shaders,
+        &node_resources.shader_handles,
+        texture_format,
+        node.custom_shader.as_ref().map(|custom_shader| custom_shader.source.as_str()),
+    )?;
+
+    // carries over edge-driven inputs like images, which aren't serialized
+    for &input_id in old_kind.input_fields() {
+        if let Some(value) = old_kind.get_input(input_id) {
+            let _ = new_kind.set_input(input_id, value);
+        }
+    }
+
+    Ok(new_kind)
+}
+
+fn reload_modified_shaders(
+    mut shader_events: EventReader<AssetEvent<Shader>>,
+    mut q_pipeline: Query<&mut DisjointPipelineGraph>,
+    node_resources: NodeResources,
+    project: Res<Project>,
+    mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+) {
+    let modified: Vec<AssetId<Shader>> = shader_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    if modified.is_empty() {
+        return;
+    }
+
+    let Ok(mut pipeline) = q_pipeline.get_single_mut() else {
+        return;
+    };
+
+    let NodeResources { shaders, shader_handles, .. } = &node_resources;
+    let mut did_reload = false;
+
+    for id in modified {
+        let Some(handle) = [
+            &shader_handles.default_frag,
+            &shader_handles.default_vert,
+            &shader_handles.shape,
+            &shader_handles.blend,
+            &shader_handles.dither,
+            &shader_handles.levels,
+            &shader_handles.curves,
+            &shader_handles.premultiply,
+            &shader_handles.unpremultiply,
+            &shader_handles.convolve,
+            &shader_handles.blur,
+            &shader_handles.sharpen,
+            &shader_handles.displace,
+        ]
+        .into_iter()
+        .find(|handle| handle.id() == id) else {
+            continue;
+        };
+
+        // a broken shader would panic on the device, so keep the old pipelines until it's fixed
+        let source = match shader_source(shaders, handle) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("Not reloading {:?}: {}", handle.path(), e);
+                continue;
+            }
+        };
+        if let Err(e) = validate_wgsl(&source) {
+            eprintln!("Not reloading {:?}, it failed to compile:\n{}", handle.path(), e);
+            continue;
+        }
+
+        let affected_nodes: Vec<_> = pipeline
+            .graph
+            .node_indices()
+            .filter(|index| {
+                node_shaders(&pipeline.graph[*index].kind, shader_handles).contains(&handle)
+            })
+            .collect();
+
+        for index in affected_nodes {
+            let node = &mut pipeline.graph[index];
+            match rebuild_node_kind(node, &node_resources, project.texture_format()) {
+                Ok(kind) => node.kind = kind,
+                Err(e) => {
+                    eprintln!("Not reloading {}: {}", node_kind_name(&node.kind), e);
+                    continue;
+                }
+            }
+            // the new shader has to run even though the inputs haven't changed
+            node.processed_inputs = None;
+            did_reload = true;
+        }
+
+        println!("Reloaded {:?}", handle.path());
+    }
+
+    if did_reload {
+        ev_process_pipeline.send(RequestProcessPipeline);
+    }
+}
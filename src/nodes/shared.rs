@@ -1,4 +1,12 @@
-use bevy::{prelude::*, render::render_resource::Source};
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, Source, TextureDimension, TextureFormat},
+    },
+};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -9,10 +17,135 @@ pub struct Vertex {
 
 pub const U32_SIZE: u32 = std::mem::size_of::<u32>() as u32;
 
-pub fn shader_source(shaders: &Res<Assets<Shader>>, shader: &Handle<Shader>) -> String {
-    let shader = shaders.get(shader).unwrap();
-    match &shader.source {
-        Source::Wgsl(src) => src.to_string(),
-        _ => panic!("Only WGSL supported"),
+// Compute shaders with tunable parameters read them from one uniform of this many floats, bound right after
+// their textures as `var<uniform> params: array<vec4<f32>, 2>`, so param N is `params[N / 4][N % 4]`.
+// A comment line like `// @param 2 Strength` names param 2 wherever it's shown.
+pub const SHADER_PARAM_COUNT: usize = 8;
+
+pub fn shader_param_labels(shader_source: &str) -> [Option<String>; SHADER_PARAM_COUNT] {
+    let mut labels: [Option<String>; SHADER_PARAM_COUNT] = Default::default();
+
+    for line in shader_source.lines() {
+        let Some(rest) = line.trim().strip_prefix("//").map(str::trim) else {
+            continue;
+        };
+        let Some(rest) = rest.strip_prefix("@param") else {
+            continue;
+        };
+
+        let mut parts = rest.trim().splitn(2, char::is_whitespace);
+        let index = parts.next().and_then(|index| index.parse::<usize>().ok());
+        let label = parts.next().map(str::trim).filter(|label| !label.is_empty());
+
+        if let (Some(index), Some(label)) = (index, label) {
+            if index < SHADER_PARAM_COUNT {
+                labels[index] = Some(label.to_string());
+            }
+        }
+    }
+
+    labels
+}
+
+// Always milliseconds at two decimal places, so the readout doesn't jump between units
+pub fn format_process_time(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.)
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    // the asset isn't in Assets<Shader>, e.g. asked for before loading finished or while it's being reloaded
+    NotLoaded(String),
+    NotWgsl(String),
+}
+
+impl std::fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderError::NotLoaded(path) => write!(f, "shader {} isn't loaded", path),
+            ShaderError::NotWgsl(path) => write!(f, "shader {} isn't WGSL, which is all nodes support", path),
+        }
+    }
+}
+
+pub fn shader_source(shaders: &Res<Assets<Shader>>, shader: &Handle<Shader>) -> Result<String, ShaderError> {
+    let path = || shader.path().map(|path| path.to_string()).unwrap_or_else(|| format!("{:?}", shader.id()));
+
+    let Some(loaded) = shaders.get(shader) else {
+        return Err(ShaderError::NotLoaded(path()));
+    };
+
+    match &loaded.source {
+        Source::Wgsl(src) => Ok(src.to_string()),
+        _ => Err(ShaderError::NotWgsl(path())),
     }
 }
+
+// Size of one pixel in bytes, e.g. 4 for Rgba8Unorm and 8 for Rgba16Float
+pub fn bytes_per_pixel(format: TextureFormat) -> u32 {
+    format.block_copy_size(None).unwrap_or(4)
+}
+
+// The compute shaders declare their storage textures as rgba8unorm. This swaps in the format the node actually writes.
+pub fn shader_for_format(shader_source: &str, texture_format: TextureFormat) -> String {
+    let format_name = match texture_format {
+        TextureFormat::Rgba16Float => "rgba16float",
+        _ => "rgba8unorm",
+    };
+
+    shader_source.replace("texture_storage_2d<rgba8unorm", &format!("texture_storage_2d<{}", format_name))
+}
+
+// Nearest-neighbor resample of an image so that neither side exceeds max_dimension.
+// Returns None when the image is already small enough.
+pub fn downsample_image(image: &Image, max_dimension: u32) -> Option<Image> {
+    let size = image.texture_descriptor.size;
+    let pixel_count = (size.width * size.height) as usize;
+
+    if (size.width <= max_dimension && size.height <= max_dimension) || pixel_count == 0 {
+        return None;
+    }
+
+    let bytes_per_pixel = image.data.len() / pixel_count;
+    let scale = max_dimension as f32 / size.width.max(size.height) as f32;
+    let new_width = ((size.width as f32 * scale).round() as u32).max(1);
+    let new_height = ((size.height as f32 * scale).round() as u32).max(1);
+
+    let mut data = Vec::with_capacity((new_width * new_height) as usize * bytes_per_pixel);
+    for y in 0..new_height {
+        let source_y = y * size.height / new_height;
+        for x in 0..new_width {
+            let source_x = x * size.width / new_width;
+            let start = (source_y * size.width + source_x) as usize * bytes_per_pixel;
+            data.extend_from_slice(&image.data[start..start + bytes_per_pixel]);
+        }
+    }
+
+    Some(Image::new(
+        Extent3d {
+            width: new_width,
+            height: new_height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        image.texture_descriptor.format,
+        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+    ))
+}
+
+// Parses and validates WGSL on the CPU, so a broken shader can be rejected before
+// it reaches the device, where a compile error would be fatal.
+pub fn validate_wgsl(source: &str) -> Result<(), String> {
+    let module = wgpu::naga::front::wgsl::parse_str(source)
+        .map_err(|e| e.emit_to_string(source))?;
+
+    wgpu::naga::valid::Validator::new(
+        wgpu::naga::valid::ValidationFlags::all(),
+        wgpu::naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|e| e.emit_to_string(source))?;
+
+    Ok(())
+}
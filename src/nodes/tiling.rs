@@ -0,0 +1,230 @@
+use bevy::{
+    prelude::*,
+    render::{render_asset::RenderAssetUsages, render_resource::*},
+};
+
+use crate::setup::{CustomGpuDevice, CustomGpuQueue};
+
+use super::{progress::NodeProgress, shared::bytes_per_pixel};
+
+// Images with a side longer than this go through tiled processing when tiling is enabled.
+// None processes every image whole, which is fastest when it fits in VRAM.
+pub const MAX_TILE_SIZE_CHOICES: [Option<u32>; 4] = [None, Some(1024), Some(2048), Some(4096)];
+
+// Whether an image is large enough that a node should tile it
+pub fn needs_tiling(size: Extent3d, max_tile_size: Option<u32>) -> Option<u32> {
+    max_tile_size.filter(|max| size.width > *max || size.height > *max)
+}
+
+// Runs a compute shader over the whole image in a single dispatch, for images too small to need tiling
+// and for nodes that sample neighbors or absolute coordinates and so can't be tiled. Same binding layout
+// as process_per_pixel_tiled.
+pub fn process_whole_image(
+    render_device: &CustomGpuDevice,
+    render_queue: &CustomGpuQueue,
+    compute_pipeline: &ComputePipeline,
+    bind_group_layout: &BindGroupLayout,
+    extra_entries: &[BindGroupEntry],
+    input: &Image,
+    output_format: TextureFormat,
+    workgroup_size: u32,
+    progress: &NodeProgress,
+) -> Image {
+    let size = input.texture_descriptor.size;
+
+    // a single tile covering the image never splits it, so any shader is correct here
+    process_per_pixel_tiled(
+        render_device,
+        render_queue,
+        compute_pipeline,
+        bind_group_layout,
+        extra_entries,
+        input,
+        output_format,
+        size.width.max(size.height),
+        workgroup_size,
+        progress,
+    )
+}
+
+// Runs a per-pixel compute shader over an image one tile at a time, so the GPU only ever
+// holds a tile of input and output. Only correct when every output pixel depends on the input
+// pixel at the same coordinate alone; nodes that sample neighbors would need overlapping tiles.
+// The shader's layout must have the input texture at binding 0 and the output storage texture at
+// binding 1; extra_entries fill in everything after that. Progress advances once per finished tile.
+pub fn process_per_pixel_tiled(
+    render_device: &CustomGpuDevice,
+    render_queue: &CustomGpuQueue,
+    compute_pipeline: &ComputePipeline,
+    bind_group_layout: &BindGroupLayout,
+    extra_entries: &[BindGroupEntry],
+    input: &Image,
+    output_format: TextureFormat,
+    max_tile_size: u32,
+    workgroup_size: u32,
+    progress: &NodeProgress,
+) -> Image {
+    let size = input.texture_descriptor.size;
+    let input_pixel_bytes = bytes_per_pixel(input.texture_descriptor.format);
+    let output_pixel_bytes = bytes_per_pixel(output_format);
+    let tile_extents = Extent3d {
+        width: size.width.min(max_tile_size),
+        height: size.height.min(max_tile_size),
+        depth_or_array_layers: 1,
+    };
+
+    let input_texture = render_device.create_texture(&TextureDescriptor {
+        label: Some("Tile Input Texture"),
+        size: tile_extents,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: input.texture_descriptor.format,
+        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    let output_texture = render_device.create_texture(&TextureDescriptor {
+        label: Some("Tile Output Texture"),
+        size: tile_extents,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: output_format,
+        usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    // buffer copies need rows aligned to 256 bytes, which a partial tile won't be
+    let padded_bytes_per_row = (output_pixel_bytes * tile_extents.width).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let readback_buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("Tile Readback Buffer"),
+        size: (padded_bytes_per_row * tile_extents.height) as BufferAddress,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let input_view = input_texture.create_view(&Default::default());
+    let output_view = output_texture.create_view(&Default::default());
+
+    let mut entries = vec![
+        BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::TextureView(&input_view),
+        },
+        BindGroupEntry {
+            binding: 1,
+            resource: BindingResource::TextureView(&output_view),
+        },
+    ];
+    entries.extend(extra_entries.iter().cloned());
+
+    let bind_group = render_device.create_bind_group("Tile Compute Bind Group", bind_group_layout, &entries);
+
+    let mut data = vec![0u8; (output_pixel_bytes * size.width * size.height) as usize];
+
+    let tile_count = size.width.div_ceil(tile_extents.width) * size.height.div_ceil(tile_extents.height);
+    let mut tiles_done = 0;
+
+    for tile_y in (0..size.height).step_by(tile_extents.height as usize) {
+        for tile_x in (0..size.width).step_by(tile_extents.width as usize) {
+            let tile = Extent3d {
+                width: (size.width - tile_x).min(tile_extents.width),
+                height: (size.height - tile_y).min(tile_extents.height),
+                depth_or_array_layers: 1,
+            };
+
+            // the source rows are read straight out of the full image
+            render_queue.write_texture(
+                ImageCopyTexture {
+                    texture: &input_texture,
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                &input.data,
+                ImageDataLayout {
+                    offset: (input_pixel_bytes * (tile_y * size.width + tile_x)) as BufferAddress,
+                    bytes_per_row: Some(input_pixel_bytes * size.width),
+                    rows_per_image: Some(tile.height),
+                },
+                tile,
+            );
+
+            let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Tile Compute Encoder"),
+            });
+
+            {
+                let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("Tile Compute Pass"),
+                    timestamp_writes: None,
+                });
+                compute_pass.set_pipeline(compute_pipeline);
+                compute_pass.set_bind_group(0, &bind_group, &[]);
+                compute_pass.dispatch_workgroups(
+                    (tile.width + workgroup_size - 1) / workgroup_size,
+                    (tile.height + workgroup_size - 1) / workgroup_size,
+                    1,
+                );
+            }
+
+            encoder.copy_texture_to_buffer(
+                ImageCopyTexture {
+                    texture: &output_texture,
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                ImageCopyBuffer {
+                    buffer: &readback_buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_bytes_per_row),
+                        rows_per_image: Some(tile.height),
+                    },
+                },
+                tile,
+            );
+
+            render_queue.submit(Some(encoder.finish()));
+
+            let buffer_slice = readback_buffer.slice(..);
+            let (tx, rx) = crossbeam_channel::unbounded();
+
+            buffer_slice.map_async(MapMode::Read, move |result| {
+                tx.send(result).expect("Failed to send map_async result");
+            });
+
+            render_device.poll(Maintain::Wait);
+
+            match rx.recv().expect("Failed to receive map_async result") {
+                Ok(_) => {
+                    let mapped = buffer_slice.get_mapped_range();
+                    let row_bytes = (output_pixel_bytes * tile.width) as usize;
+
+                    for row in 0..tile.height {
+                        let source = (row * padded_bytes_per_row) as usize;
+                        let destination = (output_pixel_bytes * ((tile_y + row) * size.width + tile_x)) as usize;
+                        data[destination..destination + row_bytes]
+                            .copy_from_slice(&mapped[source..source + row_bytes]);
+                    }
+                }
+                Err(e) => panic!("Failed to map tile buffer: {:?}", e),
+            }
+
+            readback_buffer.unmap();
+
+            tiles_done += 1;
+            progress.set(tiles_done as f32 / tile_count as f32);
+        }
+    }
+
+    Image::new(
+        size,
+        TextureDimension::D2,
+        data,
+        output_format,
+        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+    )
+}
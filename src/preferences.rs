@@ -0,0 +1,274 @@
+use std::fs;
+
+use bevy::{color::palettes::css::MAGENTA, ecs::system::SystemParam, prelude::*, scene::ron};
+use serde::{Deserialize, Serialize};
+use wgpu::TextureFormat;
+
+use crate::{
+    line_renderer::{EdgeStyle, GraphOrientation},
+    nodes::{kinds::shape::Shape, tiling::MAX_TILE_SIZE_CHOICES, RequestSpawnNodeKind},
+};
+
+const PREFERENCES_PATH: &str = "preferences.ron";
+
+pub struct PreferencesPlugin;
+
+impl Plugin for PreferencesPlugin {
+    fn build(&self, app: &mut App) {
+        let preferences = load_preferences();
+
+        app.insert_resource(preferences.node_defaults);
+        app.insert_resource(preferences.ui);
+
+        app.observe(handle_set_preference);
+    }
+}
+
+// The values a node is created with when spawned from the context menu.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeDefaults {
+    pub texture_size: u32,
+    pub texture_format: TextureFormat,
+    pub color: LinearRgba,
+    pub shape: Shape,
+}
+
+impl Default for NodeDefaults {
+    fn default() -> Self {
+        Self {
+            texture_size: 512,
+            texture_format: TextureFormat::Rgba8Unorm,
+            color: MAGENTA.into(),
+            shape: Shape::Circle(100.0),
+        }
+    }
+}
+
+pub const TEXTURE_SIZE_CHOICES: [u32; 4] = [256, 512, 1024, 2048];
+pub const TEXTURE_FORMAT_CHOICES: [TextureFormat; 3] = [
+    TextureFormat::Rgba8Unorm,
+    TextureFormat::Rgba8UnormSrgb,
+    TextureFormat::Bgra8Unorm,
+];
+
+impl NodeDefaults {
+    pub fn next_texture_size(&self) -> u32 {
+        next_choice(&TEXTURE_SIZE_CHOICES, &self.texture_size)
+    }
+
+    pub fn next_texture_format(&self) -> TextureFormat {
+        next_choice(&TEXTURE_FORMAT_CHOICES, &self.texture_format)
+    }
+
+    pub fn next_shape(&self) -> Shape {
+        match self.shape {
+            Shape::Circle(_) => Shape::Rectangle(200.0, 200.0),
+            Shape::Rectangle(_, _) => Shape::Triangle(200.0, 200.0),
+            Shape::Triangle(_, _) => Shape::Circle(100.0),
+        }
+    }
+}
+
+pub(crate) fn next_choice<T: PartialEq + Clone>(choices: &[T], current: &T) -> T {
+    let next_index = choices
+        .iter()
+        .position(|choice| choice == current)
+        .map(|index| (index + 1) % choices.len())
+        .unwrap_or(0);
+
+    choices[next_index].clone()
+}
+
+// How the editor itself looks and behaves.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiPreferences {
+    // node previews larger than this are downsampled for display; None shows full resolution
+    pub preview_max_dimension: Option<u32>,
+    // percent of the window width taken by the inspector panel
+    pub inspector_width: f32,
+    pub edge_style: EdgeStyle,
+    pub orientation: GraphOrientation,
+    pub node_shadows: bool,
+    // the per-node timing readout under each node, mostly useful when working on node performance
+    pub show_process_time: bool,
+    // per-pixel nodes split images larger than this into tiles; None processes everything whole
+    pub max_tile_size: Option<u32>,
+    // deleting a node between two others wires them together, when the types allow
+    pub bridge_on_delete: bool,
+    // how many nodes may process at once. Lower keeps the GPU and task pool free for drawing the UI
+    // while a heavy graph runs, at the cost of the graph taking longer; None runs everything ready at once
+    pub max_concurrent_nodes: Option<u32>,
+    // saving embeds a small preview of the graph's final image so project files are recognizable
+    pub embed_thumbnail: bool,
+    // how many pixels the pointer must move while pressed before a click becomes a drag
+    pub drag_threshold: f32,
+    // right-dragging the canvas pans like middle-dragging. The context menu then opens on release instead of press
+    pub right_drag_pan: bool,
+    // ports are ordered by where their connected nodes sit instead of declaration order, so fewer edges cross
+    pub arrange_ports_by_connection: bool,
+    // offers nodes that instrument the editor itself, like Probe. Always on in debug builds
+    pub developer_nodes: bool,
+}
+
+impl Default for UiPreferences {
+    fn default() -> Self {
+        Self {
+            preview_max_dimension: Some(256),
+            inspector_width: 20.,
+            edge_style: EdgeStyle::Curved,
+            orientation: GraphOrientation::Horizontal,
+            node_shadows: true,
+            show_process_time: false,
+            max_tile_size: None,
+            bridge_on_delete: false,
+            max_concurrent_nodes: None,
+            embed_thumbnail: false,
+            drag_threshold: 4.,
+            right_drag_pan: false,
+            arrange_ports_by_connection: false,
+            developer_nodes: false,
+        }
+    }
+}
+
+pub const INSPECTOR_WIDTH_MIN: f32 = 10.;
+pub const INSPECTOR_WIDTH_MAX: f32 = 60.;
+
+pub const PREVIEW_MAX_DIMENSION_CHOICES: [Option<u32>; 4] = [Some(128), Some(256), Some(512), None];
+
+pub const MAX_CONCURRENT_NODES_CHOICES: [Option<u32>; 4] = [None, Some(1), Some(2), Some(4)];
+
+pub const DRAG_THRESHOLD_CHOICES: [f32; 4] = [0., 2., 4., 8.];
+
+impl UiPreferences {
+    pub fn next_preview_max_dimension(&self) -> Option<u32> {
+        next_choice(&PREVIEW_MAX_DIMENSION_CHOICES, &self.preview_max_dimension)
+    }
+
+    pub fn next_edge_style(&self) -> EdgeStyle {
+        next_choice(&EdgeStyle::ALL, &self.edge_style)
+    }
+
+    pub fn next_orientation(&self) -> GraphOrientation {
+        next_choice(&GraphOrientation::ALL, &self.orientation)
+    }
+
+    pub fn next_max_tile_size(&self) -> Option<u32> {
+        next_choice(&MAX_TILE_SIZE_CHOICES, &self.max_tile_size)
+    }
+
+    pub fn next_max_concurrent_nodes(&self) -> Option<u32> {
+        next_choice(&MAX_CONCURRENT_NODES_CHOICES, &self.max_concurrent_nodes)
+    }
+
+    pub fn next_drag_threshold(&self) -> f32 {
+        next_choice(&DRAG_THRESHOLD_CHOICES, &self.drag_threshold)
+    }
+
+    pub fn offers_node_kind(&self, spawn_kind: &RequestSpawnNodeKind) -> bool {
+        !spawn_kind.is_developer_only() || self.developer_nodes || cfg!(debug_assertions)
+    }
+
+    // the saved width is hand-editable, so never trust it to be in range
+    pub fn inspector_width(&self) -> f32 {
+        self.inspector_width.clamp(INSPECTOR_WIDTH_MIN, INSPECTOR_WIDTH_MAX)
+    }
+}
+
+// Read-only access to every preference resource, for UI that displays them.
+#[derive(SystemParam)]
+pub struct Preferences<'w> {
+    pub node_defaults: Res<'w, NodeDefaults>,
+    pub ui: Res<'w, UiPreferences>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct PreferencesFile {
+    node_defaults: NodeDefaults,
+    ui: UiPreferences,
+}
+
+fn load_preferences() -> PreferencesFile {
+    match fs::read_to_string(PREFERENCES_PATH) {
+        Ok(contents) => match ron::from_str::<PreferencesFile>(&contents) {
+            Ok(preferences) => preferences,
+            Err(e) => {
+                eprintln!("Failed to parse {}, using defaults: {}", PREFERENCES_PATH, e);
+                PreferencesFile::default()
+            }
+        },
+        Err(_) => PreferencesFile::default(),
+    }
+}
+
+fn save_preferences(preferences: &PreferencesFile) {
+    let serialized = ron::ser::to_string_pretty(preferences, ron::ser::PrettyConfig::default());
+
+    match serialized {
+        Ok(serialized) => {
+            if let Err(e) = fs::write(PREFERENCES_PATH, serialized) {
+                eprintln!("Failed to write {}: {}", PREFERENCES_PATH, e);
+            }
+        }
+        Err(e) => eprintln!("Failed to serialize preferences: {}", e),
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub enum SetPreference {
+    DefaultTextureSize(u32),
+    DefaultTextureFormat(TextureFormat),
+    DefaultShape(Shape),
+    PreviewMaxDimension(Option<u32>),
+    InspectorWidth(f32),
+    EdgeStyle(EdgeStyle),
+    Orientation(GraphOrientation),
+    NodeShadows(bool),
+    ShowProcessTime(bool),
+    MaxTileSize(Option<u32>),
+    BridgeOnDelete(bool),
+    MaxConcurrentNodes(Option<u32>),
+    EmbedThumbnail(bool),
+    DragThreshold(f32),
+    RightDragPan(bool),
+    ArrangePortsByConnection(bool),
+    DeveloperNodes(bool),
+}
+
+fn handle_set_preference(
+    trigger: Trigger<SetPreference>,
+    mut node_defaults: ResMut<NodeDefaults>,
+    mut ui_preferences: ResMut<UiPreferences>,
+) {
+    match trigger.event() {
+        SetPreference::DefaultTextureSize(size) => node_defaults.texture_size = *size,
+        SetPreference::DefaultTextureFormat(format) => node_defaults.texture_format = *format,
+        SetPreference::DefaultShape(shape) => node_defaults.shape = shape.clone(),
+        SetPreference::PreviewMaxDimension(max_dimension) => {
+            ui_preferences.preview_max_dimension = *max_dimension
+        }
+        SetPreference::InspectorWidth(width) => {
+            ui_preferences.inspector_width = width.clamp(INSPECTOR_WIDTH_MIN, INSPECTOR_WIDTH_MAX)
+        }
+        SetPreference::EdgeStyle(style) => ui_preferences.edge_style = *style,
+        SetPreference::Orientation(orientation) => ui_preferences.orientation = *orientation,
+        SetPreference::NodeShadows(enabled) => ui_preferences.node_shadows = *enabled,
+        SetPreference::ShowProcessTime(enabled) => ui_preferences.show_process_time = *enabled,
+        SetPreference::MaxTileSize(max_tile_size) => ui_preferences.max_tile_size = *max_tile_size,
+        SetPreference::BridgeOnDelete(enabled) => ui_preferences.bridge_on_delete = *enabled,
+        SetPreference::MaxConcurrentNodes(max_nodes) => ui_preferences.max_concurrent_nodes = *max_nodes,
+        SetPreference::EmbedThumbnail(enabled) => ui_preferences.embed_thumbnail = *enabled,
+        SetPreference::DragThreshold(threshold) => ui_preferences.drag_threshold = threshold.max(0.),
+        SetPreference::RightDragPan(enabled) => ui_preferences.right_drag_pan = *enabled,
+        SetPreference::ArrangePortsByConnection(enabled) => ui_preferences.arrange_ports_by_connection = *enabled,
+        SetPreference::DeveloperNodes(enabled) => ui_preferences.developer_nodes = *enabled,
+    }
+
+    save_preferences(&PreferencesFile {
+        node_defaults: node_defaults.clone(),
+        ui: ui_preferences.clone(),
+    });
+}
@@ -7,7 +7,7 @@ use petgraph::{prelude::StableDiGraph};
 use wgpu::{Features, Limits};
 
 use crate::{
-    asset::GeneratedMeshes, graph::{DisjointPipelineGraph, Edge, RequestProcessPipeline}, nodes::GraphNode, ApplicationState
+    asset::{FontAssets, GeneratedMeshes}, graph::{DisjointPipelineGraph, Edge, RequestProcessPipeline}, nodes::GraphNode, ApplicationState
 };
 
 pub struct SetupPlugin;
@@ -18,13 +18,19 @@ impl Plugin for SetupPlugin {
             OnEnter(ApplicationState::Setup),
             (
                 setup_device_and_queue,
-                (spawn_graph_entity, setup_scene, done_setting_up),
+                (spawn_graph_entity, setup_scene, done_setting_up).run_if(resource_exists::<CustomGpuDevice>),
             )
                 .chain(),
         );
+
+        app.add_systems(OnEnter(ApplicationState::GpuUnavailable), show_gpu_unavailable_screen);
     }
 }
 
+// Why node processing couldn't get a device, shown on the error screen
+#[derive(Resource)]
+struct GpuUnavailable(String);
+
 #[derive(Component)]
 pub struct ApplicationCanvas;
 
@@ -51,7 +57,84 @@ pub struct CustomGpuDevice(RenderDevice);
 #[derive(Resource, Deref, Clone)]
 pub struct CustomGpuQueue(RenderQueue);
 
-fn setup_device_and_queue(mut commands: Commands, bevy_render_device: Res<RenderDevice>, adapter: Res<RenderAdapter>) {
+// Bevy's renderer already panics at startup when there is no adapter at all, so this only
+// has to cope with an adapter that can't give node processing a device of its own.
+fn setup_device_and_queue(
+    mut commands: Commands,
+    bevy_render_device: Res<RenderDevice>,
+    adapter: Res<RenderAdapter>,
+    mut next_state: ResMut<NextState<ApplicationState>>,
+) {
+    let limits = if cfg!(target_arch = "wasm32") {
+        Limits::downlevel_webgl2_defaults()
+    } else {
+        bevy_render_device.limits().clone()
+    };
+
+    // older and software adapters may not manage a second device with the renderer's limits
+    let result = request_gpu_device(&adapter, limits)
+        .or_else(|_| request_gpu_device(&adapter, Limits::downlevel_defaults()));
+
+    match result {
+        Ok((device, queue)) => {
+            commands.insert_resource(device);
+            commands.insert_resource(queue)
+        }
+        Err(e) => {
+            eprintln!("Failed to create a GPU device for node processing: {}", e);
+            commands.insert_resource(GpuUnavailable(e));
+            next_state.set(ApplicationState::GpuUnavailable);
+        }
+    }
+}
+
+fn show_gpu_unavailable_screen(mut commands: Commands, fonts: Res<FontAssets>, gpu_unavailable: Res<GpuUnavailable>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.),
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "No usable GPU was found",
+                TextStyle {
+                    font: fonts.deja_vu_sans.clone(),
+                    font_size: 32.,
+                    color: Color::WHITE,
+                },
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "Raster Reshaper needs a GPU device to process nodes. Check your graphics drivers, \
+                    or set WGPU_BACKEND to try a different backend.\n\n{}",
+                    gpu_unavailable.0
+                ),
+                TextStyle {
+                    font: fonts.deja_vu_sans.clone(),
+                    font_size: 16.,
+                    color: Color::srgb(0.7, 0.7, 0.7),
+                },
+            ).with_style(Style {
+                max_width: Val::Px(640.),
+                ..default()
+            }));
+        });
+}
+
+// Node processing gets its own device on the same adapter as Bevy's renderer, so heavy compute work
+// doesn't queue up behind frame rendering.
+fn request_gpu_device(adapter: &wgpu::Adapter, limits: Limits) -> Result<(CustomGpuDevice, CustomGpuQueue), String> {
     let (device, queue) = block_on(async {
         adapter
             .request_device(
@@ -59,23 +142,38 @@ fn setup_device_and_queue(mut commands: Commands, bevy_render_device: Res<Render
                     // bevy doesn't reexport this and we are manually pulling in wgpu just to get to it...
                     label: None,
                     required_features: Features::empty(),
-                    required_limits: if cfg!(target_arch = "wasm32") {
-                        Limits::downlevel_webgl2_defaults()
-                    } else {
-                        bevy_render_device.limits().clone()
-                    },
+                    required_limits: limits,
                 },
                 None,
             )
             .await
-            .unwrap()
-    });
+    })
+    .map_err(|e| e.to_string())?;
 
     let bevy_compat_device: RenderDevice = device.into();
     let bevy_compat_queue: RenderQueue = RenderQueue(Arc::new(WgpuWrapper::new(queue)));
 
-    commands.insert_resource(CustomGpuDevice(bevy_compat_device));
-    commands.insert_resource(CustomGpuQueue(bevy_compat_queue))
+    Ok((CustomGpuDevice(bevy_compat_device), CustomGpuQueue(bevy_compat_queue)))
+}
+
+// A device and queue straight from wgpu, for running nodes outside the app (tests, headless rendering).
+// Nothing here depends on Bevy's render plugin, so no window or render world is needed.
+//
+// wgpu picks the backend: Vulkan on Linux and Windows, Metal on macOS, with DX12 as the
+// Windows fallback (the dx12 and metal wgpu features in Cargo.toml). Set WGPU_BACKEND to
+// vulkan, metal, dx12 or gl to force one. CI machines without a GPU need a software Vulkan
+// driver such as lavapipe, or WGPU_ADAPTER_NAME to pick it when several adapters are present.
+#[allow(dead_code)]
+pub fn create_gpu_device() -> Result<(CustomGpuDevice, CustomGpuQueue), String> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::util::backend_bits_from_env().unwrap_or_default(),
+        ..default()
+    });
+
+    let adapter = block_on(wgpu::util::initialize_adapter_from_env_or_default(&instance, None))
+        .ok_or_else(|| String::from("No GPU adapter available"))?;
+
+    request_gpu_device(&adapter, Limits::default())
 }
 
 fn spawn_graph_entity(mut commands: Commands) {
@@ -1,16 +1,28 @@
-use crate::{asset::FontAssets, ApplicationState};
+use crate::{asset::FontAssets, preferences::UiPreferences, ApplicationState};
 use bevy::{ecs::system::EntityCommands, prelude::ChildBuilder, prelude::*};
-use bevy_cosmic_edit::{
-    change_active_editor_ui, deselect_editor_on_esc, CosmicEditPlugin, CosmicFontConfig,
-};
+use bevy_cosmic_edit::{change_active_editor_ui, CosmicEditPlugin, CosmicFontConfig};
 use bevy_mod_picking::prelude::Pickable;
+use comments::CommentsPlugin;
 use context_menu::{ContextMenuPlugin, UIContext};
+use diff_panel::{DiffPanel, DiffPanelPlugin};
+use empty_hint::{EmptyHint, EmptyHintPlugin};
+use groups::GroupsPlugin;
+use history_panel::{HistoryPanel, HistoryPanelPlugin};
 use inspector::{InspectorPanel, InspectorPlugin};
+use issues_panel::{IssuesPanel, IssuesPanelPlugin};
 use menu_bar::{MenuBar, MenuBarPlugin};
+use status_bar::{StatusBar, StatusBarPlugin};
 
+pub mod comments;
 pub mod context_menu;
+pub mod diff_panel;
+pub mod empty_hint;
+pub mod groups;
+pub mod history_panel;
 pub mod inspector;
+pub mod issues_panel;
 pub mod menu_bar;
+pub mod status_bar;
 
 pub struct UiPlugin;
 
@@ -24,9 +36,16 @@ impl Plugin for UiPlugin {
         };
 
         app.add_plugins((
+            CommentsPlugin,
             ContextMenuPlugin,
+            DiffPanelPlugin,
+            EmptyHintPlugin,
+            GroupsPlugin,
+            HistoryPanelPlugin,
             InspectorPlugin,
+            IssuesPanelPlugin,
             MenuBarPlugin,
+            StatusBarPlugin,
             CosmicEditPlugin {
                 font_config,
                 ..default()
@@ -34,10 +53,10 @@ impl Plugin for UiPlugin {
         ));
 
         app.add_systems(OnEnter(ApplicationState::Setup), ui_setup);
+        // escape is handled by the inspector's text inputs, which revert the edit before dropping focus
         app.add_systems(
             PreUpdate,
-            (change_active_editor_ui, deselect_editor_on_esc)
-                .run_if(in_state(ApplicationState::MainLoop)),
+            change_active_editor_ui.run_if(in_state(ApplicationState::MainLoop)),
         );
     }
 }
@@ -51,7 +70,10 @@ pub struct NodeEditArea;
 fn ui_setup(
     mut commands: Commands,
     fonts: Res<FontAssets>,
+    ui_preferences: Res<UiPreferences>,
 ) {
+    let inspector_width = ui_preferences.inspector_width();
+
     let ui_root = commands
         .spawn(NodeBundle {
             style: Style {
@@ -98,7 +120,7 @@ fn ui_setup(
     let node_edit_area = commands
         .spawn(NodeBundle {
             style: Style {
-                width: Val::Percent(80.),
+                width: Val::Percent(100. - inspector_width),
                 height: Val::Percent(100.),
                 ..default()
             },
@@ -113,9 +135,23 @@ fn ui_setup(
         })
         .id();
 
+    let empty_hint = EmptyHint::spawn(&mut commands, fonts.deja_vu_sans.clone());
+    commands.entity(node_edit_area).add_child(empty_hint);
+
+    let issues_panel = IssuesPanel::spawn(&mut commands);
+    commands.entity(node_edit_area).add_child(issues_panel);
+
+    let history_panel = HistoryPanel::spawn(&mut commands);
+    commands.entity(node_edit_area).add_child(history_panel);
+
+    let diff_panel = DiffPanel::spawn(&mut commands);
+    commands.entity(node_edit_area).add_child(diff_panel);
+
     let menu_bar = MenuBar::spawn(&mut commands, fonts.deja_vu_sans.clone());
 
-    let inspector_panel = InspectorPanel::spawn(&mut commands);
+    let status_bar = StatusBar::spawn(&mut commands, fonts.deja_vu_sans.clone());
+
+    let inspector_panel = InspectorPanel::spawn(&mut commands, inspector_width);
 
     commands
         .entity(ui_root)
@@ -123,7 +159,7 @@ fn ui_setup(
     
     commands
         .entity(root_vertical_layout)
-        .push_children(&[menu_bar, everything_but_menu_bar]);
+        .push_children(&[menu_bar, everything_but_menu_bar, status_bar]);
 
     commands.entity(everything_but_menu_bar)
         .push_children(&[node_edit_area, inspector_panel]);
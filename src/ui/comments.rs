@@ -0,0 +1,445 @@
+use bevy::{
+    color::palettes::{
+        css::ORANGE,
+        tailwind::{GREEN_200, PINK_200, SKY_200, SLATE_900, YELLOW_200},
+    },
+    prelude::*,
+};
+use bevy_cosmic_edit::*;
+use bevy_mod_picking::{
+    events::{Down, Drag, DragEnd, DragStart, Pointer},
+    prelude::{Pickable, PointerButton},
+    PickableBundle,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::comment_events::SetCommentEvent,
+    nodes::{NodeDisplay, Selected},
+    setup::ApplicationCanvas,
+    ApplicationState,
+};
+
+use super::context_menu::UIContext;
+
+pub struct CommentsPlugin;
+
+impl Plugin for CommentsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            change_active_editor_sprite.run_if(in_state(ApplicationState::MainLoop)),
+        );
+        app.add_systems(
+            Update,
+            (
+                (handle_comment_selection, handle_comment_drag, commit_comment_text),
+                (update_comment_layout, update_comment_outline),
+            )
+                .chain()
+                .run_if(in_state(ApplicationState::MainLoop)),
+        );
+
+        app.observe(cycle_comment_color);
+    }
+}
+
+pub const COMMENT_COLORS: [Srgba; 4] = [YELLOW_200, GREEN_200, SKY_200, PINK_200];
+pub const COMMENT_DEFAULT_SIZE: Vec2 = Vec2::new(260., 160.);
+const COMMENT_MIN_SIZE: Vec2 = Vec2::new(120., 80.);
+// behind every node, in front of the canvas
+const COMMENT_Z: f32 = -500.;
+const COMMENT_HEADER_HEIGHT: f32 = 20.;
+const COMMENT_PADDING: f32 = 8.;
+const COMMENT_RESIZE_HANDLE_SIZE: f32 = 12.;
+const COMMENT_OUTLINE_WIDTH: f32 = 2.;
+const COMMENT_TEXT_SIZE: f32 = 16.;
+
+// Everything about a comment that is saved and undone. Position is the center of the note.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CommentData {
+    pub position: Vec2,
+    pub size: Vec2,
+    pub color: LinearRgba,
+    pub text: String,
+}
+
+impl CommentData {
+    // A fresh note with its top left corner at the given point
+    pub fn new(top_left: Vec2) -> Self {
+        Self {
+            position: top_left + Vec2::new(COMMENT_DEFAULT_SIZE.x, -COMMENT_DEFAULT_SIZE.y) / 2.,
+            size: COMMENT_DEFAULT_SIZE,
+            color: COMMENT_COLORS[0].into(),
+            text: String::new(),
+        }
+    }
+
+    fn text_area_size(&self) -> Vec2 {
+        (self.size - Vec2::new(COMMENT_PADDING * 2., COMMENT_PADDING * 2. + COMMENT_HEADER_HEIGHT))
+            .max(Vec2::ONE)
+    }
+}
+
+// A sticky note on the canvas. Comments never enter the processing graph; they only exist as entities.
+// Removed comments lose this component but keep their entity, so undo can restore them.
+#[derive(Component)]
+pub struct Comment {
+    pub data: CommentData,
+}
+
+#[derive(Component)]
+pub struct CommentParts {
+    pub text_editor: Entity,
+    pub resize_handle: Entity,
+    pub outline: Entity,
+}
+
+#[derive(Component)]
+pub struct CommentTextEditor {
+    pub comment: Entity,
+}
+
+#[derive(Component)]
+pub struct CommentResizeHandle {
+    pub comment: Entity,
+}
+
+#[derive(Component)]
+struct CommentOutline;
+
+impl Comment {
+    pub fn spawn(commands: &mut Commands, font_system: &mut CosmicFontSystem, data: CommentData) -> Entity {
+        let attrs = Attrs::new().color(Color::from(SLATE_900).to_cosmic());
+        let metrics = Metrics::new(COMMENT_TEXT_SIZE, COMMENT_TEXT_SIZE * 1.2);
+
+        let comment_entity = commands.spawn_empty().id();
+
+        let text_editor = commands
+            .spawn((
+                CosmicEditBundle {
+                    buffer: CosmicBuffer::new(font_system, metrics).with_text(font_system, &data.text, attrs),
+                    cursor_color: CursorColor(Color::from(SLATE_900)),
+                    selection_color: SelectionColor(Color::linear_rgba(0.3, 0.3, 0.7, 0.5)),
+                    fill_color: CosmicBackgroundColor(data.color.into()),
+                    mode: CosmicWrap::Wrap,
+                    ..default()
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(data.text_area_size()),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0., -COMMENT_HEADER_HEIGHT / 2., 0.1),
+                    ..default()
+                },
+            ))
+            .insert(CommentTextEditor {
+                comment: comment_entity,
+            })
+            .id();
+
+        let resize_handle = commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK.with_alpha(0.3),
+                    custom_size: Some(Vec2::splat(COMMENT_RESIZE_HANDLE_SIZE)),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(PickableBundle::default())
+            .insert(CommentResizeHandle {
+                comment: comment_entity,
+            })
+            .id();
+
+        let outline = commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: ORANGE.into(),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., 0., -0.1),
+                visibility: Visibility::Hidden,
+                ..default()
+            })
+            .insert(Pickable::IGNORE)
+            .insert(CommentOutline)
+            .id();
+
+        commands
+            .entity(comment_entity)
+            .insert(SpriteBundle {
+                transform: Transform::from_translation(data.position.extend(COMMENT_Z)),
+                ..default()
+            })
+            .insert(PickableBundle::default())
+            .insert(Name::new("Comment"))
+            .insert(UIContext::Comment(comment_entity))
+            .insert(CommentParts {
+                text_editor,
+                resize_handle,
+                outline,
+            })
+            .insert(Comment { data })
+            .push_children(&[text_editor, resize_handle, outline]);
+
+        comment_entity
+    }
+}
+
+// Keeps the sprites and text in line with the comment's data, whether it changed from a drag, an undo or a load
+fn update_comment_layout(
+    mut commands: Commands,
+    mut font_system: ResMut<CosmicFontSystem>,
+    focused: Res<FocusedWidget>,
+    mut q_comments: Query<(&Comment, &CommentParts, &mut Sprite, &mut Transform), Changed<Comment>>,
+    mut q_parts: Query<(&mut Sprite, &mut Transform), Without<Comment>>,
+    mut q_buffers: Query<&mut CosmicBuffer>,
+) {
+    for (comment, parts, mut sprite, mut transform) in q_comments.iter_mut() {
+        let data = &comment.data;
+
+        sprite.color = data.color.into();
+        sprite.custom_size = Some(data.size);
+        transform.translation = data.position.extend(transform.translation.z);
+
+        if let Ok((mut editor_sprite, _)) = q_parts.get_mut(parts.text_editor) {
+            editor_sprite.custom_size = Some(data.text_area_size());
+        }
+
+        if let Ok((_, mut handle_transform)) = q_parts.get_mut(parts.resize_handle) {
+            let corner = Vec2::new(data.size.x, -data.size.y) / 2.;
+            let inset = Vec2::new(-COMMENT_RESIZE_HANDLE_SIZE, COMMENT_RESIZE_HANDLE_SIZE) / 2.;
+            handle_transform.translation = (corner + inset).extend(0.2);
+        }
+
+        if let Ok((mut outline_sprite, _)) = q_parts.get_mut(parts.outline) {
+            outline_sprite.custom_size = Some(data.size + Vec2::splat(COMMENT_OUTLINE_WIDTH * 2.));
+        }
+
+        commands
+            .entity(parts.text_editor)
+            .insert(CosmicBackgroundColor(data.color.into()));
+
+        // never clobber text that is being typed
+        if focused.0 != Some(parts.text_editor) {
+            if let Ok(mut buffer) = q_buffers.get_mut(parts.text_editor) {
+                if buffer.get_text() != data.text {
+                    buffer.set_text(
+                        &mut font_system,
+                        &data.text,
+                        Attrs::new().color(Color::from(SLATE_900).to_cosmic()),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn update_comment_outline(
+    q_comments: Query<(&CommentParts, Has<Selected>), With<Comment>>,
+    mut q_outlines: Query<&mut Visibility, With<CommentOutline>>,
+) {
+    for (parts, is_selected) in q_comments.iter() {
+        if let Ok(mut visibility) = q_outlines.get_mut(parts.outline) {
+            let wanted = if is_selected {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+
+            if *visibility != wanted {
+                *visibility = wanted;
+            }
+        }
+    }
+}
+
+// Comments are selected the same way nodes are: click to select, Shift to add, Ctrl to toggle
+fn handle_comment_selection(
+    mut commands: Commands,
+    mut down_events: EventReader<Pointer<Down>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    q_comments: Query<Has<Selected>, With<Comment>>,
+    q_selected: Query<Entity, With<Selected>>,
+    q_selected_comments: Query<Entity, (With<Comment>, With<Selected>)>,
+    q_canvas: Query<(), With<ApplicationCanvas>>,
+    q_nodes: Query<(), With<NodeDisplay>>,
+) {
+    let shift_pressed =
+        keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+    let control_pressed = keyboard_input.pressed(KeyCode::ControlLeft)
+        || keyboard_input.pressed(KeyCode::ControlRight);
+
+    for event in down_events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+
+        // nodes clear their own selection, but not the selected comments
+        let is_canvas_or_node = q_canvas.contains(event.target) || q_nodes.contains(event.target);
+        if is_canvas_or_node && !shift_pressed && !control_pressed {
+            for entity in q_selected_comments.iter() {
+                commands.entity(entity).remove::<Selected>();
+            }
+        }
+
+        if let Ok(is_selected) = q_comments.get(event.target) {
+            if is_selected {
+                if control_pressed {
+                    commands.entity(event.target).remove::<Selected>();
+                }
+            } else {
+                if !shift_pressed && !control_pressed {
+                    for entity in q_selected.iter() {
+                        commands.entity(entity).remove::<Selected>();
+                    }
+                }
+                commands.entity(event.target).insert(Selected);
+            }
+        }
+    }
+}
+
+enum CommentDragKind {
+    Move,
+    Resize,
+}
+
+// Dragging the body moves a comment, dragging the corner handle resizes it. Either is one undo step.
+fn handle_comment_drag(
+    mut commands: Commands,
+    mut drag_start_events: EventReader<Pointer<DragStart>>,
+    mut drag_events: EventReader<Pointer<Drag>>,
+    mut drag_end_events: EventReader<Pointer<DragEnd>>,
+    camera_query: Query<&OrthographicProjection>,
+    q_handles: Query<&CommentResizeHandle>,
+    mut q_comments: Query<&mut Comment>,
+    mut drag_info: Local<Option<(Entity, CommentDragKind, CommentData)>>,
+) {
+    let camera_scale = camera_query.single().scale;
+
+    for event in drag_start_events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+
+        let target = match q_handles.get(event.target) {
+            Ok(handle) => Some((handle.comment, CommentDragKind::Resize)),
+            Err(_) if q_comments.contains(event.target) => Some((event.target, CommentDragKind::Move)),
+            Err(_) => None,
+        };
+
+        if let Some((comment_entity, kind)) = target {
+            if let Ok(comment) = q_comments.get(comment_entity) {
+                *drag_info = Some((comment_entity, kind, comment.data.clone()));
+            }
+        }
+    }
+
+    for event in drag_events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+
+        let Some((comment_entity, kind, _)) = drag_info.as_ref() else {
+            continue;
+        };
+
+        let Ok(mut comment) = q_comments.get_mut(*comment_entity) else {
+            continue;
+        };
+
+        let delta = Vec2::new(event.delta.x, -event.delta.y) * camera_scale;
+
+        match kind {
+            CommentDragKind::Move => comment.data.position += delta,
+            CommentDragKind::Resize => {
+                // the top left corner stays put while the bottom right follows the pointer
+                let old_size = comment.data.size;
+                let new_size = (old_size + Vec2::new(delta.x, -delta.y)).max(COMMENT_MIN_SIZE);
+                let growth = new_size - old_size;
+
+                comment.data.size = new_size;
+                comment.data.position += Vec2::new(growth.x, -growth.y) / 2.;
+            }
+        }
+    }
+
+    for event in drag_end_events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+
+        if let Some((comment_entity, _, old_data)) = drag_info.take() {
+            if let Ok(comment) = q_comments.get(comment_entity) {
+                commands.trigger(SetCommentEvent {
+                    comment_entity,
+                    old_data,
+                    new_data: comment.data.clone(),
+                });
+            }
+        }
+    }
+}
+
+// Typed text is recorded once the editor loses focus and hands its text back to the buffer
+fn commit_comment_text(
+    mut commands: Commands,
+    focused: Res<FocusedWidget>,
+    q_editors: Query<(Entity, &CosmicBuffer, &CommentTextEditor), (Changed<CosmicBuffer>, Without<CosmicEditor>)>,
+    q_comments: Query<&Comment>,
+) {
+    for (editor_entity, buffer, text_editor) in q_editors.iter() {
+        if focused.0 == Some(editor_entity) {
+            continue;
+        }
+
+        if let Ok(comment) = q_comments.get(text_editor.comment) {
+            let text = buffer.get_text();
+
+            if text != comment.data.text {
+                commands.trigger(SetCommentEvent {
+                    comment_entity: text_editor.comment,
+                    old_data: comment.data.clone(),
+                    new_data: CommentData {
+                        text,
+                        ..comment.data.clone()
+                    },
+                });
+            }
+        }
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct RequestCycleCommentColor {
+    pub comment_entity: Entity,
+}
+
+fn cycle_comment_color(
+    trigger: Trigger<RequestCycleCommentColor>,
+    mut commands: Commands,
+    q_comments: Query<&Comment>,
+) {
+    let comment_entity = trigger.event().comment_entity;
+
+    if let Ok(comment) = q_comments.get(comment_entity) {
+        let next_index = COMMENT_COLORS
+            .iter()
+            .position(|color| LinearRgba::from(*color) == comment.data.color)
+            .map(|index| (index + 1) % COMMENT_COLORS.len())
+            .unwrap_or(0);
+
+        commands.trigger(SetCommentEvent {
+            comment_entity,
+            old_data: comment.data.clone(),
+            new_data: CommentData {
+                color: COMMENT_COLORS[next_index].into(),
+                ..comment.data.clone()
+            },
+        });
+    }
+}
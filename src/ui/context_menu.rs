@@ -1,13 +1,19 @@
 use crate::{
-    asset::FontAssets,
+    asset::{FontAssets, NODE_WIDTH},
+    camera::RequestFrameNodes,
     events::{
-        edge_events::RemoveEdgeEvent, node_events::{AddNodeEvent, AddNodeKind, RemoveNodeEvent}, RequestRedo, RequestUndo
+        comment_events::{AddCommentEvent, RemoveCommentEvent}, edge_events::RemoveEdgeEvent, field_events::SetInputFieldEvent, group_events::RemoveGroupEvent, node_events::{AddNodeEvent, AddNodeKind, ConnectOnSpawn, RemoveNodeEvent, RequestReseedAll}, RequestRedo, RequestUndo
     },
-    graph::DisjointPipelineGraph,
+    export::{RequestBatchExport, RequestExportImage},
+    graph::{connected_nodes, directed_reachable_nodes, DisjointPipelineGraph, RequestReprocessFrom},
     nodes::{
+        drag_threshold::{apply_drag_threshold, ThresholdDragStart},
+        fields::can_convert_field,
         ports::{InputPort, OutputPort},
-        InputId, NodeDisplay, OutputId, RequestSpawnNodeKind, Selected,
+        input_default, node_kind_name, EdgeLine, HighlightedEdge, InputId, NodeDisplay, NodeTrait, OutputId, RequestSpawnNodeKind,
+        Selected, ToggleIsolateSelection,
     },
+    preferences::{NodeDefaults, Preferences, SetPreference, UiPreferences},
     ApplicationState,
 };
 use bevy::{
@@ -30,7 +36,11 @@ use bevy_mod_picking::{
 use petgraph::{visit::EdgeRef, Direction};
 
 use super::{
-    menu_bar::{CopyEvent, ExitEvent, LoadEvent, MenuButton, NewProjectEvent, PasteEvent, SaveEvent},
+    comments::{CommentData, RequestCycleCommentColor},
+    groups::{RequestAddGroup, RequestCycleGroupColor},
+    diff_panel::ToggleDiffPanel,
+    history_panel::ToggleHistoryPanel,
+    menu_bar::{CopyEvent, ExitEvent, ExtractSelectionEvent, ImportFolderEvent, LoadEvent, MenuButton, NewProjectEvent, PasteEvent, PasteImageEvent, Project, SaveEvent, SetRenderScale, SetTextureFormat},
     Spawner, UiRoot,
 };
 
@@ -49,14 +59,21 @@ impl Plugin for ContextMenuPlugin {
                 clamp_context_menu_to_window,
             )
                 .chain()
+                .after(apply_drag_threshold)
                 .run_if(in_state(ApplicationState::MainLoop)),
         );
 
         app.observe(on_made_any_context_menu_selection);
         app.observe(detatch_input);
         app.observe(detatch_output);
+        app.observe(reset_input);
         app.observe(handle_remove_node_request);
+        app.observe(handle_apply_effect_request);
+        app.observe(handle_select_connected_request);
+        app.observe(handle_select_directed_request);
+        app.observe(handle_select_same_kind_request);
         app.observe(open_context_menu);
+        app.observe(open_port_drop_menu);
     }
 }
 
@@ -67,9 +84,33 @@ pub enum UIContext {
     NodeEditArea,
     Inspector,
     Node(Entity),
+    Comment(Entity),
+    Group(Entity),
     InputPort(InputPortContext),
     OutputPort(OutputPortContext),
     MenuBar(MenuBarContext),
+    PortDrop(PortDropContext),
+}
+
+// A connection dragged from a port and released over empty canvas
+#[derive(Debug)]
+pub struct PortDropContext {
+    pub connect: ConnectOnSpawn,
+}
+
+impl PortDropContext {
+    fn is_compatible_with(&self, spawn_kind: &RequestSpawnNodeKind) -> bool {
+        match &self.connect {
+            ConnectOnSpawn::FromOutput { field, .. } => spawn_kind
+                .input_prototypes()
+                .iter()
+                .any(|(_, input)| can_convert_field(field, input)),
+            ConnectOnSpawn::ToInput { field, .. } => spawn_kind
+                .output_prototypes()
+                .iter()
+                .any(|(_, output)| can_convert_field(output, field)),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -99,6 +140,7 @@ impl ContextMenu {
         cursor_world_pos: Vec2,
         ctx: &UIContext,
         font: Handle<Font>,
+        preferences: &Preferences,
     ) -> EntityCommands<'a> {
         let mut ec = spawner.spawn_bundle(NodeBundle {
             style: Style {
@@ -136,6 +178,13 @@ impl ContextMenu {
                         font.clone(),
                         PasteEvent::FromCursor(cursor_world_pos),
                     );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Paste Image",
+                        font.clone(),
+                        PasteImageEvent::FromCursor(cursor_world_pos),
+                    );
                     
                     ContextMenuEntry::spawn(child_builder, "Undo", font.clone(), RequestUndo);
 
@@ -143,42 +192,78 @@ impl ContextMenu {
 
                     ContextMenuDivider::spawn(child_builder);
 
+                    ContextMenuEntry::spawn(child_builder, "Frame All", font.clone(), RequestFrameNodes::All);
+
+                    ContextMenuEntry::spawn(child_builder, "Frame Selected", font.clone(), RequestFrameNodes::Selected);
+
+                    ContextMenuDivider::spawn(child_builder);
+
                     ContextMenuEntry::spawn(
                         child_builder,
-                        "Example Node",
-                        font.clone(),
-                        AddNodeEvent::FromKind(AddNodeKind {
-                            position: cursor_world_pos,
-                            spawn_kind: RequestSpawnNodeKind::Example,
-                        }),
-                    );
-                    ContextMenuEntry::spawn(
-                        child_builder,
-                        "Color Node",
-                        font.clone(),
-                        AddNodeEvent::FromKind(AddNodeKind {
-                            position: cursor_world_pos,
-                            spawn_kind: RequestSpawnNodeKind::Color,
-                        }),
-                    );
-                    ContextMenuEntry::spawn(
-                        child_builder,
-                        "Shape Node",
+                        "Comment",
                         font.clone(),
-                        AddNodeEvent::FromKind(AddNodeKind {
-                            position: cursor_world_pos,
-                            spawn_kind: RequestSpawnNodeKind::Shape,
-                        }),
+                        AddCommentEvent {
+                            data: CommentData::new(cursor_world_pos),
+                        },
                     );
+
                     ContextMenuEntry::spawn(
                         child_builder,
-                        "Blend Node",
+                        "Group",
                         font.clone(),
-                        AddNodeEvent::FromKind(AddNodeKind {
+                        RequestAddGroup {
                             position: cursor_world_pos,
-                            spawn_kind: RequestSpawnNodeKind::Blend,
-                        }),
+                        },
                     );
+
+                    for spawn_kind in RequestSpawnNodeKind::ALL
+                        .iter()
+                        .filter(|spawn_kind| preferences.ui.offers_node_kind(spawn_kind))
+                    {
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("{} Node", spawn_kind.name()),
+                            font.clone(),
+                            AddNodeEvent::FromKind(AddNodeKind {
+                                position: cursor_world_pos,
+                                spawn_kind: spawn_kind.clone(),
+                                connect: None,
+                            }),
+                        );
+                    }
+                });
+            }
+            UIContext::PortDrop(port_drop_context) => {
+                ec.with_children(|child_builder| {
+                    let compatible_kinds: Vec<&RequestSpawnNodeKind> = RequestSpawnNodeKind::ALL
+                        .iter()
+                        .filter(|spawn_kind| preferences.ui.offers_node_kind(spawn_kind))
+                        .filter(|spawn_kind| port_drop_context.is_compatible_with(spawn_kind))
+                        .collect();
+
+                    if compatible_kinds.is_empty() {
+                        child_builder.spawn(TextBundle::from_section(
+                            "No compatible nodes",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 16.,
+                                color: GRAY_400.into(),
+                            },
+                        ));
+                    }
+
+                    for spawn_kind in compatible_kinds {
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("{} Node", spawn_kind.name()),
+                            font.clone(),
+                            AddNodeEvent::FromKind(AddNodeKind {
+                                position: cursor_world_pos,
+                                spawn_kind: spawn_kind.clone(),
+                                connect: Some(port_drop_context.connect.clone()),
+                            }),
+                        );
+                    }
                 });
             }
             UIContext::Inspector => {
@@ -203,6 +288,135 @@ impl ContextMenu {
                             node_entity: *entity,
                         },
                     );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Export Image",
+                        font.clone(),
+                        RequestExportImage {
+                            node_entity: *entity,
+                        },
+                    );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Batch Export Folder...",
+                        font.clone(),
+                        RequestBatchExport {
+                            node_entity: *entity,
+                        },
+                    );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Reprocess from Here",
+                        font.clone(),
+                        RequestReprocessFrom {
+                            node_entity: *entity,
+                        },
+                    );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Select Connected",
+                        font.clone(),
+                        RequestSelectConnected {
+                            node_entity: *entity,
+                        },
+                    );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Select Upstream",
+                        font.clone(),
+                        RequestSelectDirected {
+                            node_entity: *entity,
+                            direction: Direction::Incoming,
+                        },
+                    );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Select Downstream",
+                        font.clone(),
+                        RequestSelectDirected {
+                            node_entity: *entity,
+                            direction: Direction::Outgoing,
+                        },
+                    );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Select Same Kind",
+                        font.clone(),
+                        RequestSelectSameKind {
+                            node_entity: *entity,
+                        },
+                    );
+
+                    let effect_kinds: Vec<&RequestSpawnNodeKind> = RequestSpawnNodeKind::ALL
+                        .iter()
+                        .filter(|spawn_kind| preferences.ui.offers_node_kind(spawn_kind))
+                        .filter(|spawn_kind| spawn_kind.is_image_effect())
+                        .collect();
+
+                    if !effect_kinds.is_empty() {
+                        ContextMenuDivider::spawn(child_builder);
+                    }
+
+                    for spawn_kind in effect_kinds {
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("Apply {} to Selection", spawn_kind.name()),
+                            font.clone(),
+                            RequestApplyEffect {
+                                node_entity: *entity,
+                                spawn_kind: spawn_kind.clone(),
+                            },
+                        );
+                    }
+                });
+            }
+            UIContext::Comment(entity) => {
+                ec.with_children(|child_builder| {
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Change Color",
+                        font.clone(),
+                        RequestCycleCommentColor {
+                            comment_entity: *entity,
+                        },
+                    );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Delete",
+                        font.clone(),
+                        RemoveCommentEvent {
+                            comment_entity: *entity,
+                        },
+                    );
+                });
+            }
+            UIContext::Group(entity) => {
+                ec.with_children(|child_builder| {
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Change Color",
+                        font.clone(),
+                        RequestCycleGroupColor {
+                            group_entity: *entity,
+                        },
+                    );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Delete",
+                        font.clone(),
+                        RemoveGroupEvent {
+                            group_entity: *entity,
+                        },
+                    );
                 });
             }
             UIContext::InputPort(input_port_context) => {
@@ -216,6 +430,16 @@ impl ContextMenu {
                             port: input_port_context.port,
                         },
                     );
+
+                    ContextMenuEntry::spawn(
+                        child_builder,
+                        "Reset to Default",
+                        font.clone(),
+                        RequestResetInput {
+                            node: input_port_context.node,
+                            port: input_port_context.port,
+                        },
+                    );
                 });
             }
             UIContext::OutputPort(output_port_context) => {
@@ -236,10 +460,35 @@ impl ContextMenu {
                     MenuButton::File => {
                         ContextMenuEntry::spawn(child_builder, "New", font.clone(), NewProjectEvent);
 
-                        ContextMenuEntry::spawn(child_builder, "Save", font.clone(), SaveEvent);
+                        ContextMenuEntry::spawn(child_builder, "Save", font.clone(), SaveEvent::ToKnownPath);
+
+                        ContextMenuEntry::spawn(child_builder, "Save As", font.clone(), SaveEvent::Prompt);
 
                         ContextMenuEntry::spawn(child_builder, "Load", font.clone(), LoadEvent);
 
+                        ContextMenuEntry::spawn(child_builder, "Import Folder", font.clone(), ImportFolderEvent);
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            "Extract Selection to New Project",
+                            font.clone(),
+                            ExtractSelectionEvent,
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("Export Scale: {}x", project.render_scale()),
+                            font.clone(),
+                            SetRenderScale(project.next_render_scale()),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("Texture Format: {:?}", project.texture_format()),
+                            font.clone(),
+                            SetTextureFormat(project.next_texture_format()),
+                        );
+
                         ContextMenuEntry::spawn(child_builder, "Exit", font.clone(), ExitEvent);
                     }
                     MenuButton::Edit => {
@@ -252,9 +501,172 @@ impl ContextMenu {
                             PasteEvent::FromMenu,
                         );
 
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            "Paste Image",
+                            font.clone(),
+                            PasteImageEvent::FromMenu,
+                        );
+
                         ContextMenuEntry::spawn(child_builder, "Undo", font.clone(), RequestUndo);
 
                         ContextMenuEntry::spawn(child_builder, "Redo", font.clone(), RequestRedo);
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            "Reseed All",
+                            font.clone(),
+                            RequestReseedAll,
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            "History",
+                            font.clone(),
+                            ToggleHistoryPanel,
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            "Changes Since Save",
+                            font.clone(),
+                            ToggleDiffPanel,
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            "Isolate Selection",
+                            font.clone(),
+                            ToggleIsolateSelection,
+                        );
+                    }
+                    MenuButton::Preferences => {
+                        let node_defaults = &preferences.node_defaults;
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("Default Size: {}", node_defaults.texture_size),
+                            font.clone(),
+                            SetPreference::DefaultTextureSize(node_defaults.next_texture_size()),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("Default Format: {:?}", node_defaults.texture_format),
+                            font.clone(),
+                            SetPreference::DefaultTextureFormat(node_defaults.next_texture_format()),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("Default Shape: {}", node_defaults.shape.name()),
+                            font.clone(),
+                            SetPreference::DefaultShape(node_defaults.next_shape()),
+                        );
+
+                        ContextMenuDivider::spawn(child_builder);
+
+                        let ui = &preferences.ui;
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            match ui.preview_max_dimension {
+                                Some(max_dimension) => format!("Preview Size: {}", max_dimension),
+                                None => String::from("Preview Size: Full"),
+                            },
+                            font.clone(),
+                            SetPreference::PreviewMaxDimension(ui.next_preview_max_dimension()),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("Edge Style: {}", ui.edge_style.name()),
+                            font.clone(),
+                            SetPreference::EdgeStyle(ui.next_edge_style()),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("Orientation: {}", ui.orientation.name()),
+                            font.clone(),
+                            SetPreference::Orientation(ui.next_orientation()),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            if ui.node_shadows { "Node Shadows: On" } else { "Node Shadows: Off" },
+                            font.clone(),
+                            SetPreference::NodeShadows(!ui.node_shadows),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            if ui.show_process_time { "Process Time: Shown" } else { "Process Time: Hidden" },
+                            font.clone(),
+                            SetPreference::ShowProcessTime(!ui.show_process_time),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            match ui.max_tile_size {
+                                Some(max_tile_size) => format!("Max Tile Size: {}", max_tile_size),
+                                None => String::from("Max Tile Size: Off"),
+                            },
+                            font.clone(),
+                            SetPreference::MaxTileSize(ui.next_max_tile_size()),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            if ui.bridge_on_delete { "Bridge On Delete: On" } else { "Bridge On Delete: Off" },
+                            font.clone(),
+                            SetPreference::BridgeOnDelete(!ui.bridge_on_delete),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            match ui.max_concurrent_nodes {
+                                Some(max_nodes) => format!("Concurrent Nodes: {}", max_nodes),
+                                None => String::from("Concurrent Nodes: Unlimited"),
+                            },
+                            font.clone(),
+                            SetPreference::MaxConcurrentNodes(ui.next_max_concurrent_nodes()),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            if ui.embed_thumbnail { "Save Thumbnail: On" } else { "Save Thumbnail: Off" },
+                            font.clone(),
+                            SetPreference::EmbedThumbnail(!ui.embed_thumbnail),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            format!("Drag Threshold: {}px", ui.drag_threshold),
+                            font.clone(),
+                            SetPreference::DragThreshold(ui.next_drag_threshold()),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            if ui.right_drag_pan { "Right Drag Pans: On" } else { "Right Drag Pans: Off" },
+                            font.clone(),
+                            SetPreference::RightDragPan(!ui.right_drag_pan),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            if ui.arrange_ports_by_connection { "Port Order: By Connection" } else { "Port Order: Declared" },
+                            font.clone(),
+                            SetPreference::ArrangePortsByConnection(!ui.arrange_ports_by_connection),
+                        );
+
+                        ContextMenuEntry::spawn(
+                            child_builder,
+                            if ui.developer_nodes { "Developer Nodes: On" } else { "Developer Nodes: Off" },
+                            font.clone(),
+                            SetPreference::DeveloperNodes(!ui.developer_nodes),
+                        );
                     }
                 });
             }
@@ -338,11 +750,27 @@ impl ContextMenuDivider {
     }
 }
 
+// With right drag panning on, the menu waits for the button to come back up, and a drag past the threshold cancels it
 pub fn handle_uicontext_right_click(
     mut commands: Commands,
     mut mouse_events: EventReader<Pointer<Down>>,
+    mut up_events: EventReader<Pointer<Up>>,
+    mut drag_start_events: EventReader<ThresholdDragStart>,
     q_contextualized: Query<&UIContext>,
+    ui_preferences: Res<UiPreferences>,
+    mut pending_right_click: Local<Option<Entity>>,
 ) {
+    let right_drag_started = drag_start_events
+        .read()
+        .filter(|event| event.button == PointerButton::Secondary)
+        .count()
+        > 0;
+    let right_released = up_events
+        .read()
+        .filter(|event| event.button == PointerButton::Secondary)
+        .count()
+        > 0;
+
     let right_click_event = mouse_events
         .read()
         .filter(|event| {
@@ -355,14 +783,35 @@ pub fn handle_uicontext_right_click(
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
+    let right_clicked = right_click_event.map(|event| event.target);
+
+    let source = if ui_preferences.right_drag_pan {
+        if right_clicked.is_some() {
+            *pending_right_click = right_clicked;
+        }
+
+        if right_drag_started {
+            *pending_right_click = None;
+        }
+
+        if right_released {
+            pending_right_click.take()
+        } else {
+            None
+        }
+    } else {
+        *pending_right_click = None;
+        right_clicked
+    };
+
     // If there's no right-click event on an entity configured with UIContext, bail
-    let right_click_event = match right_click_event {
-        Some(event) => event,
+    let source = match source {
+        Some(source) => source,
         None => return,
     };
 
     commands.trigger(RequestOpenContextMenu {
-        source: right_click_event.target,
+        source,
         position_source: ContextMenuPositionSource::Cursor,
         position_offset: Vec2::ZERO,
     });
@@ -391,6 +840,8 @@ pub fn open_context_menu(
     q_ui_root: Query<Entity, With<UiRoot>>,
     q_window: Query<&Window, With<PrimaryWindow>>,
     q_transform: Query<&GlobalTransform>,
+    preferences: Preferences,
+    project: Res<Project>,
 ) {
     let window = match q_window.get_single() {
         Ok(w) => w,
@@ -441,11 +892,66 @@ pub fn open_context_menu(
                 world_position,
                 ctx,
                 fonts.deja_vu_sans.clone(),
+                &preferences,
             );
         });
     }
 }
 
+#[derive(Event, Clone, Debug)]
+pub struct RequestOpenPortDropMenu {
+    pub connect: ConnectOnSpawn,
+}
+
+// Unlike other menus, this one has no entity carrying its UIContext; it opens at the cursor
+pub fn open_port_drop_menu(
+    trigger: Trigger<RequestOpenPortDropMenu>,
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    q_camera: Query<(&Camera, &GlobalTransform)>,
+    q_context_menu: Query<Entity, With<ContextMenu>>,
+    q_ui_root: Query<Entity, With<UiRoot>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    preferences: Preferences,
+) {
+    let window = match q_window.get_single() {
+        Ok(w) => w,
+        Err(_) => return,
+    };
+
+    let cursor_position = match window.cursor_position() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let (camera, camera_transform) = q_camera.single();
+    let world_position = match camera.viewport_to_world(camera_transform, cursor_position) {
+        Some(p) => p.origin.truncate(),
+        None => return,
+    };
+
+    for old_context_menu_entity in q_context_menu.iter() {
+        commands.entity(old_context_menu_entity).despawn_recursive();
+    }
+
+    let ctx = UIContext::PortDrop(PortDropContext {
+        connect: trigger.event().connect.clone(),
+    });
+
+    let ui_root = q_ui_root.single();
+
+    commands.entity(ui_root).with_children(|child_builder| {
+        ContextMenu::spawn(
+            child_builder,
+            cursor_position,
+            world_position,
+            &ctx,
+            fonts.deja_vu_sans.clone(),
+            &preferences,
+        );
+    });
+}
+
 pub fn cancel_context_menu(
     mut commands: Commands,
     mut click_down_events: EventReader<Pointer<Down>>,
@@ -634,6 +1140,58 @@ fn detatch_output(
     }
 }
 
+#[derive(Event, Clone)]
+pub struct RequestResetInput {
+    pub node: Entity,
+    pub port: InputId,
+}
+
+// Sets an input back to the value a freshly spawned node starts with. Inputs driven by an edge are detatched instead,
+//  since removing the edge already restores the stored value.
+fn reset_input(
+    trigger: Trigger<RequestResetInput>,
+    mut commands: Commands,
+    q_nodes: Query<&NodeDisplay>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    node_defaults: Res<NodeDefaults>,
+) {
+    let pipeline = q_pipeline.single();
+    let target_port = trigger.event().port;
+
+    let target_node_index = match q_nodes.get(trigger.event().node) {
+        Ok(node_display) => node_display.index,
+        Err(_) => return,
+    };
+
+    if let Some(edge) = pipeline
+        .graph
+        .edges_directed(target_node_index, Direction::Incoming)
+        .find(|edge| edge.weight().to_field == target_port)
+    {
+        commands.trigger(RemoveEdgeEvent {
+            start_node: edge.weight().from_node,
+            start_id: edge.weight().from_field,
+            end_node: edge.weight().to_node,
+            end_id: edge.weight().to_field,
+        });
+        return;
+    }
+
+    if let Some(node) = pipeline.graph.node_weight(target_node_index) {
+        if let (Some(old_value), Some(new_value)) = (
+            node.kind.get_input(target_port),
+            input_default(&node.kind, target_port, &node_defaults),
+        ) {
+            commands.trigger(SetInputFieldEvent {
+                node: target_node_index,
+                input_id: target_port,
+                old_value,
+                new_value,
+            });
+        }
+    }
+}
+
 #[derive(Event, Clone, Debug)]
 pub struct RequestRemoveNode {
     pub node_entity: Entity,
@@ -660,6 +1218,190 @@ pub fn handle_remove_node_request(
     }
 
     for node_entity in nodes_to_remove {
-        commands.trigger(RemoveNodeEvent { node_entity });
+        commands.trigger(RemoveNodeEvent { node_entity, bridge: true });
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct RequestSelectConnected {
+    pub node_entity: Entity,
+}
+
+// Selects the whole subgraph the node belongs to, adding to the selection while Shift is held
+pub fn handle_select_connected_request(
+    trigger: Trigger<RequestSelectConnected>,
+    mut commands: Commands,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_node_display: Query<(Entity, &NodeDisplay)>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    let graph = &q_pipeline.single().graph;
+
+    let Ok((_, node_display)) = q_node_display.get(trigger.event().node_entity) else {
+        return;
+    };
+
+    let connected = connected_nodes(graph, node_display.index);
+
+    let shift_pressed =
+        keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+
+    for (entity, node_display) in q_node_display.iter() {
+        if connected.contains(&node_display.index) {
+            commands.entity(entity).insert(Selected);
+        } else if !shift_pressed {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct RequestSelectSameKind {
+    pub node_entity: Entity,
+}
+
+// Selects every node of the same kind as this one, e.g. all the Color nodes, adding to the selection while Shift is held
+pub fn handle_select_same_kind_request(
+    trigger: Trigger<RequestSelectSameKind>,
+    mut commands: Commands,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_node_display: Query<(Entity, &NodeDisplay)>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    let graph = &q_pipeline.single().graph;
+
+    let Ok((_, node_display)) = q_node_display.get(trigger.event().node_entity) else {
+        return;
+    };
+
+    let kind_name = node_kind_name(&graph[node_display.index].kind);
+
+    let shift_pressed =
+        keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+
+    for (entity, node_display) in q_node_display.iter() {
+        let same_kind = graph
+            .node_weight(node_display.index)
+            .is_some_and(|node| node_kind_name(&node.kind) == kind_name);
+
+        if same_kind {
+            commands.entity(entity).insert(Selected);
+        } else if !shift_pressed {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct RequestSelectDirected {
+    pub node_entity: Entity,
+    // Incoming selects upstream dependencies, Outgoing downstream consumers
+    pub direction: Direction,
+}
+
+pub fn handle_select_directed_request(
+    trigger: Trigger<RequestSelectDirected>,
+    mut commands: Commands,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_node_display: Query<(Entity, &NodeDisplay)>,
+    q_edge_lines: Query<(Entity, &EdgeLine)>,
+    q_output_ports: Query<&OutputPort>,
+    q_input_ports: Query<&InputPort>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    let graph = &q_pipeline.single().graph;
+
+    let Ok((_, node_display)) = q_node_display.get(trigger.event().node_entity) else {
+        return;
+    };
+
+    let reachable = directed_reachable_nodes(graph, node_display.index, trigger.event().direction);
+
+    let shift_pressed =
+        keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+
+    let mut reachable_entities: Vec<Entity> = Vec::new();
+    for (entity, node_display) in q_node_display.iter() {
+        if reachable.contains(&node_display.index) {
+            commands.entity(entity).insert(Selected);
+            reachable_entities.push(entity);
+        } else if !shift_pressed {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+
+    // within a transitive closure, every edge between two of its nodes lies on a traversed path
+    for (line_entity, edge_line) in q_edge_lines.iter() {
+        if let (Ok(output), Ok(input)) = (
+            q_output_ports.get(edge_line.start_port),
+            q_input_ports.get(edge_line.end_port),
+        ) {
+            if reachable_entities.contains(&output.node_entity)
+                && reachable_entities.contains(&input.node_entity)
+            {
+                commands.entity(line_entity).insert(HighlightedEdge);
+            }
+        }
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct RequestApplyEffect {
+    pub node_entity: Entity,
+    pub spawn_kind: RequestSpawnNodeKind,
+}
+
+const APPLIED_EFFECT_OFFSET: Vec2 = Vec2::new(NODE_WIDTH + 100., 0.);
+
+// Adds an effect node downstream of each source and wires it up.
+// Everything is triggered this frame, so the whole batch is a single undo step.
+pub fn handle_apply_effect_request(
+    trigger: Trigger<RequestApplyEffect>,
+    mut commands: Commands,
+    query_selected: Query<Entity, With<Selected>>,
+    query_node_display: Query<(&NodeDisplay, &Transform)>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+) {
+    let graph = &q_pipeline.single().graph;
+    let spawn_kind = &trigger.event().spawn_kind;
+
+    let source_nodes: Vec<Entity> = if query_selected.contains(trigger.event().node_entity) {
+        query_selected.iter().collect()
+    } else {
+        vec![trigger.event().node_entity]
+    };
+
+    let effect_inputs = spawn_kind.input_prototypes();
+
+    for source_entity in source_nodes {
+        let (node_display, transform) = match query_node_display.get(source_entity) {
+            Ok(node) => node,
+            Err(_) => continue,
+        };
+
+        let source_node = match graph.node_weight(node_display.index) {
+            Some(node) => node,
+            None => continue,
+        };
+
+        let maybe_source_output = source_node.kind.output_fields().iter().find_map(|&output_id| {
+            let field = source_node.kind.get_output(output_id)?;
+            effect_inputs
+                .iter()
+                .any(|(_, input)| can_convert_field(&field, input))
+                .then_some((output_id, field))
+        });
+
+        if let Some((output_id, field)) = maybe_source_output {
+            commands.trigger(AddNodeEvent::FromKind(AddNodeKind {
+                position: transform.translation.truncate() + APPLIED_EFFECT_OFFSET,
+                spawn_kind: spawn_kind.clone(),
+                connect: Some(ConnectOnSpawn::FromOutput {
+                    node: source_entity,
+                    output_id,
+                    field,
+                }),
+            }));
+        }
     }
-}
\ No newline at end of file
+}
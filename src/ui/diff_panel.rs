@@ -0,0 +1,289 @@
+use bevy::{
+    color::palettes::tailwind::{BLUE_400, BLUE_900, GREEN_400, GREEN_900, RED_400, SLATE_900},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_mod_picking::prelude::Pickable;
+use uuid::Uuid;
+
+use crate::{
+    asset::{FontAssets, NodeDisplayMaterial},
+    graph::{DisjointPipelineGraph, GraphWasUpdated, SerializableEdge},
+    nodes::{
+        NodeDisplay, NodeId, NodeTrait, SerializableGraphNode, SerializableGraphNodeKind,
+        SerializableInputId, SerializableOutputId,
+    },
+    validation::NODE_BACKGROUND_COLOR,
+    ApplicationState,
+};
+
+use super::menu_bar::Project;
+
+const NODE_ADDED_COLOR: Srgba = GREEN_900;
+const NODE_MODIFIED_COLOR: Srgba = BLUE_900;
+
+pub struct DiffPanelPlugin;
+
+impl Plugin for DiffPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiffPanelState>();
+        app.add_systems(
+            Update,
+            update_diff_panel.run_if(in_state(ApplicationState::MainLoop)),
+        );
+
+        app.observe(toggle_diff_panel);
+        app.observe(mark_diff_stale);
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct ToggleDiffPanel;
+
+#[derive(Resource, Default)]
+struct DiffPanelState {
+    visible: bool,
+    stale: bool,
+}
+
+// Summarizes what changed since the last save, docked to the top left of the node edit area.
+// While it is open, added and modified nodes are tinted.
+#[derive(Component)]
+pub struct DiffPanel;
+
+impl DiffPanel {
+    pub fn spawn(commands: &mut Commands) -> Entity {
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.),
+                    top: Val::Px(10.),
+                    width: Val::Px(240.),
+                    max_height: Val::Percent(60.),
+                    display: Display::None,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.),
+                    padding: UiRect::all(Val::Px(8.)),
+                    overflow: Overflow::clip_y(),
+                    ..default()
+                },
+                background_color: SLATE_900.with_alpha(0.9).into(),
+                border_radius: BorderRadius::all(Val::Px(4.)),
+                ..default()
+            })
+            .insert(Name::new("Diff Panel"))
+            .insert(DiffPanel)
+            .insert(Pickable::IGNORE)
+            .id()
+    }
+}
+
+#[derive(Default)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<Entity>,
+    pub modified_nodes: Vec<Entity>,
+    // removed nodes no longer have anything to highlight, so only their kinds are kept
+    pub removed_nodes: Vec<&'static str>,
+    pub added_edges: usize,
+    pub removed_edges: usize,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.modified_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges == 0
+            && self.removed_edges == 0
+    }
+}
+
+type EdgeKey = (Uuid, SerializableOutputId, Uuid, SerializableInputId);
+
+fn edge_key(edge: SerializableEdge) -> EdgeKey {
+    (edge.from_node_id, edge.from_field, edge.to_node_id, edge.to_field)
+}
+
+// The serialized contents of a node kind with its entity blanked out, since entities differ between sessions
+fn kind_fingerprint(kind: &SerializableGraphNodeKind) -> Option<Vec<u8>> {
+    let mut node = SerializableGraphNode {
+        id: Uuid::nil(),
+        position: Vec3::ZERO,
+        kind: kind.clone(),
+        cacheable: true,
+        custom_shader: None,
+    };
+    node.set_entity(Entity::PLACEHOLDER);
+
+    rmp_serde::to_vec(&node.kind).ok()
+}
+
+pub fn diff_against_saved(
+    pipeline: &DisjointPipelineGraph,
+    q_node_ids: &Query<(Entity, &NodeId, &Handle<NodeDisplayMaterial>), With<NodeDisplay>>,
+    project: &Project,
+) -> GraphDiff {
+    let (saved_nodes, saved_edges) = project.saved_graph();
+    let saved_nodes: HashMap<Uuid, &SerializableGraphNode> =
+        saved_nodes.iter().map(|node| (node.id, node)).collect();
+
+    let mut diff = GraphDiff::default();
+    let mut entity_to_id: HashMap<Entity, Uuid> = HashMap::new();
+
+    for node in pipeline.graph.node_weights() {
+        let entity = node.kind.entity();
+        let Ok((_, node_id, _)) = q_node_ids.get(entity) else {
+            continue;
+        };
+
+        entity_to_id.insert(entity, node_id.0);
+
+        match saved_nodes.get(&node_id.0) {
+            None => diff.added_nodes.push(entity),
+            Some(saved_node) => {
+                let current = kind_fingerprint(&SerializableGraphNodeKind::from(&node.kind));
+                if current != kind_fingerprint(&saved_node.kind) {
+                    diff.modified_nodes.push(entity);
+                }
+            }
+        }
+    }
+
+    let current_ids: HashSet<Uuid> = entity_to_id.values().copied().collect();
+    diff.removed_nodes = saved_nodes
+        .values()
+        .filter(|node| !current_ids.contains(&node.id))
+        .map(|node| node.kind_name())
+        .collect();
+
+    let current_edges: HashSet<EdgeKey> = pipeline
+        .graph
+        .edge_weights()
+        .filter_map(|edge| {
+            let from_id = *entity_to_id.get(&edge.from_node)?;
+            let to_id = *entity_to_id.get(&edge.to_node)?;
+            Some(edge_key(SerializableEdge::from_edge(edge, from_id, to_id)))
+        })
+        .collect();
+    let saved_edges: HashSet<EdgeKey> = saved_edges.iter().cloned().map(edge_key).collect();
+
+    diff.added_edges = current_edges.difference(&saved_edges).count();
+    diff.removed_edges = saved_edges.difference(&current_edges).count();
+
+    diff
+}
+
+fn toggle_diff_panel(_trigger: Trigger<ToggleDiffPanel>, mut state: ResMut<DiffPanelState>) {
+    state.visible = !state.visible;
+    state.stale = true;
+}
+
+fn mark_diff_stale(_trigger: Trigger<GraphWasUpdated>, mut state: ResMut<DiffPanelState>) {
+    if state.visible {
+        state.stale = true;
+    }
+}
+
+fn update_diff_panel(
+    mut commands: Commands,
+    mut state: ResMut<DiffPanelState>,
+    project: Res<Project>,
+    fonts: Res<FontAssets>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_nodes: Query<(Entity, &NodeId, &Handle<NodeDisplayMaterial>), With<NodeDisplay>>,
+    mut q_panel: Query<(Entity, &mut Style), With<DiffPanel>>,
+    mut materials: ResMut<Assets<NodeDisplayMaterial>>,
+) {
+    // saving replaces the snapshot without touching the graph
+    if project.is_changed() && state.visible {
+        state.stale = true;
+    }
+
+    if !state.stale {
+        return;
+    }
+    state.stale = false;
+
+    let Ok((panel_entity, mut style)) = q_panel.get_single_mut() else {
+        return;
+    };
+
+    let diff = if state.visible {
+        diff_against_saved(q_pipeline.single(), &q_nodes, &project)
+    } else {
+        GraphDiff::default()
+    };
+
+    for (node_entity, _, material_handle) in q_nodes.iter() {
+        let background_color: LinearRgba = if diff.added_nodes.contains(&node_entity) {
+            NODE_ADDED_COLOR.into()
+        } else if diff.modified_nodes.contains(&node_entity) {
+            NODE_MODIFIED_COLOR.into()
+        } else {
+            NODE_BACKGROUND_COLOR.into()
+        };
+
+        let needs_update = materials
+            .get(material_handle.id())
+            .map_or(false, |material| material.background_color != background_color);
+
+        if needs_update {
+            if let Some(material) = materials.get_mut(material_handle.id()) {
+                material.background_color = background_color;
+            }
+        }
+    }
+
+    style.display = if state.visible {
+        Display::Flex
+    } else {
+        Display::None
+    };
+
+    commands.entity(panel_entity).despawn_descendants();
+
+    if !state.visible {
+        return;
+    }
+
+    let mut lines: Vec<(String, Color)> = vec![(String::from("Changes Since Save"), Color::WHITE)];
+
+    if diff.is_empty() {
+        lines.push((String::from("No changes"), Color::WHITE.with_alpha(0.6)));
+    }
+
+    if !diff.added_nodes.is_empty() {
+        lines.push((format!("+ {} node(s) added", diff.added_nodes.len()), GREEN_400.into()));
+    }
+
+    if !diff.modified_nodes.is_empty() {
+        lines.push((format!("~ {} node(s) modified", diff.modified_nodes.len()), BLUE_400.into()));
+    }
+
+    for kind_name in diff.removed_nodes.iter() {
+        lines.push((format!("- {} node removed", kind_name), RED_400.into()));
+    }
+
+    if diff.added_edges > 0 || diff.removed_edges > 0 {
+        lines.push((
+            format!("Edges: +{} / -{}", diff.added_edges, diff.removed_edges),
+            Color::WHITE,
+        ));
+    }
+
+    commands.entity(panel_entity).with_children(|child_builder| {
+        for (index, (text, color)) in lines.into_iter().enumerate() {
+            child_builder
+                .spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font: fonts.deja_vu_sans.clone(),
+                        font_size: if index == 0 { 16. } else { 14. },
+                        color,
+                    },
+                ))
+                .insert(Pickable::IGNORE);
+        }
+    });
+}
@@ -0,0 +1,72 @@
+use bevy::{color::palettes::tailwind::SLATE_400, prelude::*};
+use bevy_mod_picking::prelude::Pickable;
+
+use crate::{graph::DisjointPipelineGraph, ApplicationState};
+
+pub struct EmptyHintPlugin;
+
+impl Plugin for EmptyHintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_empty_hint.run_if(in_state(ApplicationState::MainLoop)),
+        );
+    }
+}
+
+// Centered guidance in the node edit area, only shown while the graph has no nodes
+#[derive(Component)]
+pub struct EmptyHint;
+
+impl EmptyHint {
+    pub fn spawn(commands: &mut Commands, font: Handle<Font>) -> Entity {
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    display: Display::None,
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(Name::new("Empty Hint"))
+            .insert(EmptyHint)
+            .insert(Pickable::IGNORE)
+            .with_children(|child_builder| {
+                child_builder
+                    .spawn(TextBundle::from_section(
+                        "Right-click to add a node",
+                        TextStyle {
+                            font,
+                            font_size: 20.,
+                            color: SLATE_400.into(),
+                        },
+                    ))
+                    .insert(Pickable::IGNORE);
+            })
+            .id()
+    }
+}
+
+fn update_empty_hint(
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    mut q_hint: Query<&mut Style, With<EmptyHint>>,
+) {
+    let (Ok(pipeline), Ok(mut style)) = (q_pipeline.get_single(), q_hint.get_single_mut()) else {
+        return;
+    };
+
+    let display = if pipeline.graph.node_count() == 0 {
+        Display::Flex
+    } else {
+        Display::None
+    };
+
+    if style.display != display {
+        style.display = display;
+    }
+}
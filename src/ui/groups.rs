@@ -0,0 +1,460 @@
+use bevy::{
+    color::palettes::tailwind::{AMBER_700, EMERALD_700, ROSE_700, SKY_700, SLATE_600},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_cosmic_edit::*;
+use bevy_mod_picking::{
+    events::{Drag, DragEnd, DragStart, Pointer},
+    prelude::{Pickable, PointerButton},
+    PickableBundle,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset::{NODE_TEXTURE_DISPLAY_DIMENSION, NODE_TITLE_BAR_SIZE},
+    events::{
+        group_events::{AddGroupEvent, SetGroupEvent},
+        node_events::UndoableDragNodeEvent,
+        UndoableEvent,
+    },
+    nodes::{NodeDisplay, Selected},
+    ApplicationState,
+};
+
+use super::context_menu::UIContext;
+
+pub struct GroupsPlugin;
+
+impl Plugin for GroupsPlugin {
+    fn build(&self, app: &mut App) {
+        // sprite editors are already focused by the comments plugin's change_active_editor_sprite
+        app.add_systems(
+            Update,
+            ((handle_group_drag, commit_group_title), update_group_layout)
+                .chain()
+                .run_if(in_state(ApplicationState::MainLoop)),
+        );
+
+        app.observe(add_group_around_selection);
+        app.observe(cycle_group_color);
+    }
+}
+
+pub const GROUP_COLORS: [Srgba; 5] = [SLATE_600, SKY_700, EMERALD_700, AMBER_700, ROSE_700];
+pub const GROUP_DEFAULT_SIZE: Vec2 = Vec2::new(480., 320.);
+const GROUP_MIN_SIZE: Vec2 = Vec2::new(160., 100.);
+// behind comments and nodes, in front of the canvas
+const GROUP_Z: f32 = -600.;
+const GROUP_HEADER_HEIGHT: f32 = 28.;
+const GROUP_PADDING: f32 = 24.;
+const GROUP_BODY_ALPHA: f32 = 0.25;
+const GROUP_RESIZE_HANDLE_SIZE: f32 = 14.;
+const GROUP_TITLE_SIZE: f32 = 16.;
+const GROUP_TITLE_MAX_WIDTH: f32 = 240.;
+
+// Everything about a group that is saved and undone. Position is the center of the frame, header included.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroupData {
+    pub position: Vec2,
+    pub size: Vec2,
+    pub title: String,
+    pub color: LinearRgba,
+}
+
+impl GroupData {
+    // A fresh group with its top left corner at the given point
+    pub fn new(top_left: Vec2) -> Self {
+        Self {
+            position: top_left + Vec2::new(GROUP_DEFAULT_SIZE.x, -GROUP_DEFAULT_SIZE.y) / 2.,
+            size: GROUP_DEFAULT_SIZE,
+            title: String::from("Group"),
+            color: GROUP_COLORS[0].into(),
+        }
+    }
+
+    // A group just large enough to hold the given node centers, with room for the header
+    pub fn around(node_positions: &[Vec2]) -> Option<Self> {
+        let half_node = Vec2::new(NODE_TEXTURE_DISPLAY_DIMENSION, NODE_TEXTURE_DISPLAY_DIMENSION + NODE_TITLE_BAR_SIZE) / 2.;
+
+        let min = node_positions.iter().copied().reduce(Vec2::min)? - half_node - Vec2::splat(GROUP_PADDING);
+        let max = node_positions.iter().copied().reduce(Vec2::max)? + half_node
+            + Vec2::new(GROUP_PADDING, GROUP_PADDING + GROUP_HEADER_HEIGHT);
+
+        Some(Self {
+            position: (min + max) / 2.,
+            size: (max - min).max(GROUP_MIN_SIZE),
+            ..Self::new(Vec2::ZERO)
+        })
+    }
+
+    // Nodes belong to a group when their center is inside its frame
+    pub fn contains(&self, point: Vec2) -> bool {
+        let half_size = self.size / 2.;
+        let offset = (point - self.position).abs();
+        offset.x <= half_size.x && offset.y <= half_size.y
+    }
+
+    fn title_size(&self) -> Vec2 {
+        let width = (self.size.x - GROUP_PADDING * 2. - GROUP_HEADER_HEIGHT).clamp(1., GROUP_TITLE_MAX_WIDTH);
+        Vec2::new(width, GROUP_HEADER_HEIGHT - 4.)
+    }
+}
+
+// A titled frame drawn behind a set of nodes. Like comments, groups never enter the processing graph.
+// Removed groups lose this component but keep their entity, so undo can restore them.
+#[derive(Component)]
+pub struct Group {
+    pub data: GroupData,
+}
+
+#[derive(Component)]
+pub struct GroupParts {
+    pub header: Entity,
+    pub title_editor: Entity,
+    pub resize_handle: Entity,
+}
+
+#[derive(Component)]
+pub struct GroupHeader {
+    pub group: Entity,
+}
+
+#[derive(Component)]
+pub struct GroupTitleEditor {
+    pub group: Entity,
+}
+
+#[derive(Component)]
+pub struct GroupResizeHandle {
+    pub group: Entity,
+}
+
+impl Group {
+    pub fn spawn(commands: &mut Commands, font_system: &mut CosmicFontSystem, data: GroupData) -> Entity {
+        let attrs = Attrs::new().color(Color::WHITE.to_cosmic());
+        let metrics = Metrics::new(GROUP_TITLE_SIZE, GROUP_TITLE_SIZE * 1.2);
+
+        let group_entity = commands.spawn_empty().id();
+
+        // the header is the grab area; the body lets clicks through so box selection still works inside a group
+        let header = commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: data.color.into(),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., 0., 0.1),
+                ..default()
+            })
+            .insert(PickableBundle::default())
+            .insert(UIContext::Group(group_entity))
+            .insert(GroupHeader { group: group_entity })
+            .id();
+
+        let title_editor = commands
+            .spawn((
+                CosmicEditBundle {
+                    buffer: CosmicBuffer::new(font_system, metrics).with_text(font_system, &data.title, attrs),
+                    max_lines: MaxLines(1),
+                    cursor_color: CursorColor(Color::WHITE),
+                    selection_color: SelectionColor(Color::linear_rgba(0.3, 0.3, 0.7, 0.5)),
+                    fill_color: CosmicBackgroundColor(data.color.into()),
+                    mode: CosmicWrap::Wrap,
+                    ..default()
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(data.title_size()),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .insert(GroupTitleEditor { group: group_entity })
+            .id();
+
+        let resize_handle = commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::WHITE.with_alpha(0.3),
+                    custom_size: Some(Vec2::splat(GROUP_RESIZE_HANDLE_SIZE)),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(PickableBundle::default())
+            .insert(GroupResizeHandle { group: group_entity })
+            .id();
+
+        commands
+            .entity(group_entity)
+            .insert(SpriteBundle {
+                transform: Transform::from_translation(data.position.extend(GROUP_Z)),
+                ..default()
+            })
+            .insert(Pickable::IGNORE)
+            .insert(Name::new("Group"))
+            .insert(GroupParts {
+                header,
+                title_editor,
+                resize_handle,
+            })
+            .insert(Group { data })
+            .push_children(&[header, title_editor, resize_handle]);
+
+        group_entity
+    }
+}
+
+// Keeps the sprites and title in line with the group's data, whether it changed from a drag, an undo or a load
+fn update_group_layout(
+    mut commands: Commands,
+    mut font_system: ResMut<CosmicFontSystem>,
+    focused: Res<FocusedWidget>,
+    mut q_groups: Query<(&Group, &GroupParts, &mut Sprite, &mut Transform), Changed<Group>>,
+    mut q_parts: Query<(&mut Sprite, &mut Transform), Without<Group>>,
+    mut q_buffers: Query<&mut CosmicBuffer>,
+) {
+    for (group, parts, mut sprite, mut transform) in q_groups.iter_mut() {
+        let data = &group.data;
+        let header_y = (data.size.y - GROUP_HEADER_HEIGHT) / 2.;
+
+        sprite.color = Color::from(data.color).with_alpha(GROUP_BODY_ALPHA);
+        sprite.custom_size = Some(data.size);
+        transform.translation = data.position.extend(transform.translation.z);
+
+        if let Ok((mut header_sprite, mut header_transform)) = q_parts.get_mut(parts.header) {
+            header_sprite.color = data.color.into();
+            header_sprite.custom_size = Some(Vec2::new(data.size.x, GROUP_HEADER_HEIGHT));
+            header_transform.translation = Vec3::new(0., header_y, 0.1);
+        }
+
+        if let Ok((mut title_sprite, mut title_transform)) = q_parts.get_mut(parts.title_editor) {
+            let title_size = data.title_size();
+            title_sprite.custom_size = Some(title_size);
+            title_transform.translation =
+                Vec3::new((title_size.x - data.size.x) / 2. + GROUP_PADDING / 2., header_y, 0.2);
+        }
+
+        if let Ok((_, mut handle_transform)) = q_parts.get_mut(parts.resize_handle) {
+            let corner = Vec2::new(data.size.x, -data.size.y) / 2.;
+            let inset = Vec2::new(-GROUP_RESIZE_HANDLE_SIZE, GROUP_RESIZE_HANDLE_SIZE) / 2.;
+            handle_transform.translation = (corner + inset).extend(0.2);
+        }
+
+        commands
+            .entity(parts.title_editor)
+            .insert(CosmicBackgroundColor(data.color.into()));
+
+        // never clobber a title that is being typed
+        if focused.0 != Some(parts.title_editor) {
+            if let Ok(mut buffer) = q_buffers.get_mut(parts.title_editor) {
+                if buffer.get_text() != data.title {
+                    buffer.set_text(&mut font_system, &data.title, Attrs::new().color(Color::WHITE.to_cosmic()));
+                }
+            }
+        }
+    }
+}
+
+enum GroupDragKind {
+    // the nodes inside the group when the drag started, moved along with it
+    Move(HashMap<Entity, UndoableDragNodeEvent>),
+    Resize,
+}
+
+// Dragging the header moves a group and its nodes, dragging the corner handle resizes it.
+// Either is one undo step, since every event fired on drag end lands in the same frame.
+fn handle_group_drag(
+    mut commands: Commands,
+    mut drag_start_events: EventReader<Pointer<DragStart>>,
+    mut drag_events: EventReader<Pointer<Drag>>,
+    mut drag_end_events: EventReader<Pointer<DragEnd>>,
+    camera_query: Query<&OrthographicProjection>,
+    q_headers: Query<&GroupHeader>,
+    q_handles: Query<&GroupResizeHandle>,
+    mut q_groups: Query<&mut Group>,
+    mut q_nodes: Query<(Entity, &mut Transform), With<NodeDisplay>>,
+    mut drag_info: Local<Option<(Entity, GroupDragKind, GroupData)>>,
+) {
+    let camera_scale = camera_query.single().scale;
+
+    for event in drag_start_events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+
+        let target = if let Ok(header) = q_headers.get(event.target) {
+            Some((header.group, true))
+        } else if let Ok(handle) = q_handles.get(event.target) {
+            Some((handle.group, false))
+        } else {
+            None
+        };
+
+        if let Some((group_entity, is_move)) = target {
+            if let Ok(group) = q_groups.get(group_entity) {
+                let kind = if is_move {
+                    let members = q_nodes
+                        .iter()
+                        .filter(|(_, transform)| group.data.contains(transform.translation.truncate()))
+                        .map(|(entity, transform)| {
+                            (
+                                entity,
+                                UndoableDragNodeEvent {
+                                    node_entity: entity,
+                                    old_position: transform.translation,
+                                    new_position: transform.translation,
+                                },
+                            )
+                        })
+                        .collect();
+                    GroupDragKind::Move(members)
+                } else {
+                    GroupDragKind::Resize
+                };
+
+                *drag_info = Some((group_entity, kind, group.data.clone()));
+            }
+        }
+    }
+
+    for event in drag_events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+
+        let Some((group_entity, kind, _)) = (*drag_info).as_mut() else {
+            continue;
+        };
+
+        let Ok(mut group) = q_groups.get_mut(*group_entity) else {
+            continue;
+        };
+
+        let delta = Vec2::new(event.delta.x, -event.delta.y) * camera_scale;
+
+        match kind {
+            GroupDragKind::Move(members) => {
+                group.data.position += delta;
+
+                for (node_entity, drag_event) in members.iter_mut() {
+                    if let Ok((_, mut transform)) = q_nodes.get_mut(*node_entity) {
+                        transform.translation += delta.extend(0.);
+                        drag_event.new_position = transform.translation;
+                    }
+                }
+            }
+            GroupDragKind::Resize => {
+                // the top left corner stays put while the bottom right follows the pointer
+                let old_size = group.data.size;
+                let new_size = (old_size + Vec2::new(delta.x, -delta.y)).max(GROUP_MIN_SIZE);
+                let growth = new_size - old_size;
+
+                group.data.size = new_size;
+                group.data.position += Vec2::new(growth.x, -growth.y) / 2.;
+            }
+        }
+    }
+
+    for event in drag_end_events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+
+        if let Some((group_entity, kind, old_data)) = drag_info.take() {
+            if let GroupDragKind::Move(members) = kind {
+                for drag_event in members.into_values() {
+                    if drag_event.old_position != drag_event.new_position {
+                        commands.trigger(UndoableEvent::DragNode(drag_event));
+                    }
+                }
+            }
+
+            if let Ok(group) = q_groups.get(group_entity) {
+                commands.trigger(SetGroupEvent {
+                    group_entity,
+                    old_data,
+                    new_data: group.data.clone(),
+                });
+            }
+        }
+    }
+}
+
+// A typed title is recorded once the editor loses focus and hands its text back to the buffer
+fn commit_group_title(
+    mut commands: Commands,
+    focused: Res<FocusedWidget>,
+    q_editors: Query<(Entity, &CosmicBuffer, &GroupTitleEditor), (Changed<CosmicBuffer>, Without<CosmicEditor>)>,
+    q_groups: Query<&Group>,
+) {
+    for (editor_entity, buffer, title_editor) in q_editors.iter() {
+        if focused.0 == Some(editor_entity) {
+            continue;
+        }
+
+        if let Ok(group) = q_groups.get(title_editor.group) {
+            let title = buffer.get_text();
+
+            if title != group.data.title {
+                commands.trigger(SetGroupEvent {
+                    group_entity: title_editor.group,
+                    old_data: group.data.clone(),
+                    new_data: GroupData {
+                        title,
+                        ..group.data.clone()
+                    },
+                });
+            }
+        }
+    }
+}
+
+// Wraps the selected nodes when there are any, otherwise drops an empty group at the cursor
+#[derive(Event, Clone)]
+pub struct RequestAddGroup {
+    pub position: Vec2,
+}
+
+fn add_group_around_selection(
+    trigger: Trigger<RequestAddGroup>,
+    mut commands: Commands,
+    q_selected_nodes: Query<&Transform, (With<NodeDisplay>, With<Selected>)>,
+) {
+    let node_positions: Vec<Vec2> = q_selected_nodes
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .collect();
+
+    let data = GroupData::around(&node_positions).unwrap_or_else(|| GroupData::new(trigger.event().position));
+
+    commands.trigger(AddGroupEvent { data });
+}
+
+#[derive(Event, Clone)]
+pub struct RequestCycleGroupColor {
+    pub group_entity: Entity,
+}
+
+fn cycle_group_color(trigger: Trigger<RequestCycleGroupColor>, mut commands: Commands, q_groups: Query<&Group>) {
+    let group_entity = trigger.event().group_entity;
+
+    if let Ok(group) = q_groups.get(group_entity) {
+        let next_index = GROUP_COLORS
+            .iter()
+            .position(|color| LinearRgba::from(*color) == group.data.color)
+            .map(|index| (index + 1) % GROUP_COLORS.len())
+            .unwrap_or(0);
+
+        commands.trigger(SetGroupEvent {
+            group_entity,
+            old_data: group.data.clone(),
+            new_data: GroupData {
+                color: GROUP_COLORS[next_index].into(),
+                ..group.data.clone()
+            },
+        });
+    }
+}
@@ -0,0 +1,148 @@
+use bevy::{
+    color::palettes::tailwind::{BLUE_600, SLATE_900},
+    prelude::*,
+};
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::{On, Pickable},
+};
+
+use crate::{
+    asset::FontAssets,
+    events::{describe_events, HistoricalActions, RequestHistoryJump},
+    ApplicationState,
+};
+
+pub struct HistoryPanelPlugin;
+
+impl Plugin for HistoryPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_history_panel.run_if(in_state(ApplicationState::MainLoop)),
+        );
+
+        app.observe(toggle_history_panel);
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct ToggleHistoryPanel;
+
+// Lists every undo step, docked to the right of the node edit area. Clicking a row jumps there.
+#[derive(Component)]
+pub struct HistoryPanel;
+
+impl HistoryPanel {
+    pub fn spawn(commands: &mut Commands) -> Entity {
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.),
+                    top: Val::Px(10.),
+                    width: Val::Px(240.),
+                    max_height: Val::Percent(60.),
+                    display: Display::None,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.),
+                    padding: UiRect::all(Val::Px(8.)),
+                    overflow: Overflow::clip_y(),
+                    ..default()
+                },
+                background_color: SLATE_900.with_alpha(0.9).into(),
+                border_radius: BorderRadius::all(Val::Px(4.)),
+                ..default()
+            })
+            .insert(Name::new("History Panel"))
+            .insert(HistoryPanel)
+            .id()
+    }
+}
+
+fn toggle_history_panel(
+    _trigger: Trigger<ToggleHistoryPanel>,
+    mut q_panel: Query<&mut Style, With<HistoryPanel>>,
+) {
+    if let Ok(mut style) = q_panel.get_single_mut() {
+        style.display = match style.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+fn update_history_panel(
+    mut commands: Commands,
+    history: Res<HistoricalActions>,
+    fonts: Res<FontAssets>,
+    q_panel: Query<Entity, With<HistoryPanel>>,
+) {
+    if !history.is_changed() {
+        return;
+    }
+
+    let Ok(panel_entity) = q_panel.get_single() else {
+        return;
+    };
+
+    let font = fonts.deja_vu_sans.clone();
+
+    commands.entity(panel_entity).despawn_descendants();
+
+    commands.entity(panel_entity).with_children(|child_builder| {
+        child_builder
+            .spawn(TextBundle::from_section(
+                "History",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 16.,
+                    color: Color::WHITE,
+                },
+            ))
+            .insert(Pickable::IGNORE);
+
+        // index 0 is the state before any recorded action
+        let labels = std::iter::once(String::from("Start"))
+            .chain(history.actions().iter().map(|events| describe_events(events)));
+
+        for (index, label) in labels.enumerate() {
+            let is_current = index == history.current_index();
+            let is_undone = index > history.current_index();
+
+            child_builder
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+                        ..default()
+                    },
+                    background_color: if is_current {
+                        BLUE_600.into()
+                    } else {
+                        Color::NONE.into()
+                    },
+                    border_radius: BorderRadius::all(Val::Px(4.)),
+                    ..default()
+                })
+                .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+                    commands.trigger(RequestHistoryJump { index });
+                }))
+                .with_children(|row_builder| {
+                    row_builder
+                        .spawn(TextBundle::from_section(
+                            label,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 14.,
+                                color: if is_undone {
+                                    Color::WHITE.with_alpha(0.4)
+                                } else {
+                                    Color::WHITE
+                                },
+                            },
+                        ))
+                        .insert(Pickable::IGNORE);
+                });
+        }
+    });
+}
@@ -1,48 +1,95 @@
 use bevy::{
-    color::palettes::tailwind::{SLATE_400, SLATE_500, SLATE_600, SLATE_900}, prelude::*, ui::Direction as UIDirection, utils::HashSet,
+    color::palettes::tailwind::{BLUE_600, SLATE_400, SLATE_500, SLATE_600, SLATE_700, SLATE_900}, prelude::*, ui::Direction as UIDirection, utils::HashSet, window::PrimaryWindow,
 };
 use bevy_cosmic_edit::*;
-use field_heading::FieldHeadingWidget;
+use bevy_mod_picking::{
+    events::{Click, Drag, DragEnd, Pointer},
+    focus::PickingInteraction,
+    prelude::{On, Pickable, PointerButton},
+    PickableBundle,
+};
+use curve::{CurveInputWidget, CurveWidgetPlugin, RequestUpdateCurveInput};
+use edge_mode::{EdgeModeInputWidget, EdgeModeWidgetPlugin, RequestUpdateEdgeModeInput};
+use field_heading::{FieldHeadingWidget, RequestSetAllPortsVisible};
+use image_input::{ImageInputPlugin, ImageInputWidget, RequestUpdateImageInput};
+use image_info::{image_info, ImageInfoOutputWidget, ImageInfoPlugin, RequestUpdateImageInfoOutput};
+use kernel::{KernelInputWidget, KernelWidgetCallbacks, KernelWidgetPlugin, RequestUpdateKernelInput};
 use linear_rgba::{
     LinearRgbaInputWidget, LinearRgbaOutputWidget, LinearRgbaPlugin, LinearRgbaWidgetCallbacks,
     RequestUpdateLinearRgbaInput, RequestUpdateLinearRgbaOutput,
 };
+use math_operation::{
+    MathOperationInputWidget, MathOperationWidgetPlugin, RequestUpdateMathOperationInput,
+};
+use number::{NumberInputWidget, NumberPlugin, NumberWidgetCallbacks, RequestUpdateNumberInput};
 use petgraph::Direction;
-use text_input::TextInputPlugin;
+use shader_source::{ShaderSourcePlugin, ShaderSourceWidget};
+use shape::{RequestUpdateShapeInput, ShapeInputWidget, ShapeWidgetCallbacks, ShapeWidgetPlugin};
+use text_input::{TextInputLimits, TextInputPlugin};
 
 use crate::{
     asset::FontAssets,
-    graph::{DisjointPipelineGraph, GraphWasUpdated},
+    graph::{DisjointPipelineGraph, GraphWasUpdated, RequestProcessPipeline},
     nodes::{
         fields::Field,
+        custom_shader::{
+            custom_shader_label, supports_custom_shader, CustomShader, CustomShaderChange, CustomShaderLabel,
+            RequestPickCustomShader, RequestSetCustomShader,
+        },
+        input_label, node_kind_name,
         ports::{format_label_text, InputPort, OutputPort},
-        NodeDisplay, NodeTrait, Selected,
+        GraphNodeKind, NodeDisplay, NodeTrait, Selected,
     },
+    preferences::{SetPreference, INSPECTOR_WIDTH_MAX, INSPECTOR_WIDTH_MIN},
     ApplicationState,
 };
 
-use super::UIContext;
+use super::{context_menu::InputPortContext, NodeEditArea, UIContext};
 
+pub mod curve;
+pub mod edge_mode;
 pub mod field_heading;
+pub mod image_input;
+pub mod image_info;
+pub mod kernel;
 pub mod linear_rgba;
+pub mod math_operation;
+pub mod number;
+pub mod shader_source;
+pub mod shape;
 pub mod text_input;
 
 pub struct InspectorPlugin;
 
 impl Plugin for InspectorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((TextInputPlugin, LinearRgbaPlugin));
+        app.add_plugins((
+            TextInputPlugin,
+            LinearRgbaPlugin,
+            NumberPlugin,
+            ShapeWidgetPlugin,
+            ImageInfoPlugin,
+            ImageInputPlugin,
+            CurveWidgetPlugin,
+            MathOperationWidgetPlugin,
+            KernelWidgetPlugin,
+            EdgeModeWidgetPlugin,
+            ShaderSourcePlugin,
+        ));
         app.add_systems(
             Update,
             (
                 on_node_selection_changed,
                 field_heading::on_click_input_visibility_switch,
                 field_heading::on_click_output_visibility_switch,
+                resize_inspector_panel,
             )
                 .run_if(in_state(ApplicationState::MainLoop)),
         );
 
         app.observe(trigger_inspector_updates);
+        app.observe(field_heading::set_all_ports_visible);
+        app.observe(toggle_cacheable);
     }
 }
 
@@ -68,6 +115,17 @@ pub struct OutputPortVisibilitySwitch {
     pub is_visible: bool,
 }
 
+// Highlighted while the node is set to reprocess on every pass
+#[derive(Component)]
+pub struct CacheableSwitch {
+    pub node: Entity,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestToggleCacheable {
+    pub node: Entity,
+}
+
 impl InspectorPanel {
     pub fn new() -> Self {
         Self {
@@ -75,11 +133,11 @@ impl InspectorPanel {
         }
     }
 
-    pub fn spawn(commands: &mut Commands) -> Entity {
+    pub fn spawn(commands: &mut Commands, width_percent: f32) -> Entity {
         let panel_entity = commands
             .spawn(NodeBundle {
                 style: Style {
-                    width: Val::Percent(20.),
+                    width: Val::Percent(width_percent),
                     height: Val::Percent(100.),
                     flex_direction: FlexDirection::Column,
                     padding: UiRect::all(Val::Px(10.0)),
@@ -95,15 +153,106 @@ impl InspectorPanel {
             .insert(InspectorPanel::new())
             .id();
 
+        let splitter_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(-SPLITTER_WIDTH / 2.),
+                    top: Val::Px(0.),
+                    width: Val::Px(SPLITTER_WIDTH),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                z_index: ZIndex::Local(1),
+                ..default()
+            })
+            .insert(Name::new("Inspector Splitter"))
+            .insert(InspectorSplitter)
+            .insert(PickableBundle::default())
+            .id();
+
+        commands.entity(panel_entity).add_child(splitter_entity);
+
         panel_entity
     }
 }
 
+const SPLITTER_WIDTH: f32 = 6.;
+
+// The grab handle on the inspector's left edge, dragged to resize the panel
+#[derive(Component)]
+pub struct InspectorSplitter;
+
+fn resize_inspector_panel(
+    mut commands: Commands,
+    mut drag_events: EventReader<Pointer<Drag>>,
+    mut drag_end_events: EventReader<Pointer<DragEnd>>,
+    q_splitter: Query<&PickingInteraction, With<InspectorSplitter>>,
+    mut q_inspector_style: Query<&mut Style, (With<InspectorPanel>, Without<NodeEditArea>)>,
+    mut q_node_edit_area_style: Query<&mut Style, (With<NodeEditArea>, Without<InspectorPanel>)>,
+    mut q_window: Query<&mut Window, With<PrimaryWindow>>,
+    mut dragged_width: Local<Option<f32>>,
+    mut showing_resize_cursor: Local<bool>,
+) {
+    let mut window = match q_window.get_single_mut() {
+        Ok(w) => w,
+        Err(_) => return,
+    };
+
+    let is_splitter_hovered = q_splitter
+        .iter()
+        .any(|interaction| *interaction != PickingInteraction::None);
+
+    // only touch the cursor on transitions, so other widgets can still set their own
+    let wants_resize_cursor = is_splitter_hovered || dragged_width.is_some();
+    if wants_resize_cursor != *showing_resize_cursor {
+        window.cursor.icon = if wants_resize_cursor {
+            CursorIcon::ColResize
+        } else {
+            CursorIcon::Default
+        };
+        *showing_resize_cursor = wants_resize_cursor;
+    }
+
+    for event in drag_events.read() {
+        if event.button != PointerButton::Primary || !q_splitter.contains(event.target) {
+            continue;
+        }
+
+        let width = (100. - (event.pointer_location.position.x / window.width()) * 100.)
+            .clamp(INSPECTOR_WIDTH_MIN, INSPECTOR_WIDTH_MAX);
+
+        if let Ok(mut style) = q_inspector_style.get_single_mut() {
+            style.width = Val::Percent(width);
+        }
+
+        if let Ok(mut style) = q_node_edit_area_style.get_single_mut() {
+            style.width = Val::Percent(100. - width);
+        }
+
+        *dragged_width = Some(width);
+    }
+
+    for event in drag_end_events.read() {
+        if event.button != PointerButton::Primary || !q_splitter.contains(event.target) {
+            continue;
+        }
+
+        // only persist once the drag is over, rather than writing the file every frame
+        if let Some(width) = dragged_width.take() {
+            commands.trigger(SetPreference::InspectorWidth(width));
+        }
+    }
+}
+
 // Tracks added and removed Selected components this frame
 //  and builds the appropriate widgets in the inspector, given those changes.
 fn on_node_selection_changed(
     mut commands: Commands,
     linear_rgba_callbacks: Res<LinearRgbaWidgetCallbacks>,
+    shape_callbacks: Res<ShapeWidgetCallbacks>,
+    number_callbacks: Res<NumberWidgetCallbacks>,
+    kernel_callbacks: Res<KernelWidgetCallbacks>,
     selected_nodes: Query<Entity, (With<NodeDisplay>, With<Selected>)>,
     mut removed_selections: RemovedComponents<Selected>,
     nodes: Query<&NodeDisplay>,
@@ -165,6 +314,44 @@ fn on_node_selection_changed(
                         18.,
                     );
 
+                    spawn_port_visibility_buttons(
+                        &mut commands,
+                        section_entity,
+                        selected_entity,
+                        &fonts,
+                    );
+
+                    spawn_cacheable_switch(
+                        &mut commands,
+                        section_entity,
+                        selected_entity,
+                        node.cacheable,
+                        &fonts,
+                    );
+
+                    if supports_custom_shader(node_kind_name(&node.kind)) {
+                        spawn_custom_shader_controls(
+                            &mut commands,
+                            section_entity,
+                            selected_entity,
+                            node.custom_shader.as_ref(),
+                            &fonts,
+                        );
+                    }
+
+                    if let GraphNodeKind::CustomCompute(custom_compute) = &node.kind {
+                        spawn_header(&mut commands, section_entity, "Shader", &fonts, 16.);
+                        ShaderSourceWidget::spawn(
+                            &mut commands,
+                            &mut font_system,
+                            fonts.deja_vu_sans.clone(),
+                            section_entity,
+                            selected_entity,
+                            &custom_compute.shader_source,
+                            custom_compute.shader_error.as_deref(),
+                        );
+                    }
+
                     spawn_header(&mut commands, section_entity, "Inputs", &fonts, 16.);
 
                     // Get children of the selected node
@@ -194,15 +381,28 @@ fn on_node_selection_changed(
 
                                     let widget_entity = FieldHeadingWidget::spawn(
                                         &mut commands,
-                                        &format_label_text(input_id.1),
+                                        &input_label(&node.kind, input_id),
                                         input_port,
                                         true,
                                         is_visible,
                                         fonts.deja_vu_sans.clone(),
                                     );
 
+                                    commands
+                                        .entity(widget_entity)
+                                        .insert(UIContext::InputPort(InputPortContext {
+                                            node: selected_entity,
+                                            port: input_id,
+                                        }));
+
                                     commands.entity(section_entity).add_child(widget_entity);
 
+                                    let limits = node
+                                        .kind
+                                        .get_input_meta(input_id)
+                                        .map(TextInputLimits::from)
+                                        .unwrap_or_default();
+
                                     // spawn the specific kind of widget
                                     match field {
                                         Field::LinearRgba(color) => {
@@ -215,9 +415,104 @@ fn on_node_selection_changed(
                                                 selected_entity,
                                                 input_id,
                                                 color,
+                                                limits,
                                             );
                                             commands.entity(section_entity).add_child(widget);
                                         }
+                                        Field::F32(value) => {
+                                            NumberInputWidget::spawn(
+                                                &mut commands,
+                                                &number_callbacks,
+                                                &mut font_system,
+                                                fonts.deja_vu_sans.clone(),
+                                                section_entity,
+                                                selected_entity,
+                                                input_id,
+                                                value,
+                                                limits,
+                                            );
+                                        }
+                                        Field::U32(value) => {
+                                            NumberInputWidget::spawn(
+                                                &mut commands,
+                                                &number_callbacks,
+                                                &mut font_system,
+                                                fonts.deja_vu_sans.clone(),
+                                                section_entity,
+                                                selected_entity,
+                                                input_id,
+                                                value as f32,
+                                                limits,
+                                            );
+                                        }
+                                        Field::Shape(shape) => {
+                                            ShapeInputWidget::spawn(
+                                                &mut commands,
+                                                &shape_callbacks,
+                                                &mut font_system,
+                                                fonts.deja_vu_sans.clone(),
+                                                section_entity,
+                                                selected_entity,
+                                                input_id,
+                                                &shape,
+                                            );
+                                        }
+                                        Field::Curve(curve) => {
+                                            CurveInputWidget::spawn(
+                                                &mut commands,
+                                                section_entity,
+                                                selected_entity,
+                                                input_id,
+                                                &curve,
+                                            );
+                                        }
+                                        Field::MathOperation(operation) => {
+                                            MathOperationInputWidget::spawn(
+                                                &mut commands,
+                                                fonts.deja_vu_sans.clone(),
+                                                section_entity,
+                                                selected_entity,
+                                                input_id,
+                                                operation,
+                                            );
+                                        }
+                                        Field::Kernel(kernel) => {
+                                            KernelInputWidget::spawn(
+                                                &mut commands,
+                                                &kernel_callbacks,
+                                                &mut font_system,
+                                                fonts.deja_vu_sans.clone(),
+                                                section_entity,
+                                                selected_entity,
+                                                input_id,
+                                                &kernel,
+                                            );
+                                        }
+                                        Field::EdgeMode(edge_mode) => {
+                                            EdgeModeInputWidget::spawn(
+                                                &mut commands,
+                                                fonts.deja_vu_sans.clone(),
+                                                section_entity,
+                                                selected_entity,
+                                                input_id,
+                                                edge_mode,
+                                            );
+                                        }
+                                        Field::Image(_) => {
+                                            let is_connected = pipeline
+                                                .graph
+                                                .edges_directed(node_index, Direction::Incoming)
+                                                .any(|edge| edge.weight().to_field == input_id);
+
+                                            ImageInputWidget::spawn(
+                                                &mut commands,
+                                                fonts.deja_vu_sans.clone(),
+                                                section_entity,
+                                                selected_entity,
+                                                input_id,
+                                                is_connected,
+                                            );
+                                        }
                                         // Add more field types here as we implement more widgets
                                         _ => {}
                                     }
@@ -272,6 +567,16 @@ fn on_node_selection_changed(
                                             );
                                             commands.entity(section_entity).add_child(widget);
                                         }
+                                        Field::Image(image) => {
+                                            ImageInfoOutputWidget::spawn(
+                                                &mut commands,
+                                                fonts.deja_vu_sans.clone(),
+                                                section_entity,
+                                                image.as_ref(),
+                                                selected_entity,
+                                                output_id,
+                                            );
+                                        }
                                         // Add more field types here as we implement more widgets
                                         _ => {}
                                     }
@@ -295,6 +600,14 @@ fn trigger_inspector_updates(
     q_node_displays: Query<&NodeDisplay>,
     q_linear_rgba_inputs: Query<(Entity, &LinearRgbaInputWidget)>,
     q_linear_rgba_outputs: Query<(Entity, &LinearRgbaOutputWidget)>,
+    q_shape_inputs: Query<(Entity, &ShapeInputWidget)>,
+    q_curve_inputs: Query<(Entity, &CurveInputWidget)>,
+    q_math_operation_inputs: Query<(Entity, &MathOperationInputWidget)>,
+    q_kernel_inputs: Query<(Entity, &KernelInputWidget)>,
+    q_edge_mode_inputs: Query<(Entity, &EdgeModeInputWidget)>,
+    q_number_inputs: Query<(Entity, &NumberInputWidget)>,
+    q_image_info_outputs: Query<(Entity, &ImageInfoOutputWidget)>,
+    q_image_inputs: Query<(Entity, &ImageInputWidget)>,
 ) {
     let graph = &q_graph.single().graph;
 
@@ -311,6 +624,28 @@ fn trigger_inspector_updates(
                         .edges_directed(node_display.index, Direction::Incoming)
                         .any(|edge| edge.weight().to_field == *input_id);
 
+                    let number_value = match field {
+                        Field::U32(value) => Some(value as f32),
+                        Field::F32(value) => Some(value),
+                        _ => None,
+                    };
+
+                    if let Some(value) = number_value {
+                        q_number_inputs
+                            .iter()
+                            .for_each(|(number_entity, number_widget)| {
+                                if number_widget.node == node_entity
+                                    && number_widget.input_id == *input_id
+                                {
+                                    commands.trigger(RequestUpdateNumberInput {
+                                        value,
+                                        widget_entity: number_entity,
+                                        is_readonly,
+                                    });
+                                }
+                            });
+                    }
+
                     match field {
                         Field::U32(_) => {}
                         Field::F32(_) => {}
@@ -340,10 +675,114 @@ fn trigger_inspector_updates(
                         }
                         Field::Extent3d(_) => {}
                         Field::TextureFormat(_) => {}
-                        Field::Image(_) => {},
-                        Field::Shape(_) => {}
+                        Field::Image(_) => {
+                            q_image_inputs
+                                .iter()
+                                .for_each(|(image_input_entity, image_input_widget)| {
+                                    if image_input_widget.node == node_entity
+                                        && image_input_widget.input_id == *input_id
+                                    {
+                                        commands.trigger(RequestUpdateImageInput {
+                                            is_connected: is_readonly,
+                                            widget_entity: image_input_entity,
+                                        });
+                                    }
+                                });
+                        }
+                        Field::Shape(shape) => {
+                            q_shape_inputs
+                                .iter()
+                                .for_each(|(shape_entity, shape_widget)| {
+                                    if shape_widget.node == node_entity
+                                        && shape_widget.input_id == *input_id
+                                    {
+                                        commands.trigger(RequestUpdateShapeInput {
+                                            value: shape.clone(),
+                                            widget_entity: shape_entity,
+                                            is_readonly,
+                                        });
+                                    }
+                                });
+                        }
+                        Field::Curve(curve) => {
+                            q_curve_inputs
+                                .iter()
+                                .for_each(|(curve_entity, curve_widget)| {
+                                    if curve_widget.node == node_entity
+                                        && curve_widget.input_id == *input_id
+                                    {
+                                        commands.trigger(RequestUpdateCurveInput {
+                                            value: curve.clone(),
+                                            widget_entity: curve_entity,
+                                            is_readonly,
+                                        });
+                                    }
+                                });
+                        }
+                        Field::MathOperation(operation) => {
+                            q_math_operation_inputs
+                                .iter()
+                                .for_each(|(operation_entity, operation_widget)| {
+                                    if operation_widget.node == node_entity
+                                        && operation_widget.input_id == *input_id
+                                    {
+                                        commands.trigger(RequestUpdateMathOperationInput {
+                                            value: operation,
+                                            widget_entity: operation_entity,
+                                            is_readonly,
+                                        });
+                                    }
+                                });
+                        }
+                        Field::Kernel(kernel) => {
+                            q_kernel_inputs
+                                .iter()
+                                .for_each(|(kernel_entity, kernel_widget)| {
+                                    if kernel_widget.node == node_entity
+                                        && kernel_widget.input_id == *input_id
+                                    {
+                                        commands.trigger(RequestUpdateKernelInput {
+                                            value: kernel.clone(),
+                                            widget_entity: kernel_entity,
+                                            is_readonly,
+                                        });
+                                    }
+                                });
+                        }
+                        Field::EdgeMode(edge_mode) => {
+                            q_edge_mode_inputs
+                                .iter()
+                                .for_each(|(edge_mode_entity, edge_mode_widget)| {
+                                    if edge_mode_widget.node == node_entity
+                                        && edge_mode_widget.input_id == *input_id
+                                    {
+                                        commands.trigger(RequestUpdateEdgeModeInput {
+                                            value: edge_mode,
+                                            widget_entity: edge_mode_entity,
+                                            is_readonly,
+                                        });
+                                    }
+                                });
+                        }
                     };
                 }
+
+                for output_id in node.kind.output_fields() {
+                    if let Some(Field::Image(image)) = node.kind.get_output(*output_id) {
+                        q_image_info_outputs
+                            .iter()
+                            .for_each(|(image_info_entity, image_info_widget)| {
+                                if image_info_widget.node == node_entity
+                                    && image_info_widget.output_id == *output_id
+                                {
+                                    commands.trigger(RequestUpdateImageInfoOutput {
+                                        image_info: image.as_ref().map(image_info),
+                                        widget_entity: image_info_entity,
+                                    });
+                                }
+                            });
+                    }
+                }
             }
         }
     }
@@ -367,3 +806,209 @@ fn spawn_header(commands: &mut Commands, parent: Entity, text: &str, fonts: &Res
 
     commands.entity(parent).add_child(header_entity);
 }
+
+fn spawn_port_visibility_buttons(commands: &mut Commands, parent: Entity, node: Entity, fonts: &Res<FontAssets>) {
+    let row_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(4.),
+                margin: UiRect::bottom(Val::Px(10.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .id();
+
+    for (text, visible) in [("Show All Ports", true), ("Hide All Ports", false)] {
+        let button_entity = commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    flex_grow: 1.,
+                    padding: UiRect::all(Val::Px(4.)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: SLATE_700.into(),
+                border_radius: BorderRadius::all(Val::Px(4.)),
+                ..default()
+            })
+            .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+                commands.trigger(RequestSetAllPortsVisible { node, visible });
+            }))
+            .with_children(|child_builder| {
+                child_builder
+                    .spawn(TextBundle::from_section(
+                        text,
+                        TextStyle {
+                            font: fonts.deja_vu_sans.clone(),
+                            font_size: 14.0,
+                            color: Color::WHITE,
+                        },
+                    ))
+                    .insert(Pickable::IGNORE);
+            })
+            .id();
+
+        commands.entity(row_entity).add_child(button_entity);
+    }
+
+    commands.entity(parent).add_child(row_entity);
+}
+
+// Which shader the node runs, with buttons to pick a WGSL file, read it again after editing, or go back to the built-in one
+fn spawn_custom_shader_controls(
+    commands: &mut Commands,
+    parent: Entity,
+    node: Entity,
+    custom_shader: Option<&CustomShader>,
+    fonts: &Res<FontAssets>,
+) {
+    let label_entity = commands
+        .spawn(TextBundle::from_section(
+            custom_shader_label(custom_shader),
+            TextStyle {
+                font: fonts.deja_vu_sans.clone(),
+                font_size: 14.0,
+                color: SLATE_400.into(),
+            },
+        ))
+        .insert(Style {
+            margin: UiRect::bottom(Val::Px(4.0)),
+            ..default()
+        })
+        .insert(CustomShaderLabel { node })
+        .id();
+
+    let row_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(4.),
+                margin: UiRect::bottom(Val::Px(10.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .id();
+
+    for text in ["Custom Shader...", "Reload Shader", "Built-in Shader"] {
+        let button_entity = commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    flex_grow: 1.,
+                    padding: UiRect::all(Val::Px(4.)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: SLATE_700.into(),
+                border_radius: BorderRadius::all(Val::Px(4.)),
+                ..default()
+            })
+            .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| match text {
+                "Custom Shader..." => commands.trigger(RequestPickCustomShader { node }),
+                "Reload Shader" => commands.trigger(RequestSetCustomShader {
+                    node,
+                    change: CustomShaderChange::Reload,
+                }),
+                _ => commands.trigger(RequestSetCustomShader {
+                    node,
+                    change: CustomShaderChange::BuiltIn,
+                }),
+            }))
+            .with_children(|child_builder| {
+                child_builder
+                    .spawn(TextBundle::from_section(
+                        text,
+                        TextStyle {
+                            font: fonts.deja_vu_sans.clone(),
+                            font_size: 14.0,
+                            color: Color::WHITE,
+                        },
+                    ))
+                    .insert(Pickable::IGNORE);
+            })
+            .id();
+
+        commands.entity(row_entity).add_child(button_entity);
+    }
+
+    commands.entity(parent).push_children(&[label_entity, row_entity]);
+}
+
+fn cacheable_switch_color(is_cacheable: bool) -> Srgba {
+    if is_cacheable {
+        SLATE_700
+    } else {
+        BLUE_600
+    }
+}
+
+fn spawn_cacheable_switch(commands: &mut Commands, parent: Entity, node: Entity, is_cacheable: bool, fonts: &Res<FontAssets>) {
+    let button_entity = commands
+        .spawn(ButtonBundle {
+            style: Style {
+                padding: UiRect::all(Val::Px(4.)),
+                margin: UiRect::bottom(Val::Px(10.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: cacheable_switch_color(is_cacheable).into(),
+            border_radius: BorderRadius::all(Val::Px(4.)),
+            ..default()
+        })
+        .insert(CacheableSwitch { node })
+        .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+            commands.trigger(RequestToggleCacheable { node });
+        }))
+        .with_children(|child_builder| {
+            child_builder
+                .spawn(TextBundle::from_section(
+                    "Always Reprocess",
+                    TextStyle {
+                        font: fonts.deja_vu_sans.clone(),
+                        font_size: 14.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(Pickable::IGNORE);
+        })
+        .id();
+
+    commands.entity(parent).add_child(button_entity);
+}
+
+// Turning caching off reprocesses right away, so the node's output is fresh without waiting for an edit
+fn toggle_cacheable(
+    trigger: Trigger<RequestToggleCacheable>,
+    mut q_pipeline: Query<&mut DisjointPipelineGraph>,
+    q_nodes: Query<&NodeDisplay>,
+    mut q_switches: Query<(&CacheableSwitch, &mut BackgroundColor)>,
+    mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+) {
+    let node_entity = trigger.event().node;
+
+    let Ok(node_display) = q_nodes.get(node_entity) else {
+        return;
+    };
+
+    let mut pipeline = q_pipeline.single_mut();
+    let Some(node) = pipeline.graph.node_weight_mut(node_display.index) else {
+        return;
+    };
+
+    node.cacheable = !node.cacheable;
+    let is_cacheable = node.cacheable;
+
+    for (switch, mut background_color) in q_switches.iter_mut() {
+        if switch.node == node_entity {
+            *background_color = cacheable_switch_color(is_cacheable).into();
+        }
+    }
+
+    if !is_cacheable {
+        ev_process_pipeline.send(RequestProcessPipeline);
+    }
+}
@@ -0,0 +1,360 @@
+use bevy::{
+    color::palettes::tailwind::{BLUE_400, SLATE_400, SLATE_800},
+    prelude::*,
+};
+use bevy_mod_picking::{
+    events::{Click, Drag, DragEnd, Pointer},
+    prelude::{Pickable, PointerButton},
+    PickableBundle,
+};
+
+use crate::{
+    events::field_events::SetInputFieldEvent,
+    graph::DisjointPipelineGraph,
+    nodes::{fields::Field, kinds::curves::Curve, InputId, NodeDisplay, NodeTrait},
+    ApplicationState,
+};
+
+pub struct CurveWidgetPlugin;
+
+impl Plugin for CurveWidgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                drag_curve_handles,
+                edit_curve_points,
+                redraw_curve_widgets,
+            )
+                .chain()
+                .run_if(in_state(ApplicationState::MainLoop)),
+        );
+
+        app.observe(update_curve_input);
+    }
+}
+
+const CURVE_EDITOR_SIZE: f32 = 200.;
+const CURVE_HANDLE_SIZE: f32 = 10.;
+const CURVE_TRACE_SAMPLES: usize = 64;
+const CURVE_TRACE_SIZE: f32 = 2.;
+
+#[derive(Event)]
+pub struct RequestUpdateCurveInput {
+    pub value: Curve,
+    pub widget_entity: Entity,
+    pub is_readonly: bool,
+}
+
+// An editable tone curve: drag points to move them, click empty space to add one,
+//  and Ctrl+click a point to remove it
+#[derive(Component)]
+pub struct CurveInputWidget {
+    pub node: Entity,
+    pub input_id: InputId,
+    pub canvas: Entity,
+    curve: Curve,
+    is_readonly: bool,
+    handles: Vec<Entity>,
+    trace: Vec<Entity>,
+}
+
+#[derive(Component)]
+struct CurveHandle {
+    widget: Entity,
+    index: usize,
+}
+
+#[derive(Component)]
+struct CurveCanvas {
+    widget: Entity,
+}
+
+impl CurveInputWidget {
+    pub fn spawn(
+        commands: &mut Commands,
+        parent: Entity,
+        node: Entity,
+        input_id: InputId,
+        value: &Curve,
+    ) -> Entity {
+        let widget_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let canvas = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(CURVE_EDITOR_SIZE),
+                    height: Val::Px(CURVE_EDITOR_SIZE),
+                    border: UiRect::all(Val::Px(1.)),
+                    ..default()
+                },
+                background_color: SLATE_800.into(),
+                border_color: SLATE_400.into(),
+                ..default()
+            })
+            .insert(PickableBundle::default())
+            .insert(CurveCanvas {
+                widget: widget_entity,
+            })
+            .id();
+
+        let trace: Vec<Entity> = (0..CURVE_TRACE_SAMPLES)
+            .map(|_| {
+                commands
+                    .spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            width: Val::Px(CURVE_TRACE_SIZE),
+                            height: Val::Px(CURVE_TRACE_SIZE),
+                            ..default()
+                        },
+                        background_color: SLATE_400.into(),
+                        ..default()
+                    })
+                    .insert(Pickable::IGNORE)
+                    .id()
+            })
+            .collect();
+
+        commands.entity(canvas).push_children(&trace);
+
+        commands
+            .entity(widget_entity)
+            .add_child(canvas)
+            .insert(CurveInputWidget {
+                node,
+                input_id,
+                canvas,
+                curve: value.normalized(),
+                is_readonly: false,
+                handles: vec![],
+                trace,
+            });
+
+        commands.entity(parent).add_child(widget_entity);
+
+        widget_entity
+    }
+}
+
+// unit square coordinates, with y up, to pixel offsets within the canvas
+fn curve_to_canvas(point: Vec2) -> Vec2 {
+    Vec2::new(point.x, 1. - point.y) * CURVE_EDITOR_SIZE
+}
+
+fn drag_curve_handles(
+    mut drag_events: EventReader<Pointer<Drag>>,
+    q_handles: Query<&CurveHandle>,
+    mut q_widgets: Query<&mut CurveInputWidget>,
+) {
+    for event in drag_events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+
+        let Ok(handle) = q_handles.get(event.target) else {
+            continue;
+        };
+
+        let Ok(mut widget) = q_widgets.get_mut(handle.widget) else {
+            continue;
+        };
+
+        if widget.is_readonly {
+            continue;
+        }
+
+        let last_index = widget.curve.points.len() - 1;
+        let delta = Vec2::new(event.delta.x, -event.delta.y) / CURVE_EDITOR_SIZE;
+
+        // points can't pass their neighbors, and the ends stay pinned to the left and right edges
+        let min_x = if handle.index == 0 { 0. } else { widget.curve.points[handle.index - 1].x };
+        let max_x = if handle.index == last_index { 1. } else { widget.curve.points[handle.index + 1].x };
+
+        let point = &mut widget.curve.points[handle.index];
+        let moved = *point + delta;
+
+        point.y = moved.y.clamp(0., 1.);
+        if handle.index != 0 && handle.index != last_index {
+            point.x = moved.x.clamp(min_x, max_x);
+        }
+    }
+}
+
+fn edit_curve_points(
+    mut commands: Commands,
+    mut click_events: EventReader<Pointer<Click>>,
+    mut drag_end_events: EventReader<Pointer<DragEnd>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    q_handles: Query<&CurveHandle>,
+    q_canvases: Query<(&CurveCanvas, &Node, &GlobalTransform)>,
+    mut q_widgets: Query<&mut CurveInputWidget>,
+    q_graph: Query<&DisjointPipelineGraph>,
+    q_node_display: Query<&NodeDisplay>,
+) {
+    let control_pressed = keyboard_input.pressed(KeyCode::ControlLeft)
+        || keyboard_input.pressed(KeyCode::ControlRight);
+
+    let mut edited_widgets: Vec<Entity> = vec![];
+
+    for event in click_events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+
+        if let Ok(handle) = q_handles.get(event.target) {
+            if !control_pressed {
+                continue;
+            }
+
+            if let Ok(mut widget) = q_widgets.get_mut(handle.widget) {
+                let last_index = widget.curve.points.len() - 1;
+                if !widget.is_readonly && handle.index != 0 && handle.index != last_index {
+                    widget.curve.points.remove(handle.index);
+                    edited_widgets.push(handle.widget);
+                }
+            }
+        } else if let Ok((canvas, node, transform)) = q_canvases.get(event.target) {
+            let rect = node.logical_rect(transform);
+            let relative = (event.pointer_location.position - rect.min) / rect.size();
+            let point = Vec2::new(relative.x, 1. - relative.y).clamp(Vec2::ZERO, Vec2::ONE);
+
+            if let Ok(mut widget) = q_widgets.get_mut(canvas.widget) {
+                if !widget.is_readonly {
+                    widget.curve.points.push(point);
+                    widget.curve = widget.curve.normalized();
+                    edited_widgets.push(canvas.widget);
+                }
+            }
+        }
+    }
+
+    // a drag only edits the widget's copy, so the graph sees one change per drag
+    for event in drag_end_events.read() {
+        if let Ok(handle) = q_handles.get(event.target) {
+            edited_widgets.push(handle.widget);
+        }
+    }
+
+    let graph = &q_graph.single().graph;
+
+    for widget_entity in edited_widgets {
+        let Ok(widget) = q_widgets.get(widget_entity) else {
+            continue;
+        };
+
+        let Ok(node_display) = q_node_display.get(widget.node) else {
+            continue;
+        };
+
+        if let Some(node) = graph.node_weight(node_display.index) {
+            if let Some(old_value) = node.kind.get_input(widget.input_id) {
+                commands.trigger(SetInputFieldEvent {
+                    node: node_display.index,
+                    input_id: widget.input_id,
+                    old_value,
+                    new_value: Field::Curve(widget.curve.clone()),
+                });
+            }
+        }
+    }
+}
+
+fn redraw_curve_widgets(
+    mut commands: Commands,
+    mut q_widgets: Query<(Entity, &mut CurveInputWidget), Changed<CurveInputWidget>>,
+    mut q_style: Query<&mut Style>,
+    mut q_background_color: Query<&mut BackgroundColor>,
+) {
+    for (widget_entity, mut widget) in q_widgets.iter_mut() {
+        let points = widget.curve.points.clone();
+
+        // handles are only respawned when points are added or removed, so a drag keeps its target
+        if widget.handles.len() != points.len() {
+            for handle in widget.handles.drain(..) {
+                commands.entity(handle).despawn_recursive();
+            }
+
+            let handle_color = if widget.is_readonly { SLATE_400 } else { BLUE_400 };
+
+            let handles: Vec<Entity> = points
+                .iter()
+                .enumerate()
+                .map(|(index, point)| {
+                    let position = curve_to_canvas(*point) - CURVE_HANDLE_SIZE / 2.;
+
+                    commands
+                        .spawn(NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(position.x),
+                                top: Val::Px(position.y),
+                                width: Val::Px(CURVE_HANDLE_SIZE),
+                                height: Val::Px(CURVE_HANDLE_SIZE),
+                                ..default()
+                            },
+                            background_color: handle_color.into(),
+                            border_radius: BorderRadius::all(Val::Percent(50.)),
+                            ..default()
+                        })
+                        .insert(PickableBundle::default())
+                        .insert(CurveHandle {
+                            widget: widget_entity,
+                            index,
+                        })
+                        .id()
+                })
+                .collect();
+
+            commands.entity(widget.canvas).push_children(&handles);
+
+            widget.handles = handles;
+        } else {
+            for (&handle, point) in widget.handles.iter().zip(points.iter()) {
+                let position = curve_to_canvas(*point) - CURVE_HANDLE_SIZE / 2.;
+                if let Ok(mut style) = q_style.get_mut(handle) {
+                    style.left = Val::Px(position.x);
+                    style.top = Val::Px(position.y);
+                }
+                if let Ok(mut background_color) = q_background_color.get_mut(handle) {
+                    let color = if widget.is_readonly { SLATE_400 } else { BLUE_400 };
+                    *background_color = color.into();
+                }
+            }
+        }
+
+        for (sample_index, &dot) in widget.trace.iter().enumerate() {
+            let x = sample_index as f32 / (CURVE_TRACE_SAMPLES - 1) as f32;
+            let position = curve_to_canvas(Vec2::new(x, widget.curve.sample(x))) - CURVE_TRACE_SIZE / 2.;
+            if let Ok(mut style) = q_style.get_mut(dot) {
+                style.left = Val::Px(position.x);
+                style.top = Val::Px(position.y);
+            }
+        }
+    }
+}
+
+fn update_curve_input(
+    trigger: Trigger<RequestUpdateCurveInput>,
+    mut q_widgets: Query<&mut CurveInputWidget>,
+) {
+    if let Ok(mut widget) = q_widgets.get_mut(trigger.event().widget_entity) {
+        let curve = trigger.event().value.normalized();
+        let is_readonly = trigger.event().is_readonly;
+
+        if widget.curve != curve || widget.is_readonly != is_readonly {
+            widget.curve = curve;
+            widget.is_readonly = is_readonly;
+        }
+    }
+}
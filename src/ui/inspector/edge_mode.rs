@@ -0,0 +1,185 @@
+use bevy::{
+    color::palettes::tailwind::{BLUE_600, SLATE_700},
+    prelude::*,
+};
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::{On, Pickable},
+};
+use petgraph::Direction;
+
+use crate::{
+    events::field_events::SetInputFieldEvent,
+    graph::DisjointPipelineGraph,
+    nodes::{fields::Field, kinds::convolve::EdgeMode, InputId, NodeDisplay, NodeTrait},
+};
+
+pub struct EdgeModeWidgetPlugin;
+
+impl Plugin for EdgeModeWidgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.observe(update_edge_mode_input);
+        app.observe(select_edge_mode);
+    }
+}
+
+const EDGE_MODE_BUTTON_COLOR: Srgba = SLATE_700;
+const EDGE_MODE_SELECTED_COLOR: Srgba = BLUE_600;
+
+#[derive(Event)]
+pub struct RequestUpdateEdgeModeInput {
+    pub value: EdgeMode,
+    pub widget_entity: Entity,
+    pub is_readonly: bool,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestSelectEdgeMode {
+    pub widget_entity: Entity,
+    pub edge_mode: EdgeMode,
+}
+
+// One button per edge mode, with the current one highlighted
+#[derive(Component)]
+pub struct EdgeModeInputWidget {
+    pub node: Entity,
+    pub input_id: InputId,
+    pub buttons: Vec<Entity>,
+}
+
+impl EdgeModeInputWidget {
+    pub fn spawn(
+        commands: &mut Commands,
+        font: Handle<Font>,
+        parent: Entity,
+        node: Entity,
+        input_id: InputId,
+        value: EdgeMode,
+    ) -> Entity {
+        let widget_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(4.),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let text_style = TextStyle {
+            font: font.clone(),
+            font_size: 14.0,
+            color: Color::WHITE,
+        };
+
+        let buttons: Vec<Entity> = EdgeMode::ALL
+            .iter()
+            .map(|&edge_mode| {
+                commands
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+                            ..default()
+                        },
+                        background_color: edge_mode_button_color(edge_mode == value, false).into(),
+                        border_radius: BorderRadius::all(Val::Px(4.)),
+                        ..default()
+                    })
+                    .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+                        commands.trigger(RequestSelectEdgeMode {
+                            widget_entity,
+                            edge_mode,
+                        });
+                    }))
+                    .with_children(|child_builder| {
+                        child_builder
+                            .spawn(TextBundle::from_section(edge_mode.name(), text_style.clone()))
+                            .insert(Pickable::IGNORE);
+                    })
+                    .id()
+            })
+            .collect();
+
+        commands
+            .entity(widget_entity)
+            .push_children(&buttons)
+            .insert(EdgeModeInputWidget {
+                node,
+                input_id,
+                buttons,
+            });
+
+        commands.entity(parent).add_child(widget_entity);
+
+        widget_entity
+    }
+}
+
+fn edge_mode_button_color(is_selected: bool, is_readonly: bool) -> Srgba {
+    let color = if is_selected { EDGE_MODE_SELECTED_COLOR } else { EDGE_MODE_BUTTON_COLOR };
+    if is_readonly {
+        color.with_alpha(0.4)
+    } else {
+        color
+    }
+}
+
+fn select_edge_mode(
+    trigger: Trigger<RequestSelectEdgeMode>,
+    mut commands: Commands,
+    q_graph: Query<&DisjointPipelineGraph>,
+    q_widget: Query<&EdgeModeInputWidget>,
+    q_node_display: Query<&NodeDisplay>,
+) {
+    let graph = &q_graph.single().graph;
+
+    let Ok(widget) = q_widget.get(trigger.event().widget_entity) else {
+        return;
+    };
+
+    let Ok(node_display) = q_node_display.get(widget.node) else {
+        return;
+    };
+
+    let is_driven_by_edge = graph
+        .edges_directed(node_display.index, Direction::Incoming)
+        .any(|edge| edge.weight().to_field == widget.input_id);
+
+    if is_driven_by_edge {
+        return;
+    }
+
+    if let Some(node) = graph.node_weight(node_display.index) {
+        if let Some(Field::EdgeMode(old_edge_mode)) = node.kind.get_input(widget.input_id) {
+            let new_edge_mode = trigger.event().edge_mode;
+
+            if old_edge_mode != new_edge_mode {
+                commands.trigger(SetInputFieldEvent {
+                    node: node_display.index,
+                    input_id: widget.input_id,
+                    old_value: Field::EdgeMode(old_edge_mode),
+                    new_value: Field::EdgeMode(new_edge_mode),
+                });
+            }
+        }
+    }
+}
+
+fn update_edge_mode_input(
+    trigger: Trigger<RequestUpdateEdgeModeInput>,
+    q_widget: Query<&EdgeModeInputWidget>,
+    mut q_background_color: Query<&mut BackgroundColor>,
+) {
+    if let Ok(widget) = q_widget.get(trigger.event().widget_entity) {
+        let RequestUpdateEdgeModeInput { value, is_readonly, .. } = *trigger.event();
+
+        for (button, edge_mode) in widget.buttons.iter().zip(EdgeMode::ALL.iter()) {
+            if let Ok(mut background_color) = q_background_color.get_mut(*button) {
+                *background_color = edge_mode_button_color(*edge_mode == value, is_readonly).into();
+            }
+        }
+    }
+}
@@ -5,9 +5,9 @@ use bevy::{
 };
 use bevy_mod_picking::{
     events::{Down, Pointer},
-    prelude::PointerButton,
+    prelude::{Pickable, PointerButton},
 };
-use petgraph::{visit::EdgeRef, Direction};
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction};
 
 use crate::{
     events::{edge_events::RemoveEdgeEvent, field_events::{SetInputFieldMetaEvent, SetOutputFieldMetaEvent}},
@@ -23,6 +23,7 @@ use super::{InputPortVisibilitySwitch, OutputPortVisibilitySwitch};
 pub struct FieldHeadingWidget {
     port_entity: Entity,
     is_input: bool,
+    label_entity: Entity,
 }
 
 impl FieldHeadingWidget {
@@ -83,6 +84,7 @@ impl FieldHeadingWidget {
                     color: Color::WHITE,
                 },
             ))
+            .insert(Pickable::IGNORE)
             .id();
 
         if is_input {
@@ -107,10 +109,19 @@ impl FieldHeadingWidget {
             .insert(FieldHeadingWidget {
                 port_entity,
                 is_input,
+                label_entity,
             });
 
         widget_entity
     }
+
+    pub fn port_entity(&self) -> Entity {
+        self.port_entity
+    }
+
+    pub fn label_entity(&self) -> Entity {
+        self.label_entity
+    }
 }
 
 pub fn on_click_input_visibility_switch(
@@ -118,41 +129,27 @@ pub fn on_click_input_visibility_switch(
     mut down_events: EventReader<Pointer<Down>>,
     q_nodes: Query<&NodeDisplay>,
     q_switches: Query<(&mut InputPortVisibilitySwitch, &mut BackgroundColor)>,
-    mut q_pipeline: Query<&mut DisjointPipelineGraph>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
     q_input_ports: Query<&InputPort>,
 ) {
     for event in down_events.read() {
         if event.button == PointerButton::Primary {
             if let Ok((switch, _)) = q_switches.get(event.target) {
-                let mut pipeline = q_pipeline.single_mut();
+                let pipeline = q_pipeline.single();
                 let port = q_input_ports.get(switch.input_port).unwrap();
                 let port_node_index = q_nodes.get(port.node_entity).unwrap().index;
 
-                if let Some(ref mut node) = pipeline.graph.node_weight_mut(port_node_index) {
+                if let Some(node) = pipeline.graph.node_weight(port_node_index) {
                     if let Some(meta) = node.kind.get_input_meta(port.input_id) {
-                        commands.trigger(SetInputFieldMetaEvent {
-                            input_port: switch.input_port,
-                            meta: FieldMeta {
-                                visible: !meta.visible,
-                                ..meta.clone()
-                            },
-                        });
-
-                        if meta.visible {   // i.e field WAS visible, now hidden
-                            for edge in pipeline
-                                .graph
-                                .edges_directed(port_node_index, Direction::Incoming)
-                            {
-                                if edge.weight().to_field == port.input_id {
-                                    commands.trigger(RemoveEdgeEvent {
-                                        start_node: edge.weight().from_node,
-                                        start_id: edge.weight().from_field,
-                                        end_node: edge.weight().to_node,
-                                        end_id: edge.weight().to_field,
-                                    });
-                                }
-                            }
-                        }
+                        set_input_port_visible(
+                            &mut commands,
+                            pipeline,
+                            port_node_index,
+                            switch.input_port,
+                            port,
+                            meta,
+                            !meta.visible,
+                        );
                     }
                 }
             }
@@ -177,32 +174,129 @@ pub fn on_click_output_visibility_switch(
 
                 if let Some(node) = pipeline.graph.node_weight(port_node_index) {
                     if let Some(meta) = node.kind.get_output_meta(port.output_id) {
-                        commands.trigger(SetOutputFieldMetaEvent {
-                            output_port: switch.output_port,
-                            meta: FieldMeta {
-                                visible: !meta.visible,
-                                ..meta.clone()
-                            },
-                        });
-
-                        if meta.visible {   // i.e field WAS visible, now hidden
-                            for edge in pipeline
-                                .graph
-                                .edges_directed(port_node_index, Direction::Outgoing)
-                            {
-                                if edge.weight().from_field == port.output_id {
-                                    commands.trigger(RemoveEdgeEvent {
-                                        start_node: edge.weight().from_node,
-                                        start_id: edge.weight().from_field,
-                                        end_node: edge.weight().to_node,
-                                        end_id: edge.weight().to_field,
-                                    });
-                                }
-                            }
-                        }
+                        set_output_port_visible(
+                            &mut commands,
+                            pipeline,
+                            port_node_index,
+                            switch.output_port,
+                            port,
+                            meta,
+                            !meta.visible,
+                        );
                     }
                 }
             }
         }
     }
 }
+
+#[derive(Event, Clone)]
+pub struct RequestSetAllPortsVisible {
+    pub node: Entity,
+    pub visible: bool,
+}
+
+// Every change is triggered in the same frame, so the whole batch is a single undo step
+pub fn set_all_ports_visible(
+    trigger: Trigger<RequestSetAllPortsVisible>,
+    mut commands: Commands,
+    q_nodes: Query<&NodeDisplay>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_input_ports: Query<(Entity, &InputPort)>,
+    q_output_ports: Query<(Entity, &OutputPort)>,
+) {
+    let pipeline = q_pipeline.single();
+    let RequestSetAllPortsVisible { node: node_entity, visible } = *trigger.event();
+
+    let node_index = match q_nodes.get(node_entity) {
+        Ok(node_display) => node_display.index,
+        Err(_) => return,
+    };
+
+    if let Some(node) = pipeline.graph.node_weight(node_index) {
+        for (port_entity, port) in q_input_ports.iter().filter(|(_, port)| port.node_entity == node_entity) {
+            if let Some(meta) = node.kind.get_input_meta(port.input_id) {
+                if meta.visible != visible {
+                    set_input_port_visible(&mut commands, pipeline, node_index, port_entity, port, meta, visible);
+                }
+            }
+        }
+
+        for (port_entity, port) in q_output_ports.iter().filter(|(_, port)| port.node_entity == node_entity) {
+            if let Some(meta) = node.kind.get_output_meta(port.output_id) {
+                if meta.visible != visible {
+                    set_output_port_visible(&mut commands, pipeline, node_index, port_entity, port, meta, visible);
+                }
+            }
+        }
+    }
+}
+
+// Hiding a port also detaches any edges that were connected to it
+fn set_input_port_visible(
+    commands: &mut Commands,
+    pipeline: &DisjointPipelineGraph,
+    node_index: NodeIndex,
+    port_entity: Entity,
+    port: &InputPort,
+    meta: &FieldMeta,
+    visible: bool,
+) {
+    commands.trigger(SetInputFieldMetaEvent {
+        input_port: port_entity,
+        meta: FieldMeta {
+            visible,
+            ..meta.clone()
+        },
+    });
+
+    if !visible {
+        for edge in pipeline
+            .graph
+            .edges_directed(node_index, Direction::Incoming)
+        {
+            if edge.weight().to_field == port.input_id {
+                commands.trigger(RemoveEdgeEvent {
+                    start_node: edge.weight().from_node,
+                    start_id: edge.weight().from_field,
+                    end_node: edge.weight().to_node,
+                    end_id: edge.weight().to_field,
+                });
+            }
+        }
+    }
+}
+
+fn set_output_port_visible(
+    commands: &mut Commands,
+    pipeline: &DisjointPipelineGraph,
+    node_index: NodeIndex,
+    port_entity: Entity,
+    port: &OutputPort,
+    meta: &FieldMeta,
+    visible: bool,
+) {
+    commands.trigger(SetOutputFieldMetaEvent {
+        output_port: port_entity,
+        meta: FieldMeta {
+            visible,
+            ..meta.clone()
+        },
+    });
+
+    if !visible {
+        for edge in pipeline
+            .graph
+            .edges_directed(node_index, Direction::Outgoing)
+        {
+            if edge.weight().from_field == port.output_id {
+                commands.trigger(RemoveEdgeEvent {
+                    start_node: edge.weight().from_node,
+                    start_id: edge.weight().from_field,
+                    end_node: edge.weight().to_node,
+                    end_id: edge.weight().to_field,
+                });
+            }
+        }
+    }
+}
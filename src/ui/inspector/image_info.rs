@@ -0,0 +1,99 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureFormat},
+};
+
+use crate::nodes::OutputId;
+
+pub struct ImageInfoPlugin;
+
+impl Plugin for ImageInfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.observe(update_image_info_output);
+    }
+}
+
+#[derive(Event)]
+pub struct RequestUpdateImageInfoOutput {
+    pub image_info: Option<(Extent3d, TextureFormat)>,
+    pub widget_entity: Entity,
+}
+
+// A read-only readout of an image output's dimensions and format
+#[derive(Component)]
+pub struct ImageInfoOutputWidget {
+    pub node: Entity,
+    pub output_id: OutputId,
+
+    pub info_text: Entity,
+}
+
+impl ImageInfoOutputWidget {
+    pub fn spawn(
+        commands: &mut Commands,
+        font: Handle<Font>,
+        parent: Entity,
+        image: Option<&Image>,
+        node: Entity,
+        output_id: OutputId,
+    ) -> Entity {
+        let widget_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let info_text = commands
+            .spawn(TextBundle::from_section(
+                image_info_text(image.map(image_info)),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    color: Color::WHITE,
+                },
+            ))
+            .id();
+
+        commands
+            .entity(widget_entity)
+            .add_child(info_text)
+            .insert(ImageInfoOutputWidget {
+                node,
+                output_id,
+                info_text,
+            });
+
+        commands.entity(parent).add_child(widget_entity);
+
+        widget_entity
+    }
+}
+
+pub fn image_info(image: &Image) -> (Extent3d, TextureFormat) {
+    (image.texture_descriptor.size, image.texture_descriptor.format)
+}
+
+fn image_info_text(image_info: Option<(Extent3d, TextureFormat)>) -> String {
+    match image_info {
+        Some((size, format)) => format!("{} × {}, {:?}", size.width, size.height, format),
+        None => String::from("No image"),
+    }
+}
+
+fn update_image_info_output(
+    trigger: Trigger<RequestUpdateImageInfoOutput>,
+    q_image_info_out: Query<&ImageInfoOutputWidget>,
+    mut q_text: Query<&mut Text>,
+) {
+    if let Ok(image_info_widget) = q_image_info_out.get(trigger.event().widget_entity) {
+        if let Ok(mut text) = q_text.get_mut(image_info_widget.info_text) {
+            text.sections[0].value = image_info_text(trigger.event().image_info);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::{
+    color::palettes::tailwind::{GREEN_400, SLATE_400, SLATE_700},
+    prelude::*,
+};
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::{On, Pickable},
+};
+use petgraph::Direction;
+
+use crate::{
+    events::edge_events::RemoveEdgeEvent,
+    graph::DisjointPipelineGraph,
+    nodes::{InputId, NodeDisplay},
+};
+
+pub struct ImageInputPlugin;
+
+impl Plugin for ImageInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.observe(update_image_input);
+        app.observe(clear_image_input);
+    }
+}
+
+#[derive(Event)]
+pub struct RequestUpdateImageInput {
+    pub is_connected: bool,
+    pub widget_entity: Entity,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestClearImageInput {
+    pub node: Entity,
+    pub input_id: InputId,
+}
+
+// Shows whether an image input is fed by an edge or left empty, with a button to detach it
+#[derive(Component)]
+pub struct ImageInputWidget {
+    pub node: Entity,
+    pub input_id: InputId,
+
+    pub status_text: Entity,
+    pub clear_button: Entity,
+}
+
+impl ImageInputWidget {
+    pub fn spawn(
+        commands: &mut Commands,
+        font: Handle<Font>,
+        parent: Entity,
+        node: Entity,
+        input_id: InputId,
+        is_connected: bool,
+    ) -> Entity {
+        let widget_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let (status, color) = image_input_status(is_connected);
+        let status_text = commands
+            .spawn(TextBundle::from_section(
+                status,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    color,
+                },
+            ))
+            .id();
+
+        let clear_button = commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+                    display: if is_connected { Display::Flex } else { Display::None },
+                    ..default()
+                },
+                background_color: SLATE_700.into(),
+                border_radius: BorderRadius::all(Val::Px(4.)),
+                ..default()
+            })
+            .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+                commands.trigger(RequestClearImageInput { node, input_id });
+            }))
+            .with_children(|child_builder| {
+                child_builder
+                    .spawn(TextBundle::from_section(
+                        "Clear",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 14.0,
+                            color: Color::WHITE,
+                        },
+                    ))
+                    .insert(Pickable::IGNORE);
+            })
+            .id();
+
+        commands
+            .entity(widget_entity)
+            .push_children(&[status_text, clear_button])
+            .insert(ImageInputWidget {
+                node,
+                input_id,
+                status_text,
+                clear_button,
+            });
+
+        commands.entity(parent).add_child(widget_entity);
+
+        widget_entity
+    }
+}
+
+fn image_input_status(is_connected: bool) -> (&'static str, Color) {
+    if is_connected {
+        ("● Connected", GREEN_400.into())
+    } else {
+        ("○ Empty", SLATE_400.into())
+    }
+}
+
+fn update_image_input(
+    trigger: Trigger<RequestUpdateImageInput>,
+    q_image_inputs: Query<&ImageInputWidget>,
+    mut q_text: Query<&mut Text>,
+    mut q_style: Query<&mut Style>,
+) {
+    let RequestUpdateImageInput { is_connected, widget_entity } = *trigger.event();
+
+    if let Ok(image_input_widget) = q_image_inputs.get(widget_entity) {
+        if let Ok(mut text) = q_text.get_mut(image_input_widget.status_text) {
+            let (status, color) = image_input_status(is_connected);
+            text.sections[0].value = status.to_string();
+            text.sections[0].style.color = color;
+        }
+
+        if let Ok(mut style) = q_style.get_mut(image_input_widget.clear_button) {
+            style.display = if is_connected { Display::Flex } else { Display::None };
+        }
+    }
+}
+
+// Detaches whatever edge feeds the input, leaving it empty
+fn clear_image_input(
+    trigger: Trigger<RequestClearImageInput>,
+    mut commands: Commands,
+    q_nodes: Query<&NodeDisplay>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+) {
+    let RequestClearImageInput { node, input_id } = trigger.event().clone();
+    let graph = &q_pipeline.single().graph;
+
+    let Ok(node_display) = q_nodes.get(node) else {
+        return;
+    };
+
+    for edge in graph.edges_directed(node_display.index, Direction::Incoming) {
+        if edge.weight().to_field == input_id {
+            commands.trigger(RemoveEdgeEvent {
+                start_node: edge.weight().from_node,
+                start_id: edge.weight().from_field,
+                end_node: edge.weight().to_node,
+                end_id: edge.weight().to_field,
+            });
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use bevy::{
+    color::palettes::tailwind::{BLUE_600, SLATE_700},
+    ecs::system::SystemId,
+    prelude::*,
+};
+use bevy_cosmic_edit::CosmicFontSystem;
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::{On, Pickable},
+};
+use petgraph::Direction;
+
+use crate::{
+    events::field_events::SetInputFieldEvent,
+    graph::DisjointPipelineGraph,
+    nodes::{
+        fields::Field,
+        kinds::convolve::{Kernel, KernelPreset, KERNEL_MAX_SIZE, KERNEL_SIZES},
+        InputId, NodeDisplay, NodeTrait,
+    },
+};
+
+use super::text_input::{RequestUpdateTextInput, TextInputHandlerInput, TextInputWidget};
+
+#[derive(Resource)]
+pub struct KernelWidgetCallbacks {
+    pub weight_changed: SystemId<TextInputHandlerInput>,
+}
+
+pub struct KernelWidgetPlugin;
+
+impl Plugin for KernelWidgetPlugin {
+    fn build(&self, app: &mut App) {
+        let weight_changed = app.register_system(kernel_weight_handler);
+
+        app.insert_resource(KernelWidgetCallbacks { weight_changed });
+
+        app.observe(update_kernel_input);
+        app.observe(resize_kernel);
+        app.observe(apply_kernel_preset);
+    }
+}
+
+const KERNEL_BUTTON_COLOR: Srgba = SLATE_700;
+const KERNEL_BUTTON_SELECTED_COLOR: Srgba = BLUE_600;
+const KERNEL_CELL_WIDTH: f32 = 48.;
+
+#[derive(Event)]
+pub struct RequestUpdateKernelInput {
+    pub value: Kernel,
+    pub widget_entity: Entity,
+    pub is_readonly: bool,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestResizeKernel {
+    pub widget_entity: Entity,
+    pub size: u32,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestApplyKernelPreset {
+    pub widget_entity: Entity,
+    pub preset: KernelPreset,
+}
+
+// A grid of weights sized to the kernel, with buttons to switch sizes and to load presets
+#[derive(Component)]
+pub struct KernelInputWidget {
+    pub node: Entity,
+    pub input_id: InputId,
+    // every cell of the largest kernel, row by row, as (cell, text input)
+    cells: Vec<(Entity, Entity)>,
+    size_buttons: Vec<Entity>,
+    preset_buttons: Vec<Entity>,
+}
+
+// The cell a text input in the grid edits
+#[derive(Component)]
+struct KernelCell {
+    widget: Entity,
+    x: u32,
+    y: u32,
+}
+
+impl KernelInputWidget {
+    pub fn spawn(
+        commands: &mut Commands,
+        callbacks: &KernelWidgetCallbacks,
+        font_system: &mut CosmicFontSystem,
+        font: Handle<Font>,
+        parent: Entity,
+        node: Entity,
+        input_id: InputId,
+        value: &Kernel,
+    ) -> Entity {
+        let kernel = value.normalized();
+
+        let widget_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let text_style = TextStyle {
+            font: font.clone(),
+            font_size: 14.0,
+            color: Color::WHITE,
+        };
+
+        let size_row = spawn_button_row(commands);
+        let size_buttons: Vec<Entity> = KERNEL_SIZES
+            .iter()
+            .map(|&size| {
+                spawn_kernel_button(
+                    commands,
+                    &text_style,
+                    &format!("{}×{}", size, size),
+                    size_button_color(size == kernel.size),
+                    On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+                        commands.trigger(RequestResizeKernel { widget_entity, size });
+                    }),
+                )
+            })
+            .collect();
+        commands.entity(size_row).push_children(&size_buttons);
+
+        let grid = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let mut cells = vec![];
+        for y in 0..KERNEL_MAX_SIZE {
+            let row = spawn_button_row(commands);
+
+            for x in 0..KERNEL_MAX_SIZE {
+                let is_used = x < kernel.size && y < kernel.size;
+                let weight = if is_used { kernel.weights[(y * kernel.size + x) as usize] } else { 0. };
+
+                let cell = commands
+                    .spawn(NodeBundle {
+                        style: Style {
+                            display: if is_used { Display::Flex } else { Display::None },
+                            width: Val::Px(KERNEL_CELL_WIDTH),
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(KernelCell { widget: widget_entity, x, y })
+                    .id();
+
+                let text_input = TextInputWidget::spawn(
+                    commands,
+                    font_system,
+                    font.clone(),
+                    "",
+                    weight,
+                    callbacks.weight_changed,
+                    cell,
+                );
+
+                commands.entity(cell).add_child(text_input);
+                commands.entity(row).add_child(cell);
+                cells.push((cell, text_input));
+            }
+
+            commands.entity(grid).add_child(row);
+        }
+
+        let preset_row = spawn_button_row(commands);
+        let preset_buttons: Vec<Entity> = KernelPreset::ALL
+            .iter()
+            .map(|&preset| {
+                spawn_kernel_button(
+                    commands,
+                    &text_style,
+                    preset.name(),
+                    KERNEL_BUTTON_COLOR.into(),
+                    On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+                        commands.trigger(RequestApplyKernelPreset { widget_entity, preset });
+                    }),
+                )
+            })
+            .collect();
+        commands.entity(preset_row).push_children(&preset_buttons);
+
+        commands
+            .entity(widget_entity)
+            .push_children(&[size_row, grid, preset_row])
+            .insert(KernelInputWidget {
+                node,
+                input_id,
+                cells,
+                size_buttons,
+                preset_buttons,
+            });
+
+        commands.entity(parent).add_child(widget_entity);
+
+        widget_entity
+    }
+}
+
+fn spawn_button_row(commands: &mut Commands) -> Entity {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(4.),
+                ..default()
+            },
+            ..default()
+        })
+        .id()
+}
+
+fn spawn_kernel_button(
+    commands: &mut Commands,
+    text_style: &TextStyle,
+    label: &str,
+    color: Color,
+    on_click: On<Pointer<Click>>,
+) -> Entity {
+    commands
+        .spawn(ButtonBundle {
+            style: Style {
+                padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+                ..default()
+            },
+            background_color: color.into(),
+            border_radius: BorderRadius::all(Val::Px(4.)),
+            ..default()
+        })
+        .insert(on_click)
+        .with_children(|child_builder| {
+            child_builder
+                .spawn(TextBundle::from_section(label, text_style.clone()))
+                .insert(Pickable::IGNORE);
+        })
+        .id()
+}
+
+fn size_button_color(is_selected: bool) -> Color {
+    if is_selected {
+        KERNEL_BUTTON_SELECTED_COLOR.into()
+    } else {
+        KERNEL_BUTTON_COLOR.into()
+    }
+}
+
+// Replaces the node's whole kernel, unless an edge is driving it
+fn set_kernel(
+    commands: &mut Commands,
+    graph: &DisjointPipelineGraph,
+    q_node_display: &Query<&NodeDisplay>,
+    widget: &KernelInputWidget,
+    edit: impl FnOnce(&Kernel) -> Kernel,
+) {
+    let Ok(node_display) = q_node_display.get(widget.node) else {
+        return;
+    };
+
+    let is_driven_by_edge = graph
+        .graph
+        .edges_directed(node_display.index, Direction::Incoming)
+        .any(|edge| edge.weight().to_field == widget.input_id);
+
+    if is_driven_by_edge {
+        return;
+    }
+
+    if let Some(node) = graph.graph.node_weight(node_display.index) {
+        if let Some(Field::Kernel(old_kernel)) = node.kind.get_input(widget.input_id) {
+            let new_kernel = edit(&old_kernel.normalized());
+
+            if new_kernel != old_kernel {
+                commands.trigger(SetInputFieldEvent {
+                    node: node_display.index,
+                    input_id: widget.input_id,
+                    old_value: Field::Kernel(old_kernel),
+                    new_value: Field::Kernel(new_kernel),
+                });
+            }
+        }
+    }
+}
+
+fn kernel_weight_handler(
+    In(input): In<TextInputHandlerInput>,
+    mut commands: Commands,
+    q_graph: Query<&DisjointPipelineGraph>,
+    q_cells: Query<&KernelCell>,
+    q_widgets: Query<&KernelInputWidget>,
+    q_node_display: Query<&NodeDisplay>,
+) {
+    let Ok(weight) = input.value.trim().parse::<f32>() else {
+        return;
+    };
+
+    let Ok(cell) = q_cells.get(input.controlling_widget) else {
+        return;
+    };
+
+    let Ok(widget) = q_widgets.get(cell.widget) else {
+        return;
+    };
+
+    set_kernel(&mut commands, q_graph.single(), &q_node_display, widget, |kernel| {
+        let mut kernel = kernel.clone();
+        if cell.x < kernel.size && cell.y < kernel.size {
+            kernel.weights[(cell.y * kernel.size + cell.x) as usize] = weight;
+        }
+        kernel
+    });
+}
+
+fn resize_kernel(
+    trigger: Trigger<RequestResizeKernel>,
+    mut commands: Commands,
+    q_graph: Query<&DisjointPipelineGraph>,
+    q_widgets: Query<&KernelInputWidget>,
+    q_node_display: Query<&NodeDisplay>,
+) {
+    let RequestResizeKernel { widget_entity, size } = *trigger.event();
+
+    if let Ok(widget) = q_widgets.get(widget_entity) {
+        set_kernel(&mut commands, q_graph.single(), &q_node_display, widget, |kernel| {
+            kernel.resized(size)
+        });
+    }
+}
+
+fn apply_kernel_preset(
+    trigger: Trigger<RequestApplyKernelPreset>,
+    mut commands: Commands,
+    q_graph: Query<&DisjointPipelineGraph>,
+    q_widgets: Query<&KernelInputWidget>,
+    q_node_display: Query<&NodeDisplay>,
+) {
+    let RequestApplyKernelPreset { widget_entity, preset } = *trigger.event();
+
+    if let Ok(widget) = q_widgets.get(widget_entity) {
+        set_kernel(&mut commands, q_graph.single(), &q_node_display, widget, |kernel| {
+            preset.kernel(kernel.size)
+        });
+    }
+}
+
+fn update_kernel_input(
+    trigger: Trigger<RequestUpdateKernelInput>,
+    mut commands: Commands,
+    q_widgets: Query<&KernelInputWidget>,
+    q_cells: Query<&KernelCell>,
+    mut q_style: Query<&mut Style>,
+    mut q_background_color: Query<&mut BackgroundColor>,
+) {
+    let Ok(widget) = q_widgets.get(trigger.event().widget_entity) else {
+        return;
+    };
+
+    let kernel = trigger.event().value.normalized();
+    let is_readonly = trigger.event().is_readonly;
+
+    for &(cell_entity, text_input) in widget.cells.iter() {
+        let Ok(cell) = q_cells.get(cell_entity) else {
+            continue;
+        };
+
+        let is_used = cell.x < kernel.size && cell.y < kernel.size;
+
+        if let Ok(mut style) = q_style.get_mut(cell_entity) {
+            style.display = if is_used { Display::Flex } else { Display::None };
+        }
+
+        if is_used {
+            commands.trigger(RequestUpdateTextInput {
+                value: kernel.weights[(cell.y * kernel.size + cell.x) as usize],
+                widget_entity: text_input,
+                is_readonly,
+            });
+        }
+    }
+
+    for (button, size) in widget.size_buttons.iter().zip(KERNEL_SIZES.iter()) {
+        if let Ok(mut background_color) = q_background_color.get_mut(*button) {
+            let color = size_button_color(*size == kernel.size);
+            *background_color = if is_readonly { color.with_alpha(0.4).into() } else { color.into() };
+        }
+    }
+
+    for button in widget.preset_buttons.iter() {
+        if let Ok(mut background_color) = q_background_color.get_mut(*button) {
+            *background_color = if is_readonly {
+                KERNEL_BUTTON_COLOR.with_alpha(0.4).into()
+            } else {
+                KERNEL_BUTTON_COLOR.into()
+            };
+        }
+    }
+}
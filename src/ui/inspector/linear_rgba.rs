@@ -3,7 +3,7 @@ use bevy_cosmic_edit::{ CosmicFontSystem};
 
 use crate::{events::field_events::SetInputFieldEvent, graph::DisjointPipelineGraph, nodes::{fields::Field, InputId, NodeDisplay, NodeTrait, OutputId}};
 
-use super::text_input::{RequestUpdateTextInput, TextInputHandlerInput, TextInputWidget};
+use super::text_input::{RequestUpdateTextInput, TextInputHandlerInput, TextInputLimits, TextInputWidget};
 
 #[derive(Resource)]
 pub struct LinearRgbaWidgetCallbacks {
@@ -68,6 +68,7 @@ impl LinearRgbaInputWidget {
         node: Entity,
         input_id: InputId,
         value: LinearRgba,
+        limits: TextInputLimits,
     ) -> Entity {
         let widget_entity = commands
             .spawn(NodeBundle {
@@ -86,6 +87,10 @@ impl LinearRgbaInputWidget {
         let blue = TextInputWidget::spawn(commands, font_system, font.clone(), "B", value.blue, callbacks.blue_changed, widget_entity);
         let alpha = TextInputWidget::spawn(commands, font_system, font.clone(), "A", value.alpha, callbacks.alpha_changed, widget_entity);
     
+        for component in [red, green, blue, alpha] {
+            commands.entity(component).insert(limits);
+        }
+
         commands
             .entity(widget_entity)
             .push_children(&[red, green, blue, alpha])
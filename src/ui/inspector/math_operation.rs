@@ -0,0 +1,289 @@
+use bevy::{
+    color::palettes::tailwind::{BLUE_600, SLATE_600, SLATE_700, SLATE_800},
+    prelude::*,
+};
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::{On, Pickable},
+};
+use petgraph::Direction;
+
+use crate::{
+    events::field_events::SetInputFieldEvent,
+    graph::DisjointPipelineGraph,
+    nodes::{fields::Field, kinds::math::MathOperation, InputId, NodeDisplay, NodeTrait},
+};
+
+pub struct MathOperationWidgetPlugin;
+
+impl Plugin for MathOperationWidgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.observe(update_math_operation_input);
+        app.observe(toggle_math_operation_dropdown);
+        app.observe(select_math_operation);
+    }
+}
+
+const DROPDOWN_BUTTON_COLOR: Srgba = SLATE_700;
+const DROPDOWN_OPTION_COLOR: Srgba = SLATE_800;
+const DROPDOWN_OPTION_SELECTED_COLOR: Srgba = BLUE_600;
+
+#[derive(Event)]
+pub struct RequestUpdateMathOperationInput {
+    pub value: MathOperation,
+    pub widget_entity: Entity,
+    pub is_readonly: bool,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestToggleMathOperationDropdown {
+    pub widget_entity: Entity,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestSelectMathOperation {
+    pub widget_entity: Entity,
+    pub operation: MathOperation,
+}
+
+// A button showing the current operation, which opens a list of every operation below it
+#[derive(Component)]
+pub struct MathOperationInputWidget {
+    pub node: Entity,
+    pub input_id: InputId,
+    pub button: Entity,
+    pub button_text: Entity,
+    pub options: Entity,
+    pub option_buttons: Vec<Entity>,
+    is_readonly: bool,
+}
+
+impl MathOperationInputWidget {
+    pub fn spawn(
+        commands: &mut Commands,
+        font: Handle<Font>,
+        parent: Entity,
+        node: Entity,
+        input_id: InputId,
+        value: MathOperation,
+    ) -> Entity {
+        let widget_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let text_style = TextStyle {
+            font: font.clone(),
+            font_size: 14.0,
+            color: Color::WHITE,
+        };
+
+        let mut button_text = Entity::PLACEHOLDER;
+        let button = commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(4.)),
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                background_color: DROPDOWN_BUTTON_COLOR.into(),
+                border_radius: BorderRadius::all(Val::Px(4.)),
+                ..default()
+            })
+            .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+                commands.trigger(RequestToggleMathOperationDropdown { widget_entity });
+            }))
+            .with_children(|child_builder| {
+                button_text = child_builder
+                    .spawn(TextBundle::from_section(value.name(), text_style.clone()))
+                    .insert(Pickable::IGNORE)
+                    .id();
+
+                child_builder
+                    .spawn(TextBundle::from_section("v", text_style.clone()))
+                    .insert(Pickable::IGNORE);
+            })
+            .id();
+
+        let options = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    flex_direction: FlexDirection::Column,
+                    margin: UiRect::top(Val::Px(2.)),
+                    border: UiRect::all(Val::Px(1.)),
+                    ..default()
+                },
+                background_color: DROPDOWN_OPTION_COLOR.into(),
+                border_color: SLATE_600.into(),
+                border_radius: BorderRadius::all(Val::Px(4.)),
+                ..default()
+            })
+            .id();
+
+        let option_buttons: Vec<Entity> = MathOperation::ALL
+            .iter()
+            .map(|&operation| {
+                commands
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(4.)),
+                            ..default()
+                        },
+                        background_color: if operation == value {
+                            DROPDOWN_OPTION_SELECTED_COLOR.into()
+                        } else {
+                            DROPDOWN_OPTION_COLOR.into()
+                        },
+                        ..default()
+                    })
+                    .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+                        commands.trigger(RequestSelectMathOperation {
+                            widget_entity,
+                            operation,
+                        });
+                    }))
+                    .with_children(|child_builder| {
+                        child_builder
+                            .spawn(TextBundle::from_section(operation.name(), text_style.clone()))
+                            .insert(Pickable::IGNORE);
+                    })
+                    .id()
+            })
+            .collect();
+
+        commands.entity(options).push_children(&option_buttons);
+
+        commands
+            .entity(widget_entity)
+            .push_children(&[button, options])
+            .insert(MathOperationInputWidget {
+                node,
+                input_id,
+                button,
+                button_text,
+                options,
+                option_buttons,
+                is_readonly: false,
+            });
+
+        commands.entity(parent).add_child(widget_entity);
+
+        widget_entity
+    }
+}
+
+fn toggle_math_operation_dropdown(
+    trigger: Trigger<RequestToggleMathOperationDropdown>,
+    q_widget: Query<&MathOperationInputWidget>,
+    mut q_style: Query<&mut Style>,
+) {
+    if let Ok(widget) = q_widget.get(trigger.event().widget_entity) {
+        if widget.is_readonly {
+            return;
+        }
+
+        if let Ok(mut style) = q_style.get_mut(widget.options) {
+            style.display = match style.display {
+                Display::None => Display::Flex,
+                _ => Display::None,
+            };
+        }
+    }
+}
+
+fn select_math_operation(
+    trigger: Trigger<RequestSelectMathOperation>,
+    mut commands: Commands,
+    q_graph: Query<&DisjointPipelineGraph>,
+    q_widget: Query<&MathOperationInputWidget>,
+    q_node_display: Query<&NodeDisplay>,
+    mut q_style: Query<&mut Style>,
+) {
+    let graph = &q_graph.single().graph;
+
+    let Ok(widget) = q_widget.get(trigger.event().widget_entity) else {
+        return;
+    };
+
+    if let Ok(mut style) = q_style.get_mut(widget.options) {
+        style.display = Display::None;
+    }
+
+    let Ok(node_display) = q_node_display.get(widget.node) else {
+        return;
+    };
+
+    let is_driven_by_edge = graph
+        .edges_directed(node_display.index, Direction::Incoming)
+        .any(|edge| edge.weight().to_field == widget.input_id);
+
+    if is_driven_by_edge {
+        return;
+    }
+
+    if let Some(node) = graph.node_weight(node_display.index) {
+        if let Some(Field::MathOperation(old_operation)) = node.kind.get_input(widget.input_id) {
+            let new_operation = trigger.event().operation;
+
+            if old_operation != new_operation {
+                commands.trigger(SetInputFieldEvent {
+                    node: node_display.index,
+                    input_id: widget.input_id,
+                    old_value: Field::MathOperation(old_operation),
+                    new_value: Field::MathOperation(new_operation),
+                });
+            }
+        }
+    }
+}
+
+fn update_math_operation_input(
+    trigger: Trigger<RequestUpdateMathOperationInput>,
+    mut q_widget: Query<&mut MathOperationInputWidget>,
+    mut q_text: Query<&mut Text>,
+    mut q_style: Query<&mut Style>,
+    mut q_background_color: Query<&mut BackgroundColor>,
+) {
+    if let Ok(mut widget) = q_widget.get_mut(trigger.event().widget_entity) {
+        let operation = trigger.event().value;
+        let is_readonly = trigger.event().is_readonly;
+
+        widget.is_readonly = is_readonly;
+
+        if let Ok(mut text) = q_text.get_mut(widget.button_text) {
+            text.sections[0].value = operation.name().to_string();
+        }
+
+        if let Ok(mut background_color) = q_background_color.get_mut(widget.button) {
+            *background_color = if is_readonly {
+                DROPDOWN_BUTTON_COLOR.with_alpha(0.4).into()
+            } else {
+                DROPDOWN_BUTTON_COLOR.into()
+            };
+        }
+
+        if is_readonly {
+            if let Ok(mut style) = q_style.get_mut(widget.options) {
+                style.display = Display::None;
+            }
+        }
+
+        for (button, option) in widget.option_buttons.iter().zip(MathOperation::ALL.iter()) {
+            if let Ok(mut background_color) = q_background_color.get_mut(*button) {
+                *background_color = if *option == operation {
+                    DROPDOWN_OPTION_SELECTED_COLOR.into()
+                } else {
+                    DROPDOWN_OPTION_COLOR.into()
+                };
+            }
+        }
+    }
+}
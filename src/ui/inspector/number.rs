@@ -0,0 +1,140 @@
+use bevy::{ecs::system::SystemId, prelude::*};
+use bevy_cosmic_edit::CosmicFontSystem;
+
+use crate::{
+    events::field_events::SetInputFieldEvent,
+    graph::DisjointPipelineGraph,
+    nodes::{fields::Field, InputId, NodeDisplay, NodeTrait},
+};
+
+use super::text_input::{
+    RequestUpdateTextInput, TextInputHandlerInput, TextInputLimits, TextInputWidget,
+};
+
+#[derive(Resource)]
+pub struct NumberWidgetCallbacks {
+    pub value_changed: SystemId<TextInputHandlerInput>,
+}
+
+pub struct NumberPlugin;
+
+impl Plugin for NumberPlugin {
+    fn build(&self, app: &mut App) {
+        let value_changed = app.register_system(number_input_handler);
+
+        app.insert_resource(NumberWidgetCallbacks { value_changed });
+
+        app.observe(update_number_input);
+    }
+}
+
+#[derive(Event)]
+pub struct RequestUpdateNumberInput {
+    pub value: f32,
+    pub widget_entity: Entity,
+    pub is_readonly: bool,
+}
+
+// An input widget for Field::F32 and Field::U32
+#[derive(Component)]
+pub struct NumberInputWidget {
+    pub node: Entity,
+    pub input_id: InputId,
+    pub text_input: Entity,
+}
+
+impl NumberInputWidget {
+    pub fn spawn(
+        commands: &mut Commands,
+        callbacks: &NumberWidgetCallbacks,
+        font_system: &mut CosmicFontSystem,
+        font: Handle<Font>,
+        parent: Entity,
+        node: Entity,
+        input_id: InputId,
+        value: f32,
+        limits: TextInputLimits,
+    ) -> Entity {
+        let widget_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let text_input = TextInputWidget::spawn(
+            commands,
+            font_system,
+            font,
+            "Value",
+            value,
+            callbacks.value_changed,
+            widget_entity,
+        );
+
+        commands.entity(text_input).insert(limits);
+
+        commands
+            .entity(widget_entity)
+            .add_child(text_input)
+            .insert(NumberInputWidget {
+                node,
+                input_id,
+                text_input,
+            });
+
+        commands.entity(parent).add_child(widget_entity);
+
+        widget_entity
+    }
+}
+
+pub fn number_input_handler(
+    In(input): In<TextInputHandlerInput>,
+    mut commands: Commands,
+    q_graph: Query<&DisjointPipelineGraph>,
+    q_number_in: Query<&NumberInputWidget>,
+    q_node_display: Query<&NodeDisplay>,
+) {
+    if let Ok(float_input) = input.value.trim().parse::<f32>() {
+        let graph = &q_graph.single().graph;
+
+        let number_widget = q_number_in.get(input.controlling_widget).expect("Called number_input_handler with entity that does not exist.");
+        let node_display = q_node_display.get(number_widget.node).expect("Had NumberInputWidget with bad Node reference.");
+
+        let node = graph.node_weight(node_display.index).expect("Tried to modify value of deleted node.");
+        let old_value = node.kind.get_input(number_widget.input_id).expect("Tried to get invalid input from a NumberInputWidget");
+
+        let new_value = match old_value {
+            Field::F32(_) => Field::F32(float_input),
+            Field::U32(_) => Field::U32(float_input.round().max(0.) as u32),
+            _ => panic!("number_input_handler in NumberInputWidget was triggered with an unexpected input field type."),
+        };
+
+        commands.trigger(SetInputFieldEvent {
+            node: node_display.index,
+            input_id: number_widget.input_id,
+            new_value,
+            old_value,
+        });
+    }
+}
+
+fn update_number_input(
+    trigger: Trigger<RequestUpdateNumberInput>,
+    mut commands: Commands,
+    q_number_in: Query<&NumberInputWidget>,
+) {
+    if let Ok(number_widget) = q_number_in.get(trigger.event().widget_entity) {
+        commands.trigger(RequestUpdateTextInput {
+            widget_entity: number_widget.text_input,
+            value: trigger.event().value,
+            is_readonly: trigger.event().is_readonly,
+        });
+    }
+}
@@ -0,0 +1,293 @@
+use bevy::{
+    color::palettes::tailwind::{RED_400, SLATE_700, SLATE_800},
+    prelude::*,
+};
+use bevy_cosmic_edit::*;
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::{On, Pickable},
+};
+
+use crate::{
+    asset::ShaderAssets,
+    graph::{DisjointPipelineGraph, RequestProcessPipeline},
+    nodes::{
+        custom_shader::RequestPickCustomShader,
+        input_label,
+        ports::{InputPort, PortLabel},
+        shared::shader_source,
+        GraphNodeKind, InputId, NodeDisplay, NodeTrait,
+    },
+};
+
+use super::field_heading::FieldHeadingWidget;
+
+pub struct ShaderSourcePlugin;
+
+impl Plugin for ShaderSourcePlugin {
+    fn build(&self, app: &mut App) {
+        app.observe(compile_shader_source);
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct RequestCompileShaderSource {
+    pub node: Entity,
+    // None compiles whatever is in the node's editor
+    pub source: Option<String>,
+}
+
+// A multiline WGSL editor for a Custom Compute node, with the last compile error under it
+#[derive(Component)]
+pub struct ShaderSourceWidget {
+    pub node: Entity,
+    pub editor: Entity,
+    pub error_text: Entity,
+}
+
+const EDITOR_HEIGHT: f32 = 280.;
+
+impl ShaderSourceWidget {
+    pub fn spawn(
+        commands: &mut Commands,
+        font_system: &mut CosmicFontSystem,
+        font: Handle<Font>,
+        parent: Entity,
+        node: Entity,
+        source: &str,
+        error: Option<&str>,
+    ) -> Entity {
+        let widget_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let editor = commands
+            .spawn((
+                CosmicEditBundle {
+                    buffer: CosmicBuffer::new(font_system, Metrics::new(13., 16.)).with_text(
+                        font_system,
+                        source,
+                        Attrs::new().color(Color::WHITE.to_cosmic()),
+                    ),
+                    cursor_color: CursorColor(Color::linear_rgba(0.5, 0.5, 0.5, 1.0).into()),
+                    selection_color: SelectionColor(Color::linear_rgba(0.3, 0.3, 0.7, 1.0).into()),
+                    fill_color: CosmicBackgroundColor(SLATE_800.into()),
+                    mode: CosmicWrap::InfiniteLine,
+                    ..default()
+                },
+                Style {
+                    display: Display::None,
+                    ..default()
+                },
+                Node::DEFAULT,
+            ))
+            .id();
+
+        let editor_area = commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Px(EDITOR_HEIGHT),
+                    ..default()
+                },
+                background_color: SLATE_800.into(),
+                ..default()
+            })
+            .insert(CosmicSource(editor))
+            .add_child(editor)
+            .id();
+
+        let button_row = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(4.),
+                    margin: UiRect::top(Val::Px(4.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        for text in ["Compile", "Load File..."] {
+            let button_entity = commands
+                .spawn(ButtonBundle {
+                    style: Style {
+                        flex_grow: 1.,
+                        padding: UiRect::all(Val::Px(4.)),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: SLATE_700.into(),
+                    border_radius: BorderRadius::all(Val::Px(4.)),
+                    ..default()
+                })
+                .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| match text {
+                    "Compile" => commands.trigger(RequestCompileShaderSource { node, source: None }),
+                    _ => commands.trigger(RequestPickCustomShader { node }),
+                }))
+                .with_children(|child_builder| {
+                    child_builder
+                        .spawn(TextBundle::from_section(
+                            text,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 14.0,
+                                color: Color::WHITE,
+                            },
+                        ))
+                        .insert(Pickable::IGNORE);
+                })
+                .id();
+
+            commands.entity(button_row).add_child(button_entity);
+        }
+
+        let error_text = commands
+            .spawn(TextBundle::from_section(
+                error.unwrap_or_default(),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 12.0,
+                    color: RED_400.into(),
+                },
+            ))
+            .insert(Style {
+                margin: UiRect::top(Val::Px(4.0)),
+                ..default()
+            })
+            .id();
+
+        commands
+            .entity(widget_entity)
+            .push_children(&[editor_area, button_row, error_text])
+            .insert(ShaderSourceWidget {
+                node,
+                editor,
+                error_text,
+            });
+
+        commands.entity(parent).add_child(widget_entity);
+
+        widget_entity
+    }
+}
+
+// Recompiles a Custom Compute node. A shader that doesn't compile leaves the node without output until it's fixed.
+fn compile_shader_source(
+    trigger: Trigger<RequestCompileShaderSource>,
+    mut q_pipeline: Query<&mut DisjointPipelineGraph>,
+    q_nodes: Query<&NodeDisplay>,
+    q_widgets: Query<&ShaderSourceWidget>,
+    q_editors: Query<&CosmicEditor>,
+    mut q_buffers: Query<&mut CosmicBuffer>,
+    mut q_text: Query<&mut Text>,
+    q_input_ports: Query<(Entity, &InputPort, &Children)>,
+    q_port_labels: Query<(), With<PortLabel>>,
+    q_headings: Query<&FieldHeadingWidget>,
+    mut font_system: ResMut<CosmicFontSystem>,
+    shaders: Res<Assets<Shader>>,
+    shader_handles: Res<ShaderAssets>,
+    mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+) {
+    let node_entity = trigger.event().node;
+    let widget = q_widgets.iter().find(|widget| widget.node == node_entity);
+
+    // a focused editor holds the latest text, the buffer only catches up once it loses focus
+    let editor_text = || {
+        let widget = widget?;
+        match q_editors.get(widget.editor) {
+            Ok(editor) => Some(editor.with_buffer(|buffer| buffer.get_text())),
+            Err(_) => q_buffers.get(widget.editor).ok().map(|buffer| buffer.get_text()),
+        }
+    };
+
+    let Some(source) = trigger.event().source.clone().or_else(editor_text) else {
+        return;
+    };
+
+    let Ok(node_display) = q_nodes.get(node_entity) else {
+        return;
+    };
+
+    let mut pipeline = q_pipeline.single_mut();
+    let Some(node) = pipeline.graph.node_weight_mut(node_display.index) else {
+        return;
+    };
+
+    let GraphNodeKind::CustomCompute(custom_compute) = &mut node.kind else {
+        return;
+    };
+
+    let template = match shader_source(&shaders, &shader_handles.custom_compute) {
+        Ok(template) => template,
+        Err(e) => {
+            eprintln!("Couldn't compile: {}", e);
+            return;
+        }
+    };
+
+    let result = custom_compute.set_shader_source(source.clone(), &template);
+    match &result {
+        Ok(_) => println!("Custom Compute shader compiled"),
+        Err(e) => eprintln!("Custom Compute shader didn't compile:\n{}", e),
+    }
+    node.processed_inputs = None;
+
+    // the source may have renamed its params, so the node's ports and the inspector headings follow
+    let labels: Vec<(InputId, String)> = node
+        .kind
+        .input_fields()
+        .iter()
+        .map(|&input_id| (input_id, input_label(&node.kind, input_id)))
+        .collect();
+
+    for (input_port_entity, input_port, children) in q_input_ports.iter() {
+        if input_port.node_entity != node_entity {
+            continue;
+        }
+        let Some((_, label)) = labels.iter().find(|(input_id, _)| *input_id == input_port.input_id) else {
+            continue;
+        };
+
+        let port_label_entities = children.iter().filter(|child| q_port_labels.contains(**child));
+        let heading_label_entities = q_headings
+            .iter()
+            .filter(|heading| heading.port_entity() == input_port_entity)
+            .map(|heading| heading.label_entity());
+
+        for label_entity in port_label_entities.copied().chain(heading_label_entities) {
+            if let Ok(mut text) = q_text.get_mut(label_entity) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+
+    if let Some(widget) = widget {
+        if let Ok(mut text) = q_text.get_mut(widget.error_text) {
+            text.sections[0].value = result.err().unwrap_or_default();
+        }
+
+        // source loaded from a file replaces what was in the editor
+        if trigger.event().source.is_some() {
+            if let Ok(mut buffer) = q_buffers.get_mut(widget.editor) {
+                buffer.set_text(
+                    &mut font_system,
+                    &source,
+                    Attrs::new().color(Color::WHITE.to_cosmic()),
+                );
+            }
+        }
+    }
+
+    ev_process_pipeline.send(RequestProcessPipeline);
+}
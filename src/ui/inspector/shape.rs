@@ -0,0 +1,338 @@
+use bevy::{
+    color::palettes::tailwind::{BLUE_600, SLATE_700},
+    ecs::system::SystemId,
+    prelude::*,
+};
+use bevy_cosmic_edit::CosmicFontSystem;
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::{On, Pickable},
+};
+use petgraph::Direction;
+
+use crate::{
+    events::field_events::SetInputFieldEvent,
+    graph::DisjointPipelineGraph,
+    nodes::{fields::Field, kinds::shape::Shape, InputId, NodeDisplay, NodeTrait},
+};
+
+use super::text_input::{
+    RequestUpdateTextInput, TextInputHandlerInput, TextInputLimits, TextInputWidget,
+};
+
+#[derive(Resource)]
+pub struct ShapeWidgetCallbacks {
+    pub first_param_changed: SystemId<TextInputHandlerInput>,
+    pub second_param_changed: SystemId<TextInputHandlerInput>,
+}
+
+pub struct ShapeWidgetPlugin;
+
+impl Plugin for ShapeWidgetPlugin {
+    fn build(&self, app: &mut App) {
+        let first_param_changed = app.register_system(shape_param_handler::<0>);
+        let second_param_changed = app.register_system(shape_param_handler::<1>);
+
+        app.insert_resource(ShapeWidgetCallbacks {
+            first_param_changed,
+            second_param_changed,
+        });
+
+        app.observe(update_shape_input);
+        app.observe(select_shape_kind);
+    }
+}
+
+const SHAPE_KINDS: [(&str, fn(f32) -> Shape); 3] = [
+    ("Circle", Shape::circle_with_extent),
+    ("Rectangle", Shape::rectangle_with_extent),
+    ("Triangle", Shape::triangle_with_extent),
+];
+
+const KIND_BUTTON_COLOR: Srgba = SLATE_700;
+const KIND_BUTTON_SELECTED_COLOR: Srgba = BLUE_600;
+
+#[derive(Event)]
+pub struct RequestUpdateShapeInput {
+    pub value: Shape,
+    pub widget_entity: Entity,
+    pub is_readonly: bool,
+}
+
+#[derive(Event, Clone)]
+pub struct RequestSelectShapeKind {
+    pub widget_entity: Entity,
+    pub kind_index: usize,
+}
+
+#[derive(Component)]
+pub struct ShapeInputWidget {
+    pub node: Entity,
+    pub input_id: InputId,
+    pub kind_buttons: Vec<Entity>,
+    pub first_param: Entity,
+    pub second_param: Entity,
+}
+
+impl ShapeInputWidget {
+    pub fn spawn(
+        commands: &mut Commands,
+        callbacks: &ShapeWidgetCallbacks,
+        font_system: &mut CosmicFontSystem,
+        font: Handle<Font>,
+        parent: Entity,
+        node: Entity,
+        input_id: InputId,
+        value: &Shape,
+    ) -> Entity {
+        let widget_entity = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let kind_row = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(4.),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+
+        let kind_buttons: Vec<Entity> = SHAPE_KINDS
+            .iter()
+            .enumerate()
+            .map(|(kind_index, (kind_name, _))| {
+                let is_selected = value.name() == *kind_name;
+
+                commands
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            flex_grow: 1.,
+                            padding: UiRect::all(Val::Px(4.)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: if is_selected {
+                            KIND_BUTTON_SELECTED_COLOR.into()
+                        } else {
+                            KIND_BUTTON_COLOR.into()
+                        },
+                        border_radius: BorderRadius::all(Val::Px(4.)),
+                        ..default()
+                    })
+                    .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+                        commands.trigger(RequestSelectShapeKind {
+                            widget_entity,
+                            kind_index,
+                        });
+                    }))
+                    .with_children(|child_builder| {
+                        child_builder
+                            .spawn(TextBundle::from_section(
+                                *kind_name,
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 14.0,
+                                    color: Color::WHITE,
+                                },
+                            ))
+                            .insert(Pickable::IGNORE);
+                    })
+                    .id()
+            })
+            .collect();
+
+        commands.entity(kind_row).push_children(&kind_buttons);
+
+        let (first_name, second_name) = value.param_names();
+        let (first_value, second_value) = value.params();
+
+        let first_param = TextInputWidget::spawn(
+            commands,
+            font_system,
+            font.clone(),
+            first_name,
+            first_value,
+            callbacks.first_param_changed,
+            widget_entity,
+        );
+
+        let second_param = TextInputWidget::spawn(
+            commands,
+            font_system,
+            font.clone(),
+            second_name.unwrap_or_default(),
+            second_value,
+            callbacks.second_param_changed,
+            widget_entity,
+        );
+
+        for param in [first_param, second_param] {
+            commands.entity(param).insert(TextInputLimits {
+                min: Some(0.),
+                ..default()
+            });
+        }
+
+        if second_name.is_none() {
+            commands.add(move |world: &mut World| {
+                if let Some(mut style) = world.get_mut::<Style>(second_param) {
+                    style.display = Display::None;
+                }
+            });
+        }
+
+        commands
+            .entity(widget_entity)
+            .push_children(&[kind_row, first_param, second_param])
+            .insert(ShapeInputWidget {
+                node,
+                input_id,
+                kind_buttons,
+                first_param,
+                second_param,
+            });
+
+        commands.entity(parent).add_child(widget_entity);
+
+        widget_entity
+    }
+}
+
+fn select_shape_kind(
+    trigger: Trigger<RequestSelectShapeKind>,
+    mut commands: Commands,
+    q_graph: Query<&DisjointPipelineGraph>,
+    q_shape_widget: Query<&ShapeInputWidget>,
+    q_node_display: Query<&NodeDisplay>,
+) {
+    let graph = &q_graph.single().graph;
+
+    if let Ok(shape_widget) = q_shape_widget.get(trigger.event().widget_entity) {
+        if let Ok(node_display) = q_node_display.get(shape_widget.node) {
+            let is_driven_by_edge = graph
+                .edges_directed(node_display.index, Direction::Incoming)
+                .any(|edge| edge.weight().to_field == shape_widget.input_id);
+
+            if is_driven_by_edge {
+                return;
+            }
+
+            if let Some(node) = graph.node_weight(node_display.index) {
+                if let Some(Field::Shape(old_shape)) = node.kind.get_input(shape_widget.input_id) {
+                    let (_, make_shape) = SHAPE_KINDS[trigger.event().kind_index];
+                    let new_shape = make_shape(old_shape.extent());
+
+                    if !old_shape.is_same_kind(&new_shape) {
+                        commands.trigger(SetInputFieldEvent {
+                            node: node_display.index,
+                            input_id: shape_widget.input_id,
+                            old_value: Field::Shape(old_shape),
+                            new_value: Field::Shape(new_shape),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn shape_param_handler<const PARAM: usize>(
+    In(input): In<TextInputHandlerInput>,
+    mut commands: Commands,
+    q_graph: Query<&DisjointPipelineGraph>,
+    q_shape_widget: Query<&ShapeInputWidget>,
+    q_node_display: Query<&NodeDisplay>,
+) {
+    if let Ok(float_input) = input.value.parse::<f32>() {
+        let graph = &q_graph.single().graph;
+
+        let shape_widget = q_shape_widget.get(input.controlling_widget).expect("Called shape_param_handler with entity that does not exist.");
+        let node_display = q_node_display.get(shape_widget.node).expect("Had ShapeInputWidget with bad Node reference.");
+
+        let node = graph.node_weight(node_display.index).expect("Tried to modify value of deleted node.");
+        let old_value = node.kind.get_input(shape_widget.input_id).expect("Tried to get invalid input from a ShapeInputWidget");
+
+        let new_shape = match &old_value {
+            Field::Shape(shape) => shape.with_param(PARAM, float_input),
+            _ => panic!("shape_param_handler in ShapeInputWidget was triggered with an unexpected input field type.")
+        };
+
+        commands.trigger(SetInputFieldEvent {
+            node: node_display.index,
+            input_id: shape_widget.input_id,
+            new_value: Field::Shape(new_shape),
+            old_value,
+        });
+    }
+}
+
+fn update_shape_input(
+    trigger: Trigger<RequestUpdateShapeInput>,
+    mut commands: Commands,
+    q_shape_widget: Query<&ShapeInputWidget>,
+    q_text_input: Query<&TextInputWidget>,
+    mut q_text: Query<&mut Text>,
+    mut q_style: Query<&mut Style>,
+    mut q_background_color: Query<&mut BackgroundColor>,
+) {
+    if let Ok(shape_widget) = q_shape_widget.get(trigger.event().widget_entity) {
+        let shape = &trigger.event().value;
+        let (first_name, second_name) = shape.param_names();
+        let (first_value, second_value) = shape.params();
+
+        for (param_entity, maybe_name) in [
+            (shape_widget.first_param, Some(first_name)),
+            (shape_widget.second_param, second_name),
+        ] {
+            if let Ok(mut style) = q_style.get_mut(param_entity) {
+                style.display = if maybe_name.is_some() {
+                    Display::Flex
+                } else {
+                    Display::None
+                };
+            }
+
+            if let (Some(name), Ok(text_input)) = (maybe_name, q_text_input.get(param_entity)) {
+                if let Ok(mut text) = q_text.get_mut(text_input.label) {
+                    text.sections[0].value = format!("{}: ", name);
+                }
+            }
+        }
+
+        commands.trigger(RequestUpdateTextInput {
+            widget_entity: shape_widget.first_param,
+            value: first_value,
+            is_readonly: trigger.event().is_readonly,
+        });
+
+        commands.trigger(RequestUpdateTextInput {
+            widget_entity: shape_widget.second_param,
+            value: second_value,
+            is_readonly: trigger.event().is_readonly,
+        });
+
+        for (button, (kind_name, _)) in shape_widget.kind_buttons.iter().zip(SHAPE_KINDS.iter()) {
+            if let Ok(mut background_color) = q_background_color.get_mut(*button) {
+                let is_selected = trigger.event().value.name() == *kind_name;
+
+                *background_color = match (is_selected, trigger.event().is_readonly) {
+                    (true, _) => KIND_BUTTON_SELECTED_COLOR.into(),
+                    (false, true) => KIND_BUTTON_COLOR.with_alpha(0.4).into(),
+                    (false, false) => KIND_BUTTON_COLOR.into(),
+                };
+            }
+        }
+    }
+}
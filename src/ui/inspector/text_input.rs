@@ -1,8 +1,17 @@
-use bevy::{color::palettes::tailwind::SLATE_800, ecs::system::SystemId, prelude::*};
+use bevy::{
+    color::palettes::tailwind::{RED_900, SLATE_800},
+    ecs::system::SystemId,
+    prelude::*,
+};
 use bevy_cosmic_edit::*;
-use bevy_mod_picking::events::{Down, Pointer};
+use bevy_mod_picking::{
+    events::{Down, Drag, DragEnd, DragStart, Pointer},
+    prelude::PointerButton,
+};
 
-use crate::ApplicationState;
+use crate::{nodes::fields::FieldMeta, ApplicationState};
+
+use super::InspectorSection;
 
 pub struct TextInputPlugin;
 
@@ -10,7 +19,13 @@ impl Plugin for TextInputPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             PreUpdate,
-            (drop_text_focus, confirm_on_enter).run_if(in_state(ApplicationState::MainLoop)),
+            (cycle_focus_on_tab, drop_text_focus, confirm_on_enter)
+                .chain()
+                .run_if(in_state(ApplicationState::MainLoop)),
+        );
+        app.add_systems(
+            Update,
+            scrub_text_input.run_if(in_state(ApplicationState::MainLoop)),
         );
 
         app.observe(update_text_input);
@@ -20,6 +35,7 @@ impl Plugin for TextInputPlugin {
 #[derive(Component)]
 pub struct TextInputWidget {
     pub cosmic_edit: Entity,
+    pub label: Entity,
 }
 
 #[derive(Event)]
@@ -34,6 +50,36 @@ pub struct TextInputHandlerInput {
     pub controlling_widget: Entity,
 }
 
+// marks a text input label as a handle that can be dragged to change the value
+#[derive(Component)]
+pub struct ScrubLabel {
+    pub text_input: Entity,
+}
+
+// optional bounds for a text input, taken from the FieldMeta of the field it edits
+#[derive(Component, Clone, Copy, Default)]
+pub struct TextInputLimits {
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub step: Option<f32>,
+}
+
+impl From<&FieldMeta> for TextInputLimits {
+    fn from(meta: &FieldMeta) -> Self {
+        Self {
+            min: meta.min,
+            max: meta.max,
+            step: meta.step,
+        }
+    }
+}
+
+impl TextInputLimits {
+    pub fn clamp(&self, value: f32) -> f32 {
+        value.clamp(self.min.unwrap_or(f32::MIN), self.max.unwrap_or(f32::MAX))
+    }
+}
+
 // marks a cosmic edit bundle as controlled by a specific system
 #[derive(Component)]
 pub struct ControlledTextInput {
@@ -65,7 +111,7 @@ impl TextInputWidget {
                     cursor_color: CursorColor(Color::linear_rgba(0.5, 0.5, 0.5, 1.0).into()),
                     selection_color: SelectionColor(Color::linear_rgba(0.3, 0.3, 0.7, 1.0).into()),
                     fill_color: CosmicBackgroundColor(
-                        SLATE_800.into(),
+                        TEXT_INPUT_BACKGROUND_COLOR.into(),
                     ),
                     mode: CosmicWrap::Wrap,
                     ..default()
@@ -82,6 +128,26 @@ impl TextInputWidget {
             })
             .id();
 
+        // an empty label leaves just the box, for inputs laid out in a grid
+        let is_labeled = !label.is_empty();
+        let label_text = if is_labeled { format!("{}: ", label) } else { String::new() };
+        let label = commands
+            .spawn(TextBundle::from_section(
+                label_text,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ))
+            .insert(Style {
+                margin: UiRect::right(Val::Px(if is_labeled { 5.0 } else { 0. })),
+                min_width: Val::Px(if is_labeled { 20. } else { 0. }),
+                ..default()
+            })
+            .id();
+
         let input_row = commands
             .spawn(NodeBundle {
                 style: Style {
@@ -94,23 +160,8 @@ impl TextInputWidget {
                 },
                 ..default()
             })
+            .add_child(label)
             .with_children(|parent| {
-                parent
-                    .spawn(TextBundle::from_section(
-                        format!("{}: ", label),
-                        TextStyle {
-                            font: font.clone(),
-                            font_size: 14.0,
-                            color: Color::WHITE,
-                            ..default()
-                        },
-                    ))
-                    .insert(Style {
-                        margin: UiRect::right(Val::Px(5.0)),
-                        min_width: Val::Px(20.),
-                        ..default()
-                    });
-
                 parent
                     .spawn(ButtonBundle {
                         style: Style {
@@ -128,10 +179,13 @@ impl TextInputWidget {
                     .insert(CosmicSource(cosmic_edit))
                     .insert(ScrollDisabled);
             })
-            .insert(TextInputWidget { cosmic_edit })
+            .insert(TextInputWidget { cosmic_edit, label })
             .id();
 
         commands.entity(input_row).add_child(cosmic_edit);
+        commands.entity(label).insert(ScrubLabel {
+            text_input: input_row,
+        });
 
         input_row
     }
@@ -152,6 +206,10 @@ fn update_text_input(
                 Attrs::new().color(Color::WHITE.to_cosmic()),
             );
 
+            commands
+                .entity(buffer_entity)
+                .insert(CosmicBackgroundColor(TEXT_INPUT_BACKGROUND_COLOR.into()));
+
             if trigger.event().is_readonly {
                 commands.entity(buffer_entity).insert(ReadOnly);
             } else if maybe_readonly_tag.is_some() {
@@ -161,27 +219,87 @@ fn update_text_input(
     }
 }
 
-// run text input handlers when focus is lost
+const TEXT_INPUT_BACKGROUND_COLOR: Srgba = SLATE_800;
+const INVALID_TEXT_INPUT_BACKGROUND_COLOR: Srgba = RED_900;
+
+// Hands a committed edit to the input's handler.
+// Text that isn't a number never reaches a handler, the field is flagged as invalid instead.
+fn commit_text_input(
+    commands: &mut Commands,
+    cosmic_edit: Entity,
+    controlled: &ControlledTextInput,
+    value: String,
+) {
+    if value.trim().parse::<f32>().is_ok() {
+        commands
+            .entity(cosmic_edit)
+            .insert(CosmicBackgroundColor(TEXT_INPUT_BACKGROUND_COLOR.into()));
+
+        let input = TextInputHandlerInput {
+            value,
+            controlling_widget: controlled.controlling_widget,
+        };
+        commands.run_system_with_input::<TextInputHandlerInput>(controlled.handler, input);
+    } else {
+        commands
+            .entity(cosmic_edit)
+            .insert(CosmicBackgroundColor(INVALID_TEXT_INPUT_BACKGROUND_COLOR.into()));
+    }
+}
+
+// run text input handlers when focus is lost, or revert the edit if focus was dropped with escape
 // only applies to text inputs - this is bevy-cosmic-edit specific
 // cursed, likely bug ridden, pls bevy give official textbox
 // has to cache the edit buffer value in case the user clicks directly from one input to another
 fn drop_text_focus(
     mut commands: Commands,
     mut ev_down: EventReader<Pointer<Down>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
     mut focused: ResMut<FocusedWidget>,
+    mut font_system: ResMut<CosmicFontSystem>,
     q_cosmic_source: Query<&CosmicSource>,
     q_cosmic_edit: Query<Option<&ControlledTextInput>>,
     mut old_focused: Local<Option<Entity>>,
     mut last_buffer_value: Local<String>,
+    mut value_before_edit: Local<String>,
+    mut pending_revert: Local<Option<(Entity, String)>>,
     q_cosmic_editor: Query<&CosmicEditor>,
+    mut q_cosmic_buffer: Query<&mut CosmicBuffer>,
 ) {
     let mut clicked_on_not_a_text_input = false;
     for event in ev_down.read() {
-        if !q_cosmic_source.contains(event.target) {
+        // editors drawn as sprites (comments) are clicked directly rather than through a CosmicSource
+        if !q_cosmic_source.contains(event.target) && !q_cosmic_buffer.contains(event.target) {
             clicked_on_not_a_text_input = true;
         }
     }
 
+    // The editor copies its text back into the buffer when it goes away, so a revert has to wait for that
+    if let Some((entity, value)) = pending_revert.take() {
+        if q_cosmic_editor.contains(entity) {
+            *pending_revert = Some((entity, value));
+        } else if let Ok(mut buffer) = q_cosmic_buffer.get_mut(entity) {
+            buffer.set_text(&mut font_system, &value, Attrs::new().color(Color::WHITE.to_cosmic()));
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        if let Some(focus) = focused.0 {
+            // editors outside the inspector style their own text, and keep what was typed when escaped
+            if let Ok(Some(_)) = q_cosmic_edit.get(focus) {
+                commands
+                    .entity(focus)
+                    .insert(CosmicBackgroundColor(TEXT_INPUT_BACKGROUND_COLOR.into()));
+
+                *pending_revert = Some((focus, value_before_edit.clone()));
+            }
+
+            focused.0 = None;
+            *old_focused = None;
+            return;
+        }
+    }
+
     let mut field_to_update: Option<Entity> = None;
 
     if clicked_on_not_a_text_input {
@@ -199,19 +317,22 @@ fn drop_text_focus(
         }
     }
 
+    // Remember what a newly focused field held, so escape can put it back
+    if let Some(focus) = focused.0 {
+        if *old_focused != Some(focus) {
+            if let Ok(buffer) = q_cosmic_buffer.get(focus) {
+                *value_before_edit = buffer.get_text();
+            }
+        }
+    }
+
     // Update the old_focused for the next frame
     *old_focused = focused.0;
 
     // Process the field that needs updating
     if let Some(field_to_update) = field_to_update {
-        if let Ok(maybe_controlled) = q_cosmic_edit.get(field_to_update) {
-            if let Some(controlled) = maybe_controlled {
-                let input = TextInputHandlerInput {
-                    value: last_buffer_value.clone(),
-                    controlling_widget: controlled.controlling_widget,
-                };
-                commands.run_system_with_input::<TextInputHandlerInput>(controlled.handler, input);
-            }
+        if let Ok(Some(controlled)) = q_cosmic_edit.get(field_to_update) {
+            commit_text_input(&mut commands, field_to_update, controlled, last_buffer_value.clone());
         }
     }
 
@@ -235,16 +356,170 @@ fn confirm_on_enter(
         if let Some(focused_entity) = focused.0 {
             if let Ok(controlled_input) = q_cosmic_edit.get(focused_entity) {
                 if let Ok(editor) = q_cosmic_editor.get(focused_entity) {
-                    editor.with_buffer(|buffer| {
-                        let input = TextInputHandlerInput {
-                            value: buffer.get_text(),
-                            controlling_widget: controlled_input.controlling_widget,
-                        };
-                        commands.run_system_with_input::<TextInputHandlerInput>(controlled_input.handler, input);
-                    });
+                    let value = editor.with_buffer(|buffer| buffer.get_text());
+                    commit_text_input(&mut commands, focused_entity, controlled_input, value);
+                }
+            }
+        }
+    }
+}
+
+// Tab / Shift+Tab move focus to the next / previous editable field of the same inspector section.
+// Moving focus is enough to commit the field being left, drop_text_focus handles that.
+fn cycle_focus_on_tab(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut focused: ResMut<FocusedWidget>,
+    q_parent: Query<&Parent>,
+    q_children: Query<&Children>,
+    q_section: Query<(), With<InspectorSection>>,
+    q_controlled: Query<(), (With<ControlledTextInput>, Without<ReadOnly>)>,
+    q_style: Query<&Style>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    let focused_entity = match focused.0 {
+        Some(entity) if q_controlled.contains(entity) => entity,
+        _ => return,
+    };
+
+    let section = match q_parent
+        .iter_ancestors(focused_entity)
+        .find(|ancestor| q_section.contains(*ancestor))
+    {
+        Some(section) => section,
+        None => return,
+    };
+
+    // the editable bundle itself is never displayed, so visibility is decided by its row
+    let is_shown = |entity: Entity| {
+        q_parent
+            .iter_ancestors(entity)
+            .take_while(|ancestor| *ancestor != section)
+            .all(|ancestor| q_style.get(ancestor).map_or(true, |style| style.display != Display::None))
+    };
+
+    let mut fields: Vec<Entity> = vec![];
+    collect_in_layout_order(section, &q_children, &mut fields);
+    fields.retain(|entity| q_controlled.contains(*entity) && is_shown(*entity));
+
+    if let Some(index) = fields.iter().position(|entity| *entity == focused_entity) {
+        let backwards =
+            keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+
+        let next_index = if backwards {
+            (index + fields.len() - 1) % fields.len()
+        } else {
+            (index + 1) % fields.len()
+        };
+
+        focused.0 = Some(fields[next_index]);
+    }
+}
+
+fn collect_in_layout_order(entity: Entity, q_children: &Query<&Children>, out: &mut Vec<Entity>) {
+    out.push(entity);
+
+    if let Ok(children) = q_children.get(entity) {
+        for &child in children.iter() {
+            collect_in_layout_order(child, q_children, out);
+        }
+    }
+}
+
+struct ScrubState {
+    label: Entity,
+    cosmic_edit: Entity,
+    value: f32,
+    limits: TextInputLimits,
+}
+
+const SCRUB_SPEED: f32 = 0.005;
+const SCRUB_MIN_STEP: f32 = 0.01;
+const SCRUB_FINE_FACTOR: f32 = 0.1;
+
+// dragging horizontally on a text input's label changes its value
+// the value is only committed to the handler when the drag ends, so a scrub is one undo step
+fn scrub_text_input(
+    mut commands: Commands,
+    mut ev_drag_start: EventReader<Pointer<DragStart>>,
+    mut ev_drag: EventReader<Pointer<Drag>>,
+    mut ev_drag_end: EventReader<Pointer<DragEnd>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut font_system: ResMut<CosmicFontSystem>,
+    q_scrub_label: Query<&ScrubLabel>,
+    q_text_input: Query<(&TextInputWidget, Option<&TextInputLimits>)>,
+    q_controlled: Query<&ControlledTextInput>,
+    mut q_cosmic_buffer: Query<(&mut CosmicBuffer, Option<&ReadOnly>)>,
+    mut scrub_state: Local<Option<ScrubState>>,
+) {
+    for event in ev_drag_start.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
 
+        if let Ok(scrub_label) = q_scrub_label.get(event.target) {
+            if let Ok((text_input, maybe_limits)) = q_text_input.get(scrub_label.text_input) {
+                if let Ok((buffer, maybe_readonly)) = q_cosmic_buffer.get(text_input.cosmic_edit) {
+                    if maybe_readonly.is_some() {
+                        continue;
+                    }
+
+                    if let Ok(value) = buffer.get_text().trim().parse::<f32>() {
+                        *scrub_state = Some(ScrubState {
+                            label: event.target,
+                            cosmic_edit: text_input.cosmic_edit,
+                            value,
+                            limits: maybe_limits.copied().unwrap_or_default(),
+                        });
+                    }
                 }
             }
         }
     }
-}
\ No newline at end of file
+
+    let fine_adjustment =
+        keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+
+    for event in ev_drag.read() {
+        let state = match scrub_state.as_mut() {
+            Some(state) if state.label == event.target => state,
+            _ => continue,
+        };
+
+        let mut step = state
+            .limits
+            .step
+            .unwrap_or((state.value.abs() * SCRUB_SPEED).max(SCRUB_MIN_STEP));
+        if fine_adjustment {
+            step *= SCRUB_FINE_FACTOR;
+        }
+
+        state.value = state.limits.clamp(state.value + event.delta.x * step);
+
+        if let Ok((mut buffer, _)) = q_cosmic_buffer.get_mut(state.cosmic_edit) {
+            buffer.set_text(
+                &mut font_system,
+                &format!("{:.2}", state.value),
+                Attrs::new().color(Color::WHITE.to_cosmic()),
+            );
+        }
+    }
+
+    for event in ev_drag_end.read() {
+        if !matches!(scrub_state.as_ref(), Some(state) if state.label == event.target) {
+            continue;
+        }
+
+        if let Some(state) = scrub_state.take() {
+            if let Ok(controlled) = q_controlled.get(state.cosmic_edit) {
+                let input = TextInputHandlerInput {
+                    value: state.value.to_string(),
+                    controlling_widget: controlled.controlling_widget,
+                };
+                commands.run_system_with_input::<TextInputHandlerInput>(controlled.handler, input);
+            }
+        }
+    }
+}
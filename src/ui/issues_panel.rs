@@ -0,0 +1,87 @@
+use bevy::{
+    color::palettes::tailwind::{AMBER_400, SLATE_900},
+    prelude::*,
+};
+use bevy_mod_picking::prelude::Pickable;
+
+use crate::{asset::FontAssets, validation::ValidationIssues, ApplicationState};
+
+pub struct IssuesPanelPlugin;
+
+impl Plugin for IssuesPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_issues_panel.run_if(in_state(ApplicationState::MainLoop)),
+        );
+    }
+}
+
+// Lists validation issues in the corner of the node edit area, hidden when there are none
+#[derive(Component)]
+pub struct IssuesPanel;
+
+impl IssuesPanel {
+    pub fn spawn(commands: &mut Commands) -> Entity {
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    max_width: Val::Percent(50.),
+                    display: Display::None,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.),
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: SLATE_900.with_alpha(0.9).into(),
+                border_radius: BorderRadius::all(Val::Px(4.)),
+                ..default()
+            })
+            .insert(Name::new("Issues Panel"))
+            .insert(IssuesPanel)
+            .insert(Pickable::IGNORE)
+            .id()
+    }
+}
+
+fn update_issues_panel(
+    mut commands: Commands,
+    validation_issues: Res<ValidationIssues>,
+    fonts: Res<FontAssets>,
+    mut q_panel: Query<(Entity, &mut Style), With<IssuesPanel>>,
+) {
+    if !validation_issues.is_changed() {
+        return;
+    }
+
+    let (panel_entity, mut style) = match q_panel.get_single_mut() {
+        Ok(panel) => panel,
+        Err(_) => return,
+    };
+
+    style.display = if validation_issues.0.is_empty() {
+        Display::None
+    } else {
+        Display::Flex
+    };
+
+    commands.entity(panel_entity).despawn_descendants();
+
+    commands.entity(panel_entity).with_children(|child_builder| {
+        for issue in validation_issues.0.iter() {
+            child_builder
+                .spawn(TextBundle::from_section(
+                    format!("⚠ {}", issue.message),
+                    TextStyle {
+                        font: fonts.deja_vu_sans.clone(),
+                        font_size: 14.,
+                        color: AMBER_400.into(),
+                    },
+                ))
+                .insert(Pickable::IGNORE);
+        }
+    });
+}
@@ -1,4 +1,7 @@
-use std::io::Cursor;
+use std::{
+    io::Cursor,
+    path::{Path, PathBuf},
+};
 
 use bevy::{
     color::palettes::{
@@ -7,38 +10,53 @@ use bevy::{
     },
     math::VectorSpace,
     prelude::*,
+    render::render_resource::TextureFormat,
     utils::hashbrown::HashMap,
     window::PrimaryWindow,
 };
-use bevy_file_dialog::{DialogFileLoaded, DialogFileSaved, FileDialogExt, FileDialogPlugin};
+use bevy_file_dialog::{DialogDirectoryPicked, DialogFileLoaded, DialogFileSaved, FileDialogExt, FileDialogPlugin};
 use bevy_mod_picking::{
     events::{Down, Out, Over, Pointer, Up},
     focus::PickingInteraction,
     prelude::{On, Pickable},
 };
-use petgraph::visit::{IntoEdgeReferences, IntoNodeReferences};
+use petgraph::{
+    prelude::StableDiGraph,
+    visit::{IntoEdgeReferences, IntoNodeReferences},
+    Direction,
+};
 use rmp_serde::{Deserializer, Serializer};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
+    asset::{NODE_CONTENT_PADDING, NODE_TEXTURE_DISPLAY_DIMENSION, NODE_TITLE_BAR_SIZE, NODE_WIDTH},
     camera::MainCamera,
+    export::{encode_thumbnail, BatchOutputFolder, BatchSourceFolder, ExportedImage},
     events::{
+        comment_events::{AddCommentEvent, RemoveCommentEvent},
         edge_events::{AddEdgeEvent, AddSerializedEdge},
-        node_events::{AddNodeEvent, AddNodeKind, AddSerializedNode, RemoveNodeEvent},
+        group_events::{AddGroupEvent, RemoveGroupEvent},
+        node_events::{AddNodeEvent, AddNodeKind, AddSerializedNode, NodeResources, RemoveNodeEvent},
     },
-    graph::{DisjointPipelineGraph, Edge, SerializableEdge},
+    graph::{DisjointPipelineGraph, Edge, RequestProcessPipeline, SerializableEdge},
     nodes::{
         fields::{Field, FieldMeta},
-        kinds::{color::SerializableColorNode, example::SerializableExampleNode},
-        GraphNodeKind, InputId, NodeDisplay, NodeId, NodeIdMapping, NodeTrait,
+        kinds::{
+            color::SerializableColorNode, example::SerializableExampleNode,
+            image::{EmbeddedPixels, SerializableImageNode},
+        },
+        custom_shader::CustomShaderFile, node_kind_name, shader_reload::rebuild_node_kind, GraphNode, InputId, NodeDisplay, NodeId, NodeIdMapping, NodePlacement, NodeTrait,
         RequestSpawnNodeKind, Selected, SerializableGraphNode, SerializableGraphNodeKind,
         SerializableInputId,
     },
+    preferences::{next_choice, UiPreferences},
     ApplicationState,
 };
 
 use super::{
+    comments::{Comment, CommentData},
+    groups::{Group, GroupData},
     context_menu::{ContextMenuPositionSource, MenuBarContext, RequestOpenContextMenu, UIContext},
     Spawner,
 };
@@ -50,14 +68,21 @@ impl Plugin for MenuBarPlugin {
         app.add_plugins(
             FileDialogPlugin::new()
                 .with_save_file::<SaveFile>()
-                .with_load_file::<SaveFile>(),
+                .with_load_file::<SaveFile>()
+                .with_save_file::<ExportedImage>()
+                .with_load_file::<CustomShaderFile>()
+                .with_pick_directory::<ImportFolder>()
+                .with_pick_directory::<BatchSourceFolder>()
+                .with_pick_directory::<BatchOutputFolder>(),
         );
         app.add_systems(
             Update,
             (
                 file_save_complete,
                 file_load_complete,
+                import_folder_picked,
                 handle_copy_paste_input,
+                handle_save_input,
             )
                 .run_if(in_state(ApplicationState::MainLoop)),
         );
@@ -66,12 +91,26 @@ impl Plugin for MenuBarPlugin {
             .observe(handle_load_request)
             .observe(handle_copy_request)
             .observe(handle_paste_request)
+            .observe(handle_paste_image_request)
+            .observe(handle_import_folder_request)
             .observe(handle_exit_request)
-            .observe(handle_new_project_event);
+            .observe(handle_new_project_event)
+            .observe(handle_extract_selection_request)
+            .observe(queue_extracted_project)
+            .observe(open_extracted_project)
+            .observe(set_render_scale)
+            .observe(set_texture_format);
 
         app.insert_resource(Project {
             id: Uuid::new_v4(),
             working_filename: String::from("new_project"),
+            path: None,
+            saving_project_id: None,
+            render_scale: 1.,
+            texture_format: TextureFormat::Rgba8Unorm,
+            saved_snapshot: None,
+            saving_snapshot: None,
+            extract_after_save: None,
         });
     }
 }
@@ -104,6 +143,7 @@ impl MenuBar {
         ec.with_children(|parent| {
             MenuButton::File.spawn(parent, "File", font.clone());
             MenuButton::Edit.spawn(parent, "Edit", font.clone());
+            MenuButton::Preferences.spawn(parent, "Preferences", font.clone());
         });
         ec.id()
     }
@@ -113,6 +153,7 @@ impl MenuBar {
 pub enum MenuButton {
     File,
     Edit,
+    Preferences,
 }
 
 impl MenuButton {
@@ -173,8 +214,12 @@ impl MenuButton {
     }
 }
 
+// ToKnownPath writes straight to the project's file when it has one, and falls back to the dialog otherwise
 #[derive(Clone, Event)]
-pub struct SaveEvent;
+pub enum SaveEvent {
+    ToKnownPath,
+    Prompt,
+}
 
 #[derive(Clone, Event)]
 pub struct LoadEvent;
@@ -185,15 +230,39 @@ struct SaveFile {
     project_id: Uuid,
     nodes: Vec<SerializableGraphNode>,
     edges: Vec<SerializableEdge>,
+    #[serde(default)]
+    comments: Vec<CommentData>,
+    #[serde(default)]
+    groups: Vec<GroupData>,
+    #[serde(default = "default_render_scale")]
+    render_scale: f32,
+    #[serde(default = "default_texture_format")]
+    texture_format: TextureFormat,
+    // PNG preview of the graph's final image, only written when the preference is on
+    #[serde(default)]
+    thumbnail: Option<Vec<u8>>,
+}
+
+const THUMBNAIL_MAX_DIMENSION: u32 = 128;
+
+fn default_render_scale() -> f32 {
+    1.
+}
+
+fn default_texture_format() -> TextureFormat {
+    TextureFormat::Rgba8Unorm
 }
 
 pub fn handle_save_request(
     trigger: Trigger<SaveEvent>,
     q_graph: Query<&DisjointPipelineGraph>,
     q_node_display: Query<(&Transform, &NodeDisplay, &NodeId)>,
+    q_comments: Query<&Comment>,
+    q_groups: Query<&Group>,
     mut commands: Commands,
     node_id_map: Res<NodeIdMapping>,
-    project: Res<Project>,
+    mut project: ResMut<Project>,
+    ui_preferences: Res<UiPreferences>,
 ) {
     let graph = &q_graph.single().graph;
 
@@ -206,14 +275,7 @@ pub fn handle_save_request(
     let nodes: Vec<SerializableGraphNode> = graph
         .node_weights()
         .map(|node| {
-            let kind = match &node.kind {
-                GraphNodeKind::Example(example_node) => {
-                    SerializableGraphNodeKind::from(example_node)
-                }
-                GraphNodeKind::Color(color_node) => SerializableGraphNodeKind::from(color_node),
-                GraphNodeKind::Shape(shape_node) => SerializableGraphNodeKind::from(shape_node),
-                GraphNodeKind::Blend(blend_node) => SerializableGraphNodeKind::from(blend_node),
-            };
+            let kind = SerializableGraphNodeKind::from(&node.kind);
 
             let (transform, node_display, node_id) =
                 q_node_display.get(node.kind.entity()).unwrap();
@@ -222,6 +284,8 @@ pub fn handle_save_request(
                 id: node_id.0,
                 kind,
                 position: transform.translation,
+                cacheable: node.cacheable,
+                custom_shader: node.custom_shader.as_ref().map(|custom_shader| custom_shader.path.clone()),
             }
         })
         .collect();
@@ -241,6 +305,15 @@ pub fn handle_save_request(
         project_id: project.id,
         nodes,
         edges,
+        comments: q_comments.iter().map(|comment| comment.data.clone()).collect(),
+        groups: q_groups.iter().map(|group| group.data.clone()).collect(),
+        render_scale: project.render_scale,
+        texture_format: project.texture_format,
+        thumbnail: if ui_preferences.embed_thumbnail {
+            project_thumbnail(graph, &q_node_display)
+        } else {
+            None
+        },
     };
 
     let maybe_serialized = rmp_serde::to_vec(save_file);
@@ -248,6 +321,22 @@ pub fn handle_save_request(
 
     match maybe_serialized {
         Ok(serialized) => {
+            if let (SaveEvent::ToKnownPath, Some(path)) = (trigger.event(), project.path.clone()) {
+                match std::fs::write(&path, serialized) {
+                    Ok(_) => {
+                        eprintln!("File {} successfully saved", path.display());
+                        project.saved_snapshot = Some(save_file.clone());
+                    }
+                    Err(err) => eprintln!("Failed to save {}: {}", path.display(), err),
+                }
+                return;
+            }
+
+            project.saving_project_id = Some(project.id);
+            project.saving_snapshot = Some(save_file.clone());
+            // a cancelled dialog never reports back, so an extraction from an earlier dialog is dropped here
+            project.extract_after_save = None;
+
             commands
                 .dialog()
                 .add_filter("Raster Reshaper Project", &["rrproj"])
@@ -258,17 +347,59 @@ pub fn handle_save_request(
     }
 }
 
+// Previews the rightmost node that produces an image and feeds nothing else, which is usually the graph's result
+fn project_thumbnail(
+    graph: &StableDiGraph<GraphNode, Edge>,
+    q_node_display: &Query<(&Transform, &NodeDisplay, &NodeId)>,
+) -> Option<Vec<u8>> {
+    let (_, image) = graph
+        .node_references()
+        .filter(|(index, _)| graph.edges_directed(*index, Direction::Outgoing).next().is_none())
+        .filter_map(|(_, node)| {
+            let (transform, _, _) = q_node_display.get(node.kind.entity()).ok()?;
+            Some((transform.translation.x, node.kind.primary_output_image()?))
+        })
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))?;
+
+    match encode_thumbnail(image, THUMBNAIL_MAX_DIMENSION) {
+        Ok(png) => Some(png),
+        Err(e) => {
+            eprintln!("Failed to create a thumbnail: {}", e);
+            None
+        }
+    }
+}
+
 fn file_save_complete(
+    mut commands: Commands,
     mut ev_saved: EventReader<DialogFileSaved<SaveFile>>,
     mut project: ResMut<Project>,
 ) {
     for ev in ev_saved.read() {
+        let extracted = project.extract_after_save.take();
+
         match ev.result {
             Ok(_) => {
                 eprintln!("File {} successfully saved", ev.file_name);
-                project.working_filename = ev.file_name.clone();
+
+                // the project may have been replaced while the dialog was open
+                if project.saving_project_id == Some(project.id) {
+                    project.working_filename = ev.file_name.clone();
+                    project.path = Some(ev.path.clone());
+                    project.saved_snapshot = project.saving_snapshot.take();
+
+                    if let Some(extracted) = extracted {
+                        commands.trigger(extracted);
+                    }
+                }
+            }
+            Err(ref err) => {
+                eprintln!("Failed to save {}: {}", ev.file_name, err);
+
+                if extracted.is_some() {
+                    eprintln!("The selection wasn't extracted, since the project it came from wasn't saved.");
+                }
             }
-            Err(ref err) => eprintln!("Failed to save {}: {}", ev.file_name, err),
         }
     }
 }
@@ -289,6 +420,8 @@ fn file_load_complete(
     mut commands: Commands,
     mut ev_loaded: EventReader<DialogFileLoaded<SaveFile>>,
     mut q_pipeline: Query<(&mut DisjointPipelineGraph)>,
+    q_comments: Query<Entity, With<Comment>>,
+    q_groups: Query<Entity, With<Group>>,
     mut project: ResMut<Project>,
 ) {
     let graph = &q_pipeline.single_mut().graph;
@@ -298,44 +431,132 @@ fn file_load_complete(
         match maybe_deserialized {
             Ok(save_file) => {
                 project.id = save_file.project_id.clone();
+                // anything but a known scale could size export textures at zero or past the GPU's limits
+                project.render_scale = if RENDER_SCALE_CHOICES.contains(&save_file.render_scale) {
+                    save_file.render_scale
+                } else {
+                    eprintln!("Ignoring unsupported render scale {} in the loaded project.", save_file.render_scale);
+                    default_render_scale()
+                };
+                project.texture_format = save_file.texture_format;
+                project.path = Some(ev.path.clone());
+
+                let uuid_map = replace_graph(
+                    &mut commands,
+                    graph,
+                    &q_comments,
+                    &q_groups,
+                    &save_file.nodes,
+                    &save_file.edges,
+                    &save_file.comments,
+                    &save_file.groups,
+                );
+
+                // loaded nodes get fresh ids, so the snapshot has to follow them to stay comparable
+                project.saved_snapshot = Some(remap_save_file_ids(save_file, &uuid_map));
+            }
+            Err(err) => println!("file not loaded because {}", err),
+        }
+    }
+}
 
-                for (_, node) in graph.node_references() {
-                    commands.trigger(RemoveNodeEvent {
-                        node_entity: node.kind.entity(),
-                    });
-                }
+// Clears the current graph, comments and groups and rebuilds them from serialized ones, giving every node a fresh id.
+// Returns the map from each serialized id to the fresh one.
+fn replace_graph(
+    commands: &mut Commands,
+    graph: &StableDiGraph<GraphNode, Edge>,
+    q_comments: &Query<Entity, With<Comment>>,
+    q_groups: &Query<Entity, With<Group>>,
+    nodes: &[SerializableGraphNode],
+    edges: &[SerializableEdge],
+    comments: &[CommentData],
+    groups: &[GroupData],
+) -> HashMap<Uuid, Uuid> {
+    for (_, node) in graph.node_references() {
+        commands.trigger(RemoveNodeEvent {
+            node_entity: node.kind.entity(),
+            bridge: false,
+        });
+    }
 
-                // old -> new
-                let mut uuid_map: HashMap<Uuid, Uuid> = HashMap::new();
-                for loaded_node in &save_file.nodes {
-                    let new_uuid = Uuid::new_v4();
+    for comment_entity in q_comments.iter() {
+        commands.trigger(RemoveCommentEvent { comment_entity });
+    }
 
-                    uuid_map.insert(loaded_node.id, new_uuid);
+    for comment in comments {
+        commands.trigger(AddCommentEvent {
+            data: comment.clone(),
+        });
+    }
 
-                    commands.trigger(AddNodeEvent::FromSerialized(AddSerializedNode {
-                        node_id: new_uuid,
-                        node: loaded_node.clone(),
-                    }));
-                }
+    for group_entity in q_groups.iter() {
+        commands.trigger(RemoveGroupEvent { group_entity });
+    }
 
-                for edge in &save_file.edges {
-                    if let (Some(&new_start), Some(&new_end)) = (
-                        uuid_map.get(&edge.from_node_id),
-                        uuid_map.get(&edge.to_node_id),
-                    ) {
-                        commands.trigger(AddEdgeEvent::FromSerialized(AddSerializedEdge {
-                            edge: SerializableEdge {
-                                from_node_id: new_start,
-                                to_node_id: new_end,
-                                ..edge.clone()
-                            },
-                        }));
-                    }
-                }
-            }
-            Err(err) => println!("file not loaded because {}", err),
+    for group in groups {
+        commands.trigger(AddGroupEvent { data: group.clone() });
+    }
+
+    // old -> new
+    let mut uuid_map: HashMap<Uuid, Uuid> = HashMap::new();
+    for loaded_node in nodes {
+        let new_uuid = Uuid::new_v4();
+
+        uuid_map.insert(loaded_node.id, new_uuid);
+
+        commands.trigger(AddNodeEvent::FromSerialized(AddSerializedNode {
+            node_id: new_uuid,
+            node: loaded_node.clone(),
+        }));
+    }
+
+    for edge in edges {
+        if let (Some(&new_start), Some(&new_end)) = (
+            uuid_map.get(&edge.from_node_id),
+            uuid_map.get(&edge.to_node_id),
+        ) {
+            commands.trigger(AddEdgeEvent::FromSerialized(AddSerializedEdge {
+                edge: SerializableEdge {
+                    from_node_id: new_start,
+                    to_node_id: new_end,
+                    ..edge.clone()
+                },
+            }));
         }
     }
+
+    uuid_map
+}
+
+fn remap_save_file_ids(save_file: SaveFile, uuid_map: &HashMap<Uuid, Uuid>) -> SaveFile {
+    let nodes = save_file
+        .nodes
+        .into_iter()
+        .filter_map(|node| {
+            Some(SerializableGraphNode {
+                id: *uuid_map.get(&node.id)?,
+                ..node
+            })
+        })
+        .collect();
+
+    let edges = save_file
+        .edges
+        .into_iter()
+        .filter_map(|edge| {
+            Some(SerializableEdge {
+                from_node_id: *uuid_map.get(&edge.from_node_id)?,
+                to_node_id: *uuid_map.get(&edge.to_node_id)?,
+                ..edge
+            })
+        })
+        .collect();
+
+    SaveFile {
+        nodes,
+        edges,
+        ..save_file
+    }
 }
 
 #[derive(Clone, Event)]
@@ -351,6 +572,89 @@ pub enum PasteEvent {
 pub struct Project {
     id: Uuid,
     working_filename: String,
+    // where the project was last saved or loaded, so it can be saved again without a dialog
+    path: Option<PathBuf>,
+    // the project a save dialog was opened for, so a late result can't rename a different project
+    saving_project_id: Option<Uuid>,
+    // source nodes render this many times larger when exporting; the editor always previews at 1x
+    render_scale: f32,
+    // what the compute nodes write their output in
+    texture_format: TextureFormat,
+    // the graph as of the last save or load, with ids matching the live nodes
+    saved_snapshot: Option<SaveFile>,
+    // what the open save dialog is writing, which becomes saved_snapshot if it succeeds
+    saving_snapshot: Option<SaveFile>,
+    // a selection to open as its own project once the open save dialog has written the current one
+    extract_after_save: Option<OpenExtractedProject>,
+}
+
+pub const RENDER_SCALE_CHOICES: [f32; 4] = [1., 2., 4., 8.];
+// Rgba32Float isn't filterable, so node previews couldn't sample it
+pub const PROJECT_TEXTURE_FORMAT_CHOICES: [TextureFormat; 2] = [TextureFormat::Rgba8Unorm, TextureFormat::Rgba16Float];
+
+impl Project {
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    pub fn next_render_scale(&self) -> f32 {
+        next_choice(&RENDER_SCALE_CHOICES, &self.render_scale)
+    }
+
+    pub fn texture_format(&self) -> TextureFormat {
+        self.texture_format
+    }
+
+    pub fn next_texture_format(&self) -> TextureFormat {
+        next_choice(&PROJECT_TEXTURE_FORMAT_CHOICES, &self.texture_format)
+    }
+
+    // Nodes and edges as of the last save or load. A project that was never saved has none.
+    pub fn saved_graph(&self) -> (&[SerializableGraphNode], &[SerializableEdge]) {
+        match &self.saved_snapshot {
+            Some(save_file) => (&save_file.nodes, &save_file.edges),
+            None => (&[], &[]),
+        }
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct SetRenderScale(pub f32);
+
+fn set_render_scale(trigger: Trigger<SetRenderScale>, mut project: ResMut<Project>) {
+    project.render_scale = trigger.event().0;
+}
+
+#[derive(Event, Clone)]
+pub struct SetTextureFormat(pub TextureFormat);
+
+// Every compute node's pipelines are built for one format, so they're all rebuilt and the graph runs again
+fn set_texture_format(
+    trigger: Trigger<SetTextureFormat>,
+    mut q_pipeline: Query<&mut DisjointPipelineGraph>,
+    node_resources: NodeResources,
+    mut project: ResMut<Project>,
+    mut ev_process_pipeline: EventWriter<RequestProcessPipeline>,
+) {
+    let texture_format = trigger.event().0;
+    if project.texture_format == texture_format {
+        return;
+    }
+    project.texture_format = texture_format;
+
+    let mut pipeline = q_pipeline.single_mut();
+    let indices: Vec<_> = pipeline.graph.node_indices().collect();
+    for index in indices {
+        let node = &mut pipeline.graph[index];
+        match rebuild_node_kind(node, &node_resources, texture_format) {
+            // left in its old format, the node still works; its output just won't match the rest of the graph
+            Err(e) => eprintln!("Couldn't rebuild {} for {:?}: {}", node_kind_name(&node.kind), texture_format, e),
+            Ok(kind) => node.kind = kind,
+        }
+        node.processed_inputs = None;
+    }
+
+    ev_process_pipeline.send(RequestProcessPipeline);
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -360,6 +664,13 @@ struct CopyData {
     edges: Vec<SerializableEdge>,
 }
 
+// Brings an image from the OS clipboard in as a new Image node, as opposed to PasteEvent which pastes nodes
+#[derive(Clone, Event)]
+pub enum PasteImageEvent {
+    FromCursor(Vec2),
+    FromMenu,
+}
+
 #[derive(Resource)]
 struct Clipboard(Option<Vec<u8>>);
 
@@ -393,12 +704,9 @@ fn handle_copy_request(
             let serializable_node = SerializableGraphNode {
                 id: node_id.0,
                 position: transform.translation,
-                kind: match &node.kind {
-                    GraphNodeKind::Example(ex) => SerializableGraphNodeKind::from(ex),
-                    GraphNodeKind::Color(color) => SerializableGraphNodeKind::from(color),
-                    GraphNodeKind::Shape(shape) => SerializableGraphNodeKind::from(shape),
-                    GraphNodeKind::Blend(blend) => SerializableGraphNodeKind::from(blend),
-                },
+                kind: SerializableGraphNodeKind::from(&node.kind),
+                cacheable: node.cacheable,
+                custom_shader: node.custom_shader.as_ref().map(|custom_shader| custom_shader.path.clone()),
             };
             copy_data.nodes.push(serializable_node);
         }
@@ -428,9 +736,10 @@ fn handle_paste_request(
     clipboard: Res<Clipboard>,
     camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
     node_id_map: Res<NodeIdMapping>,
+    placement: NodePlacement,
 ) {
     let id_to_node = &node_id_map.0;
-    
+
     if let Some(serialized) = &clipboard.0 {
         if let Ok(copy_data) = rmp_serde::from_slice::<CopyData>(serialized) {
             let center = copy_data
@@ -450,6 +759,14 @@ fn handle_paste_request(
                 }
             };
 
+            // the pasted nodes move as one so they keep their layout while clearing existing nodes
+            let pasted_positions: Vec<Vec2> = copy_data
+                .nodes
+                .iter()
+                .map(|node| paste_position + node.position.truncate() - center)
+                .collect();
+            let paste_position = paste_position + placement.free_offset(&pasted_positions);
+
             // map from the pasted guid to the nuid guide
             let mut pasted_guid_map: HashMap<Uuid, Uuid> = HashMap::new();
             for pasted_node in copy_data.nodes {
@@ -513,6 +830,169 @@ fn handle_paste_request(
     }
 }
 
+fn handle_paste_image_request(
+    trigger: Trigger<PasteImageEvent>,
+    mut commands: Commands,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    let clipboard_image = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_image());
+
+    let image_data = match clipboard_image {
+        Ok(image_data) => image_data,
+        Err(e) => {
+            eprintln!("No image to paste from the clipboard: {}", e);
+            return;
+        }
+    };
+
+    let paste_position = match trigger.event() {
+        PasteImageEvent::FromCursor(pos) => *pos,
+        PasteImageEvent::FromMenu => camera_query
+            .get_single()
+            .map(|transform| transform.translation.truncate())
+            .unwrap_or(Vec2::ZERO),
+    };
+
+    commands.trigger(AddNodeEvent::FromSerialized(AddSerializedNode {
+        node_id: Uuid::new_v4(),
+        node: SerializableGraphNode {
+            id: Uuid::new_v4(),
+            position: paste_position.extend(0.),
+            kind: SerializableGraphNodeKind::Image(SerializableImageNode {
+                entity: Entity::PLACEHOLDER,
+                pixels: EmbeddedPixels::Rgba {
+                    width: image_data.width as u32,
+                    height: image_data.height as u32,
+                    rgba: image_data.bytes.into_owned(),
+                },
+                input_meta: HashMap::new(),
+                output_meta: HashMap::new(),
+            }),
+            cacheable: true,
+            custom_shader: None,
+        },
+    }));
+}
+
+// Brings every PNG in a folder in as a grid of Image nodes, e.g. the frames of an animation
+#[derive(Event, Clone)]
+pub struct ImportFolderEvent;
+
+pub struct ImportFolder;
+
+// Past this many images the import stops, since each one is a node and a texture
+const MAX_FOLDER_IMPORT: usize = 100;
+const FOLDER_IMPORT_SPACING: Vec2 = Vec2::new(
+    NODE_WIDTH + 40.,
+    NODE_TITLE_BAR_SIZE + NODE_TEXTURE_DISPLAY_DIMENSION + NODE_CONTENT_PADDING + 60.,
+);
+
+fn handle_import_folder_request(_trigger: Trigger<ImportFolderEvent>, mut commands: Commands) {
+    commands.dialog().pick_directory_path::<ImportFolder>();
+}
+
+fn import_folder_picked(
+    mut commands: Commands,
+    mut ev_picked: EventReader<DialogDirectoryPicked<ImportFolder>>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    placement: NodePlacement,
+) {
+    for ev in ev_picked.read() {
+        let mut image_paths = match folder_pngs(&ev.path) {
+            Ok(image_paths) => image_paths,
+            Err(e) => {
+                eprintln!("Couldn't read {}: {}", ev.path.display(), e);
+                continue;
+            }
+        };
+
+        if image_paths.is_empty() {
+            eprintln!("There are no PNG images in {}", ev.path.display());
+            continue;
+        }
+
+        if image_paths.len() > MAX_FOLDER_IMPORT {
+            eprintln!(
+                "{} has {} images, only importing the first {}",
+                ev.path.display(),
+                image_paths.len(),
+                MAX_FOLDER_IMPORT
+            );
+            image_paths.truncate(MAX_FOLDER_IMPORT);
+        }
+
+        // the bytes are decoded on the async compute pool once each node exists
+        let images: Vec<Vec<u8>> = image_paths
+            .iter()
+            .filter_map(|path| match std::fs::read(path) {
+                Ok(png) => Some(png),
+                Err(e) => {
+                    eprintln!("Couldn't read {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect();
+
+        let center = camera_query
+            .get_single()
+            .map(|transform| transform.translation.truncate())
+            .unwrap_or(Vec2::ZERO);
+
+        let positions = grid_positions(images.len(), center);
+        let offset = placement.free_offset(&positions);
+
+        // added in one frame, so a single undo removes the whole grid
+        for (png, position) in images.into_iter().zip(positions) {
+            commands.trigger(AddNodeEvent::FromSerialized(AddSerializedNode {
+                node_id: Uuid::new_v4(),
+                node: SerializableGraphNode {
+                    id: Uuid::new_v4(),
+                    position: (position + offset).extend(0.),
+                    kind: SerializableGraphNodeKind::Image(SerializableImageNode {
+                        entity: Entity::PLACEHOLDER,
+                        pixels: EmbeddedPixels::Png(png),
+                        input_meta: HashMap::new(),
+                        output_meta: HashMap::new(),
+                    }),
+                    cacheable: true,
+                    custom_shader: None,
+                },
+            }));
+        }
+    }
+}
+
+// Sorted by name, so numbered frames come in order
+pub fn folder_pngs(folder: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |extension| extension.eq_ignore_ascii_case("png"))
+        })
+        .collect();
+
+    paths.sort();
+    Ok(paths)
+}
+
+// As square a grid as the count allows, filled row by row and centered on center
+fn grid_positions(count: usize, center: Vec2) -> Vec<Vec2> {
+    let columns = (count as f32).sqrt().ceil().max(1.) as usize;
+    let rows = count.div_ceil(columns);
+    let size = Vec2::new((columns - 1) as f32, (rows.max(1) - 1) as f32) * FOLDER_IMPORT_SPACING;
+    let top_left = center + Vec2::new(-size.x, size.y) / 2.;
+
+    (0..count)
+        .map(|index| {
+            let column = (index % columns) as f32;
+            let row = (index / columns) as f32;
+            top_left + Vec2::new(column, -row) * FOLDER_IMPORT_SPACING
+        })
+        .collect()
+}
+
 #[derive(Event, Clone)]
 pub struct ExitEvent;
 
@@ -527,18 +1007,33 @@ pub fn handle_new_project_event(
     trigger: Trigger<NewProjectEvent>,
     mut commands: Commands,
     mut q_pipeline: Query<(&mut DisjointPipelineGraph)>,
+    q_comments: Query<Entity, With<Comment>>,
+    q_groups: Query<Entity, With<Group>>,
     mut project: ResMut<Project>,
 ) {
     let graph = &q_pipeline.single_mut().graph;
 
     project.id = Uuid::new_v4();
     project.working_filename = String::from("new_project");
+    project.path = None;
+    project.render_scale = 1.;
+    project.texture_format = TextureFormat::Rgba8Unorm;
+    project.saved_snapshot = None;
 
     for (_, node) in graph.node_references() {
         commands.trigger(RemoveNodeEvent {
             node_entity: node.kind.entity(),
+            bridge: false,
         });
     }
+
+    for comment_entity in q_comments.iter() {
+        commands.trigger(RemoveCommentEvent { comment_entity });
+    }
+
+    for group_entity in q_groups.iter() {
+        commands.trigger(RemoveGroupEvent { group_entity });
+    }
 }
 
 fn handle_copy_paste_input(
@@ -554,6 +1049,9 @@ fn handle_copy_paste_input(
         }
 
         if keyboard_input.just_pressed(KeyCode::KeyV) {
+            let shift_pressed = keyboard_input.pressed(KeyCode::ShiftLeft)
+                || keyboard_input.pressed(KeyCode::ShiftRight);
+
             if let Ok(window) = window_query.get_single() {
                 if let Some(cursor_position) = window.cursor_position() {
                     if let Ok((camera, camera_transform)) = camera_query.get_single() {
@@ -561,7 +1059,13 @@ fn handle_copy_paste_input(
                             camera.viewport_to_world(camera_transform, cursor_position)
                         {
                             let cursor_world_position = cursor_world_position.origin.truncate();
-                            commands.trigger(PasteEvent::FromCursor(cursor_world_position));
+
+                            // Ctrl+Shift+V pastes an image from the OS clipboard instead of copied nodes
+                            if shift_pressed {
+                                commands.trigger(PasteImageEvent::FromCursor(cursor_world_position));
+                            } else {
+                                commands.trigger(PasteEvent::FromCursor(cursor_world_position));
+                            }
                         }
                     }
                 }
@@ -569,3 +1073,138 @@ fn handle_copy_paste_input(
         }
     }
 }
+
+// Ctrl+S saves over the project's file, Ctrl+Shift+S always asks where to save
+fn handle_save_input(mut commands: Commands, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    let ctrl_pressed =
+        keyboard_input.pressed(KeyCode::ControlLeft) || keyboard_input.pressed(KeyCode::ControlRight);
+
+    if ctrl_pressed && keyboard_input.just_pressed(KeyCode::KeyS) {
+        let shift_pressed =
+            keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+
+        if shift_pressed {
+            commands.trigger(SaveEvent::Prompt);
+        } else {
+            commands.trigger(SaveEvent::ToKnownPath);
+        }
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct ExtractSelectionEvent;
+
+// Splits the selection off into its own project. The whole graph is offered for saving first, and the
+// selection only replaces it once that save succeeds. Only edges with both ends in the selection come along.
+fn handle_extract_selection_request(
+    _trigger: Trigger<ExtractSelectionEvent>,
+    mut commands: Commands,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_selected: Query<(&NodeDisplay, &NodeId, &Transform), With<Selected>>,
+    q_selected_comments: Query<&Comment, With<Selected>>,
+    q_groups: Query<&Group>,
+    node_id_map: Res<NodeIdMapping>,
+) {
+    let graph = &q_pipeline.single().graph;
+
+    let node_to_id: HashMap<Entity, Uuid> = node_id_map
+        .0
+        .iter()
+        .map(|(uuid, entity)| (*entity, *uuid))
+        .collect();
+
+    let nodes: Vec<SerializableGraphNode> = q_selected
+        .iter()
+        .filter_map(|(node_display, node_id, transform)| {
+            graph
+                .node_weight(node_display.index)
+                .map(|node| SerializableGraphNode {
+                    id: node_id.0,
+                    position: transform.translation,
+                    kind: SerializableGraphNodeKind::from(&node.kind),
+                    cacheable: node.cacheable,
+                    custom_shader: node.custom_shader.as_ref().map(|custom_shader| custom_shader.path.clone()),
+                })
+        })
+        .collect();
+
+    let comments: Vec<CommentData> = q_selected_comments
+        .iter()
+        .map(|comment| comment.data.clone())
+        .collect();
+
+    // groups come along when they hold any of the selected nodes
+    let groups: Vec<GroupData> = q_groups
+        .iter()
+        .filter(|group| nodes.iter().any(|node| group.data.contains(node.position.truncate())))
+        .map(|group| group.data.clone())
+        .collect();
+
+    if nodes.is_empty() && comments.is_empty() {
+        return;
+    }
+
+    let selected_ids: Vec<Uuid> = nodes.iter().map(|node| node.id).collect();
+
+    let edges: Vec<SerializableEdge> = graph
+        .edge_weights()
+        .filter_map(|edge| {
+            let from_id = *node_to_id.get(&edge.from_node)?;
+            let to_id = *node_to_id.get(&edge.to_node)?;
+
+            (selected_ids.contains(&from_id) && selected_ids.contains(&to_id))
+                .then(|| SerializableEdge::from_edge(edge, from_id, to_id))
+        })
+        .collect();
+
+    // triggered in order, so the extraction is queued on the dialog this save opens
+    commands.trigger(SaveEvent::Prompt);
+    commands.trigger(QueueExtractedProject(OpenExtractedProject {
+        nodes,
+        edges,
+        comments,
+        groups,
+    }));
+}
+
+#[derive(Event, Clone)]
+struct OpenExtractedProject {
+    nodes: Vec<SerializableGraphNode>,
+    edges: Vec<SerializableEdge>,
+    comments: Vec<CommentData>,
+    groups: Vec<GroupData>,
+}
+
+#[derive(Event)]
+struct QueueExtractedProject(OpenExtractedProject);
+
+fn queue_extracted_project(trigger: Trigger<QueueExtractedProject>, mut project: ResMut<Project>) {
+    project.extract_after_save = Some(trigger.event().0.clone());
+}
+
+fn open_extracted_project(
+    trigger: Trigger<OpenExtractedProject>,
+    mut commands: Commands,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_comments: Query<Entity, With<Comment>>,
+    q_groups: Query<Entity, With<Group>>,
+    mut project: ResMut<Project>,
+) {
+    let graph = &q_pipeline.single().graph;
+
+    project.id = Uuid::new_v4();
+    project.working_filename = String::from("new_project");
+    project.path = None;
+    project.saved_snapshot = None;
+
+    replace_graph(
+        &mut commands,
+        graph,
+        &q_comments,
+        &q_groups,
+        &trigger.event().nodes,
+        &trigger.event().edges,
+        &trigger.event().comments,
+        &trigger.event().groups,
+    );
+}
@@ -0,0 +1,218 @@
+use bevy::{
+    color::palettes::tailwind::{AMBER_400, SLATE_600, SLATE_700, SLATE_900},
+    prelude::*,
+};
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::{On, Pickable},
+};
+
+use crate::{
+    export::BatchExport,
+    graph::{AutoProcess, ProcessNow, ProcessProgress, StaleWhilePaused, ToggleAutoProcess},
+    nodes::fields::{field_color, Field},
+    ApplicationState,
+};
+
+use super::Spawner;
+
+pub struct StatusBarPlugin;
+
+impl Plugin for StatusBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_processing_status.run_if(in_state(ApplicationState::MainLoop)),
+        );
+    }
+}
+
+const STATUS_BAR_BG_COLOR: Srgba = SLATE_900;
+const STATUS_BAR_BUTTON_COLOR: Srgba = SLATE_700;
+
+#[derive(Component)]
+pub struct StatusBar;
+
+#[derive(Component)]
+pub struct ProcessingStatusText;
+
+#[derive(Component)]
+pub struct AutoProcessButtonText;
+
+impl StatusBar {
+    pub fn spawn(spawner: &mut impl Spawner, font: Handle<Font>) -> Entity {
+        let mut ec = spawner.spawn_bundle((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.),
+                    padding: UiRect::horizontal(Val::Px(8.)),
+                    border: UiRect::top(Val::Px(1.)),
+                    ..default()
+                },
+                background_color: STATUS_BAR_BG_COLOR.into(),
+                border_color: SLATE_600.into(),
+                ..default()
+            },
+            StatusBar,
+            Name::new("Status Bar"),
+            Pickable::IGNORE,
+        ));
+
+        ec.with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 14.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    flex_grow: 1.,
+                    ..default()
+                }),
+                ProcessingStatusText,
+                Pickable::IGNORE,
+            ));
+
+            spawn_field_color_legend(parent, font.clone());
+
+            spawn_status_bar_button(
+                parent,
+                "Pause",
+                font.clone(),
+                ToggleAutoProcess,
+                AutoProcessButtonText,
+            );
+            spawn_status_bar_button(parent, "Process Now", font.clone(), ProcessNow, ());
+        });
+
+        ec.id()
+    }
+}
+
+// A swatch per field type, in the same colors as ports and edges
+fn spawn_field_color_legend(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(4.),
+                margin: UiRect::right(Val::Px(8.)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Name::new("Field Color Legend"))
+        .insert(Pickable::IGNORE)
+        .with_children(|legend| {
+            for field in Field::all_types() {
+                legend
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(10.),
+                            height: Val::Px(10.),
+                            ..default()
+                        },
+                        background_color: Color::from(field_color(&field)).into(),
+                        border_radius: BorderRadius::all(Val::Px(5.)),
+                        ..default()
+                    })
+                    .insert(Pickable::IGNORE);
+
+                legend
+                    .spawn(TextBundle::from_section(
+                        field.type_name(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 12.0,
+                            color: Color::WHITE,
+                        },
+                    ))
+                    .insert(Style {
+                        margin: UiRect::right(Val::Px(6.)),
+                        ..default()
+                    })
+                    .insert(Pickable::IGNORE);
+            }
+        });
+}
+
+// text_bundle is added to the button's text, so systems can find it to change the label
+fn spawn_status_bar_button(
+    parent: &mut ChildBuilder,
+    text: &str,
+    font: Handle<Font>,
+    event: impl Event + Clone,
+    text_bundle: impl Bundle,
+) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                margin: UiRect::vertical(Val::Px(2.0)),
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                ..default()
+            },
+            background_color: STATUS_BAR_BUTTON_COLOR.into(),
+            border_radius: BorderRadius::all(Val::Px(4.)),
+            ..default()
+        })
+        .insert(On::<Pointer<Click>>::commands_mut(move |_click, commands| {
+            commands.trigger(event.clone());
+        }))
+        .with_children(|button| {
+            button
+                .spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font,
+                        font_size: 14.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(text_bundle)
+                .insert(Pickable::IGNORE);
+        });
+}
+
+fn update_processing_status(
+    auto_process: Res<AutoProcess>,
+    stale_while_paused: Res<StaleWhilePaused>,
+    process_progress: Res<ProcessProgress>,
+    batch_export: Res<BatchExport>,
+    mut q_status_text: Query<&mut Text, (With<ProcessingStatusText>, Without<AutoProcessButtonText>)>,
+    mut q_button_text: Query<&mut Text, (With<AutoProcessButtonText>, Without<ProcessingStatusText>)>,
+) {
+    if !auto_process.is_changed()
+        && !stale_while_paused.is_changed()
+        && !process_progress.is_changed()
+        && !batch_export.is_changed()
+    {
+        return;
+    }
+
+    if let Ok(mut text) = q_status_text.get_single_mut() {
+        let (value, color) = match (auto_process.0, stale_while_paused.0) {
+            (true, _) => ("Auto-process on", Color::WHITE),
+            (false, false) => ("Auto-process paused", AMBER_400.into()),
+            (false, true) => ("Auto-process paused, changes pending", AMBER_400.into()),
+        };
+
+        let mut value = match process_progress.overall() {
+            Some(fraction) => format!("{}, processing {:.0}%", value, fraction * 100.),
+            None => String::from(value),
+        };
+        if let Some((finished, total)) = batch_export.progress() {
+            value.push_str(&format!(", batch export {}/{}", finished, total));
+        }
+        text.sections[0].value = value;
+        text.sections[0].style.color = color;
+    }
+
+    if let Ok(mut text) = q_button_text.get_single_mut() {
+        text.sections[0].value = String::from(if auto_process.0 { "Pause" } else { "Resume" });
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::{
+    color::palettes::tailwind::{AMBER_700, SLATE_700, SLATE_800},
+    prelude::*,
+};
+use petgraph::{prelude::StableDiGraph, visit::{EdgeRef, IntoNodeReferences}, Direction};
+
+use crate::{
+    asset::NodeDisplayMaterial,
+    graph::{DisjointPipelineGraph, Edge, GraphWasUpdated},
+    nodes::{input_label, node_kind_name, GraphNode, NodeDisplay, NodeTrait},
+};
+
+pub const NODE_TITLE_BAR_COLOR: Srgba = SLATE_800;
+pub const NODE_TITLE_BAR_WARNING_COLOR: Srgba = AMBER_700;
+pub const NODE_BACKGROUND_COLOR: Srgba = SLATE_700;
+
+pub struct ValidationPlugin;
+
+impl Plugin for ValidationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ValidationIssues>();
+        app.observe(validate_graph);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationIssue {
+    pub node: Entity,
+    pub message: String,
+}
+
+// Everything currently wrong with the graph, rebuilt whenever it is processed
+#[derive(Resource, Default)]
+pub struct ValidationIssues(pub Vec<ValidationIssue>);
+
+impl ValidationIssues {
+    pub fn has_issues(&self, node: Entity) -> bool {
+        self.0.iter().any(|issue| issue.node == node)
+    }
+}
+
+pub fn find_validation_issues(graph: &StableDiGraph<GraphNode, Edge>) -> Vec<ValidationIssue> {
+    let mut issues = vec![];
+
+    for (index, node) in graph.node_references() {
+        for &input_id in node.kind.input_fields() {
+            let is_required = node
+                .kind
+                .get_input_meta(input_id)
+                .map_or(false, |meta| meta.required);
+
+            if !is_required {
+                continue;
+            }
+
+            let is_connected = graph
+                .edges_directed(index, Direction::Incoming)
+                .any(|edge| edge.weight().to_field == input_id);
+
+            if !is_connected {
+                issues.push(ValidationIssue {
+                    node: node.kind.entity(),
+                    message: format!(
+                        "{} is missing required input {}",
+                        node_kind_name(&node.kind),
+                        input_label(&node.kind, input_id)
+                    ),
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+fn validate_graph(
+    _trigger: Trigger<GraphWasUpdated>,
+    q_pipeline: Query<&DisjointPipelineGraph>,
+    q_nodes: Query<(Entity, &Handle<NodeDisplayMaterial>), With<NodeDisplay>>,
+    mut materials: ResMut<Assets<NodeDisplayMaterial>>,
+    mut validation_issues: ResMut<ValidationIssues>,
+) {
+    let issues = find_validation_issues(&q_pipeline.single().graph);
+
+    for (node_entity, material_handle) in q_nodes.iter() {
+        let title_bar_color: LinearRgba = if issues.iter().any(|issue| issue.node == node_entity) {
+            NODE_TITLE_BAR_WARNING_COLOR.into()
+        } else {
+            NODE_TITLE_BAR_COLOR.into()
+        };
+
+        let needs_update = materials
+            .get(material_handle.id())
+            .map_or(false, |material| material.title_bar_color != title_bar_color);
+
+        if needs_update {
+            if let Some(material) = materials.get_mut(material_handle.id()) {
+                material.title_bar_color = title_bar_color;
+            }
+        }
+    }
+
+    if validation_issues.0 != issues {
+        validation_issues.0 = issues;
+    }
+}